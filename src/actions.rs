@@ -0,0 +1,139 @@
+//! What the user asks of the camera and the UI, read from the keyboard and any gamepads.
+//!
+//! Systems look at [`Actions`] rather than at particular keys and buttons, so a control works
+//! the same from either. The camera keys are the ones bound in the settings. On a gamepad the
+//! right stick orbits the camera, the left stick pans its target and the triggers zoom; the
+//! D-pad moves the focus through the UI, A uses the focused element, B leaves it and Start
+//! opens or closes the settings. On an [`Adjustable`] element, Left and Right step its value
+//! instead of moving the focus, from the arrow keys and the D-pad alike. Touch gestures are
+//! read in `touch`, and move the camera by as much as the finger moves, like the mouse.
+
+use bevy::{
+    input::{InputSystems, gamepad::GamepadButton},
+    input_focus::{
+        InputFocus,
+        tab_navigation::{NavAction, TabIndex},
+    },
+    prelude::*,
+};
+
+use crate::{keybindings::KeyAction, settings::Settings, ui_navigation::Adjustable};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Actions>();
+    app.add_systems(PreUpdate, read_actions.after(InputSystems));
+}
+
+/// The actions asked for this frame.
+#[derive(Resource, Default)]
+pub struct Actions {
+    /// How fast to orbit the camera around its target, from -1 to 1 along each axis.
+    pub orbit: Vec2,
+    /// How fast to move the camera's target along its right and up axes, from -1 to 1.
+    pub pan: Vec2,
+    /// How fast to zoom in, from -1 (zooming out) to 1.
+    pub zoom: f32,
+    /// How far to orbit the camera, in pixels of a drag with the left mouse button.
+    pub orbit_drag: Vec2,
+    /// How far to pan the camera, in pixels of a drag with the right mouse button.
+    pub pan_drag: Vec2,
+    /// How far to zoom in, in lines of the scroll wheel.
+    pub zoom_steps: f32,
+    /// A step of the focus through the UI.
+    pub navigate: Option<NavAction>,
+    /// A step of the focused [`Adjustable`] element, -1 down or 1 up.
+    pub adjust: f32,
+    /// Use the focused UI element.
+    pub activate: bool,
+    /// Hand the keyboard and gamepad back to the scene.
+    pub back: bool,
+    /// Open or close the settings.
+    pub menu: bool,
+}
+
+/// A run condition for systems that open or close the settings.
+pub fn menu_pressed(actions: Res<Actions>) -> bool {
+    actions.menu
+}
+
+/// Whether a gamepad's buttons were pressed or its sticks moved this frame, mapped to an action
+/// or not.
+pub fn gamepad_used(gamepad: &Gamepad) -> bool {
+    gamepad.get_just_pressed().next().is_some()
+        || gamepad.left_stick() != Vec2::ZERO
+        || gamepad.right_stick() != Vec2::ZERO
+}
+
+pub fn read_actions(
+    mut actions: ResMut<Actions>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    focus: Res<InputFocus>,
+    focusable_query: Query<(), With<TabIndex>>,
+    adjustable_query: Query<(), With<Adjustable>>,
+    gamepad_query: Query<&Gamepad>,
+) {
+    let mut next = Actions::default();
+
+    let bindings = &settings.keybindings;
+    if bindings.pressed(&keys, KeyAction::OrbitLeft) {
+        next.orbit.x += 1.0;
+    }
+    if bindings.pressed(&keys, KeyAction::OrbitRight) {
+        next.orbit.x -= 1.0;
+    }
+    if bindings.pressed(&keys, KeyAction::ZoomIn) {
+        next.zoom += 1.0;
+    }
+    if bindings.pressed(&keys, KeyAction::ZoomOut) {
+        next.zoom -= 1.0;
+    }
+    // Outside the UI the arrow keys move the selected light.
+    let ui_focused = focus
+        .get()
+        .is_some_and(|entity| focusable_query.contains(entity));
+    let adjusting = focus
+        .get()
+        .is_some_and(|entity| adjustable_query.contains(entity));
+    if ui_focused {
+        if adjusting && keys.just_pressed(KeyCode::ArrowRight) {
+            next.adjust += 1.0;
+        } else if adjusting && keys.just_pressed(KeyCode::ArrowLeft) {
+            next.adjust -= 1.0;
+        } else if keys.any_just_pressed([KeyCode::ArrowDown, KeyCode::ArrowRight]) {
+            next.navigate = Some(NavAction::Next);
+        } else if keys.any_just_pressed([KeyCode::ArrowUp, KeyCode::ArrowLeft]) {
+            next.navigate = Some(NavAction::Previous);
+        }
+    }
+    next.activate = keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Space]);
+    next.back = keys.just_pressed(KeyCode::Escape);
+    next.menu = keys.just_pressed(KeyCode::Escape);
+
+    for gamepad in &gamepad_query {
+        // The stick orbits the way dragging the mouse in the same direction does.
+        next.orbit += gamepad.right_stick() * Vec2::new(1.0, -1.0);
+        next.pan += gamepad.left_stick();
+        next.zoom += gamepad.get(GamepadButton::RightTrigger2).unwrap_or(0.0)
+            - gamepad.get(GamepadButton::LeftTrigger2).unwrap_or(0.0);
+
+        if adjusting && gamepad.just_pressed(GamepadButton::DPadRight) {
+            next.adjust += 1.0;
+        } else if adjusting && gamepad.just_pressed(GamepadButton::DPadLeft) {
+            next.adjust -= 1.0;
+        } else if gamepad.any_just_pressed([GamepadButton::DPadDown, GamepadButton::DPadRight]) {
+            next.navigate = Some(NavAction::Next);
+        } else if gamepad.any_just_pressed([GamepadButton::DPadUp, GamepadButton::DPadLeft]) {
+            next.navigate = Some(NavAction::Previous);
+        }
+        next.activate |= gamepad.just_pressed(GamepadButton::South);
+        next.back |= gamepad.just_pressed(GamepadButton::East);
+        next.menu |= gamepad.just_pressed(GamepadButton::Start);
+    }
+
+    next.orbit = next.orbit.clamp(Vec2::NEG_ONE, Vec2::ONE);
+    next.pan = next.pan.clamp(Vec2::NEG_ONE, Vec2::ONE);
+    next.zoom = next.zoom.clamp(-1.0, 1.0);
+    next.adjust = next.adjust.clamp(-1.0, 1.0);
+    *actions = next;
+}
@@ -0,0 +1,241 @@
+//! The tubes of all-in-one liquid coolers, running from the radiator to the pump on the CPU.
+//!
+//! A radiator lying flat is mounted on top of the case, and any other at the front, see
+//! [`RadiatorMount::of`]. Its tubes leave from the bottom end of a front radiator and the rear
+//! end of a top one; the command palette flips them to the other end, or swaps the radiator
+//! for the first one in the catalog that mounts the other way. The tubes are drawn bending out
+//! of the radiator and into the side of the pump, and the compatibility checks warn when the
+//! pump sits above the radiator or a front radiator's tubes leave from its top, where the air
+//! in the loop gathers.
+
+use bevy::{color::palettes::css::DIM_GRAY, prelude::*};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest, CaseSpec},
+    command_palette::AddPaletteCommand,
+    parts::{InstalledPart, PartCatalog, PartKind, PartSpec, TogglePart},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<AioTubes>();
+    app.add_systems(Update, draw_tubes.run_if(in_state(Screen::Game)));
+    app.add_palette_command(
+        "AIO: move the tubes to the other end of the radiator",
+        |mut tubes: ResMut<AioTubes>| tubes.flipped = !tubes.flipped,
+    );
+    app.add_palette_command(
+        "AIO: mount the radiator at the front or on top",
+        swap_radiator_mount,
+    );
+}
+
+/// Which end of the radiator the tubes of a liquid cooler leave from.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct AioTubes {
+    /// Whether the tubes leave from the top of a front radiator or the front of a top one,
+    /// rather than the bottom or the rear.
+    pub flipped: bool,
+}
+
+/// Where a radiator is mounted in the case.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RadiatorMount {
+    Front,
+    Top,
+}
+
+impl RadiatorMount {
+    /// How a radiator of `size` is mounted: on top if it's thinnest from top to bottom, and
+    /// at the front otherwise.
+    pub fn of(size: [f32; 3]) -> Self {
+        let [width, height, depth] = size;
+        if height < width.min(depth) {
+            Self::Top
+        } else {
+            Self::Front
+        }
+    }
+
+    /// The way into the case from a radiator mounted here, which its tubes leave it.
+    fn inward(self) -> Vec3 {
+        match self {
+            Self::Front => Vec3::NEG_Z,
+            Self::Top => Vec3::NEG_Y,
+        }
+    }
+}
+
+impl AioTubes {
+    /// The way from the middle of a radiator mounted at `mount` to the end its tubes leave from.
+    pub fn end(self, mount: RadiatorMount) -> Vec3 {
+        match (mount, self.flipped) {
+            (RadiatorMount::Front, false) => Vec3::NEG_Y,
+            (RadiatorMount::Front, true) => Vec3::Y,
+            (RadiatorMount::Top, false) => Vec3::NEG_Z,
+            (RadiatorMount::Top, true) => Vec3::Z,
+        }
+    }
+}
+
+/// How far the pump's block stands off the CPU, in millimeters.
+const PUMP_HEIGHT: f32 = 25.0;
+const PUMP_SIZE: Vec3 = Vec3::new(PUMP_HEIGHT, 60.0, 60.0);
+/// How far in from the end of the radiator the tubes leave it, in millimeters.
+const PORT_INSET: f32 = 20.0;
+/// How far apart the two tubes run, in millimeters.
+const TUBE_SPACING: f32 = 24.0;
+/// How far the tubes run straight out of the radiator and the pump before they bend, in
+/// millimeters.
+const TUBE_BEND: f32 = 80.0;
+const TUBE_SEGMENTS: usize = 24;
+const TUBE_COLOR: Srgba = DIM_GRAY;
+
+/// Where the pump of a liquid cooler sits on a CPU mounted at `cpu_mount`, with the parts
+/// turned by `rotation`.
+pub fn pump_position(cpu_mount: Vec3, rotation: Quat) -> Vec3 {
+    cpu_mount + rotation * Vec3::X * PUMP_HEIGHT
+}
+
+/// The middle of the cap of the pump on a CPU mounted at `cpu_mount`, with the parts turned by
+/// `rotation`, facing away from the CPU.
+pub fn pump_cap(cpu_mount: Vec3, rotation: Quat) -> Vec3 {
+    pump_position(cpu_mount, rotation) + rotation * Vec3::X * PUMP_SIZE.x / 2.0
+}
+
+/// The paths of the two tubes from a radiator of `spec` mounted at `mount` to the pump at
+/// `pump`: straight out of the radiator at the end `tubes` says, bending round, and straight
+/// into the side of the pump, which faces the way the parts are turned by `rotation`.
+pub fn tube_paths(
+    spec: &PartSpec,
+    mount: Vec3,
+    pump: Vec3,
+    tubes: AioTubes,
+    rotation: Quat,
+) -> [Vec<Vec3>; 2] {
+    let radiator = RadiatorMount::of(spec.size);
+    let end = tubes.end(radiator);
+    let reach = end.abs().dot(Vec3::from(spec.size) / 2.0) - PORT_INSET;
+    let side = Vec3::X * TUBE_SPACING / 2.0;
+    let pump_side = Vec3::Z * TUBE_SPACING / 2.0;
+    let out_of_pump = rotation * Vec3::X * TUBE_BEND;
+    [-1.0, 1.0].map(|sign| {
+        let port = mount + end * reach + side * sign;
+        let inlet = pump + pump_side * sign;
+        CubicSegment::new_bezier([
+            port,
+            port + radiator.inward() * TUBE_BEND,
+            inlet + out_of_pump,
+            inlet,
+        ])
+        .iter_positions(TUBE_SEGMENTS)
+        .collect()
+    })
+}
+
+/// Draws the tubes of each installed radiator to the pump on the CPU, following the parts as
+/// the exploded view moves them.
+fn draw_tubes(
+    mut gizmos: Gizmos,
+    tubes: Res<AioTubes>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    part_query: Query<(&InstalledPart, &Transform)>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let (Some(catalog), Ok(level)) = (catalogs.get(&level_assets.catalog), level_query.single())
+    else {
+        return;
+    };
+    let rotation = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+        .map_or(Quat::IDENTITY, CaseSpec::part_rotation);
+    let of_kind = |kind| {
+        part_query.iter().filter_map(move |(part, transform)| {
+            let spec = catalog.get(&part.id).filter(|spec| spec.kind == kind)?;
+            Some((spec, transform.translation))
+        })
+    };
+    let Some((_, cpu)) = of_kind(PartKind::Cpu).next() else {
+        return;
+    };
+
+    let pump = pump_position(cpu, rotation);
+    gizmos.cube(
+        level.mul_transform(
+            Transform::from_translation(pump).with_scale((rotation * PUMP_SIZE).abs()),
+        ),
+        TUBE_COLOR,
+    );
+    for (spec, mount) in of_kind(PartKind::Radiator) {
+        for path in tube_paths(spec, mount, pump, *tubes, rotation) {
+            gizmos.linestrip(
+                path.into_iter().map(|point| level.transform_point(point)),
+                TUBE_COLOR,
+            );
+        }
+    }
+}
+
+/// Swaps the installed radiator for the first one in the catalog that mounts the other way.
+fn swap_radiator_mount(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    part_query: Query<&InstalledPart>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let Some(installed) = part_query
+        .iter()
+        .filter_map(|part| catalog.get(&part.id))
+        .find(|spec| spec.kind == PartKind::Radiator)
+    else {
+        info!("There's no radiator to mount the other way");
+        return;
+    };
+    let mount = RadiatorMount::of(installed.size);
+    let Some(other) = catalog
+        .parts
+        .iter()
+        .find(|spec| spec.kind == PartKind::Radiator && RadiatorMount::of(spec.size) != mount)
+    else {
+        info!("The catalog has no radiator that mounts the other way");
+        return;
+    };
+    commands.trigger(TogglePart(installed.id.clone()));
+    commands.trigger(TogglePart(other.id.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tubes_bend_from_the_radiator_to_the_pump() {
+        let radiator: PartSpec = ron::de::from_str(
+            "(id: \"radiator\", name: \"Radiator\", kind: Radiator, size: (140.0, 280.0, 30.0), \
+             position: (0.0, 0.0, 0.0))",
+        )
+        .unwrap();
+        assert_eq!(RadiatorMount::of(radiator.size), RadiatorMount::Front);
+        assert_eq!(RadiatorMount::of([120.0, 27.0, 275.0]), RadiatorMount::Top);
+
+        let mount = Vec3::new(40.0, 250.0, 170.0);
+        let pump = pump_position(Vec3::new(-91.5, 330.0, 0.0), Quat::IDENTITY);
+        let [first, second] = tube_paths(&radiator, mount, pump, default(), Quat::IDENTITY);
+        // Out of the bottom end of the radiator, into the case, and into the pump.
+        assert_eq!(first[0].y, 250.0 - 140.0 + PORT_INSET);
+        assert_eq!(first[0].x + TUBE_SPACING, second[0].x);
+        assert!(first[1].z < first[0].z);
+        assert!(first.last().unwrap().distance(pump) <= TUBE_SPACING);
+
+        let flipped = AioTubes { flipped: true };
+        let [up, _] = tube_paths(&radiator, mount, pump, flipped, Quat::IDENTITY);
+        assert_eq!(up[0].y, 250.0 + 140.0 - PORT_INSET);
+    }
+}
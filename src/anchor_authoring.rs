@@ -0,0 +1,328 @@
+//! An authoring mode for placing the anchors parts mount to, by clicking on the case model.
+//!
+//! Press F6 to start. Each click on the case adds an anchor for the picked kind of part where
+//! the click hit, straight into the loaded case manifest, and the installed parts mount again
+//! to take it up. "Export" saves the whole manifest as RON, to copy over
+//! `assets/models/cases.ron`. Clicks don't select parts while authoring.
+
+use bevy::{
+    color::palettes::css::{GRAY, YELLOW},
+    input_focus::tab_navigation::TabGroup,
+    prelude::*,
+};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    capture::{save_file, timestamp},
+    cases::{ActiveCase, Anchor, CaseManifest, Chamber},
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::{PressPosition, Unselectable},
+    parts::{InstalledPart, PartKind, remount_parts},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<AnchorAuthoring>();
+    app.add_systems(OnEnter(Screen::Game), spawn_authoring_panel);
+    app.add_systems(
+        Update,
+        (
+            toggle_authoring.run_if(key_just_pressed(KeyAction::AnchorAuthoring)),
+            sync_authoring_mode.run_if(
+                resource_changed::<AnchorAuthoring>
+                    .or(any_match_filter::<Added<Level>>)
+                    .or(any_match_filter::<Added<AuthoringPanel>>),
+            ),
+            update_authoring_labels,
+            draw_anchors.run_if(authoring_active),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(place_anchor);
+    app.add_observer(activate_authoring_button);
+    app.add_palette_command("Anchor authoring: start or stop", toggle_authoring);
+    app.add_palette_command(
+        "Anchor authoring: export the case manifest",
+        export_manifest,
+    );
+}
+
+#[derive(Resource, Debug, Default)]
+struct AnchorAuthoring {
+    active: bool,
+    /// Index into [`PartKind::ALL`] of the kind new anchors are for.
+    kind: usize,
+}
+
+impl AnchorAuthoring {
+    fn kind(&self) -> PartKind {
+        PartKind::ALL[self.kind % PartKind::ALL.len()]
+    }
+}
+
+#[derive(Component)]
+struct AuthoringPanel;
+
+/// Shows how many anchors the active case has.
+#[derive(Component)]
+struct AnchorCountLabel;
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum AuthoringButton {
+    NextKind,
+    RemoveLast,
+    Export,
+    Done,
+}
+
+/// Radius, in millimeters, of the marker drawn at each anchor.
+const MARKER_RADIUS: f32 = 8.0;
+const MARKER_COLOR: Srgba = GRAY;
+/// Anchors for the kind being placed stand out.
+const CURRENT_KIND_COLOR: Srgba = YELLOW;
+
+fn authoring_active(authoring: Res<AnchorAuthoring>) -> bool {
+    authoring.active
+}
+
+fn toggle_authoring(mut authoring: ResMut<AnchorAuthoring>) {
+    authoring.active = !authoring.active;
+}
+
+/// Shows the panel and keeps clicks on the level from selecting parts while authoring.
+fn sync_authoring_mode(
+    mut commands: Commands,
+    authoring: Res<AnchorAuthoring>,
+    level_query: Query<Entity, With<Level>>,
+    mut panel_query: Query<&mut Node, With<AuthoringPanel>>,
+) {
+    for level in &level_query {
+        if authoring.active {
+            commands.entity(level).insert(Unselectable);
+        } else {
+            commands.entity(level).remove::<Unselectable>();
+        }
+    }
+    for mut node in &mut panel_query {
+        node.display = if authoring.active {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn place_anchor(
+    click: On<Pointer<Click>>,
+    mut commands: Commands,
+    authoring: Res<AnchorAuthoring>,
+    press_position: Res<PressPosition>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    mut manifests: ResMut<Assets<CaseManifest>>,
+    parent_query: Query<&ChildOf>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+    part_query: Query<(), With<InstalledPart>>,
+) {
+    if !authoring.active
+        || !press_position.is_click(&click)
+        || click.entity != click.original_event_target()
+    {
+        return;
+    }
+    let Some(hit) = click.hit.position else {
+        return;
+    };
+    // Only the case itself, not the parts mounted in it.
+    let Some(level) = parent_query
+        .iter_ancestors(click.entity)
+        .take_while(|ancestor| !part_query.contains(*ancestor))
+        .find_map(|ancestor| level_query.get(ancestor).ok())
+    else {
+        return;
+    };
+    let Some(case) = manifests.get_mut(&level_assets.cases).and_then(|manifest| {
+        manifest
+            .cases
+            .iter_mut()
+            .find(|case| case.model == active_case.0)
+    }) else {
+        return;
+    };
+
+    let kind = authoring.kind();
+    let position = case
+        .lay_out(level.affine().inverse().transform_point3(hit))
+        .round();
+    let name = format!("{}_{}", kind.name(), case.anchors_for(kind).count() + 1);
+    info!("Added the anchor {name} at {position}");
+    case.anchors.push(Anchor {
+        name,
+        kind,
+        position: position.to_array(),
+        chamber: Chamber::Main,
+    });
+    commands.run_system_cached(remount_parts);
+}
+
+fn activate_authoring_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    mut authoring: ResMut<AnchorAuthoring>,
+    button_query: Query<&AuthoringButton>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    mut manifests: ResMut<Assets<CaseManifest>>,
+) {
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+
+    match button {
+        AuthoringButton::NextKind => authoring.kind = (authoring.kind + 1) % PartKind::ALL.len(),
+        AuthoringButton::RemoveLast => {
+            let removed = manifests
+                .get_mut(&level_assets.cases)
+                .and_then(|manifest| {
+                    manifest
+                        .cases
+                        .iter_mut()
+                        .find(|case| case.model == active_case.0)
+                })
+                .and_then(|case| case.anchors.pop());
+            if let Some(anchor) = removed {
+                info!("Removed the anchor {}", anchor.name);
+                commands.run_system_cached(remount_parts);
+            }
+        }
+        AuthoringButton::Export => commands.run_system_cached(export_manifest),
+        AuthoringButton::Done => authoring.active = false,
+    }
+}
+
+/// Saves the case manifest, with the anchors placed so far.
+fn export_manifest(level_assets: Res<LevelAssets>, manifests: Res<Assets<CaseManifest>>) {
+    let Some(manifest) = manifests.get(&level_assets.cases) else {
+        return;
+    };
+    match ron::ser::to_string_pretty(manifest, ron::ser::PrettyConfig::default()) {
+        Ok(ron) => save_file(&format!("cases-{}.ron", timestamp()), ron.into_bytes()),
+        Err(error) => error!("Could not write the case manifest: {error}"),
+    }
+}
+
+fn draw_anchors(
+    mut gizmos: Gizmos,
+    authoring: Res<AnchorAuthoring>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let (Some(case), Ok(level)) = (
+        manifests
+            .get(&level_assets.cases)
+            .and_then(|manifest| manifest.get(&active_case.0)),
+        level_query.single(),
+    ) else {
+        return;
+    };
+    for anchor in &case.anchors {
+        let color = if anchor.kind == authoring.kind() {
+            CURRENT_KIND_COLOR
+        } else {
+            MARKER_COLOR
+        };
+        let position = level.transform_point(case.lay_out(Vec3::from(anchor.position)));
+        gizmos.sphere(Isometry3d::from_translation(position), MARKER_RADIUS, color);
+    }
+}
+
+fn spawn_authoring_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Anchor Authoring"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            // Below the turntable status.
+            top: px(85.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            AuthoringPanel,
+            Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                // Shown while authoring.
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(6),
+            children![
+                (Text::new("Anchors"), TextColor(Color::WHITE)),
+                (
+                    AnchorCountLabel,
+                    Text::default(),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(Color::WHITE),
+                ),
+                button(AuthoringButton::NextKind, ""),
+                button(AuthoringButton::RemoveLast, "Remove last"),
+                button(AuthoringButton::Export, "Export"),
+                button(AuthoringButton::Done, "Done"),
+            ],
+        )],
+    ));
+}
+
+fn button(action: AuthoringButton, label: &str) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn update_authoring_labels(
+    authoring: Res<AnchorAuthoring>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    mut count_query: Query<&mut Text, With<AnchorCountLabel>>,
+    mut button_query: Query<(&AuthoringButton, &mut Text), Without<AnchorCountLabel>>,
+) {
+    let count = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+        .map_or(0, |case| case.anchors.len());
+    let label = format!("{count} on this case");
+    for mut text in &mut count_query {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+
+    let kind = format!("Placing: {:?}", authoring.kind());
+    for (button, mut text) in &mut button_query {
+        if *button == AuthoringButton::NextKind && text.0 != kind {
+            text.0 = kind.clone();
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Extra directories assets are looked up in, so teams can keep a shared part library in a
+//! user folder or on a network share instead of in the app's own `assets`.
+//!
+//! Give them at startup with `--asset-root <dir>`, as many times as needed, or in the
+//! `PC_CASE_ASSET_ROOTS` environment variable, separated like `PATH`. A file is looked up in
+//! each root in the order given before `assets`, so a library can bring models of its own and
+//! override the app's files, such as `parts/catalog.ron` to list its parts. Changes to files in
+//! any of the roots are hot reloaded like changes to `assets`.
+//!
+//! Asset packs dropped into `packs/` next to the app are found at startup too, each a
+//! directory or a `.zip` archive laid out like `assets`, as `validate-pack` checks. Their files
+//! are looked up after `assets`, so a pack adds models rather than replacing the app's, and
+//! each pack is also an asset source of its own, for `packs` to load and merge its catalog and
+//! cases. The web build has no folder to look in, so it doesn't load packs.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use bevy::{
+    asset::io::{
+        AssetReader, AssetReaderError, AssetSource, AssetSourceBuilder, AssetSourceId,
+        AssetWatcher, ErasedAssetReader, PathStream, Reader, file::FileAssetReader,
+    },
+    prelude::*,
+};
+
+use crate::zip_archive::{ZipArchive, ZipReader};
+
+/// The argument followed by an extra asset root.
+const ROOT_FLAG: &str = "--asset-root";
+/// The environment variable listing extra asset roots.
+const ROOTS_VARIABLE: &str = "PC_CASE_ASSET_ROOTS";
+/// How long a changed file has to be left alone before it's reloaded, as for `assets`.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// The directory next to the app asset packs are dropped into.
+const PACKS_DIR: &str = "packs";
+
+/// The extra roots assets are looked up in, once they've been registered.
+static EXTRA_ROOTS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+/// The packs found in [`PACKS_DIR`], once they've been registered.
+static PACKS: OnceLock<Vec<Pack>> = OnceLock::new();
+
+/// An asset pack found in [`PACKS_DIR`].
+#[derive(Debug, Clone)]
+pub struct Pack {
+    /// The name of the pack's directory.
+    pub name: String,
+    /// The pack's directory, or its archive.
+    pub root: PathBuf,
+    /// The asset source the pack's own files are read from, without the app's or other packs'.
+    pub source: String,
+    /// The pack's files, if it's a `.zip` archive rather than a directory.
+    archive: Option<Arc<ZipArchive>>,
+}
+
+impl Pack {
+    /// Whether the pack has a file at `path`, like `parts/catalog.ron`.
+    pub fn has(&self, path: &str) -> bool {
+        match &self.archive {
+            Some(archive) => archive.contains(Path::new(path)),
+            None => self.root.join(path).is_file(),
+        }
+    }
+
+    fn reader(&self) -> Box<dyn ErasedAssetReader> {
+        match &self.archive {
+            Some(archive) => Box::new(ZipReader(archive.clone())),
+            None => AssetSource::get_default_reader(self.root.to_string_lossy().into_owned())(),
+        }
+    }
+}
+
+/// The packs found at startup, in the order of their names.
+pub fn packs() -> &'static [Pack] {
+    PACKS.get().map_or(&[], Vec::as_slice)
+}
+
+/// Looks files up in the extra asset roots before `assets` and in the packs after it. Asset
+/// sources have to be in place before the [`AssetPlugin`] is added, so this is called before
+/// the `DefaultPlugins`.
+pub fn register_asset_roots(app: &mut App) {
+    let (extra_roots, mut problems) = requested_roots();
+    let (packs, pack_problems) = discover_packs();
+    problems.extend(pack_problems);
+    // Logged once there's a logger to log them.
+    let logged_roots = extra_roots.clone();
+    let logged_packs: Vec<String> = packs.iter().map(|pack| pack.name.clone()).collect();
+    app.add_systems(Startup, move || {
+        for problem in &problems {
+            warn!("{problem}");
+        }
+        for root in &logged_roots {
+            info!("Looking for assets in {} too", root.display());
+        }
+        for pack in &logged_packs {
+            info!("Adding the asset pack {pack}");
+        }
+    });
+    let _ = EXTRA_ROOTS.set(extra_roots.clone());
+    let _ = PACKS.set(packs.clone());
+    if extra_roots.is_empty() && packs.is_empty() {
+        return;
+    }
+
+    for pack in &packs {
+        let reader_pack = pack.clone();
+        let mut source = AssetSourceBuilder::new(move || reader_pack.reader());
+        // Archives are read once, so changes to them aren't picked up.
+        if pack.archive.is_none() {
+            source = source.with_watcher(AssetSource::get_default_watcher(
+                pack.root.to_string_lossy().into_owned(),
+                WATCH_DEBOUNCE,
+            ));
+        }
+        app.register_asset_source(AssetSourceId::from(pack.source.clone()), source);
+    }
+
+    let roots: Vec<String> = extra_roots
+        .iter()
+        .map(|root| root.to_string_lossy().into_owned())
+        .chain([AssetPlugin::default().file_path])
+        .collect();
+    let watched: Vec<String> = roots
+        .iter()
+        .cloned()
+        .chain(
+            packs
+                .iter()
+                .filter(|pack| pack.archive.is_none())
+                .map(|pack| pack.root.to_string_lossy().into_owned()),
+        )
+        .collect();
+    app.register_asset_source(
+        AssetSourceId::Default,
+        AssetSourceBuilder::new(move || {
+            Box::new(SearchPathReader(
+                roots
+                    .iter()
+                    .map(|root| AssetSource::get_default_reader(root.clone())())
+                    .chain(packs.iter().map(Pack::reader))
+                    .collect(),
+            ))
+        })
+        .with_watcher(move |sender| {
+            let watchers: Vec<_> = watched
+                .iter()
+                .filter_map(|root| {
+                    AssetSource::get_default_watcher(root.clone(), WATCH_DEBOUNCE)(sender.clone())
+                })
+                .collect();
+            (!watchers.is_empty()).then(|| Box::new(Watchers(watchers)) as Box<dyn AssetWatcher>)
+        }),
+    );
+}
+
+/// The file an asset at `path` is read from, for looking at the file itself, like when it last
+/// changed.
+pub fn locate(path: &Path) -> Option<PathBuf> {
+    let assets = FileAssetReader::get_base_path().join(AssetPlugin::default().file_path);
+    EXTRA_ROOTS
+        .get()
+        .into_iter()
+        .flatten()
+        .chain([&assets])
+        .chain(packs().iter().map(|pack| &pack.root))
+        .map(|root| root.join(path))
+        .find(|file| file.is_file())
+}
+
+/// The directories and `.zip` archives in [`PACKS_DIR`], in the order of their names, and
+/// what was wrong with the archives that had to be left out.
+fn discover_packs() -> (Vec<Pack>, Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(FileAssetReader::get_base_path().join(PACKS_DIR)) else {
+        return (Vec::new(), Vec::new());
+    };
+    let mut roots: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    roots.sort();
+
+    let mut packs = Vec::new();
+    let mut problems = Vec::new();
+    for root in roots {
+        let archive = if root.is_dir() {
+            None
+        } else if root.extension().is_some_and(|extension| extension == "zip") {
+            match ZipArchive::open(&root) {
+                Ok(archive) => Some(Arc::new(archive)),
+                Err(error) => {
+                    problems.push(format!("Can't open the pack {}: {error}", root.display()));
+                    continue;
+                }
+            }
+        } else {
+            continue;
+        };
+        packs.push(Pack {
+            name: root
+                .file_stem()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+            root,
+            // Numbered, as a file's name could have characters asset paths don't allow.
+            source: format!("pack-{}", packs.len()),
+            archive,
+        });
+    }
+    (packs, problems)
+}
+
+/// The roots from the command line, then the ones from the environment, and what was wrong
+/// with the ones that had to be left out.
+fn requested_roots() -> (Vec<PathBuf>, Vec<String>) {
+    let mut roots = Vec::new();
+    let mut problems = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == ROOT_FLAG {
+            match args.next() {
+                Some(root) => roots.push(PathBuf::from(root)),
+                None => problems.push(format!("{ROOT_FLAG} needs a directory after it")),
+            }
+        }
+    }
+    if let Some(variable) = std::env::var_os(ROOTS_VARIABLE) {
+        roots.extend(std::env::split_paths(&variable).filter(|root| !root.as_os_str().is_empty()));
+    }
+
+    let roots = roots
+        .into_iter()
+        .filter_map(|root| match root.canonicalize() {
+            Ok(root) if root.is_dir() => Some(root),
+            _ => {
+                problems.push(format!(
+                    "The asset root {} isn't a directory",
+                    root.display()
+                ));
+                None
+            }
+        })
+        .collect();
+    (roots, problems)
+}
+
+/// Reads each file from the first of the readers that has it. Errors other than a missing
+/// file are returned as they are, rather than hidden by a root further down.
+struct SearchPathReader(Vec<Box<dyn ErasedAssetReader>>);
+
+fn missing<T>(result: &Result<T, AssetReaderError>) -> bool {
+    matches!(result, Err(AssetReaderError::NotFound(_)))
+}
+
+impl AssetReader for SearchPathReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        for reader in &self.0 {
+            let result = reader.read(path).await;
+            if !missing(&result) {
+                return result;
+            }
+        }
+        Err(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        for reader in &self.0 {
+            let result = reader.read_meta(path).await;
+            if !missing(&result) {
+                return result;
+            }
+        }
+        Err(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        for reader in &self.0 {
+            let result = reader.read_directory(path).await;
+            if !missing(&result) {
+                return result;
+            }
+        }
+        Err(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        for reader in &self.0 {
+            let result = reader.is_directory(path).await;
+            if !missing(&result) {
+                return result;
+            }
+        }
+        Err(AssetReaderError::NotFound(path.to_path_buf()))
+    }
+}
+
+/// Keeps the watcher of every root running.
+#[expect(dead_code, reason = "the watchers only have to be kept alive")]
+struct Watchers(Vec<Box<dyn AssetWatcher>>);
+
+impl AssetWatcher for Watchers {}
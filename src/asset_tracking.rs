@@ -1,8 +1,16 @@
 //! A high-level way to load collections of asset handles as resources.
 
-use std::collections::VecDeque;
+use std::{marker::PhantomData, sync::Arc};
 
-use bevy::prelude::*;
+use bevy::{
+    asset::{
+        AssetLoadError, AssetLoader, LoadContext, RecursiveDependencyLoadState, UntypedAssetId,
+        io::Reader,
+    },
+    prelude::*,
+};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<ResourceHandles>();
@@ -21,51 +29,191 @@ impl LoadResource for App {
         self.init_asset::<T>();
         let world = self.world_mut();
         let value = T::from_world(world);
+        let mut dependencies = Vec::new();
+        value.visit_dependencies(&mut |id| dependencies.push(id));
         let assets = world.resource::<AssetServer>();
         let handle = assets.add(value);
         let mut handles = world.resource_mut::<ResourceHandles>();
-        handles
-            .waiting
-            .push_back((handle.untyped(), |world, handle| {
-                let assets = world.resource::<Assets<T>>();
-                if let Some(value) = assets.get(handle.id().typed::<T>()) {
-                    world.insert_resource(value.clone());
+        handles.dependencies.extend(dependencies.iter().copied());
+        handles.resources.push(TrackedResource {
+            handle: handle.untyped(),
+            dependencies,
+            inserted: false,
+            visit_dependencies: |world, handle, visit| {
+                if let Some(value) = world.get_resource::<T>() {
+                    value.visit_dependencies(&mut |id| visit(id));
+                } else if let Some(value) =
+                    world.resource::<Assets<T>>().get(handle.id().typed::<T>())
+                {
+                    value.visit_dependencies(&mut |id| visit(id));
                 }
-            }));
+            },
+            insert: |world, handle| {
+                // Moved rather than cloned out, so that the resource holds the only handles to
+                // its assets, and swapping one of them out lets the old asset unload.
+                let mut assets = world.resource_mut::<Assets<T>>();
+                if let Some(value) = assets.remove(handle.id().typed::<T>()) {
+                    world.insert_resource(value);
+                }
+            },
+        });
         self
     }
 }
 
+pub trait InitRonAsset {
+    /// Registers `T` as an [`Asset`] that is deserialized from RON files ending in one of
+    /// the `extensions`, such as `"catalog.ron"`.
+    fn init_ron_asset<T: Asset + DeserializeOwned>(
+        &mut self,
+        extensions: &'static [&'static str],
+    ) -> &mut Self;
+}
+
+impl InitRonAsset for App {
+    fn init_ron_asset<T: Asset + DeserializeOwned>(
+        &mut self,
+        extensions: &'static [&'static str],
+    ) -> &mut Self {
+        self.init_asset::<T>()
+            .register_asset_loader(RonAssetLoader::<T> {
+                extensions,
+                _marker: PhantomData,
+            })
+    }
+}
+
+#[derive(TypePath)]
+struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Error)]
+pub enum RonAssetLoaderError {
+    #[error("could not read the asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse the asset: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
+
 /// A function that inserts a loaded resource.
 type InsertLoadedResource = fn(&mut World, &UntypedHandle);
 
+/// A function that lists the assets a resource depends on, from the resource once it's been
+/// inserted, or from its asset while it's still waiting.
+type VisitResourceDependencies = fn(&World, &UntypedHandle, &mut dyn FnMut(UntypedAssetId));
+
+struct TrackedResource {
+    handle: UntypedHandle,
+    /// Checked one by one rather than through the resource's own load state, which stays
+    /// failed even after a failed dependency has been loaded again.
+    dependencies: Vec<UntypedAssetId>,
+    inserted: bool,
+    visit_dependencies: VisitResourceDependencies,
+    insert: InsertLoadedResource,
+}
+
 #[derive(Resource, Default)]
 pub struct ResourceHandles {
-    // Use a queue for waiting assets so they can be cycled through and moved to
-    // `finished` one at a time.
-    waiting: VecDeque<(UntypedHandle, InsertLoadedResource)>,
-    finished: Vec<UntypedHandle>,
+    /// The requested resources, in the order they were requested.
+    resources: Vec<TrackedResource>,
+    /// The assets the requested resources depend on, in the order they were requested.
+    /// Listed again every frame from the resources' current handles, so an asset swapped out
+    /// of a resource after it was inserted, like the active case, is tracked in its place.
+    dependencies: Vec<UntypedAssetId>,
 }
 
 impl ResourceHandles {
     /// Returns true if all requested [`Asset`]s have finished loading and are available as [`Resource`]s.
     pub fn is_all_done(&self) -> bool {
-        self.waiting.is_empty()
+        self.resources.iter().all(|resource| resource.inserted)
+    }
+
+    /// Every asset the requested resources depend on, with whether it has loaded yet.
+    pub fn dependency_progress<'a>(
+        &'a self,
+        assets: &'a AssetServer,
+    ) -> impl Iterator<Item = (UntypedAssetId, bool)> + 'a {
+        self.dependencies
+            .iter()
+            .map(|&id| (id, assets.is_loaded_with_dependencies(id)))
+    }
+
+    /// The assets the requested resources depend on that failed to load, and why.
+    pub fn failures<'a>(
+        &'a self,
+        assets: &'a AssetServer,
+    ) -> impl Iterator<Item = (UntypedAssetId, Arc<AssetLoadError>)> + 'a {
+        self.dependencies.iter().filter_map(|&id| {
+            match assets.get_recursive_dependency_load_state(id) {
+                Some(RecursiveDependencyLoadState::Failed(error)) => Some((id, error)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Starts loading the failed assets again. Their resources are still waiting, so they
+    /// get inserted once the assets do load.
+    pub fn retry_failed(&self, assets: &AssetServer) {
+        let paths: Vec<_> = self
+            .failures(assets)
+            .filter_map(|(id, _)| assets.get_path(id))
+            .map(|path| path.without_label().into_owned())
+            .collect();
+        for path in paths {
+            assets.reload(path);
+        }
     }
 }
 
 fn load_resource_assets(world: &mut World) {
     world.resource_scope(|world, mut resource_handles: Mut<ResourceHandles>| {
-        world.resource_scope(|world, assets: Mut<AssetServer>| {
-            for _ in 0..resource_handles.waiting.len() {
-                let (handle, insert_fn) = resource_handles.waiting.pop_front().unwrap();
-                if assets.is_loaded_with_dependencies(&handle) {
-                    insert_fn(world, &handle);
-                    resource_handles.finished.push(handle);
-                } else {
-                    resource_handles.waiting.push_back((handle, insert_fn));
-                }
+        let resource_handles = &mut *resource_handles;
+        resource_handles.dependencies.clear();
+        for resource in &mut resource_handles.resources {
+            resource.dependencies.clear();
+            (resource.visit_dependencies)(world, &resource.handle, &mut |id| {
+                resource.dependencies.push(id);
+            });
+            resource_handles
+                .dependencies
+                .extend(resource.dependencies.iter().copied());
+
+            if resource.inserted {
+                continue;
             }
-        });
+            let assets = world.resource::<AssetServer>();
+            let is_ready = assets.is_loaded(&resource.handle)
+                && resource
+                    .dependencies
+                    .iter()
+                    .all(|&id| assets.is_loaded_with_dependencies(id));
+            if is_ready {
+                (resource.insert)(world, &resource.handle);
+                resource.inserted = true;
+            }
+        }
     });
 }
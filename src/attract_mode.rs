@@ -0,0 +1,296 @@
+//! An attract mode that plays the playlist in `assets/attract/playlist.ron` once the app has
+//! been left alone for a while.
+//!
+//! Each entry of the playlist can show a preset build, switch the lighting environment and fly
+//! the camera along a path. Any input stops the playlist and puts the case, parts, camera and
+//! lighting back the way the user left them. Kiosk mode has its own loop, so attract mode
+//! stays out of its way.
+
+use bevy::{
+    input::{
+        InputSystems,
+        mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll},
+    },
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    actions::{gamepad_used, read_actions},
+    asset_tracking::InitRonAsset,
+    cases::{ActiveCase, SwitchCase},
+    command_palette::AddPaletteCommand,
+    kiosk::{PresetBuilds, kiosk_active},
+    lighting::LightingEnvironment,
+    orbit_camera_system,
+    parts::{InstallParts, InstalledPart, Planned},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<AttractPlaylist>(&["playlist.ron"]);
+    app.init_resource::<AttractMode>();
+    app.add_systems(
+        PreUpdate,
+        watch_for_input
+            .after(InputSystems)
+            // So the input that stops the playlist is reset before it's read as an action.
+            .before(read_actions)
+            .run_if(in_state(Screen::Game).and(not(kiosk_active))),
+    );
+    app.add_systems(
+        Update,
+        (
+            stop_attract_mode.run_if(kiosk_active),
+            play_attract_mode.before(orbit_camera_system),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(OnExit(Screen::Game), forget_attract_mode);
+    app.add_palette_command("Attract mode: play now", start_attract_mode);
+}
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct AttractPlaylist {
+    /// Minutes without input before the playlist starts.
+    pub idle_minutes: f32,
+    pub entries: Vec<PlaylistEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaylistEntry {
+    /// Seconds the entry plays for.
+    pub duration: f32,
+    /// Name of a build in `builds/presets.ron` to show, or `None` to keep the current one.
+    #[serde(default)]
+    pub build: Option<String>,
+    #[serde(default)]
+    pub lighting: Option<LightingEnvironment>,
+    /// Poses the camera moves through over the entry's duration. Left alone if empty.
+    #[serde(default)]
+    pub camera: Vec<CameraPose>,
+}
+
+impl PlaylistEntry {
+    /// The pose a fraction `t` of the way through the entry, easing in and out of each pose.
+    fn pose_at(&self, t: f32) -> Option<CameraPose> {
+        let first = self.camera.first()?;
+        if self.camera.len() == 1 {
+            return Some(*first);
+        }
+        let segments = self.camera.len() - 1;
+        let position = t.clamp(0.0, 1.0) * segments as f32;
+        let index = (position as usize).min(segments - 1);
+        let (from, to) = (self.camera[index], self.camera[index + 1]);
+        let s = position - index as f32;
+        Some(from.lerp(to, s * s * (3.0 - 2.0 * s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CameraPose {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub radius: f32,
+    pub target: [f32; 3],
+}
+
+impl CameraPose {
+    fn lerp(self, other: Self, s: f32) -> Self {
+        Self {
+            yaw: self.yaw.lerp(other.yaw, s),
+            pitch: self.pitch.lerp(other.pitch, s),
+            radius: self.radius.lerp(other.radius, s),
+            target: Vec3::from(self.target)
+                .lerp(Vec3::from(other.target), s)
+                .to_array(),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct AttractMode {
+    /// Seconds since the last input.
+    idle: f32,
+    playback: Option<Playback>,
+}
+
+struct Playback {
+    /// Index of the entry being played.
+    entry: usize,
+    /// Seconds into the entry.
+    elapsed: f32,
+    /// Whether the entry's build and lighting have been put up yet.
+    applied: bool,
+    /// What to go back to when the user returns.
+    previous: UserState,
+}
+
+/// The parts of the scene the playlist changes.
+struct UserState {
+    case_model: String,
+    parts: Vec<String>,
+    planned: Vec<String>,
+    orbit: Option<OrbitCamera>,
+    lighting: LightingEnvironment,
+}
+
+/// Counts the time since the last input, starting the playlist when it has been long enough
+/// and stopping it at the first input.
+fn watch_for_input(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut attract: ResMut<AttractMode>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse_buttons: ResMut<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    touches: Res<Touches>,
+    mut gamepad_query: Query<&mut Gamepad>,
+    level_assets: Res<LevelAssets>,
+    playlists: Res<Assets<AttractPlaylist>>,
+) {
+    let used = keys.get_just_pressed().next().is_some()
+        || mouse_buttons.get_just_pressed().next().is_some()
+        || mouse_motion.delta != Vec2::ZERO
+        || mouse_scroll.delta != Vec2::ZERO
+        || touches.iter().next().is_some()
+        || gamepad_query.iter().any(gamepad_used);
+    if !used {
+        attract.idle += time.delta_secs();
+        let idle_time = playlists
+            .get(&level_assets.playlist)
+            .map(|playlist| playlist.idle_minutes * 60.0);
+        if attract.playback.is_none()
+            && idle_time.is_some_and(|idle_time| attract.idle >= idle_time)
+        {
+            commands.run_system_cached(start_attract_mode);
+        }
+        return;
+    }
+
+    attract.idle = 0.0;
+    if attract.playback.is_some() {
+        commands.run_system_cached(stop_attract_mode);
+        // The input that woke the app up shouldn't do anything else.
+        keys.reset_all();
+        mouse_buttons.reset_all();
+        for mut gamepad in &mut gamepad_query {
+            gamepad.digital_mut().reset_all();
+        }
+    }
+}
+
+fn start_attract_mode(
+    mut attract: ResMut<AttractMode>,
+    active_case: Res<ActiveCase>,
+    lighting: Res<LightingEnvironment>,
+    part_query: Query<(&InstalledPart, Has<Planned>)>,
+    camera_query: Query<&OrbitCamera>,
+) {
+    if attract.playback.is_some() {
+        return;
+    }
+    let parts = part_query.iter().map(|(part, _)| part.id.clone()).collect();
+    let planned = part_query
+        .iter()
+        .filter(|(_, planned)| *planned)
+        .map(|(part, _)| part.id.clone())
+        .collect();
+    attract.playback = Some(Playback {
+        entry: 0,
+        elapsed: 0.0,
+        applied: false,
+        previous: UserState {
+            case_model: active_case.0.clone(),
+            parts,
+            planned,
+            orbit: camera_query.single().ok().cloned(),
+            lighting: *lighting,
+        },
+    });
+    info!("Playing the attract mode playlist");
+}
+
+fn stop_attract_mode(
+    mut commands: Commands,
+    mut attract: ResMut<AttractMode>,
+    mut lighting: ResMut<LightingEnvironment>,
+    mut camera_query: Query<&mut OrbitCamera>,
+) {
+    let Some(playback) = attract.playback.take() else {
+        return;
+    };
+    let previous = playback.previous;
+    commands.trigger(SwitchCase(previous.case_model));
+    commands.trigger(InstallParts {
+        parts: previous.parts,
+        planned: previous.planned,
+    });
+    lighting.set_if_neq(previous.lighting);
+    if let (Some(orbit), Ok(mut camera)) = (previous.orbit, camera_query.single_mut()) {
+        *camera = orbit;
+    }
+}
+
+fn play_attract_mode(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut attract: ResMut<AttractMode>,
+    mut lighting: ResMut<LightingEnvironment>,
+    level_assets: Res<LevelAssets>,
+    playlists: Res<Assets<AttractPlaylist>>,
+    presets: Res<Assets<PresetBuilds>>,
+    mut camera_query: Query<&mut OrbitCamera>,
+) {
+    let Some(playback) = &mut attract.playback else {
+        return;
+    };
+    let Some(playlist) = playlists
+        .get(&level_assets.playlist)
+        .filter(|playlist| !playlist.entries.is_empty())
+    else {
+        return;
+    };
+    let entry = &playlist.entries[playback.entry % playlist.entries.len()];
+
+    if !playback.applied {
+        playback.applied = true;
+        if let Some(name) = &entry.build {
+            match presets
+                .get(&level_assets.presets)
+                .and_then(|presets| presets.get(name))
+            {
+                Some(build) => build.apply(&mut commands),
+                None => warn!("The attract playlist shows \"{name}\", which is not a preset build"),
+            }
+        }
+        if let Some(environment) = entry.lighting {
+            lighting.set_if_neq(environment);
+        }
+    }
+
+    playback.elapsed += time.delta_secs();
+    let t = playback.elapsed / entry.duration.max(f32::EPSILON);
+    if let Some(pose) = entry.pose_at(t) {
+        for mut orbit in &mut camera_query {
+            orbit.yaw = pose.yaw;
+            orbit.pitch = pose.pitch;
+            orbit.radius = pose.radius;
+            orbit.desired_radius = pose.radius;
+            orbit.target = Vec3::from(pose.target);
+        }
+    }
+
+    if t >= 1.0 {
+        playback.entry = (playback.entry + 1) % playlist.entries.len();
+        playback.elapsed = 0.0;
+        playback.applied = false;
+    }
+}
+
+fn forget_attract_mode(mut attract: ResMut<AttractMode>) {
+    // The level is despawned, so there is nothing to restore.
+    *attract = AttractMode::default();
+}
@@ -0,0 +1,415 @@
+//! Orthographic front, side, top and back views of the case, for blueprint-style reference
+//! images.
+//!
+//! Press O, or use the panel on the right, to switch the orbit camera between its usual view
+//! and the blueprint views. A blueprint view looks straight at the case without perspective:
+//! panning and zooming still work, orbiting doesn't. A grid behind the case and dimension
+//! lines for the case's size are drawn with it, and show in captures of the view too.
+
+use bevy::{
+    camera::ScalingMode, color::palettes::css::AQUA, input_focus::tab_navigation::TabGroup,
+    prelude::*,
+};
+
+use crate::{
+    Level, LevelAssets, OrbitCamera, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    labels::BillboardLabel,
+    orbit_camera_system,
+    settings::Settings,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Blueprint>();
+    app.add_systems(OnEnter(Screen::Game), spawn_blueprint_panel);
+    app.add_systems(OnExit(Screen::Game), leave_blueprint_view);
+    app.add_systems(
+        Update,
+        (
+            cycle_blueprint_views.run_if(key_just_pressed(KeyAction::BlueprintViews)),
+            apply_blueprint_view.after(orbit_camera_system),
+            update_dimension_labels.run_if(
+                resource_changed::<Blueprint>
+                    .or(resource_changed::<ActiveCase>)
+                    .or(resource_changed::<Settings>)
+                    .or(any_match_filter::<Added<Level>>),
+            ),
+            draw_blueprint_overlay,
+            update_button_labels.run_if(
+                resource_changed::<Blueprint>.or(any_match_filter::<Added<BlueprintButton>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_blueprint_button);
+    app.add_palette_command("Blueprint: cycle the views", cycle_blueprint_views);
+    app.add_palette_command("Blueprint: show or hide the grid", toggle_grid);
+    app.add_palette_command("Blueprint: show or hide the dimensions", toggle_dimensions);
+}
+
+#[derive(Resource, Debug)]
+pub struct Blueprint {
+    /// The blueprint view the orbit camera is in, if any.
+    view: Option<BlueprintView>,
+    grid: bool,
+    dimensions: bool,
+    /// The orbit camera's angles and projection from before the blueprint view, to go back to.
+    saved: Option<(f32, f32, Projection)>,
+}
+
+impl Default for Blueprint {
+    fn default() -> Self {
+        Self {
+            view: None,
+            grid: true,
+            dimensions: true,
+            saved: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BlueprintView {
+    Front,
+    Side,
+    Top,
+    Back,
+}
+
+impl BlueprintView {
+    pub const ALL: [Self; 4] = [Self::Front, Self::Side, Self::Top, Self::Back];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Front => "Front",
+            Self::Side => "Side",
+            Self::Top => "Top",
+            Self::Back => "Back",
+        }
+    }
+
+    /// The direction from the case to the camera, and which way is up on screen.
+    pub fn toward_camera_and_up(self) -> (Vec3, Vec3) {
+        match self {
+            Self::Front => (Vec3::Z, Vec3::Y),
+            Self::Side => (Vec3::X, Vec3::Y),
+            Self::Top => (Vec3::Y, Vec3::NEG_Z),
+            Self::Back => (Vec3::NEG_Z, Vec3::Y),
+        }
+    }
+
+    /// The views in the order O steps through them, ending back at the usual view.
+    fn next(view: Option<Self>) -> Option<Self> {
+        match view {
+            None => Some(Self::Front),
+            Some(Self::Front) => Some(Self::Side),
+            Some(Self::Side) => Some(Self::Top),
+            Some(Self::Top) => Some(Self::Back),
+            Some(Self::Back) => None,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum BlueprintButton {
+    /// Switches to the view, or back to the usual one.
+    View(Option<BlueprintView>),
+    Grid,
+    Dimensions,
+}
+
+/// Labels a dimension line with its length.
+#[derive(Component, Debug, Clone, Copy)]
+struct DimensionLabel;
+
+/// Far enough out to stay clear of the case; an orthographic view doesn't shrink with distance.
+const CAMERA_DISTANCE: f32 = 2000.0;
+/// Millimeters between the lines of the grid.
+const GRID_SPACING: f32 = 50.0;
+/// Cells of the grid along each side.
+const GRID_CELLS: u32 = 40;
+const GRID_COLOR: Color = Color::srgba(0.5, 0.6, 0.8, 0.25);
+const DIMENSION_COLOR: Srgba = AQUA;
+/// How far, in millimeters, the dimension lines are drawn from the case's sides.
+const DIMENSION_OFFSET: f32 = 40.0;
+/// Length of the ticks across the ends of a dimension line, in millimeters.
+const TICK_LENGTH: f32 = 12.0;
+const LABEL_GROUP: &str = "Blueprint dimensions";
+/// The text of the button for the view that's shown.
+const ACTIVE_TEXT: Color = Color::srgb(1.0, 0.85, 0.3);
+
+fn cycle_blueprint_views(mut blueprint: ResMut<Blueprint>) {
+    blueprint.view = BlueprintView::next(blueprint.view);
+}
+
+fn toggle_grid(mut blueprint: ResMut<Blueprint>) {
+    blueprint.grid = !blueprint.grid;
+}
+
+fn toggle_dimensions(mut blueprint: ResMut<Blueprint>) {
+    blueprint.dimensions = !blueprint.dimensions;
+}
+
+/// The camera is despawned with the level, and comes back in the usual view.
+fn leave_blueprint_view(mut blueprint: ResMut<Blueprint>) {
+    blueprint.view = None;
+    blueprint.saved = None;
+}
+
+/// Puts the orbit camera in front of its target for the blueprint view, once it has panned and
+/// zoomed, and back where it was when the view is left. Cameras that follow the orbit camera
+/// run after this.
+pub fn apply_blueprint_view(
+    mut blueprint: ResMut<Blueprint>,
+    mut camera_query: Query<(&mut OrbitCamera, &mut Transform, &mut Projection)>,
+) {
+    let Ok((mut orbit, mut transform, mut projection)) = camera_query.single_mut() else {
+        return;
+    };
+    let Some(view) = blueprint.view else {
+        if let Some((yaw, pitch, saved_projection)) = blueprint.saved.take() {
+            orbit.yaw = yaw;
+            orbit.pitch = pitch;
+            *projection = saved_projection;
+            *transform = orbit.transform();
+        }
+        return;
+    };
+
+    match &blueprint.saved {
+        // Orbiting is undone, so the camera is where it was once the view is left.
+        Some((yaw, pitch, _)) => {
+            orbit.yaw = *yaw;
+            orbit.pitch = *pitch;
+        }
+        None => blueprint.saved = Some((orbit.yaw, orbit.pitch, projection.clone())),
+    }
+
+    // As much of the scene fits in view as would around the target in perspective, so
+    // zooming works the same.
+    let fov = match blueprint
+        .saved
+        .as_ref()
+        .map(|(_, _, projection)| projection)
+    {
+        Some(Projection::Perspective(perspective)) => perspective.fov,
+        _ => PerspectiveProjection::default().fov,
+    };
+    let viewport_height = 2.0 * orbit.radius * (fov / 2.0).tan();
+    match projection.as_mut() {
+        Projection::Orthographic(orthographic) => {
+            orthographic.scaling_mode = ScalingMode::FixedVertical { viewport_height };
+        }
+        _ => {
+            *projection = Projection::from(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical { viewport_height },
+                far: CAMERA_DISTANCE * 2.0,
+                ..OrthographicProjection::default_3d()
+            });
+        }
+    }
+
+    let (toward_camera, up) = view.toward_camera_and_up();
+    *transform = Transform::from_translation(orbit.target + toward_camera * CAMERA_DISTANCE)
+        .looking_at(orbit.target, up);
+}
+
+/// The size of the active case in millimeters, with its origin at the floor's center.
+fn case_box(
+    active_case: &ActiveCase,
+    level_assets: &LevelAssets,
+    manifests: &Assets<CaseManifest>,
+) -> Option<(Vec3, Vec3)> {
+    let case = manifests.get(&level_assets.cases)?.get(&active_case.0)?;
+    let size = Vec3::from(case.dimensions);
+    Some((Vec3::Y * size.y / 2.0, size / 2.0))
+}
+
+/// A dimension line across the case's box in `view`, in the level's space: its ends and where
+/// its label goes. `along_up` picks the line up the left side over the one along the bottom.
+fn dimension_line(
+    view: BlueprintView,
+    center: Vec3,
+    half_size: Vec3,
+    along_up: bool,
+) -> (Vec3, Vec3, Vec3) {
+    let (toward_camera, up) = view.toward_camera_and_up();
+    let right = up.cross(toward_camera);
+    let extent = |axis: Vec3| axis.abs().dot(half_size);
+    let front = toward_camera * extent(toward_camera);
+    let (along, across) = if along_up { (up, -right) } else { (right, -up) };
+    let start =
+        center + front - along * extent(along) + across * (extent(across) + DIMENSION_OFFSET);
+    let end = start + along * 2.0 * extent(along);
+    let label = start.midpoint(end) + across * TICK_LENGTH * 2.0;
+    (start, end, label)
+}
+
+fn update_dimension_labels(
+    mut commands: Commands,
+    blueprint: Res<Blueprint>,
+    settings: Res<Settings>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    label_query: Query<Entity, With<DimensionLabel>>,
+    level_query: Query<Entity, With<Level>>,
+) {
+    for label in &label_query {
+        commands.entity(label).despawn();
+    }
+    if !blueprint.dimensions {
+        return;
+    }
+    let (Some(view), Ok(level)) = (blueprint.view, level_query.single()) else {
+        return;
+    };
+    let Some((center, half_size)) = case_box(&active_case, &level_assets, &manifests) else {
+        return;
+    };
+
+    for along_up in [false, true] {
+        let (start, end, label) = dimension_line(view, center, half_size, along_up);
+        commands.spawn((
+            Name::new("Dimension Label"),
+            DimensionLabel,
+            Transform::from_translation(label),
+            BillboardLabel {
+                text: settings.format_length(start.distance(end)),
+                group: LABEL_GROUP.to_string(),
+                offset: Vec3::ZERO,
+            },
+            ChildOf(level),
+        ));
+    }
+}
+
+fn draw_blueprint_overlay(
+    mut gizmos: Gizmos,
+    blueprint: Res<Blueprint>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let (Some(view), Ok(level)) = (blueprint.view, level_query.single()) else {
+        return;
+    };
+    let Some((center, half_size)) = case_box(&active_case, &level_assets, &manifests) else {
+        return;
+    };
+    let (toward_camera, up) = view.toward_camera_and_up();
+
+    if blueprint.grid {
+        // Just behind the case, facing the camera.
+        let behind = center - toward_camera * (toward_camera.abs().dot(half_size) + 1.0);
+        let rotation = Transform::IDENTITY.looking_to(-toward_camera, up).rotation;
+        gizmos.grid(
+            Isometry3d::new(level.transform_point(behind), level.rotation() * rotation),
+            UVec2::splat(GRID_CELLS),
+            Vec2::splat(GRID_SPACING),
+            GRID_COLOR,
+        );
+    }
+
+    if blueprint.dimensions {
+        for along_up in [false, true] {
+            let (start, end, _) = dimension_line(view, center, half_size, along_up);
+            let across = (end - start).normalize().cross(toward_camera) * TICK_LENGTH / 2.0;
+            gizmos.line(
+                level.transform_point(start),
+                level.transform_point(end),
+                DIMENSION_COLOR,
+            );
+            for point in [start, end] {
+                gizmos.line(
+                    level.transform_point(point - across),
+                    level.transform_point(point + across),
+                    DIMENSION_COLOR,
+                );
+            }
+        }
+    }
+}
+
+fn spawn_blueprint_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Blueprint Panel"),
+            DespawnOnExit(Screen::Game),
+            Node {
+                position_type: PositionType::Absolute,
+                top: percent(40.0),
+                right: px(5.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(8),
+            children![(Text::new("Blueprint (O)"), TextColor(Color::WHITE))],
+        ))
+        .with_children(|panel| {
+            panel.spawn(button(BlueprintButton::View(None)));
+            for view in BlueprintView::ALL {
+                panel.spawn(button(BlueprintButton::View(Some(view))));
+            }
+            panel.spawn(button(BlueprintButton::Grid));
+            panel.spawn(button(BlueprintButton::Dimensions));
+        });
+}
+
+fn button(action: BlueprintButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::default(),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn update_button_labels(
+    blueprint: Res<Blueprint>,
+    mut button_query: Query<(&BlueprintButton, &mut Text, &mut TextColor)>,
+) {
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    for (button, mut text, mut color) in &mut button_query {
+        text.0 = match button {
+            BlueprintButton::View(None) => "Perspective".to_string(),
+            BlueprintButton::View(Some(view)) => view.label().to_string(),
+            BlueprintButton::Grid => format!("Grid: {}", on_off(blueprint.grid)),
+            BlueprintButton::Dimensions => {
+                format!("Dimensions: {}", on_off(blueprint.dimensions))
+            }
+        };
+        color.0 = match button {
+            BlueprintButton::View(view) if *view == blueprint.view => ACTIVE_TEXT,
+            _ => Color::WHITE,
+        };
+    }
+}
+
+fn activate_blueprint_button(
+    activate: On<Activate>,
+    button_query: Query<&BlueprintButton>,
+    mut blueprint: ResMut<Blueprint>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(BlueprintButton::View(view)) => blueprint.view = *view,
+        Ok(BlueprintButton::Grid) => blueprint.grid = !blueprint.grid,
+        Ok(BlueprintButton::Dimensions) => blueprint.dimensions = !blueprint.dimensions,
+        Err(_) => {}
+    }
+}
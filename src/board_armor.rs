@@ -0,0 +1,141 @@
+//! The armor of motherboards: the heatsinks over their M.2 slots and chipset, which can be taken
+//! off and put back from the command palette.
+//!
+//! Each piece a motherboard's catalog entry lists is shown as a box over the board. While the
+//! M.2 heatsinks are on, the M.2 drives under them are hidden, and the compatibility checks
+//! see whether the graphics card's backplate runs into any of the pieces that are on.
+
+use bevy::prelude::*;
+
+use crate::{
+    LevelAssets, Screen,
+    command_palette::AddPaletteCommand,
+    parts::{ArmorKind, DriveSize, InstalledPart, PartCatalog},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BoardArmor>();
+    app.add_systems(
+        Update,
+        (
+            spawn_armor.run_if(any_match_filter::<Added<InstalledPart>>),
+            show_fitted_armor.run_if(
+                resource_changed::<BoardArmor>
+                    .or(any_match_filter::<Added<ArmorPiece>>)
+                    .or(any_match_filter::<Added<InstalledPart>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command(
+        "Board armor: take the M.2 heatsinks off or put them back",
+        |mut armor: ResMut<BoardArmor>| armor.m2_heatsinks = !armor.m2_heatsinks,
+    );
+    app.add_palette_command(
+        "Board armor: take the chipset armor off or put it back",
+        |mut armor: ResMut<BoardArmor>| armor.chipset = !armor.chipset,
+    );
+}
+
+/// Which pieces of the motherboard's armor are on.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct BoardArmor {
+    pub m2_heatsinks: bool,
+    pub chipset: bool,
+}
+
+impl Default for BoardArmor {
+    fn default() -> Self {
+        Self {
+            m2_heatsinks: true,
+            chipset: true,
+        }
+    }
+}
+
+impl BoardArmor {
+    pub fn fitted(self, kind: ArmorKind) -> bool {
+        match kind {
+            ArmorKind::M2Heatsink => self.m2_heatsinks,
+            ArmorKind::Chipset => self.chipset,
+        }
+    }
+}
+
+/// A piece of armor, a child of the motherboard it's on.
+#[derive(Component, Debug, Clone, Copy)]
+struct ArmorPiece(ArmorKind);
+
+const ARMOR_COLOR: Color = Color::srgb(0.25, 0.26, 0.28);
+
+fn spawn_armor(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    part_query: Query<(Entity, &InstalledPart), Added<InstalledPart>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    for (entity, part) in &part_query {
+        let Some(spec) = catalog.get(&part.id) else {
+            continue;
+        };
+        for piece in &spec.armor {
+            commands.spawn((
+                Name::new(piece.kind.name()),
+                ArmorPiece(piece.kind),
+                Mesh3d(meshes.add(Cuboid::from_size(Vec3::from(piece.size)))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: ARMOR_COLOR,
+                    metallic: 0.8,
+                    perceptual_roughness: 0.4,
+                    ..default()
+                })),
+                Transform::from_translation(Vec3::from(piece.offset)),
+                ChildOf(entity),
+            ));
+        }
+    }
+}
+
+/// Shows the pieces that are on, and the M.2 drives while there are no heatsinks over them.
+fn show_fitted_armor(
+    mut commands: Commands,
+    armor: Res<BoardArmor>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    mut piece_query: Query<(&ArmorPiece, &mut Visibility)>,
+    part_query: Query<(Entity, &InstalledPart, Option<&Visibility>), Without<ArmorPiece>>,
+) {
+    let visibility = |shown| {
+        if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    };
+    let mut heatsinks = false;
+    for (piece, mut piece_visibility) in &mut piece_query {
+        piece_visibility.set_if_neq(visibility(armor.fitted(piece.0)));
+        heatsinks |= piece.0 == ArmorKind::M2Heatsink && armor.m2_heatsinks;
+    }
+
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let shown = visibility(!heatsinks);
+    for (entity, part, part_visibility) in &part_query {
+        // Parts with a model don't have a visibility of their own until they're hidden.
+        if part_visibility.copied().unwrap_or(Visibility::Inherited) != shown
+            && catalog
+                .get(&part.id)
+                .is_some_and(|spec| spec.drive_size == Some(DriveSize::M2))
+        {
+            commands.entity(entity).insert(shown);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Describing an existing PC step by step from the main menu, as a starting point for planning
+//! its upgrades.
+//!
+//! The wizard asks which of the cases in `assets/models/cases.ron` the PC is in, which of the
+//! catalog's coolers or radiators cools its CPU, and how many case fans it has, then opens the
+//! level with that case and those parts installed as owned. Escape goes back a step, and back
+//! to the main menu from the first one.
+
+use bevy::{input_focus::tab_navigation::TabGroup, prelude::*};
+
+use crate::{
+    LevelAssets, Menu, Screen,
+    actions::menu_pressed,
+    cases::{CaseManifest, SwitchCase},
+    init_spawn,
+    parts::{InstallParts, PartCatalog, PartKind},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Wizard>();
+    app.add_systems(OnEnter(Menu::Wizard), spawn_wizard_screen);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(menu_pressed),
+            rebuild_wizard_screen
+                .run_if(resource_changed::<Wizard>.or(any_match_filter::<Added<WizardScreen>>)),
+        )
+            .chain()
+            .run_if(in_state(Menu::Wizard)),
+    );
+    app.add_systems(
+        OnEnter(Screen::Game),
+        (install_described_build, forget_described_build)
+            .chain()
+            .after(init_spawn)
+            .run_if(resource_exists::<DescribedBuild>),
+    );
+    app.add_observer(activate_wizard_button);
+}
+
+/// The answers given so far.
+#[derive(Resource, Debug, Default)]
+struct Wizard {
+    step: WizardStep,
+    /// The model of the case, as listed in `models/cases.ron`.
+    case_model: String,
+    /// Catalog id of the cooler or radiator, or none for a stock or passive cooler.
+    cooler: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+enum WizardStep {
+    #[default]
+    Case,
+    Cooler,
+    Fans,
+}
+
+/// The build the wizard described, installed once the game screen is entered.
+#[derive(Resource, Debug)]
+struct DescribedBuild {
+    case_model: String,
+    parts: Vec<String>,
+}
+
+#[derive(Component)]
+struct WizardScreen;
+
+#[derive(Component, Debug, Clone, Eq, PartialEq)]
+enum WizardButton {
+    Case(String),
+    Cooler(Option<String>),
+    Fans(usize),
+    Back,
+}
+
+const BACKGROUND: Color = Color::BLACK;
+const BUTTON_WIDTH: f32 = 260.0;
+
+/// The catalog parts a PC cooled by `cooler` with `fans` case fans is made of: the cooler,
+/// then the first `fans` fans of the catalog.
+fn described_parts(catalog: &PartCatalog, cooler: Option<&str>, fans: usize) -> Vec<String> {
+    cooler
+        .map(str::to_string)
+        .into_iter()
+        .chain(
+            catalog
+                .parts
+                .iter()
+                .filter(|part| part.kind == PartKind::Fan)
+                .take(fans)
+                .map(|part| part.id.clone()),
+        )
+        .collect()
+}
+
+fn spawn_wizard_screen(mut commands: Commands, mut wizard: ResMut<Wizard>) {
+    *wizard = Wizard::default();
+    commands.spawn((
+        Name::new("Build Wizard"),
+        WizardScreen,
+        DespawnOnExit(Menu::Wizard),
+        Node {
+            width: percent(100.0),
+            height: percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: px(8.0),
+            padding: UiRect::all(px(20.0)),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND),
+        TabGroup::modal(),
+    ));
+}
+
+fn rebuild_wizard_screen(
+    mut commands: Commands,
+    wizard: Res<Wizard>,
+    screen_query: Query<Entity, With<WizardScreen>>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Ok(screen) = screen_query.single() else {
+        return;
+    };
+    let catalog = catalogs.get(&level_assets.catalog);
+    let (question, choices): (_, Vec<(String, WizardButton)>) = match wizard.step {
+        WizardStep::Case => (
+            "Which case is your PC in?",
+            manifests
+                .get(&level_assets.cases)
+                .into_iter()
+                .flat_map(|manifest| &manifest.cases)
+                .map(|case| {
+                    let label = format!("{} ({})", case.name, case.form_factor);
+                    (label, WizardButton::Case(case.model.clone()))
+                })
+                .collect(),
+        ),
+        WizardStep::Cooler => (
+            "What cools its CPU?",
+            catalog
+                .into_iter()
+                .flat_map(|catalog| &catalog.parts)
+                .filter(|part| matches!(part.kind, PartKind::Cooler | PartKind::Radiator))
+                .map(|part| {
+                    (
+                        part.name.clone(),
+                        WizardButton::Cooler(Some(part.id.clone())),
+                    )
+                })
+                .chain([(
+                    "The stock cooler, or none".to_string(),
+                    WizardButton::Cooler(None),
+                )])
+                .collect(),
+        ),
+        WizardStep::Fans => {
+            let fans = catalog.map_or(0, |catalog| {
+                catalog
+                    .parts
+                    .iter()
+                    .filter(|part| part.kind == PartKind::Fan)
+                    .count()
+            });
+            (
+                "How many case fans does it have?",
+                (0..=fans)
+                    .map(|count| (count.to_string(), WizardButton::Fans(count)))
+                    .collect(),
+            )
+        }
+    };
+
+    commands
+        .entity(screen)
+        .despawn_children()
+        .with_children(|screen| {
+            screen.spawn((Text::new(question), TextColor(Color::WHITE)));
+            for (label, action) in choices {
+                screen.spawn(button(label, action));
+            }
+            screen.spawn(button("Back (Esc)", WizardButton::Back));
+        });
+}
+
+fn button(label: impl Into<String>, action: WizardButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Center),
+        Node {
+            width: px(BUTTON_WIDTH),
+            padding: UiRect::axes(px(10.0), px(4.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn go_back(mut wizard: ResMut<Wizard>, mut next_menu: ResMut<NextState<Menu>>) {
+    match wizard.step {
+        WizardStep::Case => next_menu.set(Menu::Closed),
+        WizardStep::Cooler => wizard.step = WizardStep::Case,
+        WizardStep::Fans => wizard.step = WizardStep::Cooler,
+    }
+}
+
+fn activate_wizard_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    mut wizard: ResMut<Wizard>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    button_query: Query<&WizardButton>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Ok(action) = button_query.get(activate.entity) else {
+        return;
+    };
+    match action {
+        WizardButton::Case(model) => {
+            wizard.case_model.clone_from(model);
+            wizard.step = WizardStep::Cooler;
+        }
+        WizardButton::Cooler(cooler) => {
+            wizard.cooler.clone_from(cooler);
+            wizard.step = WizardStep::Fans;
+        }
+        WizardButton::Fans(fans) => {
+            let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+                return;
+            };
+            commands.insert_resource(DescribedBuild {
+                case_model: wizard.case_model.clone(),
+                parts: described_parts(catalog, wizard.cooler.as_deref(), *fans),
+            });
+            next_menu.set(Menu::Closed);
+            next_screen.set(Screen::Game);
+        }
+        WizardButton::Back => commands.run_system_cached(go_back),
+    }
+}
+
+fn install_described_build(mut commands: Commands, build: Res<DescribedBuild>) {
+    commands.trigger(SwitchCase(build.case_model.clone()));
+    commands.trigger(InstallParts {
+        parts: build.parts.clone(),
+        planned: Vec::new(),
+    });
+}
+
+fn forget_described_build(mut commands: Commands) {
+    commands.remove_resource::<DescribedBuild>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooler_then_fans() {
+        let part = |id: &str, kind: &str| {
+            format!(
+                "(id: \"{id}\", name: \"{id}\", kind: {kind}, size: (1.0, 1.0, 1.0), \
+                 position: (0.0, 0.0, 0.0))"
+            )
+        };
+        let catalog: PartCatalog = ron::de::from_str(&format!(
+            "(parts: [{}, {}, {}])",
+            part("fan_a", "Fan"),
+            part("tower", "Cooler"),
+            part("fan_b", "Fan"),
+        ))
+        .unwrap();
+        assert_eq!(
+            described_parts(&catalog, Some("tower"), 1),
+            ["tower", "fan_a"]
+        );
+        assert_eq!(described_parts(&catalog, None, 5), ["fan_a", "fan_b"]);
+        assert!(described_parts(&catalog, None, 0).is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+//! Moving the orbit camera to frame a part.
+//!
+//! Selecting a part, or double-clicking it, eases the camera's target to the part's center and
+//! zooms until its bounding box fits the view. F frames the selected part again after orbiting
+//! or panning away, and Home goes back to the framing the camera started with. R flips the
+//! camera over to the other side of a dual-chamber case, to look into its rear chamber, and
+//! back.
+
+use std::f32::consts::PI;
+
+use bevy::{camera::primitives::Aabb, prelude::*};
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    orbit_camera_system,
+    part_selection::{PartDoubleClicked, SelectedPart},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CameraFocus>();
+    app.add_observer(remember_home_framing);
+    app.add_observer(focus_on_double_clicked_part);
+    app.add_systems(OnExit(Screen::Game), stop_focusing);
+    app.add_systems(
+        Update,
+        (
+            focus_on_new_selection.run_if(resource_changed::<SelectedPart>),
+            frame_selection.run_if(key_just_pressed(KeyAction::FramePart)),
+            reset_framing.run_if(key_just_pressed(KeyAction::ResetView)),
+            flip_chamber.run_if(key_just_pressed(KeyAction::FlipChamber)),
+            ease_camera_target,
+        )
+            .chain()
+            .before(orbit_camera_system)
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Frame the selected part", frame_selection);
+    app.add_palette_command("Reset the camera framing", reset_framing);
+    app.add_palette_command("Flip to the other chamber", flip_chamber);
+}
+
+/// Where the orbit camera's target is easing towards, if anywhere.
+#[derive(Resource, Debug, Default)]
+pub struct CameraFocus {
+    goal: Option<Vec3>,
+}
+
+/// The target and radius the orbit camera was spawned with.
+#[derive(Component, Debug, Clone, Copy)]
+struct HomeFraming {
+    target: Vec3,
+    radius: f32,
+}
+
+/// How much room is left around a framed part, as a factor of its bounding sphere.
+const FRAMING_MARGIN: f32 = 1.2;
+/// How quickly the target catches up with the goal. Higher is snappier.
+const FOCUS_SMOOTHING: f32 = 8.0;
+/// How close, in millimeters, the target has to get for the focus to be done.
+const FOCUS_TOLERANCE: f32 = 0.5;
+
+fn remember_home_framing(
+    add: On<Add, OrbitCamera>,
+    mut commands: Commands,
+    orbit_query: Query<&OrbitCamera>,
+) {
+    if let Ok(orbit) = orbit_query.get(add.entity) {
+        commands.entity(add.entity).insert(HomeFraming {
+            target: orbit.target,
+            radius: orbit.desired_radius,
+        });
+    }
+}
+
+fn focus_on_new_selection(
+    selected: Res<SelectedPart>,
+    mut focus: ResMut<CameraFocus>,
+    part_query: Query<(&Aabb, &GlobalTransform)>,
+    mut camera_query: Query<(&mut OrbitCamera, &Projection)>,
+) {
+    if let Some(part) = selected.0 {
+        focus_on(part, &mut focus, &part_query, &mut camera_query);
+    }
+}
+
+fn focus_on_double_clicked_part(
+    double_click: On<PartDoubleClicked>,
+    mut focus: ResMut<CameraFocus>,
+    part_query: Query<(&Aabb, &GlobalTransform)>,
+    mut camera_query: Query<(&mut OrbitCamera, &Projection)>,
+) {
+    focus_on(double_click.0, &mut focus, &part_query, &mut camera_query);
+}
+
+pub fn frame_selection(
+    selected: Res<SelectedPart>,
+    mut focus: ResMut<CameraFocus>,
+    part_query: Query<(&Aabb, &GlobalTransform)>,
+    mut camera_query: Query<(&mut OrbitCamera, &Projection)>,
+) {
+    match selected.0 {
+        Some(part) => focus_on(part, &mut focus, &part_query, &mut camera_query),
+        None => info!("Select a part to frame first"),
+    }
+}
+
+/// Heads the orbit camera for `part`, zooming until its bounding sphere fits the view.
+fn focus_on(
+    part: Entity,
+    focus: &mut CameraFocus,
+    part_query: &Query<(&Aabb, &GlobalTransform)>,
+    camera_query: &mut Query<(&mut OrbitCamera, &Projection)>,
+) {
+    let Ok((aabb, transform)) = part_query.get(part) else {
+        return;
+    };
+    let Ok((mut orbit, projection)) = camera_query.single_mut() else {
+        return;
+    };
+
+    let center = transform.transform_point(aabb.center.into());
+    let scale = transform.scale().abs();
+    let sphere_radius = (Vec3::from(aabb.half_extents) * scale).length();
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        _ => PerspectiveProjection::default().fov,
+    };
+    // Distance at which the sphere touches the edges of the view.
+    let radius = sphere_radius * FRAMING_MARGIN / (fov / 2.0).sin();
+
+    focus.goal = Some(center);
+    orbit.desired_radius = radius.clamp(orbit.min_radius, orbit.max_radius);
+}
+
+fn reset_framing(
+    mut focus: ResMut<CameraFocus>,
+    mut camera_query: Query<(&mut OrbitCamera, &HomeFraming)>,
+) {
+    let Ok((mut orbit, home)) = camera_query.single_mut() else {
+        return;
+    };
+    focus.goal = Some(home.target);
+    orbit.desired_radius = home.radius;
+}
+
+/// Swings the camera over to the side of a dual-chamber case its rear chamber is on, heading
+/// for the middle of the chamber, or from there back to the middle of the case.
+fn flip_chamber(
+    active: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    mut focus: ResMut<CameraFocus>,
+    mut camera_query: Query<&mut OrbitCamera>,
+) {
+    let Some(rear) = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active.0))
+        .and_then(|case| case.rear_chamber_center())
+    else {
+        info!("The case has no rear chamber to flip to");
+        return;
+    };
+    let Ok(mut orbit) = camera_query.single_mut() else {
+        return;
+    };
+
+    // The camera is on the side of `yaw.cos()`, which mirroring the yaw flips while keeping
+    // how far round to the front or back it is.
+    let to_rear = orbit.yaw.cos().signum() != rear.x.signum();
+    orbit.yaw = PI - orbit.yaw;
+    focus.goal = Some(if to_rear {
+        rear
+    } else {
+        active.center(&level_assets, &manifests)
+    });
+}
+
+fn ease_camera_target(
+    time: Res<Time>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut focus: ResMut<CameraFocus>,
+    mut camera_query: Query<&mut OrbitCamera>,
+) {
+    let Some(goal) = focus.goal else {
+        return;
+    };
+    // Panning takes the target out of the focus's hands.
+    if mouse_buttons.any_pressed([MouseButton::Right, MouseButton::Middle]) {
+        focus.goal = None;
+        return;
+    }
+
+    for mut orbit in &mut camera_query {
+        orbit
+            .target
+            .smooth_nudge(&goal, FOCUS_SMOOTHING, time.delta_secs());
+        if orbit.target.distance(goal) < FOCUS_TOLERANCE {
+            orbit.target = goal;
+            focus.goal = None;
+        }
+    }
+}
+
+fn stop_focusing(mut focus: ResMut<CameraFocus>) {
+    focus.goal = None;
+}
@@ -0,0 +1,736 @@
+//! Exporting the current view as a PNG, at a chosen resolution and with the scene's background,
+//! a solid color, a vertical gradient or none.
+//!
+//! The background colors are set from the command palette and kept in the settings, so exports
+//! can match a style guide whatever the scene is lit like. The watermark set in the settings,
+//! a logo from the assets folder, is stamped onto every capture and turntable frame at its
+//! position and opacity. Pick the logo from the command palette.
+//!
+//! F12 or the capture panel renders the orbit camera's view to an offscreen image, so the
+//! export can be larger than the window and leaves the UI out, apart from the scene's labels.
+//! Native builds write the file to `screenshots/`, with what it shows for the gallery (see
+//! `gallery`), the web build hands it to the browser as a download.
+
+use std::sync::Arc;
+
+use bevy::{
+    camera::RenderTarget,
+    ecs::system::SystemId,
+    input_focus::tab_navigation::TabGroup,
+    prelude::*,
+    render::{
+        render_resource::TextureFormat,
+        view::screenshot::{Screenshot, ScreenshotCaptured},
+    },
+    window::PrimaryWindow,
+};
+
+use crate::{
+    OrbitCamera, Screen,
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    keybindings::{KeyAction, key_just_pressed},
+    labels::ShowsLabels,
+    settings::{Settings, WatermarkPosition, save_settings},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CaptureSettings>();
+    let prompts = CapturePrompts {
+        set_watermark: app.register_system(set_watermark),
+        set_background: app.register_system(set_background),
+    };
+    app.insert_resource(prompts);
+    app.init_resource::<WatermarkImage>();
+    app.add_systems(Update, load_watermark.run_if(resource_changed::<Settings>));
+    app.add_systems(OnEnter(Screen::Game), spawn_capture_panel);
+    app.add_systems(
+        Update,
+        (
+            start_capture.run_if(key_just_pressed(KeyAction::Capture)),
+            take_pending_captures,
+            update_button_labels.run_if(
+                resource_changed::<CaptureSettings>.or(any_match_filter::<Added<CaptureButton>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_capture_button);
+    app.add_palette_command("Capture screenshot", start_capture);
+    app.add_palette_command("Capture: set the watermark image", prompt_for_watermark);
+    app.add_palette_command("Capture: set the background colors", prompt_for_background);
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CaptureSettings {
+    pub resolution: CaptureResolution,
+    pub background: CaptureBackground,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            resolution: CaptureResolution::Window,
+            background: CaptureBackground::Scene,
+        }
+    }
+}
+
+/// What's behind the case in exported images.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CaptureBackground {
+    /// The scene's, as in the window.
+    Scene,
+    /// The top color of `Settings::export_background`.
+    Solid,
+    /// `Settings::export_background`, from top to bottom.
+    Gradient,
+    /// None, for pasting the case onto other images.
+    Transparent,
+}
+
+impl CaptureBackground {
+    fn next(self) -> Self {
+        match self {
+            Self::Scene => Self::Solid,
+            Self::Solid => Self::Gradient,
+            Self::Gradient => Self::Transparent,
+            Self::Transparent => Self::Scene,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Scene => "Background: scene",
+            Self::Solid => "Background: solid",
+            Self::Gradient => "Background: gradient",
+            Self::Transparent => "Background: none",
+        }
+    }
+}
+
+/// The prompts for the capture options set from the command palette.
+#[derive(Resource, Debug)]
+struct CapturePrompts {
+    /// Sets the watermark to the path it's given.
+    set_watermark: SystemId<In<String>>,
+    /// Sets the background colors to the ones it's given.
+    set_background: SystemId<In<String>>,
+}
+
+/// The logo of the watermark in the settings, loaded from the assets folder.
+#[derive(Resource, Debug, Default)]
+pub struct WatermarkImage {
+    /// The path `handle` was loaded from.
+    path: Option<String>,
+    handle: Option<Handle<Image>>,
+}
+
+/// What's painted onto an exported image once it's captured: the background behind the case,
+/// then the watermark.
+#[derive(Debug, Clone, Default)]
+pub struct ExportStyle {
+    /// The colors at the top and the bottom of the background, if it's painted in.
+    background: Option<[Srgba; 2]>,
+    watermark: Option<Watermark>,
+}
+
+impl ExportStyle {
+    /// The style for an image rendered by `camera`. The watermark is left out until its logo
+    /// has loaded.
+    pub fn new(
+        camera: &OffscreenCamera,
+        settings: &Settings,
+        watermark: &WatermarkImage,
+        images: &Assets<Image>,
+    ) -> Self {
+        let [top, bottom] = settings
+            .export_background
+            .map(|[red, green, blue]| Srgba::rgb(red, green, blue));
+        let background = match camera.background {
+            CaptureBackground::Solid => Some([top, top]),
+            CaptureBackground::Gradient => Some([top, bottom]),
+            CaptureBackground::Scene | CaptureBackground::Transparent => None,
+        };
+        let logo = watermark
+            .handle
+            .as_ref()
+            .and_then(|handle| images.get(handle))
+            .and_then(|logo| match logo.clone().try_into_dynamic() {
+                Ok(logo) => Some(logo.to_rgba8()),
+                Err(error) => {
+                    warn!("Could not use the watermark image: {error}");
+                    None
+                }
+            });
+        Self {
+            background,
+            watermark: logo.map(|logo| Watermark {
+                logo: Arc::new(logo),
+                position: settings.watermark_position,
+                opacity: settings.watermark_opacity,
+            }),
+        }
+    }
+
+    pub fn apply(&self, image: &mut image::RgbaImage) {
+        if let Some([top, bottom]) = self.background {
+            paint_background(image, top, bottom);
+        }
+        if let Some(watermark) = &self.watermark {
+            apply_watermark(
+                image,
+                &watermark.logo,
+                watermark.position,
+                watermark.opacity,
+            );
+        }
+    }
+}
+
+/// A logo to stamp onto exported images, see [`apply_watermark`].
+#[derive(Debug, Clone)]
+struct Watermark {
+    logo: Arc<image::RgbaImage>,
+    position: WatermarkPosition,
+    opacity: f32,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CaptureResolution {
+    /// The size of the window.
+    Window,
+    FullHd,
+    Uhd4K,
+    /// 4K, rendered at twice the size and scaled down for smoother edges.
+    Uhd4KSupersampled,
+}
+
+impl CaptureResolution {
+    fn next(self) -> Self {
+        match self {
+            Self::Window => Self::FullHd,
+            Self::FullHd => Self::Uhd4K,
+            Self::Uhd4K => Self::Uhd4KSupersampled,
+            Self::Uhd4KSupersampled => Self::Window,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Window => "Window size",
+            Self::FullHd => "1920 x 1080",
+            Self::Uhd4K => "3840 x 2160",
+            Self::Uhd4KSupersampled => "3840 x 2160 (supersampled)",
+        }
+    }
+
+    /// The size of the exported image, and how many times larger it is rendered.
+    fn size_and_supersampling(self, window_size: UVec2) -> (UVec2, u32) {
+        match self {
+            Self::Window => (window_size, 1),
+            Self::FullHd => (UVec2::new(1920, 1080), 1),
+            Self::Uhd4K => (UVec2::new(3840, 2160), 1),
+            Self::Uhd4KSupersampled => (UVec2::new(3840, 2160), 2),
+        }
+    }
+}
+
+/// Renders a capture. It's only taken after [`CAPTURE_DELAY_FRAMES`], as the first frames of a
+/// new camera can come out incomplete.
+#[derive(Component)]
+struct CaptureCamera {
+    frames_left: u32,
+}
+
+/// A camera rendering into an offscreen image, set up by [`spawn_offscreen_camera`].
+#[derive(Component, Debug, Clone)]
+pub struct OffscreenCamera {
+    pub image: Handle<Image>,
+    /// The size to export the image at, which it may be rendered larger than.
+    pub size: UVec2,
+    /// What goes behind the case. Anything but the scene's is rendered transparent and painted
+    /// in by [`ExportStyle`].
+    pub background: CaptureBackground,
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum CaptureButton {
+    Resolution,
+    Background,
+    Capture,
+}
+
+/// Frames a new camera needs before what it renders can be captured.
+pub const CAPTURE_DELAY_FRAMES: u32 = 3;
+#[cfg(not(target_arch = "wasm32"))]
+pub const CAPTURE_FOLDER: &str = "screenshots";
+
+/// The widest the watermark may be, as a fraction of the image's width. Larger logos are
+/// scaled down.
+const WATERMARK_MAX_WIDTH: f32 = 0.25;
+/// The gap between the watermark and the image's edges, as a fraction of the image's width.
+const WATERMARK_MARGIN: f32 = 0.02;
+
+/// Spawns a camera with an [`OffscreenCamera`] that renders the orbit camera's view at the
+/// resolution and background in `settings`.
+pub fn spawn_offscreen_camera<'a>(
+    commands: &'a mut Commands,
+    images: &mut Assets<Image>,
+    settings: &CaptureSettings,
+    window_size: UVec2,
+    transform: Transform,
+    projection: Projection,
+) -> EntityCommands<'a> {
+    let (size, supersampling) = settings.resolution.size_and_supersampling(window_size);
+    let render_size = size * supersampling;
+    let image = images.add(Image::new_target_texture(
+        render_size.x,
+        render_size.y,
+        TextureFormat::Rgba8UnormSrgb,
+        None,
+    ));
+    let clear_color = match settings.background {
+        CaptureBackground::Scene => ClearColorConfig::Default,
+        _ => ClearColorConfig::Custom(Color::NONE),
+    };
+
+    commands.spawn((
+        OffscreenCamera {
+            image: image.clone(),
+            size,
+            background: settings.background,
+        },
+        DespawnOnExit(Screen::Game),
+        Camera3d::default(),
+        Camera {
+            clear_color,
+            ..default()
+        },
+        RenderTarget::Image(image.into()),
+        ShowsLabels,
+        transform,
+        projection,
+    ))
+}
+
+fn load_watermark(
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut watermark: ResMut<WatermarkImage>,
+) {
+    if watermark.path == settings.watermark {
+        return;
+    }
+    watermark.path.clone_from(&settings.watermark);
+    watermark.handle = settings
+        .watermark
+        .as_ref()
+        .map(|path| asset_server.load(path));
+}
+
+fn prompt_for_watermark(mut commands: Commands, prompts: Res<CapturePrompts>) {
+    commands.trigger(OpenPrompt {
+        label: "Watermark image in the assets folder, or nothing for none".to_string(),
+        submit: prompts.set_watermark,
+    });
+}
+
+fn set_watermark(In(path): In<String>, mut commands: Commands, mut settings: ResMut<Settings>) {
+    let path = path.trim();
+    settings.watermark = (!path.is_empty()).then(|| path.to_string());
+    commands.run_system_cached(save_settings);
+}
+
+fn prompt_for_background(mut commands: Commands, prompts: Res<CapturePrompts>) {
+    commands.trigger(OpenPrompt {
+        label: "Background color, or top and bottom colors, like #202830 #0a0a10".to_string(),
+        submit: prompts.set_background,
+    });
+}
+
+/// Sets the background colors and switches exports to a solid background for one color, or a
+/// gradient for two.
+fn set_background(
+    In(text): In<String>,
+    mut commands: Commands,
+    mut settings: ResMut<Settings>,
+    mut capture_settings: ResMut<CaptureSettings>,
+) {
+    let Some(colors) = parse_background_colors(&text) else {
+        warn!("Could not read the background colors \"{text}\", expected one or two like #202830");
+        return;
+    };
+    settings.export_background = colors.map(|color| [color.red, color.green, color.blue]);
+    capture_settings.background = if colors[0] == colors[1] {
+        CaptureBackground::Solid
+    } else {
+        CaptureBackground::Gradient
+    };
+    commands.run_system_cached(save_settings);
+}
+
+/// Reads one hex color, for a solid background, or two, for a gradient from the first at the
+/// top to the second at the bottom.
+pub fn parse_background_colors(text: &str) -> Option<[Srgba; 2]> {
+    let colors = text
+        .split_whitespace()
+        .map(|hex| Srgba::hex(hex).ok())
+        .collect::<Option<Vec<_>>>()?;
+    match colors[..] {
+        [color] => Some([color, color]),
+        [top, bottom] => Some([top, bottom]),
+        _ => None,
+    }
+}
+
+/// Paints a vertical gradient from `top` to `bottom` behind `image`, which was rendered with a
+/// transparent background. Where it shows through, at antialiased edges and behind see-through
+/// materials, the image's colors are already multiplied by its alpha, so only the background
+/// is.
+pub fn paint_background(image: &mut image::RgbaImage, top: Srgba, bottom: Srgba) {
+    let height = image.height();
+    for (y, row) in image.enumerate_rows_mut() {
+        let t = y as f32 / height.saturating_sub(1).max(1) as f32;
+        let color = top.mix(&bottom, t).to_u8_array_no_alpha().map(f32::from);
+        for (_, _, pixel) in row {
+            let coverage = f32::from(pixel[3]) / 255.0;
+            for channel in 0..3 {
+                let painted = f32::from(pixel[channel]) + color[channel] * (1.0 - coverage);
+                pixel[channel] = painted.round().min(255.0) as u8;
+            }
+            pixel[3] = 255;
+        }
+    }
+}
+
+/// Stamps `logo` onto `image` at `position`, scaled down if it's wider than
+/// [`WATERMARK_MAX_WIDTH`] of the image, with its alpha multiplied by `opacity`.
+pub fn apply_watermark(
+    image: &mut image::RgbaImage,
+    logo: &image::RgbaImage,
+    position: WatermarkPosition,
+    opacity: f32,
+) {
+    let (width, height) = image.dimensions();
+    let max_width = ((width as f32 * WATERMARK_MAX_WIDTH) as u32).max(1);
+    let mut logo = if logo.width() > max_width {
+        let logo_height = (logo.height() * max_width / logo.width()).max(1);
+        image::imageops::resize(
+            logo,
+            max_width,
+            logo_height,
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        logo.clone()
+    };
+    for pixel in logo.pixels_mut() {
+        pixel[3] = (f32::from(pixel[3]) * opacity).round() as u8;
+    }
+
+    let margin = (width as f32 * WATERMARK_MARGIN) as i64;
+    let (left, top) = (margin, margin);
+    let right = i64::from(width) - i64::from(logo.width()) - margin;
+    let bottom = i64::from(height) - i64::from(logo.height()) - margin;
+    let (x, y) = match position {
+        WatermarkPosition::TopLeft => (left, top),
+        WatermarkPosition::TopRight => (right, top),
+        WatermarkPosition::BottomLeft => (left, bottom),
+        WatermarkPosition::BottomRight => (right, bottom),
+        WatermarkPosition::Center => (right / 2 + margin / 2, bottom / 2 + margin / 2),
+    };
+    image::imageops::overlay(image, &logo, x, y);
+}
+
+fn start_capture(
+    mut commands: Commands,
+    settings: Res<CaptureSettings>,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Transform, &Projection), With<OrbitCamera>>,
+) {
+    let Ok((transform, projection)) = camera_query.single() else {
+        return;
+    };
+
+    spawn_offscreen_camera(
+        &mut commands,
+        &mut images,
+        &settings,
+        window.physical_size(),
+        *transform,
+        projection.clone(),
+    )
+    .insert((
+        Name::new("Capture Camera"),
+        CaptureCamera {
+            frames_left: CAPTURE_DELAY_FRAMES,
+        },
+    ));
+}
+
+fn take_pending_captures(
+    mut commands: Commands,
+    mut camera_query: Query<(Entity, &OffscreenCamera, &mut CaptureCamera)>,
+    settings: Res<Settings>,
+    watermark: Res<WatermarkImage>,
+    images: Res<Assets<Image>>,
+    #[cfg(not(target_arch = "wasm32"))] current: crate::persistence::CurrentBuild,
+    #[cfg(not(target_arch = "wasm32"))] lighting: Res<crate::lighting::ActiveLightingPreset>,
+) {
+    for (camera, offscreen, mut capture) in &mut camera_query {
+        if capture.frames_left > 0 {
+            capture.frames_left -= 1;
+            continue;
+        }
+
+        // What the capture shows, so the gallery can take it again.
+        #[cfg(not(target_arch = "wasm32"))]
+        let info = current.get().map(|build| crate::gallery::ShotInfo {
+            build,
+            lighting: lighting.0.clone(),
+        });
+        let size = offscreen.size;
+        let style = ExportStyle::new(offscreen, &settings, &watermark, &images);
+        commands
+            .spawn(Screenshot::image(offscreen.image.clone()))
+            .observe(
+                move |captured: On<ScreenshotCaptured>, mut commands: Commands| {
+                    commands.entity(camera).despawn();
+                    if let Some(mut image) = to_rgba(&captured.image, size) {
+                        style.apply(&mut image);
+                        let Some(png) = encode_png(&image) else {
+                            return;
+                        };
+                        let file_name = format!("capture-{}.png", timestamp());
+                        save_file(&file_name, png);
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            if let Some(info) = &info {
+                                crate::gallery::save_shot_info(&file_name, info);
+                            }
+                            commands.run_system_cached(crate::gallery::refresh_gallery);
+                        }
+                    }
+                },
+            );
+        // Only take one screenshot per camera.
+        commands.entity(camera).remove::<CaptureCamera>();
+    }
+}
+
+/// Converts a captured image to RGBA, scaled down to `size` if it was rendered larger.
+pub fn to_rgba(image: &Image, size: UVec2) -> Option<image::RgbaImage> {
+    let image = match image.clone().try_into_dynamic() {
+        Ok(image) => image.to_rgba8(),
+        Err(error) => {
+            error!("Could not convert the capture to an image: {error}");
+            return None;
+        }
+    };
+    if image.dimensions() == (size.x, size.y) {
+        return Some(image);
+    }
+    Some(image::imageops::resize(
+        &image,
+        size.x,
+        size.y,
+        image::imageops::FilterType::Triangle,
+    ))
+}
+
+pub fn encode_png(image: &image::RgbaImage) -> Option<Vec<u8>> {
+    let mut png = std::io::Cursor::new(Vec::new());
+    match image.write_to(&mut png, image::ImageFormat::Png) {
+        Ok(()) => Some(png.into_inner()),
+        Err(error) => {
+            error!("Could not encode the capture: {error}");
+            None
+        }
+    }
+}
+
+/// Seconds since the Unix epoch, for naming exported files.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Seconds since the Unix epoch, for naming exported files.
+#[cfg(target_arch = "wasm32")]
+pub fn timestamp() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Writes an exported file to `path` inside the captures folder.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(path: &str, contents: Vec<u8>) {
+    let path = std::path::Path::new(CAPTURE_FOLDER).join(path);
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, contents));
+    match result {
+        Ok(()) => info!("Saved {}", path.display()),
+        Err(error) => error!("Could not save {}: {error}", path.display()),
+    }
+}
+
+/// Hands an exported file to the browser as a download named after the end of `path`.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(path: &str, contents: Vec<u8>) {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let download = || -> Result<(), JsValue> {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents.as_slice()).into());
+        let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or("no document")?;
+        let link = document
+            .create_element("a")?
+            .dyn_into::<web_sys::HtmlElement>()?;
+        link.set_attribute("href", &url)?;
+        link.set_attribute("download", file_name)?;
+        link.click();
+        web_sys::Url::revoke_object_url(&url)
+    };
+    match download() {
+        Ok(()) => info!("Downloaded {file_name}"),
+        Err(error) => error!("Could not download {file_name}: {error:?}"),
+    }
+}
+
+fn spawn_capture_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Capture Panel"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(5.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(4),
+            children![
+                button(CaptureButton::Resolution),
+                button(CaptureButton::Background),
+                button(CaptureButton::Capture),
+            ],
+        )],
+    ));
+}
+
+fn button(action: CaptureButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::default(),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn update_button_labels(
+    settings: Res<CaptureSettings>,
+    mut button_query: Query<(&CaptureButton, &mut Text)>,
+) {
+    for (button, mut text) in &mut button_query {
+        text.0 = match button {
+            CaptureButton::Resolution => settings.resolution.label().to_string(),
+            CaptureButton::Background => settings.background.label().to_string(),
+            CaptureButton::Capture => "Capture (F12)".to_string(),
+        };
+    }
+}
+
+fn activate_capture_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    button_query: Query<&CaptureButton>,
+    mut settings: ResMut<CaptureSettings>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(CaptureButton::Resolution) => settings.resolution = settings.resolution.next(),
+        Ok(CaptureButton::Background) => settings.background = settings.background.next(),
+        Ok(CaptureButton::Capture) => commands.run_system_cached(start_capture),
+        Err(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn background_colors() {
+        let [top, bottom] = parse_background_colors("#ff0000 #0000ff").unwrap();
+        assert_eq!((top, bottom), (Srgba::RED, Srgba::BLUE));
+        assert_eq!(
+            parse_background_colors(" #00ff00 "),
+            Some([Srgba::GREEN; 2])
+        );
+        assert_eq!(parse_background_colors(""), None);
+        assert_eq!(parse_background_colors("#ff0000 blue"), None);
+        assert_eq!(parse_background_colors("#ff0000 #00ff00 #0000ff"), None);
+    }
+
+    #[test]
+    fn gradient_background() {
+        let mut image = image::RgbaImage::from_pixel(2, 3, image::Rgba([0, 0, 0, 0]));
+        // A half covered pixel, its color multiplied by its alpha.
+        image.put_pixel(1, 1, image::Rgba([100, 100, 100, 128]));
+        paint_background(&mut image, Srgba::WHITE, Srgba::BLACK);
+        assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(0, 2).0, [0, 0, 0, 255]);
+        let [gray, .., alpha] = image.get_pixel(1, 1).0;
+        assert_eq!(alpha, 255);
+        assert!((155..=165).contains(&gray), "{gray}");
+    }
+
+    #[test]
+    fn watermark_placement() {
+        let logo = image::RgbaImage::from_pixel(10, 10, image::Rgba([255, 255, 255, 255]));
+        let mut image = image::RgbaImage::from_pixel(100, 50, image::Rgba([0, 0, 0, 255]));
+        apply_watermark(&mut image, &logo, WatermarkPosition::BottomRight, 1.0);
+        // Two pixels in from the corner, as the margin is 2% of the width.
+        assert_eq!(image.get_pixel(97, 47).0, [255, 255, 255, 255]);
+        assert_eq!(image.get_pixel(98, 48).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 2).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn watermark_scale_and_opacity() {
+        let logo = image::RgbaImage::from_pixel(100, 20, image::Rgba([255, 255, 255, 255]));
+        let mut image = image::RgbaImage::from_pixel(100, 100, image::Rgba([0, 0, 0, 255]));
+        apply_watermark(&mut image, &logo, WatermarkPosition::TopLeft, 0.5);
+        // Scaled to a quarter of the width, 25 x 5, and blended half way.
+        let [red, ..] = image.get_pixel(2, 2).0;
+        assert!((120..=135).contains(&red), "{red}");
+        assert_eq!(image.get_pixel(27, 2).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 7).0, [0, 0, 0, 255]);
+    }
+}
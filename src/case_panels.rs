@@ -0,0 +1,155 @@
+//! Side and front panels that come off the case to show what's inside.
+//!
+//! Nodes of the case model whose name starts with [`SLIDE_PREFIX`], like `SlidePanel_Left`,
+//! slide straight out from the active case's center, as seen in the level. Nodes starting with
+//! [`SWING_PREFIX`] swing open around their own vertical axis instead, so the model should put
+//! their origin on the hinge. Click a panel to take it off or put it back, or press P to do so
+//! with all of them.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::{PressPosition, Unselectable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(toggle_clicked_panel);
+    app.add_systems(
+        Update,
+        (
+            toggle_all_panels.run_if(key_just_pressed(KeyAction::RemovePanels)),
+            tag_panels,
+            tween_panels,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Open or close the case panels", toggle_all_panels);
+}
+
+/// A panel of the case, tweening between its place on the case and off it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CasePanel {
+    closed: Transform,
+    open: Transform,
+    /// Where the panel is heading: 0 on the case, 1 off it.
+    pub target: f32,
+    /// How far the panel has come, from 0 to 1.
+    pub progress: f32,
+}
+
+impl CasePanel {
+    fn toggle(&mut self) {
+        self.target = if self.target > 0.5 { 0.0 } else { 1.0 };
+    }
+}
+
+const SLIDE_PREFIX: &str = "SlidePanel_";
+const SWING_PREFIX: &str = "SwingPanel_";
+/// How far a sliding panel moves out, in millimeters.
+const SLIDE_DISTANCE: f32 = 300.0;
+/// How far a swinging panel turns on its hinge.
+const SWING_ANGLE: f32 = PI * 0.6;
+/// Seconds for a panel to come off or go back on.
+const PANEL_DURATION: f32 = 0.6;
+
+fn tag_panels(
+    mut commands: Commands,
+    active: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    node_query: Query<
+        (Entity, &Name, &Transform, &GlobalTransform, &ChildOf),
+        (Added<Name>, Without<CasePanel>),
+    >,
+    parent_query: Query<&GlobalTransform>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let Ok(level) = level_query.single() else {
+        return;
+    };
+    let center = active.center(&level_assets, &manifests);
+
+    for (entity, name, transform, global_transform, child_of) in &node_query {
+        let open = if name.as_str().starts_with(SLIDE_PREFIX) {
+            let Ok(parent) = parent_query.get(child_of.parent()) else {
+                continue;
+            };
+            // Worked out in the level, then moved into the panel's parent's space.
+            let position = level
+                .affine()
+                .inverse()
+                .transform_point3(global_transform.translation());
+            let direction = (position - center).with_y(0.0).normalize_or(Vec3::X);
+            let offset = parent
+                .affine()
+                .inverse()
+                .transform_vector3(level.affine().transform_vector3(direction * SLIDE_DISTANCE));
+            transform.with_translation(transform.translation + offset)
+        } else if name.as_str().starts_with(SWING_PREFIX) {
+            transform.with_rotation(transform.rotation * Quat::from_rotation_y(SWING_ANGLE))
+        } else {
+            continue;
+        };
+        commands.entity(entity).insert((
+            CasePanel {
+                closed: *transform,
+                open,
+                target: 0.0,
+                progress: 0.0,
+            },
+            // Clicking a panel opens it rather than selecting it.
+            Unselectable,
+        ));
+    }
+}
+
+fn toggle_clicked_panel(
+    click: On<Pointer<Click>>,
+    press_position: Res<PressPosition>,
+    mut panel_query: Query<&mut CasePanel>,
+) {
+    // Clicks on a panel's meshes bubble up to the panel.
+    if !press_position.is_click(&click) {
+        return;
+    }
+    if let Ok(mut panel) = panel_query.get_mut(click.entity) {
+        panel.toggle();
+    }
+}
+
+/// Takes every panel off, or puts them all back once they're all off.
+fn toggle_all_panels(mut panel_query: Query<&mut CasePanel>) {
+    let target = if panel_query.iter().all(|panel| panel.target > 0.5) {
+        0.0
+    } else {
+        1.0
+    };
+    for mut panel in &mut panel_query {
+        panel.target = target;
+    }
+}
+
+fn tween_panels(time: Res<Time>, mut panel_query: Query<(&mut CasePanel, &mut Transform)>) {
+    let step = time.delta_secs() / PANEL_DURATION;
+    for (mut panel, mut transform) in &mut panel_query {
+        let progress = panel.progress + (panel.target - panel.progress).clamp(-step, step);
+        // Others, like the timeline, may set the progress directly.
+        if progress == panel.progress && !panel.is_changed() {
+            continue;
+        }
+        if progress != panel.progress {
+            panel.progress = progress;
+        }
+
+        let eased = EaseFunction::SmoothStep.sample_clamped(progress);
+        transform.translation = panel.closed.translation.lerp(panel.open.translation, eased);
+        transform.rotation = panel.closed.rotation.slerp(panel.open.rotation, eased);
+    }
+}
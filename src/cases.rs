@@ -0,0 +1,514 @@
+//! Switching between the case models listed in `assets/models/cases.ron`.
+//!
+//! Only the active case is kept loaded: the next one loads when it is picked, and the
+//! previous one is unloaded once its scene has been despawned. If the next one fails to load,
+//! the last case that did load is switched back to.
+//!
+//! Besides the anchors listed in the manifest, a case gets one for every node of its model
+//! named like `anchor_fan_120_front_1`: the word after [`NODE_ANCHOR_PREFIX`] is the kind of
+//! part, and the node's position is the mount point. Empties tagged this way in Blender are
+//! enough to give a case its mount points.
+//!
+//! An inverted case, with its motherboard upside down on the other side wall, lists its
+//! anchors and clearances as in a standard layout, and [`CaseSpec::lay_out`] mirrors them left
+//! to right and top to bottom, turning them half a turn around the front-to-back axis. The
+//! parts mounted to them are turned the same way, fans and all.
+//!
+//! A dual-chamber case puts the anchors of its PSU and drives in the rear chamber, behind the
+//! motherboard tray, and may give clearances to one chamber, so that they only hold the parts
+//! mounted there. The camera flips over to the rear chamber with its own key.
+
+use std::f32::consts::PI;
+
+use bevy::{asset::RecursiveDependencyLoadState, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Level, LevelAssets, PC_CASE_MODEL, Screen,
+    asset_tracking::InitRonAsset,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::SelectedPart,
+    parts::{Connector, InstalledPart, MOUNT_TOLERANCE, PartKind, remount_parts},
+    settings::Settings,
+    ui_navigation::FONT_SIZE,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<CaseManifest>(&["cases.ron"]);
+    app.init_resource::<ActiveCase>();
+    app.init_resource::<FallbackCase>();
+    app.add_systems(OnEnter(Screen::Game), spawn_case_label);
+    app.add_systems(
+        Update,
+        (
+            cycle_cases.run_if(key_just_pressed(KeyAction::NextCase)),
+            fall_back_on_failed_case.run_if(|fallback: Res<FallbackCase>| fallback.0.is_some()),
+            infer_node_anchors,
+            update_case_label.run_if(
+                resource_changed::<ActiveCase>
+                    .or(resource_changed::<Settings>)
+                    .or(any_match_filter::<Added<CaseLabel>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(switch_case);
+    app.add_palette_command("Switch to the next case", cycle_cases);
+}
+
+#[derive(Asset, TypePath, Debug, Serialize, Deserialize)]
+pub struct CaseManifest {
+    pub cases: Vec<CaseSpec>,
+}
+
+impl CaseManifest {
+    pub fn get(&self, model: &str) -> Option<&CaseSpec> {
+        self.cases.iter().find(|case| case.model == model)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseSpec {
+    pub name: String,
+    /// Path to the glTF file, relative to the assets folder.
+    pub model: String,
+    pub form_factor: String,
+    /// Width, height and depth in millimeters.
+    pub dimensions: [f32; 3],
+    #[serde(default)]
+    pub clearances: Vec<Clearance>,
+    #[serde(default)]
+    pub anchors: Vec<Anchor>,
+    /// The connectors of the front panel's cables, one entry per cable, each needing a header
+    /// on the motherboard.
+    #[serde(default)]
+    pub front_io: Vec<Connector>,
+    /// Where the front panel's ports are, in millimeters, if not at the top of the front. As a
+    /// part of the shell, it isn't turned for an inverted case.
+    #[serde(default)]
+    pub front_io_position: Option<[f32; 3]>,
+    /// The fan groups whose intakes have dust filters, like "front".
+    #[serde(default)]
+    pub dust_filters: Vec<String>,
+    #[serde(default)]
+    pub drive_bays: DriveBays,
+    /// Whether the motherboard is mounted upside down on the other side wall, with the PSU at
+    /// the top, as in inverted ATX cases.
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+/// How many drives of each size the case holds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriveBays {
+    pub three_and_half: u32,
+    pub two_and_half: u32,
+    /// The 3.5" bays in a cage that comes out to make room for a front radiator.
+    pub radiator_cage: u32,
+}
+
+impl CaseSpec {
+    /// The anchors parts of `kind` mount to, in the order they fill up, laid out in the case.
+    pub fn anchors_for(&self, kind: PartKind) -> impl Iterator<Item = Anchor> {
+        self.anchors
+            .iter()
+            .filter(move |anchor| anchor.kind == kind)
+            .map(|anchor| Anchor {
+                position: self.lay_out(Vec3::from(anchor.position)).to_array(),
+                ..anchor.clone()
+            })
+    }
+
+    /// The clearances, laid out in the case.
+    pub fn laid_out_clearances(&self) -> Vec<Clearance> {
+        self.clearances
+            .iter()
+            .map(|clearance| {
+                let min = self.lay_out(Vec3::from(clearance.min));
+                let max = self.lay_out(Vec3::from(clearance.max));
+                Clearance {
+                    min: min.min(max).to_array(),
+                    max: min.max(max).to_array(),
+                    ..clearance.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Where `position`, in the standard layout the manifest lists positions in, is in the case.
+    /// An inverted case turns it half a turn around the middle of its front-to-back axis, which
+    /// also takes a position in the case back to the standard layout.
+    pub fn lay_out(&self, position: Vec3) -> Vec3 {
+        if self.inverted {
+            Vec3::new(-position.x, self.dimensions[1] - position.y, position.z)
+        } else {
+            position
+        }
+    }
+
+    /// The chamber of the anchor a part is mounted at, if `mount` is one; any other part is in
+    /// the main chamber.
+    pub fn chamber_at(&self, mount: Vec3) -> Chamber {
+        PartKind::ALL
+            .into_iter()
+            .flat_map(|kind| self.anchors_for(kind))
+            .find(|anchor| Vec3::from(anchor.position).distance(mount) < MOUNT_TOLERANCE)
+            .map_or(Chamber::Main, |anchor| anchor.chamber)
+    }
+
+    /// The middle of the rear chamber's anchors, laid out in the case, if it has any.
+    pub fn rear_chamber_center(&self) -> Option<Vec3> {
+        let positions: Vec<Vec3> = PartKind::ALL
+            .into_iter()
+            .flat_map(|kind| self.anchors_for(kind))
+            .filter(|anchor| anchor.chamber == Chamber::Rear)
+            .map(|anchor| Vec3::from(anchor.position))
+            .collect();
+        (!positions.is_empty()).then(|| positions.iter().sum::<Vec3>() / positions.len() as f32)
+    }
+
+    /// The volume of the case's box, in liters.
+    pub fn volume(&self) -> f32 {
+        self.dimensions.iter().product::<f32>() / 1e6
+    }
+
+    /// The corners of the case's box. It stands on the origin, centered on it horizontally.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let [width, height, depth] = self.dimensions;
+        (
+            Vec3::new(-width / 2.0, 0.0, -depth / 2.0),
+            Vec3::new(width / 2.0, height, depth / 2.0),
+        )
+    }
+
+    /// Where the front panel's ports are, by default in the middle of the top of the front.
+    pub fn front_io_position(&self) -> Vec3 {
+        let [_, height, depth] = self.dimensions;
+        self.front_io_position.map_or(
+            Vec3::new(0.0, height - FRONT_IO_INSET, depth / 2.0 - FRONT_IO_INSET),
+            Vec3::from,
+        )
+    }
+
+    /// How parts are turned as they mount in the case.
+    pub fn part_rotation(&self) -> Quat {
+        if self.inverted {
+            Quat::from_rotation_z(PI)
+        } else {
+            Quat::IDENTITY
+        }
+    }
+}
+
+/// Room the case has for parts of some kinds, such as the longest graphics card that fits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clearance {
+    pub name: String,
+    pub kinds: Vec<PartKind>,
+    /// Corners of the box the parts have to fit in, in millimeters.
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    /// The chamber of a dual-chamber case whose parts the clearance holds, if just one.
+    #[serde(default)]
+    pub chamber: Option<Chamber>,
+}
+
+/// A mount point for parts of one kind, such as a front fan slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anchor {
+    pub name: String,
+    pub kind: PartKind,
+    /// Where the center of a mounted part sits, in millimeters.
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub chamber: Chamber,
+}
+
+/// The sides of a dual-chamber case's motherboard tray. Other cases are all main chamber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Chamber {
+    /// Where the motherboard, graphics card and cooling are.
+    #[default]
+    Main,
+    /// Behind the motherboard tray, where the PSU and drives are hidden away.
+    Rear,
+}
+
+/// The model of the case that is shown.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ActiveCase(pub String);
+
+impl Default for ActiveCase {
+    fn default() -> Self {
+        Self(PC_CASE_MODEL.to_string())
+    }
+}
+
+impl ActiveCase {
+    /// The center of the case in the level's space. Cases stand on the level's origin, centered
+    /// on it horizontally, so this is halfway up the height in the manifest; cases missing from
+    /// it are taken to be centered on the origin.
+    pub fn center(&self, level_assets: &LevelAssets, manifests: &Assets<CaseManifest>) -> Vec3 {
+        manifests
+            .get(&level_assets.cases)
+            .and_then(|manifest| manifest.get(&self.0))
+            .map_or(Vec3::ZERO, |case| Vec3::Y * case.dimensions[1] / 2.0)
+    }
+}
+
+/// Replaces the case with the one using this model.
+#[derive(Event, Debug, Clone)]
+pub struct SwitchCase(pub String);
+
+/// The last case that loaded, while the active one is still loading.
+#[derive(Resource, Debug, Default)]
+struct FallbackCase(Option<String>);
+
+#[derive(Component)]
+struct CaseLabel;
+
+pub const NODE_ANCHOR_PREFIX: &str = "anchor_";
+/// How far in from the top and the front of a case, in millimeters, its front panel's ports are
+/// by default.
+const FRONT_IO_INSET: f32 = 20.0;
+
+/// The kind of part an anchor node named `anchor_name`, after the prefix, is for.
+pub fn node_anchor_kind(anchor_name: &str) -> Option<PartKind> {
+    let word = anchor_name.split('_').next()?;
+    PartKind::ALL.into_iter().find(|kind| kind.name() == word)
+}
+
+fn cycle_cases(
+    mut commands: Commands,
+    active: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+) {
+    let Some(manifest) = manifests.get(&level_assets.cases) else {
+        return;
+    };
+    let next = manifest
+        .cases
+        .iter()
+        .position(|case| case.model == active.0)
+        .map_or(0, |index| (index + 1) % manifest.cases.len());
+    if let Some(case) = manifest.cases.get(next) {
+        commands.trigger(SwitchCase(case.model.clone()));
+    }
+}
+
+fn switch_case(
+    switch: On<SwitchCase>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut active: ResMut<ActiveCase>,
+    mut level_assets: ResMut<LevelAssets>,
+    mut fallback: ResMut<FallbackCase>,
+    mut selected: ResMut<SelectedPart>,
+    mut level_query: Query<(Entity, &mut Visibility), With<Level>>,
+    scene_query: Query<(Entity, &ChildOf), (With<SceneRoot>, Without<InstalledPart>)>,
+) {
+    if active.0 == switch.0 {
+        return;
+    }
+
+    // A case switched away from before it loaded is no use to fall back on.
+    if asset_server.is_loaded_with_dependencies(&level_assets.pc_case) {
+        fallback.0 = Some(active.0.clone());
+    }
+    // Dropping the last handle to the old case unloads it once its scene is despawned.
+    level_assets.pc_case = asset_server.load(GltfAssetLabel::Scene(0).from_asset(switch.0.clone()));
+    active.0 = switch.0.clone();
+    // The selection may point into the old scene.
+    selected.set_if_neq(SelectedPart(None));
+
+    for (level, mut visibility) in &mut level_query {
+        for (scene, child_of) in &scene_query {
+            if child_of.parent() == level {
+                commands.entity(scene).despawn();
+            }
+        }
+        // Revealed by `reveal_level` again once the new scene has finished spawning.
+        *visibility = Visibility::Hidden;
+        commands
+            .entity(level)
+            .with_child(SceneRoot(level_assets.pc_case.clone()));
+    }
+}
+
+fn fall_back_on_failed_case(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    active: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    mut fallback: ResMut<FallbackCase>,
+) {
+    match asset_server.get_recursive_dependency_load_state(&level_assets.pc_case) {
+        Some(RecursiveDependencyLoadState::Loaded) => fallback.0 = None,
+        Some(RecursiveDependencyLoadState::Failed(error)) => {
+            let Some(model) = fallback.0.take() else {
+                return;
+            };
+            error!(
+                "Could not load the case {}, going back to {model}: {error}",
+                active.0
+            );
+            commands.trigger(SwitchCase(model));
+        }
+        _ => {}
+    }
+}
+
+/// Adds an anchor to the active case for each newly spawned node of its model named after one.
+fn infer_node_anchors(
+    mut commands: Commands,
+    active: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    mut manifests: ResMut<Assets<CaseManifest>>,
+    node_query: Query<(Entity, &Name, &GlobalTransform), Added<Name>>,
+    parent_query: Query<&ChildOf>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+    part_query: Query<(), With<InstalledPart>>,
+) {
+    let mut added = false;
+    for (entity, name, transform) in &node_query {
+        let Some(anchor_name) = name.as_str().strip_prefix(NODE_ANCHOR_PREFIX) else {
+            continue;
+        };
+        let Some(kind) = node_anchor_kind(anchor_name) else {
+            warn!(
+                "The node {name} doesn't start with a kind of part after \"{NODE_ANCHOR_PREFIX}\""
+            );
+            continue;
+        };
+        // Parts' models may have anchor nodes of their own, which aren't the case's.
+        let Some(level) = parent_query
+            .iter_ancestors(entity)
+            .take_while(|ancestor| !part_query.contains(*ancestor))
+            .find_map(|ancestor| level_query.get(ancestor).ok())
+        else {
+            continue;
+        };
+        let Some(case) = manifests.get_mut(&level_assets.cases).and_then(|manifest| {
+            manifest
+                .cases
+                .iter_mut()
+                .find(|case| case.model == active.0)
+        }) else {
+            continue;
+        };
+        // Switching back to a case spawns its nodes again.
+        if case.anchors.iter().any(|anchor| anchor.name == anchor_name) {
+            continue;
+        }
+
+        let position = case.lay_out(
+            level
+                .affine()
+                .inverse()
+                .transform_point3(transform.translation()),
+        );
+        case.anchors.push(Anchor {
+            name: anchor_name.to_string(),
+            kind,
+            position: position.to_array(),
+            chamber: Chamber::Main,
+        });
+        added = true;
+    }
+    if added {
+        commands.run_system_cached(remount_parts);
+    }
+}
+
+fn spawn_case_label(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Case Label"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the label, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(45.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            CaseLabel,
+            Text::default(),
+            TextFont::from_font_size(FONT_SIZE),
+            TextColor(Color::WHITE),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn update_case_label(
+    active: Res<ActiveCase>,
+    settings: Res<Settings>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    mut label_query: Query<&mut Text, With<CaseLabel>>,
+) {
+    let label = match manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active.0))
+    {
+        Some(case) => format!(
+            "{} ({}, {})",
+            case.name,
+            case.form_factor,
+            settings.format_size(case.dimensions)
+        ),
+        None => active.0.clone(),
+    };
+    for mut text in &mut label_query {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchor_kinds() {
+        assert_eq!(node_anchor_kind("fan_front_1"), Some(PartKind::Fan));
+        assert_eq!(node_anchor_kind("gpu"), Some(PartKind::Gpu));
+        assert_eq!(
+            node_anchor_kind("support_floor"),
+            Some(PartKind::GpuSupport)
+        );
+        // Kinds are matched as whole words, by their anchor names rather than their own.
+        assert_eq!(node_anchor_kind("fans_1"), None);
+        assert_eq!(node_anchor_kind("gpusupport_1"), None);
+        assert_eq!(node_anchor_kind("Fan_1"), None);
+        assert_eq!(node_anchor_kind(""), None);
+    }
+
+    #[test]
+    fn inverted_cases_turn_their_layout() {
+        let case: CaseSpec = ron::de::from_str(
+            "(name: \"Case\", model: \"case.glb\", form_factor: \"ATX\", \
+             dimensions: (200.0, 400.0, 400.0), inverted: true, \
+             anchors: [(name: \"PSU\", kind: Psu, position: (-50.0, 40.0, -100.0))], \
+             clearances: [(name: \"GPU length\", kinds: [Gpu], \
+                 min: (-100.0, 10.0, -200.0), max: (60.0, 390.0, 150.0))])",
+        )
+        .unwrap();
+        let psu: Vec<_> = case.anchors_for(PartKind::Psu).collect();
+        assert_eq!(psu[0].position, [50.0, 360.0, -100.0]);
+        let clearance = &case.laid_out_clearances()[0];
+        assert_eq!(clearance.min, [-60.0, 10.0, -200.0]);
+        assert_eq!(clearance.max, [100.0, 390.0, 150.0]);
+        // Turning twice takes a position back to where it started.
+        let position = Vec3::new(10.0, 20.0, 30.0);
+        assert_eq!(case.lay_out(case.lay_out(position)), position);
+    }
+}
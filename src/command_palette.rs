@@ -0,0 +1,433 @@
+//! A Ctrl+K command palette that fuzzy-searches every action modules have listed in it.
+//!
+//! While the palette is open it has the keyboard to itself: typing filters the commands, the
+//! arrow keys pick one, Enter runs it and Escape closes the palette. Modules can also open it
+//! as a prompt for a line of text with [`OpenPrompt`].
+
+use bevy::{
+    ecs::system::SystemId,
+    input::{
+        InputSystems,
+        keyboard::{Key, KeyboardInput},
+    },
+    input_focus::InputFocus,
+    prelude::*,
+};
+
+use crate::{
+    Menu, Screen,
+    actions::read_actions,
+    keybindings::KeyAction,
+    kiosk::kiosk_active,
+    settings::Settings,
+    ui_navigation::{FONT_SIZE, OVERLAY_BACKGROUND},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PaletteCommands>();
+    app.init_resource::<Palette>();
+    app.add_systems(
+        PreUpdate,
+        handle_palette_keys
+            .after(InputSystems)
+            .before(read_actions)
+            .run_if(
+                in_state(Screen::Game)
+                    .and(in_state(Menu::Closed))
+                    .and(not(kiosk_active)),
+            ),
+    );
+    app.add_systems(
+        Update,
+        (
+            sync_palette_ui.run_if(resource_changed::<Palette>),
+            update_palette_ui
+                .run_if(resource_changed::<Palette>.or(any_match_filter::<Added<PaletteResults>>)),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(OnExit(Screen::Game), close_palette);
+    app.add_observer(open_prompt);
+}
+
+pub trait AddPaletteCommand {
+    /// Lists `system` in the command palette under `name`, to be run when it's picked.
+    fn add_palette_command<M>(
+        &mut self,
+        name: impl Into<String>,
+        system: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl AddPaletteCommand for App {
+    fn add_palette_command<M>(
+        &mut self,
+        name: impl Into<String>,
+        system: impl IntoSystem<(), (), M> + 'static,
+    ) -> &mut Self {
+        let world = self.world_mut();
+        let system = world.register_system(system);
+        world
+            .get_resource_or_init::<PaletteCommands>()
+            .add(name, system);
+        self
+    }
+}
+
+/// Every command the palette can run.
+#[derive(Resource, Debug, Default)]
+pub struct PaletteCommands(Vec<PaletteCommand>);
+
+impl PaletteCommands {
+    /// Lists a system that has already been registered, such as one made at runtime. Replaces
+    /// the command that had the same name, if any.
+    pub fn add(&mut self, name: impl Into<String>, system: SystemId) {
+        let name = name.into();
+        match self.0.iter_mut().find(|command| command.name == name) {
+            Some(command) => command.system = system,
+            None => self.0.push(PaletteCommand { name, system }),
+        }
+    }
+
+    /// The system listed under `name`.
+    pub fn get(&self, name: &str) -> Option<SystemId> {
+        self.0
+            .iter()
+            .find(|command| command.name == name)
+            .map(|command| command.system)
+    }
+}
+
+/// Triggered when a command is picked in the palette, with the command's name.
+#[derive(Event, Debug, Clone)]
+pub struct PaletteCommandRun(pub String);
+
+/// Opens the palette to ask for a line of text, which is passed to `submit` on Enter.
+#[derive(Event, Debug, Clone)]
+pub struct OpenPrompt {
+    pub label: String,
+    pub submit: SystemId<In<String>>,
+}
+
+#[derive(Debug, Clone)]
+struct PaletteCommand {
+    name: String,
+    system: SystemId,
+}
+
+#[derive(Resource, Debug, Default)]
+struct Palette {
+    open: bool,
+    query: String,
+    /// Index of the highlighted command among the current matches.
+    selected: usize,
+    /// Set while the palette asks for text rather than listing commands.
+    prompt: Option<OpenPrompt>,
+}
+
+#[derive(Component)]
+struct PaletteRoot;
+
+#[derive(Component)]
+struct PaletteQuery;
+
+/// The container the matching commands are listed in.
+#[derive(Component)]
+struct PaletteResults;
+
+/// How many matches are listed at once.
+const MAX_RESULTS: usize = 8;
+const PALETTE_WIDTH: f32 = 400.0;
+const SELECTED_BACKGROUND: Color = Color::srgb(0.3, 0.3, 0.3);
+const HINT_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+/// Scores how well `query` matches `name` as a case-insensitive subsequence, or returns `None`
+/// if it doesn't match at all. Runs of consecutive letters and letters that start a word
+/// score higher, so "tev" ranks "Toggle exploded view" above "Respawn the level".
+fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let mut wanted = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in name.chars() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        let matched = c.to_ascii_lowercase() == next;
+        if matched {
+            wanted.next();
+            score += 1;
+            if previous_matched {
+                score += 2;
+            }
+            if previous.is_none_or(|previous| !previous.is_alphanumeric()) {
+                score += 3;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    wanted.peek().is_none().then_some(score)
+}
+
+/// The commands matching the query, best first.
+fn matches<'a>(commands: &'a PaletteCommands, query: &str) -> Vec<&'a PaletteCommand> {
+    let mut matches: Vec<_> = commands
+        .0
+        .iter()
+        .filter_map(|command| Some((fuzzy_score(query, &command.name)?, command)))
+        .collect();
+    matches.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.name.cmp(&b.name)));
+    matches
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, command)| command)
+        .collect()
+}
+
+/// Runs before the rest of the app sees the keyboard, so that typing into the palette doesn't
+/// also trigger the scene's shortcuts.
+fn handle_palette_keys(
+    mut commands: Commands,
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut palette: ResMut<Palette>,
+    mut focus: ResMut<InputFocus>,
+    palette_commands: Res<PaletteCommands>,
+    settings: Res<Settings>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if ctrl
+        && settings
+            .keybindings
+            .just_pressed(&keys, KeyAction::CommandPalette)
+    {
+        if palette.open {
+            *palette = Palette::default();
+        } else {
+            *palette = Palette {
+                open: true,
+                ..default()
+            };
+            // Otherwise a focused button would take Tab and Enter too.
+            focus.clear();
+        }
+        keys.reset_all();
+        keyboard_events.clear();
+        return;
+    }
+    if !palette.open {
+        keyboard_events.clear();
+        return;
+    }
+
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => {
+                *palette = Palette::default();
+                break;
+            }
+            Key::Enter => {
+                if let Some(prompt) = &palette.prompt {
+                    commands.run_system_with(prompt.submit, palette.query.trim().to_string());
+                } else if let Some(command) =
+                    matches(&palette_commands, &palette.query).get(palette.selected)
+                {
+                    info!("Running \"{}\" from the command palette", command.name);
+                    commands.run_system(command.system);
+                    commands.trigger(PaletteCommandRun(command.name.clone()));
+                }
+                *palette = Palette::default();
+                break;
+            }
+            Key::ArrowDown => {
+                let count = matches(&palette_commands, &palette.query).len();
+                palette.selected = (palette.selected + 1).min(count.saturating_sub(1));
+            }
+            Key::ArrowUp => palette.selected = palette.selected.saturating_sub(1),
+            Key::Backspace => {
+                palette.query.pop();
+                palette.selected = 0;
+            }
+            _ => {
+                if let Some(text) = &event.text
+                    && !ctrl
+                    && text.chars().all(|c| !c.is_control())
+                {
+                    palette.query.push_str(text);
+                    palette.selected = 0;
+                }
+            }
+        }
+    }
+    keys.reset_all();
+}
+
+fn close_palette(mut palette: ResMut<Palette>) {
+    *palette = Palette::default();
+}
+
+fn open_prompt(open: On<OpenPrompt>, mut palette: ResMut<Palette>, mut focus: ResMut<InputFocus>) {
+    *palette = Palette {
+        open: true,
+        prompt: Some(open.event().clone()),
+        ..default()
+    };
+    focus.clear();
+}
+
+/// Spawns the palette when it opens and despawns it when it closes.
+fn sync_palette_ui(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    root_query: Query<Entity, With<PaletteRoot>>,
+) {
+    match (palette.open, root_query.single()) {
+        (false, Ok(root)) => {
+            commands.entity(root).despawn();
+        }
+        (true, Err(_)) => {
+            commands.spawn((
+                Name::new("Command Palette"),
+                PaletteRoot,
+                DespawnOnExit(Screen::Game),
+                // Spans the window to center the palette, but shouldn't catch clicks meant for
+                // the scene.
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: percent(15.0),
+                    width: percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                Pickable::IGNORE,
+                // Above the other panels.
+                GlobalZIndex(1),
+                children![(
+                    Node {
+                        width: px(PALETTE_WIDTH),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: px(4.0),
+                        padding: UiRect::all(px(8.0)),
+                        ..default()
+                    },
+                    BackgroundColor(OVERLAY_BACKGROUND),
+                    children![
+                        (PaletteQuery, Text::default(), TextColor(Color::WHITE)),
+                        (
+                            PaletteResults,
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                ..default()
+                            },
+                        ),
+                    ],
+                )],
+            ));
+        }
+        _ => {}
+    }
+}
+
+fn update_palette_ui(
+    mut commands: Commands,
+    palette: Res<Palette>,
+    palette_commands: Res<PaletteCommands>,
+    mut query_text: Query<&mut Text, With<PaletteQuery>>,
+    results_query: Query<Entity, With<PaletteResults>>,
+) {
+    for mut text in &mut query_text {
+        text.0 = match &palette.prompt {
+            Some(prompt) => format!("{}: {}", prompt.label, palette.query),
+            None => format!("> {}", palette.query),
+        };
+    }
+
+    let Ok(results) = results_query.single() else {
+        return;
+    };
+    if palette.prompt.is_some() {
+        commands.entity(results).despawn_children();
+        return;
+    }
+    let found = matches(&palette_commands, &palette.query);
+    commands
+        .entity(results)
+        .despawn_children()
+        .with_children(|results| {
+            if found.is_empty() {
+                results.spawn((
+                    Text::new("No matching commands"),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(HINT_COLOR),
+                ));
+            }
+            for (index, command) in found.into_iter().enumerate() {
+                let background = if index == palette.selected {
+                    SELECTED_BACKGROUND
+                } else {
+                    Color::NONE
+                };
+                results.spawn((
+                    Text::new(command.name.clone()),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(Color::WHITE),
+                    Node {
+                        padding: UiRect::axes(px(6.0), px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(background),
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_runs_and_word_starts_higher() {
+        let exploded = fuzzy_score("tev", "Toggle exploded view").unwrap();
+        let respawn = fuzzy_score("tev", "Respawn the level").unwrap();
+        assert!(exploded > respawn);
+        assert!(fuzzy_score("save", "Save build") > fuzzy_score("save", "Sample your wave"));
+
+        let mut world = World::new();
+        let system = world.register_system(|| {});
+        let mut commands = PaletteCommands::default();
+        commands.add("Respawn the level", system);
+        commands.add("Toggle exploded view", system);
+        commands.add("Save build", system);
+        let names: Vec<_> = matches(&commands, "tev")
+            .into_iter()
+            .map(|command| command.name.as_str())
+            .collect();
+        assert_eq!(names, ["Toggle exploded view", "Respawn the level"]);
+    }
+
+    #[test]
+    fn folds_case() {
+        assert_eq!(
+            fuzzy_score("SAVE BUILD", "save build"),
+            fuzzy_score("save build", "Save Build")
+        );
+        assert!(fuzzy_score("xRaY", "Toggle x-ray view").is_some());
+    }
+
+    #[test]
+    fn no_match() {
+        assert_eq!(fuzzy_score("zz", "Save build"), None);
+        // The letters are all there, but not in order.
+        assert_eq!(fuzzy_score("bs", "Save build"), None);
+        assert!(matches(&PaletteCommands::default(), "save").is_empty());
+    }
+}
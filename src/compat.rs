@@ -0,0 +1,1115 @@
+//! The compatibility rules, on plain data so they can run outside the app and be tested.
+//!
+//! [`check`] takes the parts of a build, each as its catalog spec and where it's mounted, and
+//! the case, and returns every issue with the parts it involves as indices
+//! into the build. The `compatibility` module runs it on the installed parts and shows the
+//! result.
+//!
+//! First the CPU has to fit the motherboard's socket and be supported by its chipset, and the
+//! cooler needs a bracket for the socket. A build that fails those is reported as it is,
+//! without checking how its parts fit. Otherwise the motherboard needs a header for each fan,
+//! pump and RGB part and for each of the case's front-panel connectors, each drive needs a bay
+//! or slot of its size, the power supply has to carry the load with room for spikes, and each
+//! part is treated as the box of its catalog size where it is mounted, which has to stay
+//! inside the clearances for its kind and out of the other parts. The box is centered on the
+//! mount point, but for a GPU support's, which stands on it. A clearance for one chamber of a
+//! dual-chamber case only holds the parts mounted at that chamber's anchors. In a case smaller
+//! than [`SFF_VOLUME`], the cables of the graphics card and the power supply also need room,
+//! and so does a graphics card on a riser behind the motherboard. The backplate of a graphics
+//! card has to clear the pieces of the motherboard's armor that are on, as well. Last, the air
+//! in a liquid cooler's loop shouldn't gather where the pump draws it in.
+
+use bevy::math::{Quat, Vec3};
+
+use crate::{
+    aio::{AioTubes, RadiatorMount, pump_position},
+    board_armor::BoardArmor,
+    cases::{CaseSpec, Clearance},
+    parts::{Connector, DriveSize, PartKind, PartSpec},
+    settings::{Locale, Units},
+};
+
+/// Something in a build that doesn't fit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Indices of the parts involved.
+    pub parts: Vec<usize>,
+    /// Index of the clearance a part sticks out of, if that's the issue.
+    pub clearance: Option<usize>,
+    pub message: String,
+}
+
+/// How far, in millimeters, parts may overlap before they count as colliding.
+pub const COLLISION_TOLERANCE: f32 = 1.0;
+/// How much more than the build's load a power supply should deliver, for the short spikes
+/// of graphics cards and CPUs and to stay efficient.
+pub const POWER_HEADROOM: f32 = 1.3;
+/// The wattages power supplies come in, in watts.
+pub const PSU_TIERS: [f32; 8] = [450.0, 550.0, 650.0, 750.0, 850.0, 1000.0, 1200.0, 1600.0];
+/// Cases smaller than this, in liters, are packed densely enough for the checks of
+/// [`check_small_form_factor`].
+pub const SFF_VOLUME: f32 = 20.0;
+/// Room, in millimeters, the power cables of a graphics card need past its top edge to bend.
+pub const GPU_CABLE_ROOM: f32 = 35.0;
+/// How far, in millimeters, the cables of a power supply stick out of the front of it.
+pub const PSU_CABLE_ROOM: f32 = 30.0;
+/// Room, in millimeters, a graphics card behind the motherboard needs between its fans and the
+/// side panel to draw air.
+pub const SANDWICH_FAN_GAP: f32 = 10.0;
+/// The length of the usual PCIe riser cable, in millimeters.
+pub const RISER_LENGTH: f32 = 200.0;
+
+/// Every issue of the build made of `parts`, each a spec and where it's mounted, in `case`, if
+/// it's known, with the motherboard's `armor` and a liquid cooler's `tubes`. Lengths in the
+/// messages are in `units`, written as in `locale`.
+pub fn check(
+    parts: &[(&PartSpec, Vec3)],
+    case: Option<&CaseSpec>,
+    armor: BoardArmor,
+    tubes: AioTubes,
+    units: Units,
+    locale: Locale,
+) -> Vec<Issue> {
+    let specs: Vec<&PartSpec> = parts.iter().map(|&(spec, _)| spec).collect();
+    let socket_issues = check_sockets(&specs);
+    if !socket_issues.is_empty() {
+        return socket_issues;
+    }
+
+    let mut found = check_headers(&specs);
+    found.extend(check_power(&specs));
+    if let Some(case) = case {
+        found.extend(check_front_io(&specs, case));
+        found.extend(check_drive_bays(&specs, case));
+        found.extend(check_clearances(
+            parts,
+            &case.laid_out_clearances(),
+            units,
+            locale,
+        ));
+        found.extend(check_chamber_clearances(parts, case, units, locale));
+        found.extend(check_small_form_factor(parts, case, units, locale));
+    }
+    let rotation = case.map_or(Quat::IDENTITY, CaseSpec::part_rotation);
+    found.extend(check_armor(parts, rotation, armor));
+    found.extend(check_aio(parts, rotation, tubes));
+    found.extend(check_collisions(parts));
+    found
+}
+
+/// The center of the box a part takes up, from where it's mounted: the mount point itself, or
+/// for a GPU support, which is mounted at its foot, half its height over it.
+pub fn part_center(spec: &PartSpec, mount: Vec3) -> Vec3 {
+    match spec.kind {
+        PartKind::GpuSupport => mount + Vec3::Y * spec.size[1] / 2.0,
+        _ => mount,
+    }
+}
+
+/// The box a part takes up in the case, from its catalog size.
+pub fn part_bounds(spec: &PartSpec, mount: Vec3) -> (Vec3, Vec3) {
+    let center = part_center(spec, mount);
+    let half_size = Vec3::from(spec.size) / 2.0;
+    (center - half_size, center + half_size)
+}
+
+/// The parts of `kind`, with their indices.
+fn of_kind<'a>(
+    parts: &'a [&PartSpec],
+    kind: PartKind,
+) -> impl Iterator<Item = (usize, &'a PartSpec)> + Clone {
+    parts
+        .iter()
+        .enumerate()
+        .filter(move |(_, spec)| spec.kind == kind)
+        .map(|(index, spec)| (index, *spec))
+}
+
+/// Whether the CPU fits the motherboard and the cooler fits either of them.
+pub fn check_sockets(parts: &[&PartSpec]) -> Vec<Issue> {
+    let mut found = Vec::new();
+    for (cpu, cpu_spec) in of_kind(parts, PartKind::Cpu) {
+        for (board, board_spec) in of_kind(parts, PartKind::Motherboard) {
+            if cpu_spec.socket != board_spec.socket {
+                found.push(Issue {
+                    parts: vec![cpu, board],
+                    clearance: None,
+                    message: format!(
+                        "{} doesn't fit the {} socket of the {}",
+                        cpu_spec.name,
+                        board_spec.socket.as_deref().unwrap_or("unknown"),
+                        board_spec.name
+                    ),
+                });
+            } else if let Some(chipset) = &board_spec.chipset
+                && !cpu_spec.chipsets.is_empty()
+                && !cpu_spec.chipsets.contains(chipset)
+            {
+                found.push(Issue {
+                    parts: vec![cpu, board],
+                    clearance: None,
+                    message: format!(
+                        "The {chipset} chipset of the {} doesn't support the {}",
+                        board_spec.name, cpu_spec.name
+                    ),
+                });
+            }
+        }
+    }
+    // Coolers mount to the motherboard around the CPU, so either one tells the socket.
+    for (cooler, cooler_spec) in of_kind(parts, PartKind::Cooler) {
+        if cooler_spec.bracket_sockets.is_empty() {
+            continue;
+        }
+        let mounts = of_kind(parts, PartKind::Motherboard).chain(of_kind(parts, PartKind::Cpu));
+        for (part, spec) in mounts {
+            if let Some(socket) = &spec.socket
+                && !cooler_spec.bracket_sockets.contains(socket)
+            {
+                found.push(Issue {
+                    parts: vec![cooler, part],
+                    clearance: None,
+                    message: format!(
+                        "{} has no mounting bracket for the {socket} socket of the {}",
+                        cooler_spec.name, spec.name
+                    ),
+                });
+                break;
+            }
+        }
+    }
+    found
+}
+
+/// Whether the motherboard has a header for each fan, pump and ARGB part, or needs a
+/// splitter or a hub.
+pub fn check_headers(parts: &[&PartSpec]) -> Vec<Issue> {
+    let mut found = Vec::new();
+    for (board, board_spec) in of_kind(parts, PartKind::Motherboard) {
+        let fans: Vec<usize> = of_kind(parts, PartKind::Fan)
+            .chain(of_kind(parts, PartKind::Cooler))
+            .map(|(index, _)| index)
+            .collect();
+        let pumps: Vec<usize> = of_kind(parts, PartKind::Radiator)
+            .map(|(index, _)| index)
+            .collect();
+        // Pumps need a pump header of their own, fans can take any that's left.
+        let pump_overflow = pumps.len().saturating_sub(board_spec.pump_headers as usize);
+        let free_pump_headers = (board_spec.pump_headers as usize).saturating_sub(pumps.len());
+        if pump_overflow > 0 {
+            found.push(Issue {
+                parts: [board].into_iter().chain(pumps.iter().copied()).collect(),
+                clearance: None,
+                message: format!(
+                    "{} pumps for the {} pump headers of the {}, add a fan hub with a pump \
+                     header",
+                    pumps.len(),
+                    board_spec.pump_headers,
+                    board_spec.name
+                ),
+            });
+        }
+        if fans.len() > board_spec.fan_headers as usize + free_pump_headers {
+            found.push(Issue {
+                parts: [board].into_iter().chain(fans.iter().copied()).collect(),
+                clearance: None,
+                message: format!(
+                    "{} fans for the {} fan headers of the {}, add a fan splitter or hub",
+                    fans.len(),
+                    board_spec.fan_headers as usize + free_pump_headers,
+                    board_spec.name
+                ),
+            });
+        }
+
+        let lit: Vec<usize> = parts
+            .iter()
+            .enumerate()
+            .filter(|(_, spec)| spec.rgb_zone.is_some())
+            .map(|(index, _)| index)
+            .collect();
+        if lit.len() > board_spec.argb_headers as usize {
+            found.push(Issue {
+                parts: [board].into_iter().chain(lit.iter().copied()).collect(),
+                clearance: None,
+                message: format!(
+                    "{} RGB parts for the {} ARGB headers of the {}, add an ARGB splitter or hub",
+                    lit.len(),
+                    board_spec.argb_headers,
+                    board_spec.name
+                ),
+            });
+        }
+    }
+    found
+}
+
+/// Front-panel connectors of `case` the motherboard has no header left for, which can't be
+/// used.
+pub fn check_front_io(parts: &[&PartSpec], case: &CaseSpec) -> Vec<Issue> {
+    let mut found = Vec::new();
+    let count = |connectors: &[Connector], connector| {
+        connectors
+            .iter()
+            .filter(|&&other| other == connector)
+            .count()
+    };
+    for (board, board_spec) in of_kind(parts, PartKind::Motherboard) {
+        let mut checked = Vec::new();
+        for &connector in &case.front_io {
+            if checked.contains(&connector) {
+                continue;
+            }
+            checked.push(connector);
+            let cables = count(&case.front_io, connector);
+            let headers = count(&board_spec.io_headers, connector);
+            if cables > headers {
+                found.push(Issue {
+                    parts: vec![board],
+                    clearance: None,
+                    message: if headers == 0 {
+                        format!(
+                            "The {} has no {} header for the front ports of the {}",
+                            board_spec.name,
+                            connector.name(),
+                            case.name
+                        )
+                    } else {
+                        format!(
+                            "The {} has only {headers} of the {cables} {} headers the front \
+                             ports of the {} need",
+                            board_spec.name,
+                            connector.name(),
+                            case.name
+                        )
+                    },
+                });
+            }
+        }
+    }
+    found
+}
+
+/// The smallest power supply, of [`PSU_TIERS`], for the load of `parts` with
+/// [`POWER_HEADROOM`], or the largest if even that's too small.
+pub fn recommended_wattage(parts: &[&PartSpec]) -> f32 {
+    let load: f32 = parts.iter().map(|spec| spec.power_draw).sum();
+    PSU_TIERS
+        .into_iter()
+        .find(|&tier| tier >= load * POWER_HEADROOM)
+        .unwrap_or(PSU_TIERS[PSU_TIERS.len() - 1])
+}
+
+/// Power supplies smaller than the build's [`recommended_wattage`].
+pub fn check_power(parts: &[&PartSpec]) -> Vec<Issue> {
+    let mut found = Vec::new();
+    let recommended = recommended_wattage(parts);
+    let load: f32 = parts.iter().map(|spec| spec.power_draw).sum();
+    for (psu, psu_spec) in of_kind(parts, PartKind::Psu) {
+        if psu_spec.wattage < recommended {
+            found.push(Issue {
+                parts: vec![psu],
+                clearance: None,
+                message: format!(
+                    "The {} delivers {:.0} W, the build draws {load:.0} W and needs a {:.0} W \
+                     power supply",
+                    psu_spec.name, psu_spec.wattage, recommended
+                ),
+            });
+        }
+    }
+    found
+}
+
+/// Drives that don't fit the bays of `case` or the M.2 slots of the motherboard. A front
+/// radiator takes the place of the case's removable drive cage; a top one leaves it be.
+pub fn check_drive_bays(parts: &[&PartSpec], case: &CaseSpec) -> Vec<Issue> {
+    let mut found = Vec::new();
+    let drives = |size| -> Vec<usize> {
+        of_kind(parts, PartKind::Drive)
+            .filter(|(_, spec)| spec.drive_size == Some(size))
+            .map(|(index, _)| index)
+            .collect()
+    };
+    let radiators: Vec<usize> = of_kind(parts, PartKind::Radiator)
+        .filter(|(_, spec)| RadiatorMount::of(spec.size) == RadiatorMount::Front)
+        .map(|(index, _)| index)
+        .collect();
+    let bays = &case.drive_bays;
+
+    let large = drives(DriveSize::ThreeAndHalf);
+    let large_bays = if radiators.is_empty() {
+        bays.three_and_half
+    } else {
+        bays.three_and_half.saturating_sub(bays.radiator_cage)
+    } as usize;
+    if large.len() > large_bays {
+        let cage_out = !radiators.is_empty() && bays.radiator_cage > 0;
+        found.push(Issue {
+            parts: large
+                .iter()
+                .chain(radiators.iter().filter(|_| cage_out))
+                .copied()
+                .collect(),
+            clearance: None,
+            message: format!(
+                "Only {large_bays} of the {} 3.5\" drives fit the bays of the {}{}",
+                large.len(),
+                case.name,
+                if cage_out {
+                    ", with the drive cage out for the radiator"
+                } else {
+                    ""
+                }
+            ),
+        });
+    }
+
+    let small = drives(DriveSize::TwoAndHalf);
+    let small_bays = bays.two_and_half as usize + large_bays.saturating_sub(large.len());
+    if small.len() > small_bays {
+        found.push(Issue {
+            parts: small.clone(),
+            clearance: None,
+            message: format!(
+                "Only {small_bays} of the {} 2.5\" drives fit the bays of the {}",
+                small.len(),
+                case.name
+            ),
+        });
+    }
+
+    let m2 = drives(DriveSize::M2);
+    for (board, board_spec) in of_kind(parts, PartKind::Motherboard) {
+        if m2.len() > board_spec.m2_slots as usize {
+            found.push(Issue {
+                parts: [board].into_iter().chain(m2.iter().copied()).collect(),
+                clearance: None,
+                message: format!(
+                    "Only {} of the {} M.2 drives fit the slots of the {}",
+                    board_spec.m2_slots,
+                    m2.len(),
+                    board_spec.name
+                ),
+            });
+        }
+    }
+    found
+}
+
+/// Parts that stick out of a clearance for their kind. Clearances for one chamber of a
+/// dual-chamber case are left to [`check_chamber_clearances`].
+pub fn check_clearances(
+    parts: &[(&PartSpec, Vec3)],
+    clearances: &[Clearance],
+    units: Units,
+    locale: Locale,
+) -> Vec<Issue> {
+    overflowing(parts, clearances, units, locale, |_, clearance| {
+        clearance.chamber.is_none()
+    })
+}
+
+/// Parts that stick out of a clearance of `case` for their kind and the chamber they're
+/// mounted in.
+pub fn check_chamber_clearances(
+    parts: &[(&PartSpec, Vec3)],
+    case: &CaseSpec,
+    units: Units,
+    locale: Locale,
+) -> Vec<Issue> {
+    overflowing(
+        parts,
+        &case.laid_out_clearances(),
+        units,
+        locale,
+        |mount, clearance| clearance.chamber == Some(case.chamber_at(mount)),
+    )
+}
+
+/// Parts that stick out of a clearance for their kind that `applies` to where they're mounted.
+fn overflowing(
+    parts: &[(&PartSpec, Vec3)],
+    clearances: &[Clearance],
+    units: Units,
+    locale: Locale,
+    applies: impl Fn(Vec3, &Clearance) -> bool,
+) -> Vec<Issue> {
+    let mut found = Vec::new();
+    for (index, &(spec, mount)) in parts.iter().enumerate() {
+        let (min, max) = part_bounds(spec, mount);
+        for (clearance_index, clearance) in
+            clearances.iter().enumerate().filter(|(_, clearance)| {
+                clearance.kinds.contains(&spec.kind) && applies(mount, clearance)
+            })
+        {
+            let overflow = (Vec3::from(clearance.min) - min)
+                .max(max - Vec3::from(clearance.max))
+                .max_element();
+            if overflow > 0.0 {
+                found.push(Issue {
+                    parts: vec![index],
+                    clearance: Some(clearance_index),
+                    message: format!(
+                        "{} exceeds the {} clearance by {}",
+                        spec.name,
+                        clearance.name,
+                        units.format(overflow, locale)
+                    ),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// The dense packing of a case smaller than [`SFF_VOLUME`]: the room the power cables of the
+/// graphics card and the power supply need, and for a graphics card behind the motherboard, in
+/// a sandwich layout, the gap to the side panel its fans draw air through and the reach of the
+/// riser cable to it. The riser is taken to run from the middle of the motherboard to the
+/// middle of the card at right angles, around the motherboard tray.
+pub fn check_small_form_factor(
+    parts: &[(&PartSpec, Vec3)],
+    case: &CaseSpec,
+    units: Units,
+    locale: Locale,
+) -> Vec<Issue> {
+    let mut found = Vec::new();
+    if case.volume() >= SFF_VOLUME {
+        return found;
+    }
+    // Parts are turned with an inverted case, cables and all.
+    let rotation = case.part_rotation();
+    let cable_rooms = parts
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &(spec, _))| match spec.kind {
+            PartKind::Gpu => Some((index, rotation * Vec3::Y, GPU_CABLE_ROOM)),
+            PartKind::Psu => Some((index, Vec3::Z, PSU_CABLE_ROOM)),
+            _ => None,
+        });
+    for (index, direction, depth) in cable_rooms {
+        let (spec, mount) = parts[index];
+        let (min, max) = room_past(part_bounds(spec, mount), direction, depth);
+        let (case_min, case_max) = case.bounds();
+        let blocking = parts
+            .iter()
+            .enumerate()
+            .find(|&(other, &(other_spec, other_mount))| {
+                let (other_min, other_max) = part_bounds(other_spec, other_mount);
+                let overlap = max.min(other_max) - min.max(other_min);
+                other != index && overlap.min_element() > COLLISION_TOLERANCE
+            });
+        let room = units.format(depth, locale);
+        if let Some((other, &(other_spec, _))) = blocking {
+            found.push(Issue {
+                parts: vec![index, other],
+                clearance: None,
+                message: format!(
+                    "Small form factor: {} is in the {room} the cables of the {} need",
+                    other_spec.name, spec.name
+                ),
+            });
+        } else if (case_min - min).max(max - case_max).max_element() > COLLISION_TOLERANCE {
+            found.push(Issue {
+                parts: vec![index],
+                clearance: None,
+                message: format!(
+                    "Small form factor: the case has no room for the {room} the cables of the \
+                     {} need",
+                    spec.name
+                ),
+            });
+        }
+    }
+
+    // The motherboard faces the side panel along X, the other way round in an inverted case.
+    let facing = rotation * Vec3::X;
+    let boards = parts
+        .iter()
+        .enumerate()
+        .filter(|(_, (spec, _))| spec.kind == PartKind::Motherboard);
+    for (board, &(board_spec, board_mount)) in boards {
+        let (board_back, _) = extent(part_bounds(board_spec, board_mount), facing);
+        for (gpu, &(gpu_spec, gpu_mount)) in parts.iter().enumerate() {
+            let gpu_bounds = part_bounds(gpu_spec, gpu_mount);
+            let (gpu_back, gpu_front) = extent(gpu_bounds, facing);
+            if gpu_spec.kind != PartKind::Gpu || gpu_front > board_back + COLLISION_TOLERANCE {
+                continue;
+            }
+            let (case_back, _) = extent(case.bounds(), facing);
+            let gap = gpu_back - case_back;
+            if gap < SANDWICH_FAN_GAP {
+                found.push(Issue {
+                    parts: vec![gpu],
+                    clearance: None,
+                    message: format!(
+                        "Small form factor: {} is {} from the side panel behind the \
+                         motherboard, too close for its fans to draw air",
+                        gpu_spec.name,
+                        units.format(gap.max(0.0), locale)
+                    ),
+                });
+            }
+            let riser = (part_center(gpu_spec, gpu_mount) - part_center(board_spec, board_mount))
+                .abs()
+                .element_sum();
+            if riser > RISER_LENGTH {
+                found.push(Issue {
+                    parts: vec![gpu, board],
+                    clearance: None,
+                    message: format!(
+                        "Small form factor: {} needs a riser cable of {} to reach the {}, longer \
+                         than the usual {}",
+                        gpu_spec.name,
+                        units.format(riser, locale),
+                        board_spec.name,
+                        units.format(RISER_LENGTH, locale)
+                    ),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// The box of `depth` past the face of the box `bounds` that `direction`, along one of the
+/// axes, points out of.
+fn room_past((min, max): (Vec3, Vec3), direction: Vec3, depth: f32) -> (Vec3, Vec3) {
+    let center = (min + max) / 2.0;
+    let half_size = (max - min) / 2.0;
+    let along = direction.abs();
+    let room_center = center + direction * (half_size + depth / 2.0);
+    let room_half_size = half_size * (Vec3::ONE - along) + along * depth / 2.0;
+    (room_center - room_half_size, room_center + room_half_size)
+}
+
+/// How far the box `bounds` reaches back and forth along `direction`.
+fn extent((min, max): (Vec3, Vec3), direction: Vec3) -> (f32, f32) {
+    let (first, second) = (min.dot(direction), max.dot(direction));
+    (first.min(second), first.max(second))
+}
+
+/// Graphics cards whose backplate runs into a piece of a motherboard's armor that's on, with
+/// the pieces turned like the parts by `rotation`.
+pub fn check_armor(parts: &[(&PartSpec, Vec3)], rotation: Quat, armor: BoardArmor) -> Vec<Issue> {
+    let mut found = Vec::new();
+    for (board, &(board_spec, board_mount)) in parts.iter().enumerate() {
+        for piece in board_spec
+            .armor
+            .iter()
+            .filter(|piece| armor.fitted(piece.kind))
+        {
+            let center = board_mount + rotation * Vec3::from(piece.offset);
+            let half_size = (rotation * Vec3::from(piece.size)).abs() / 2.0;
+            let (min, max) = (center - half_size, center + half_size);
+            let gpus = parts
+                .iter()
+                .enumerate()
+                .filter(|(_, (spec, _))| spec.kind == PartKind::Gpu);
+            for (gpu, &(gpu_spec, gpu_mount)) in gpus {
+                let (gpu_min, gpu_max) = part_bounds(gpu_spec, gpu_mount);
+                let overlap = max.min(gpu_max) - min.max(gpu_min);
+                if overlap.min_element() > COLLISION_TOLERANCE {
+                    found.push(Issue {
+                        parts: vec![gpu, board],
+                        clearance: None,
+                        message: format!(
+                            "The backplate of the {} runs into the {} of the {}",
+                            gpu_spec.name,
+                            piece.kind.name(),
+                            board_spec.name
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Liquid coolers whose loop traps air where the pump draws it in. Air rises to the highest
+/// point of the loop, which shouldn't be the pump on the CPU, with the parts turned by
+/// `rotation`, and gathers at the top of a front radiator, which its `tubes` shouldn't leave
+/// from.
+pub fn check_aio(parts: &[(&PartSpec, Vec3)], rotation: Quat, tubes: AioTubes) -> Vec<Issue> {
+    let mut found = Vec::new();
+    let Some((cpu, &(cpu_spec, cpu_mount))) = parts
+        .iter()
+        .enumerate()
+        .find(|(_, (spec, _))| spec.kind == PartKind::Cpu)
+    else {
+        return found;
+    };
+    let pump = pump_position(cpu_mount, rotation);
+    let radiators = parts
+        .iter()
+        .enumerate()
+        .filter(|(_, (spec, _))| spec.kind == PartKind::Radiator);
+    for (radiator, &(spec, mount)) in radiators {
+        let (_, max) = part_bounds(spec, mount);
+        let radiator_mount = RadiatorMount::of(spec.size);
+        if pump.y > max.y {
+            found.push(Issue {
+                parts: vec![radiator, cpu],
+                clearance: None,
+                message: format!(
+                    "The pump on the {} sits above the {}, so air in the loop gathers in it; \
+                     mount the radiator higher",
+                    cpu_spec.name, spec.name
+                ),
+            });
+        } else if radiator_mount == RadiatorMount::Front && tubes.end(radiator_mount).y > 0.0 {
+            found.push(Issue {
+                parts: vec![radiator],
+                clearance: None,
+                message: format!(
+                    "The tubes of the {} leave from its top, where air in the loop gathers to \
+                     be drawn into the pump; turn them down",
+                    spec.name
+                ),
+            });
+        }
+    }
+    found
+}
+
+/// Pairs of parts that overlap by more than [`COLLISION_TOLERANCE`].
+pub fn check_collisions(parts: &[(&PartSpec, Vec3)]) -> Vec<Issue> {
+    let mut found = Vec::new();
+    for (first, &(first_spec, first_mount)) in parts.iter().enumerate() {
+        let (first_min, first_max) = part_bounds(first_spec, first_mount);
+        for (second, &(second_spec, second_mount)) in parts.iter().enumerate().skip(first + 1) {
+            let (second_min, second_max) = part_bounds(second_spec, second_mount);
+            let overlap = first_max.min(second_max) - first_min.max(second_min);
+            if overlap.min_element() > COLLISION_TOLERANCE {
+                found.push(Issue {
+                    parts: vec![first, second],
+                    clearance: None,
+                    message: format!("{} collides with {}", first_spec.name, second_spec.name),
+                });
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cases::Chamber;
+
+    /// A catalog part of `kind` and `size`, with `extra` catalog fields.
+    fn spec(kind: &str, size: [f32; 3], extra: &str) -> PartSpec {
+        let [x, y, z] = size;
+        ron::de::from_str(&format!(
+            "(id: \"{kind}\", name: \"{kind}\", kind: {kind}, size: ({x:?}, {y:?}, {z:?}), \
+             position: (0.0, 0.0, 0.0), {extra})"
+        ))
+        .unwrap()
+    }
+
+    fn clearance(kind: PartKind, min: [f32; 3], max: [f32; 3]) -> Clearance {
+        Clearance {
+            name: "Test".to_string(),
+            kinds: vec![kind],
+            min,
+            max,
+            chamber: None,
+        }
+    }
+
+    #[test]
+    fn matching_sockets_pass() {
+        let board = spec(
+            "Motherboard",
+            [1.0; 3],
+            "socket: Some(\"AM5\"), chipset: Some(\"B650\")",
+        );
+        let cpu = spec(
+            "Cpu",
+            [1.0; 3],
+            "socket: Some(\"AM5\"), chipsets: [\"B650\"]",
+        );
+        let cooler = spec("Cooler", [1.0; 3], "bracket_sockets: [\"AM5\"]");
+        assert!(check_sockets(&[&board, &cpu, &cooler]).is_empty());
+    }
+
+    #[test]
+    fn socket_mismatch() {
+        let board = spec("Motherboard", [1.0; 3], "socket: Some(\"LGA1700\")");
+        let cpu = spec("Cpu", [1.0; 3], "socket: Some(\"AM5\")");
+        let issues = check_sockets(&[&board, &cpu]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![1, 0]);
+        assert!(issues[0].message.contains("LGA1700"));
+    }
+
+    #[test]
+    fn chipset_mismatch() {
+        let board = spec(
+            "Motherboard",
+            [1.0; 3],
+            "socket: Some(\"AM5\"), chipset: Some(\"A620\")",
+        );
+        let cpu = spec(
+            "Cpu",
+            [1.0; 3],
+            "socket: Some(\"AM5\"), chipsets: [\"B650\", \"X670\"]",
+        );
+        let issues = check_sockets(&[&board, &cpu]);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("A620"));
+    }
+
+    #[test]
+    fn missing_cooler_bracket() {
+        let cpu = spec("Cpu", [1.0; 3], "socket: Some(\"LGA1700\")");
+        let cooler = spec("Cooler", [1.0; 3], "bracket_sockets: [\"AM4\", \"AM5\"]");
+        let issues = check_sockets(&[&cpu, &cooler]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![1, 0]);
+    }
+
+    #[test]
+    fn socket_issues_skip_the_fit_checks() {
+        let board = spec("Motherboard", [10.0; 3], "socket: Some(\"LGA1700\")");
+        let cpu = spec("Cpu", [10.0; 3], "socket: Some(\"AM5\")");
+        // Overlapping entirely, which isn't reported while the socket is wrong.
+        let issues = check(
+            &[(&board, Vec3::ZERO), (&cpu, Vec3::ZERO)],
+            None,
+            BoardArmor::default(),
+            AioTubes::default(),
+            Units::Millimeters,
+            Locale::English,
+        );
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("socket"));
+    }
+
+    #[test]
+    fn headers_fit() {
+        let board = spec(
+            "Motherboard",
+            [1.0; 3],
+            "fan_headers: 1, pump_headers: 1, argb_headers: 1",
+        );
+        let fan = spec("Fan", [1.0; 3], "rgb_zone: Some(\"Front\")");
+        let cooler = spec("Cooler", [1.0; 3], "");
+        // The cooler's fan takes the free pump header.
+        assert!(check_headers(&[&board, &fan, &cooler]).is_empty());
+    }
+
+    #[test]
+    fn too_few_headers() {
+        let board = spec(
+            "Motherboard",
+            [1.0; 3],
+            "fan_headers: 1, pump_headers: 1, argb_headers: 1",
+        );
+        let fan = spec("Fan", [1.0; 3], "rgb_zone: Some(\"Front\")");
+        let other_fan = spec("Fan", [1.0; 3], "rgb_zone: Some(\"Rear\")");
+        let radiator = spec("Radiator", [1.0; 3], "");
+        let issues = check_headers(&[&board, &fan, &other_fan, &radiator]);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].parts, vec![0, 1, 2]);
+        assert!(issues[0].message.contains("fan splitter"));
+        assert!(issues[1].message.contains("ARGB splitter"));
+    }
+
+    #[test]
+    fn front_io_headers() {
+        let case: CaseSpec = ron::de::from_str(
+            "(name: \"Case\", model: \"case.glb\", form_factor: \"ATX\", \
+             dimensions: (1.0, 1.0, 1.0), front_io: [UsbC, Usb3, Usb3, Audio])",
+        )
+        .unwrap();
+        let board = spec(
+            "Motherboard",
+            [1.0; 3],
+            "io_headers: [UsbC, Usb3, Usb3, Usb2, Audio]",
+        );
+        assert!(check_front_io(&[&board], &case).is_empty());
+
+        let older_board = spec("Motherboard", [1.0; 3], "io_headers: [Usb3, Audio]");
+        let issues = check_front_io(&[&older_board], &case);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("USB-C"));
+        assert!(issues[1].message.contains("1 of the 2 USB 3.0 headers"));
+    }
+
+    #[test]
+    fn drive_bays() {
+        let case: CaseSpec = ron::de::from_str(
+            "(name: \"Case\", model: \"case.glb\", form_factor: \"ATX\", \
+             dimensions: (1.0, 1.0, 1.0), \
+             drive_bays: (three_and_half: 1, two_and_half: 1, radiator_cage: 1))",
+        )
+        .unwrap();
+        let hdd = spec("Drive", [1.0; 3], "drive_size: Some(ThreeAndHalf)");
+        let ssd = spec("Drive", [1.0; 3], "drive_size: Some(TwoAndHalf)");
+        let m2 = spec("Drive", [1.0; 3], "drive_size: Some(M2)");
+        let board = spec("Motherboard", [1.0; 3], "m2_slots: 1");
+        assert!(check_drive_bays(&[&board, &hdd, &ssd, &m2], &case).is_empty());
+        // The second 2.5" drive takes the free 3.5" bay.
+        assert!(check_drive_bays(&[&ssd, &ssd], &case).is_empty());
+
+        let radiator = spec("Radiator", [1.0; 3], "");
+        let issues = check_drive_bays(&[&board, &hdd, &radiator, &m2, &m2], &case);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].parts, vec![1, 2]);
+        assert!(issues[0].message.contains("drive cage"));
+        assert_eq!(issues[1].parts, vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn power_budget() {
+        let cpu = spec("Cpu", [1.0; 3], "power_draw: 250.0");
+        let gpu = spec("Gpu", [1.0; 3], "power_draw: 300.0");
+        // 550 W with headroom is 715 W.
+        assert_eq!(recommended_wattage(&[&cpu, &gpu]), 750.0);
+
+        let psu = spec("Psu", [1.0; 3], "wattage: 750.0");
+        assert!(check_power(&[&cpu, &gpu, &psu]).is_empty());
+        let small_psu = spec("Psu", [1.0; 3], "wattage: 650.0");
+        let issues = check_power(&[&cpu, &gpu, &small_psu]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![2]);
+        assert!(issues[0].message.contains("750 W"));
+    }
+
+    #[test]
+    fn clearance_overflow() {
+        let gpu = spec("Gpu", [40.0, 120.0, 300.0], "");
+        let clearances = [
+            clearance(PartKind::Gpu, [-100.0, 0.0, -140.0], [100.0, 200.0, 140.0]),
+            // For other kinds, so it doesn't apply.
+            clearance(PartKind::Cooler, [0.0; 3], [1.0; 3]),
+        ];
+        let issues = check_clearances(
+            &[(&gpu, Vec3::new(0.0, 100.0, 0.0))],
+            &clearances,
+            Units::Millimeters,
+            Locale::English,
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].clearance, Some(0));
+        assert!(issues[0].message.contains("by 10 mm"));
+
+        let shorter_gpu = spec("Gpu", [40.0, 120.0, 280.0], "");
+        let issues = check_clearances(
+            &[(&shorter_gpu, Vec3::new(0.0, 100.0, 0.0))],
+            &clearances,
+            Units::Millimeters,
+            Locale::English,
+        );
+        assert!(issues.is_empty());
+
+        let issues = check_clearances(
+            &[(&gpu, Vec3::new(0.0, 100.0, 0.0))],
+            &clearances,
+            Units::Inches,
+            Locale::English,
+        );
+        assert!(issues[0].message.contains("by 0.39 in"));
+    }
+
+    #[test]
+    fn chamber_clearances() {
+        let case: CaseSpec = ron::de::from_str(
+            "(name: \"Case\", model: \"case.glb\", form_factor: \"ATX\", \
+             dimensions: (300.0, 450.0, 450.0), \
+             anchors: [(name: \"PSU\", kind: Psu, position: (100.0, 50.0, 0.0), chamber: Rear)], \
+             clearances: [(name: \"PSU length\", kinds: [Psu], chamber: Some(Rear), \
+                 min: (50.0, 0.0, -100.0), max: (150.0, 100.0, 100.0))])",
+        )
+        .unwrap();
+        let psu = spec("Psu", [86.0, 86.0, 220.0], "");
+        let rear = Vec3::new(100.0, 50.0, 0.0);
+        assert_eq!(case.chamber_at(rear), Chamber::Rear);
+        assert_eq!(case.rear_chamber_center(), Some(rear));
+        let issues =
+            check_chamber_clearances(&[(&psu, rear)], &case, Units::Millimeters, Locale::English);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].clearance, Some(0));
+        assert!(issues[0].message.contains("by 10 mm"));
+        // The rear chamber's clearance doesn't hold a PSU in the main chamber, nor is it one
+        // for every chamber.
+        let main = Vec3::new(-100.0, 50.0, 0.0);
+        assert_eq!(case.chamber_at(main), Chamber::Main);
+        assert!(
+            check_chamber_clearances(&[(&psu, main)], &case, Units::Millimeters, Locale::English)
+                .is_empty()
+        );
+        assert!(
+            check_clearances(
+                &[(&psu, rear)],
+                &case.laid_out_clearances(),
+                Units::Millimeters,
+                Locale::English
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn small_form_factor() {
+        let case = |dimensions: &str| -> CaseSpec {
+            ron::de::from_str(&format!(
+                "(name: \"Case\", model: \"case.glb\", form_factor: \"Mini-ITX\", \
+                 dimensions: {dimensions})"
+            ))
+            .unwrap()
+        };
+        let small = case("(200.0, 300.0, 300.0)");
+        let board = spec("Motherboard", [4.0, 170.0, 170.0], "");
+        let gpu = spec("Gpu", [40.0, 120.0, 250.0], "");
+        let psu = spec("Psu", [125.0, 63.0, 100.0], "");
+        let fits = [
+            (&board, Vec3::new(-40.0, 215.0, 0.0)),
+            (&gpu, Vec3::new(0.0, 80.0, 0.0)),
+            (&psu, Vec3::new(30.0, 240.0, -80.0)),
+        ];
+        let check = |parts: &[(&PartSpec, Vec3)], case: &CaseSpec| {
+            check_small_form_factor(parts, case, Units::Millimeters, Locale::English)
+        };
+        assert!(check(&fits, &small).is_empty());
+
+        // The PSU sits right over the card's power cables, and its own cables run into the
+        // front panel.
+        let packed = [
+            (&board, Vec3::new(-40.0, 215.0, 0.0)),
+            (&gpu, Vec3::new(0.0, 80.0, 0.0)),
+            (&psu, Vec3::new(30.0, 175.0, 90.0)),
+        ];
+        let issues = check(&packed, &small);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].parts, vec![1, 2]);
+        assert!(issues[0].message.contains("35 mm"));
+        assert_eq!(issues[1].parts, vec![2]);
+        // A bigger case has room to spare for the same.
+        assert!(check(&packed, &case("(250.0, 400.0, 400.0)")).is_empty());
+
+        // Behind the motherboard, close to the side panel and far from the board's middle.
+        let sandwich = [
+            (&board, Vec3::new(-40.0, 215.0, 0.0)),
+            (&gpu, Vec3::new(-75.0, 70.0, 25.0)),
+        ];
+        let issues = check(&sandwich, &small);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("5 mm from the side panel"));
+        assert_eq!(issues[1].parts, vec![1, 0]);
+        assert!(issues[1].message.contains("riser cable of 205 mm"));
+    }
+
+    #[test]
+    fn gpu_support_stands_on_its_mount() {
+        let psu = spec("Psu", [150.0, 86.0, 140.0], "");
+        let support = spec("GpuSupport", [20.0, 60.0, 20.0], "");
+        let gpu = spec("Gpu", [40.0, 120.0, 300.0], "");
+        // The support stands on the PSU, and the card rests on the support.
+        let parts = [
+            (&psu, Vec3::new(0.0, 43.0, 0.0)),
+            (&support, Vec3::new(0.0, 86.0, 0.0)),
+            (&gpu, Vec3::new(0.0, 206.0, 0.0)),
+        ];
+        assert_eq!(
+            part_bounds(&support, Vec3::new(0.0, 86.0, 0.0)),
+            (Vec3::new(-10.0, 86.0, -10.0), Vec3::new(10.0, 146.0, 10.0))
+        );
+        assert!(check_collisions(&parts).is_empty());
+
+        let floor = clearance(
+            PartKind::GpuSupport,
+            [-100.0, 0.0, -100.0],
+            [100.0, 200.0, 100.0],
+        );
+        let issues = check_clearances(
+            &[(&support, Vec3::ZERO)],
+            &[floor],
+            Units::Millimeters,
+            Locale::English,
+        );
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn armor_and_backplates() {
+        let board = spec(
+            "Motherboard",
+            [4.0, 300.0, 240.0],
+            "armor: [(kind: M2Heatsink, offset: (10.0, -40.0, 0.0), size: (16.0, 30.0, 100.0)), \
+             (kind: Chipset, offset: (6.0, -110.0, 70.0), size: (8.0, 50.0, 50.0))]",
+        );
+        let gpu = spec("Gpu", [40.0, 120.0, 300.0], "");
+        // The card's backplate is 13 mm off the board, less than the M.2 heatsink is tall.
+        let parts = [
+            (&board, Vec3::new(-95.0, 250.0, 0.0)),
+            (&gpu, Vec3::new(-60.0, 180.0, 20.0)),
+        ];
+        let issues = check_armor(&parts, Quat::IDENTITY, BoardArmor::default());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![1, 0]);
+        assert!(issues[0].message.contains("M.2 heatsink"));
+
+        let bare = BoardArmor {
+            m2_heatsinks: false,
+            ..BoardArmor::default()
+        };
+        assert!(check_armor(&parts, Quat::IDENTITY, bare).is_empty());
+        // Turned over with the parts in an inverted case.
+        let inverted = [
+            (&board, Vec3::new(95.0, 250.0, 0.0)),
+            (&gpu, Vec3::new(60.0, 320.0, 20.0)),
+        ];
+        let rotation = Quat::from_rotation_z(std::f32::consts::PI);
+        assert_eq!(
+            check_armor(&inverted, rotation, BoardArmor::default()).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn air_in_liquid_coolers() {
+        let cpu = spec("Cpu", [3.0, 40.0, 40.0], "");
+        let front = spec("Radiator", [140.0, 280.0, 30.0], "");
+        let top = spec("Radiator", [120.0, 27.0, 275.0], "");
+        let cpu_mount = Vec3::new(-91.5, 330.0, 0.0);
+        let parts = [(&cpu, cpu_mount), (&front, Vec3::new(40.0, 250.0, 170.0))];
+        assert!(check_aio(&parts, Quat::IDENTITY, AioTubes::default()).is_empty());
+
+        let tubes_up = AioTubes { flipped: true };
+        let issues = check_aio(&parts, Quat::IDENTITY, tubes_up);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![1]);
+        assert!(issues[0].message.contains("tubes"));
+
+        // Mounted low at the front, with its top under the pump.
+        let low = [(&cpu, cpu_mount), (&front, Vec3::new(40.0, 160.0, 170.0))];
+        let issues = check_aio(&low, Quat::IDENTITY, AioTubes::default());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![1, 0]);
+        assert!(issues[0].message.contains("pump"));
+
+        // A top radiator's tubes may leave from either end.
+        let on_top = [(&cpu, cpu_mount), (&top, Vec3::new(-20.0, 425.0, -10.0))];
+        assert!(check_aio(&on_top, Quat::IDENTITY, tubes_up).is_empty());
+        // No CPU, no pump.
+        assert!(check_aio(&low[1..], Quat::IDENTITY, AioTubes::default()).is_empty());
+    }
+
+    #[test]
+    fn collision_tolerance() {
+        let fan = spec("Fan", [10.0; 3], "");
+        let drive = spec("Drive", [10.0; 3], "");
+        // Overlapping by less than the tolerance.
+        let touching = [(&fan, Vec3::ZERO), (&drive, Vec3::new(9.5, 0.0, 0.0))];
+        assert!(check_collisions(&touching).is_empty());
+
+        let overlapping = [(&fan, Vec3::ZERO), (&drive, Vec3::new(8.0, 0.0, 0.0))];
+        let issues = check_collisions(&overlapping);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].parts, vec![0, 1]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Checks that the installed parts fit the case and each other, by the rules in `compat`.
+//!
+//! The parts involved in an issue are outlined in red, as is a clearance of the case (see
+//! `assets/models/cases.ron`) that a part sticks out of, and every issue is listed in a
+//! warning panel above the case label.
+
+use bevy::{color::palettes::css::RED, prelude::*};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    aio::AioTubes,
+    board_armor::BoardArmor,
+    cases::{ActiveCase, CaseManifest, Clearance},
+    compat,
+    parts::{InstalledPart, PartCatalog},
+    settings::Settings,
+    ui_navigation::{FONT_SIZE, PANEL_BACKGROUND},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CompatibilityIssues>();
+    app.add_systems(OnEnter(Screen::Game), spawn_warning_panel);
+    app.add_systems(
+        Update,
+        (
+            check_compatibility.run_if(
+                any_match_filter::<Added<InstalledPart>>
+                    .or(any_component_removed::<InstalledPart>)
+                    .or(resource_changed::<ActiveCase>)
+                    .or(resource_changed::<BoardArmor>)
+                    .or(resource_changed::<AioTubes>)
+                    .or(resource_changed::<Settings>),
+            ),
+            update_warning_panel.run_if(
+                resource_changed::<CompatibilityIssues>.or(any_match_filter::<Added<WarningList>>),
+            ),
+            draw_issue_outlines,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+/// Everything that doesn't fit in the current build.
+#[derive(Resource, Debug, Default)]
+pub struct CompatibilityIssues(pub Vec<CompatibilityIssue>);
+
+#[derive(Debug, Clone)]
+pub struct CompatibilityIssue {
+    /// The installed parts involved.
+    pub parts: Vec<Entity>,
+    /// The clearance a part sticks out of, if that's the issue rather than a collision.
+    pub clearance: Option<Clearance>,
+    pub message: String,
+}
+
+#[derive(Component)]
+struct WarningList;
+
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.4, 0.4);
+const ISSUE_COLOR: Srgba = RED;
+
+fn check_compatibility(
+    mut issues: ResMut<CompatibilityIssues>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    armor: Res<BoardArmor>,
+    tubes: Res<AioTubes>,
+    settings: Res<Settings>,
+    part_query: Query<(Entity, &InstalledPart)>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let case = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0));
+    let (entities, parts): (Vec<Entity>, Vec<_>) = part_query
+        .iter()
+        .filter_map(|(entity, part)| Some((entity, (catalog.get(&part.id)?, part.mount))))
+        .unzip();
+
+    issues.0 = compat::check(
+        &parts,
+        case,
+        *armor,
+        *tubes,
+        settings.units,
+        settings.locale,
+    )
+    .into_iter()
+    .map(|issue| CompatibilityIssue {
+        parts: issue.parts.iter().map(|&index| entities[index]).collect(),
+        clearance: issue
+            .clearance
+            .zip(case)
+            .map(|(index, case)| case.laid_out_clearances()[index].clone()),
+        message: issue.message,
+    })
+    .collect();
+}
+
+fn spawn_warning_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Compatibility Warnings"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            // Above the case label.
+            bottom: px(70.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            WarningList,
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: px(2.0),
+                padding: UiRect::all(px(6.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn update_warning_panel(
+    mut commands: Commands,
+    issues: Res<CompatibilityIssues>,
+    mut list_query: Query<(Entity, &mut Node), With<WarningList>>,
+) {
+    for (list, mut node) in &mut list_query {
+        node.display = if issues.0.is_empty() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+        commands
+            .entity(list)
+            .despawn_children()
+            .with_children(|list| {
+                for issue in &issues.0 {
+                    list.spawn((
+                        Text::new(issue.message.clone()),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(WARNING_COLOR),
+                        Pickable::IGNORE,
+                    ));
+                }
+            });
+    }
+}
+
+/// Outlines the parts involved in an issue, and the clearance a part sticks out of.
+fn draw_issue_outlines(
+    mut gizmos: Gizmos,
+    issues: Res<CompatibilityIssues>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    part_query: Query<(&InstalledPart, &GlobalTransform)>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    for issue in &issues.0 {
+        for &part in &issue.parts {
+            let Some((spec, transform)) = part_query
+                .get(part)
+                .ok()
+                .and_then(|(part, transform)| Some((catalog.get(&part.id)?, transform)))
+            else {
+                continue;
+            };
+            // Follows the part around, as in the exploded view.
+            let outline = transform.mul_transform(
+                Transform::from_translation(compat::part_center(spec, Vec3::ZERO))
+                    .with_scale(Vec3::from(spec.size)),
+            );
+            gizmos.cube(outline, ISSUE_COLOR);
+        }
+        if let (Some(clearance), Ok(level)) = (&issue.clearance, level_query.single()) {
+            let (min, max) = (Vec3::from(clearance.min), Vec3::from(clearance.max));
+            let volume = level.mul_transform(
+                Transform::from_translation((min + max) / 2.0).with_scale(max - min),
+            );
+            gizmos.cube(volume, ISSUE_COLOR.with_alpha(0.3));
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! A preview of the dust that builds up in the case over the months, to weigh up intake
+//! filters and pressure balance.
+//!
+//! Start it from the command palette, and the months tick by, up to two years, as the parts
+//! gather a coat of dust and the panel shows how much of the case it covers. Each fan group
+//! blowing into the case is an intake, filtered if the case lists it in its `dust_filters`,
+//! which the panel toggles. How much dusty air gets in follows
+//! [`ThermalModel::dusty_airflow`]: unfiltered intakes, and exhausts outrunning the intakes to
+//! draw air in through every gap of the case, both dirty it.
+
+use bevy::{
+    input_focus::tab_navigation::TabGroup,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    compat,
+    fans::{FanSpeeds, Rotor},
+    parts::{InstalledPart, PartCatalog, Planned},
+    thermals::ThermalModel,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<DustPreview>();
+    app.init_resource::<DustFilters>();
+    app.add_systems(OnEnter(Screen::Game), spawn_dust_panel);
+    app.add_systems(OnExit(Screen::Game), stop_preview);
+    app.add_systems(
+        Update,
+        (
+            reset_dust_filters,
+            advance_months.run_if(|preview: Res<DustPreview>| preview.shown),
+            // The toggle goes first, to keep track of it every frame.
+            sync_dust_coats.run_if(
+                preview_toggled
+                    .or(any_match_filter::<Added<InstalledPart>>)
+                    .or(any_component_removed::<InstalledPart>)
+                    .or(any_match_filter::<Added<Planned>>)
+                    .or(any_component_removed::<Planned>),
+            ),
+            rebuild_filter_rows
+                .run_if(any_match_filter::<Added<Rotor>>.or(any_component_removed::<Rotor>)),
+            update_dust,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(toggle_filter);
+    app.add_palette_command(
+        "Dust buildup: start or stop the preview",
+        |mut preview: ResMut<DustPreview>| {
+            preview.shown = !preview.shown;
+            preview.months = 0.0;
+        },
+    );
+}
+
+/// Whether the dust preview is running, and how far along it is.
+#[derive(Resource, Debug, Default)]
+struct DustPreview {
+    shown: bool,
+    months: f32,
+}
+
+/// The fan groups whose intakes are filtered, starting out as the active case lists them.
+#[derive(Resource, Debug, Default)]
+struct DustFilters {
+    /// The case the filters were last reset for.
+    case: Option<String>,
+    groups: HashSet<String>,
+}
+
+#[derive(Component)]
+struct DustPanel;
+
+/// The container the per-group rows are spawned into.
+#[derive(Component)]
+struct FilterList;
+
+/// Shows how long the dust has been building up and how much of the case it covers.
+#[derive(Component)]
+struct CoverageLabel;
+
+/// Shows whether the case is under positive or negative pressure.
+#[derive(Component)]
+struct PressureLabel;
+
+/// Suggests how to keep the dust out.
+#[derive(Component)]
+struct AdviceLabel;
+
+/// Shows whether the group it names blows into the case or out of it.
+#[derive(Component)]
+struct DirectionLabel(String);
+
+/// Fits or takes off the filter of the group it names.
+#[derive(Component)]
+struct FilterButton(String);
+
+/// A coat of dust on top of the installed part it's a child of.
+#[derive(Component)]
+struct DustCoat;
+
+/// Simulated months that pass every second.
+const MONTHS_PER_SECOND: f32 = 1.0;
+const MAX_MONTHS: f32 = 24.0;
+/// How thick a coat of dust looks, in millimeters.
+const COAT_THICKNESS: f32 = 1.0;
+const DUST_COLOR: Color = Color::srgb(0.55, 0.5, 0.45);
+/// How opaque the coat is once dust covers the whole case.
+const MAX_DUST_ALPHA: f32 = 0.9;
+
+fn preview_toggled(preview: Res<DustPreview>, mut shown: Local<bool>) -> bool {
+    let toggled = preview.shown != *shown;
+    *shown = preview.shown;
+    toggled
+}
+
+fn stop_preview(mut preview: ResMut<DustPreview>) {
+    preview.shown = false;
+}
+
+fn advance_months(time: Res<Time>, mut preview: ResMut<DustPreview>) {
+    preview.months = (preview.months + time.delta_secs() * MONTHS_PER_SECOND).min(MAX_MONTHS);
+}
+
+/// Fits the filters the active case comes with, once it's loaded and whenever it's swapped.
+fn reset_dust_filters(
+    mut filters: ResMut<DustFilters>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+) {
+    if filters.case.as_ref() == Some(&active_case.0) {
+        return;
+    }
+    let Some(case) = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+    else {
+        return;
+    };
+    filters.case = Some(active_case.0.clone());
+    filters.groups = case.dust_filters.iter().cloned().collect();
+}
+
+/// Coats the owned parts while the preview runs. Planned parts stay clean, being see-through.
+fn sync_dust_coats(
+    mut commands: Commands,
+    preview: Res<DustPreview>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    part_query: Query<(Entity, &InstalledPart), Without<Planned>>,
+    coat_query: Query<Entity, With<DustCoat>>,
+) {
+    for coat in &coat_query {
+        commands.entity(coat).despawn();
+    }
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    if !preview.shown {
+        return;
+    }
+    // Dust settles on what faces up, which an inverted case turns to the bottom of its parts.
+    let up = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+        .map_or(Vec3::Y, |case| case.part_rotation().inverse() * Vec3::Y);
+    let material = materials.add(StandardMaterial {
+        base_color: DUST_COLOR.with_alpha(0.0),
+        perceptual_roughness: 1.0,
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+
+    for (entity, part) in &part_query {
+        let Some(spec) = catalog.get(&part.id) else {
+            continue;
+        };
+        let size = Vec3::from(spec.size);
+        let center = compat::part_center(spec, Vec3::ZERO);
+        commands.spawn((
+            Name::new("Dust Coat"),
+            DustCoat,
+            Mesh3d(meshes.add(Cuboid::new(size.x, COAT_THICKNESS, size.z))),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(center + up * (size.y + COAT_THICKNESS) / 2.0),
+            Pickable::IGNORE,
+            ChildOf(entity),
+        ));
+    }
+}
+
+fn label(marker: impl Component) -> impl Bundle {
+    (
+        marker,
+        Text::default(),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+    )
+}
+
+fn spawn_dust_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Dust Panel"),
+        DustPanel,
+        DespawnOnExit(Screen::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            top: percent(20.0),
+            left: px(5.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(4.0),
+            padding: UiRect::all(px(6.0)),
+            // Shown while the preview runs.
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(PANEL_BACKGROUND),
+        TabGroup::new(12),
+        children![
+            (Text::new("Dust"), TextColor(Color::WHITE)),
+            label(CoverageLabel),
+            label(PressureLabel),
+            (
+                Name::new("Filter List"),
+                FilterList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    ..default()
+                },
+            ),
+            label(AdviceLabel),
+        ],
+    ));
+}
+
+/// Respawns one row per fan group in the scene, in alphabetical order.
+fn rebuild_filter_rows(
+    mut commands: Commands,
+    list_query: Query<Entity, With<FilterList>>,
+    rotor_query: Query<&Rotor>,
+) {
+    let Ok(list) = list_query.single() else {
+        return;
+    };
+
+    let mut groups: Vec<&str> = rotor_query
+        .iter()
+        .map(|rotor| rotor.group.as_str())
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+
+    commands
+        .entity(list)
+        .despawn_children()
+        .with_children(|list| {
+            for group in groups {
+                list.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(6.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        DirectionLabel(group.to_string()),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn((
+                        Button,
+                        FilterButton(group.to_string()),
+                        focusable(),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                        Node {
+                            padding: UiRect::axes(px(6.0), px(2.0)),
+                            ..default()
+                        },
+                        ButtonStyle,
+                    ));
+                });
+            }
+        });
+}
+
+/// Works out the dust in the case and shows it on the parts and in the panel, every frame as
+/// the months go by and the fans ease to their speeds.
+fn update_dust(
+    preview: Res<DustPreview>,
+    filters: Res<DustFilters>,
+    fan_speeds: Res<FanSpeeds>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    models: Res<Assets<ThermalModel>>,
+    manifests: Res<Assets<CaseManifest>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rotor_query: Query<(&Rotor, &GlobalTransform)>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+    coat_query: Query<&MeshMaterial3d<StandardMaterial>, With<DustCoat>>,
+    mut panel_query: Query<&mut Node, With<DustPanel>>,
+    mut direction_query: Query<(&DirectionLabel, &mut Text), Without<FilterButton>>,
+    mut button_query: Query<(&FilterButton, &mut Text, &mut Node), Without<DustPanel>>,
+    mut label_query: Query<
+        (&mut Text, Has<CoverageLabel>, Has<PressureLabel>),
+        (Without<DirectionLabel>, Without<FilterButton>),
+    >,
+) {
+    let display = if preview.shown {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    for mut node in &mut panel_query {
+        if node.display != display {
+            node.display = display;
+        }
+    }
+    let (Some(model), Ok(level)) = (models.get(&level_assets.thermals), level_query.single())
+    else {
+        return;
+    };
+    if !preview.shown {
+        return;
+    }
+    let height = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+        .map_or(0.0, |case| case.dimensions[1]);
+    let center = level.transform_point(Vec3::Y * height / 2.0);
+
+    // The airflow of each group, positive into the case and negative out of it.
+    let mut airflows: HashMap<String, f32> = HashMap::new();
+    for (rotor, transform) in &rotor_query {
+        let airflow = model.airflow(fan_speeds.rpm(&rotor.group));
+        let inward = Rotor::airflow_direction(transform).dot(center - transform.translation());
+        *airflows.entry(rotor.group.clone()).or_default() +=
+            if inward > 0.0 { airflow } else { -airflow };
+    }
+    let intake: f32 = airflows.values().filter(|airflow| **airflow > 0.0).sum();
+    let exhaust: f32 = -airflows
+        .values()
+        .filter(|airflow| **airflow < 0.0)
+        .sum::<f32>();
+    let dusty = model.dusty_airflow(
+        airflows
+            .iter()
+            .filter(|(_, airflow)| **airflow > 0.0)
+            .map(|(group, airflow)| (*airflow, filters.groups.contains(group))),
+        exhaust,
+    );
+    let coverage = model.dust_coverage(dusty, preview.months);
+
+    if let Some(material) = coat_query
+        .iter()
+        .next()
+        .and_then(|coat| materials.get_mut(&coat.0))
+    {
+        material.base_color.set_alpha(coverage * MAX_DUST_ALPHA);
+    }
+
+    let unfiltered = airflows
+        .iter()
+        .filter(|(group, airflow)| **airflow > 0.0 && !filters.groups.contains(*group))
+        .map(|(group, _)| group.as_str())
+        .min();
+    let advice = match unfiltered {
+        Some(group) => format!("Fit a filter to the {group} intake to keep the dust out"),
+        None if exhaust > intake => {
+            "More intake than exhaust keeps dust from being drawn in through the gaps".to_string()
+        }
+        None => "Filtered intakes and positive pressure keep the dust out".to_string(),
+    };
+    for (mut text, coverage_label, pressure_label) in &mut label_query {
+        let readout = if coverage_label {
+            format!(
+                "{:.0} months: {:.0}% covered",
+                preview.months.floor(),
+                coverage * 100.0
+            )
+        } else if pressure_label {
+            let balance = intake - exhaust;
+            let pressure = if balance >= 0.0 {
+                "Positive"
+            } else {
+                "Negative"
+            };
+            format!("{pressure} pressure, {balance:+.0} CFM")
+        } else {
+            advice.clone()
+        };
+        if text.0 != readout {
+            text.0 = readout;
+        }
+    }
+
+    for (label, mut text) in &mut direction_query {
+        let direction = match airflows.get(&label.0) {
+            Some(airflow) if *airflow > 0.0 => "intake",
+            _ => "exhaust",
+        };
+        let readout = format!("{} {direction}", label.0);
+        if text.0 != readout {
+            text.0 = readout;
+        }
+    }
+    for (button, mut text, mut node) in &mut button_query {
+        // Filters only matter on intakes.
+        let display = if airflows
+            .get(&button.0)
+            .is_some_and(|airflow| *airflow > 0.0)
+        {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
+        let label = if filters.groups.contains(&button.0) {
+            "Filtered"
+        } else {
+            "No filter"
+        };
+        if text.0 != label {
+            text.0 = label.to_string();
+        }
+    }
+}
+
+fn toggle_filter(
+    activate: On<Activate>,
+    button_query: Query<&FilterButton>,
+    mut filters: ResMut<DustFilters>,
+) {
+    let Ok(FilterButton(group)) = button_query.get(activate.entity) else {
+        return;
+    };
+    if !filters.groups.remove(group) {
+        filters.groups.insert(group.clone());
+    }
+}
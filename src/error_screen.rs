@@ -0,0 +1,118 @@
+//! Shown instead of the loading screen when assets fail to load, with a way to retry.
+
+use bevy::{input_focus::tab_navigation::TabGroup, prelude::*};
+
+use crate::{
+    Screen,
+    asset_tracking::ResourceHandles,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        enter_error_screen.run_if(in_state(Screen::Loading).and(any_asset_failed)),
+    );
+    app.add_systems(OnEnter(Screen::Error), spawn_error_screen);
+    app.add_systems(
+        Update,
+        leave_error_screen.run_if(in_state(Screen::Error).and(not(any_asset_failed))),
+    );
+    app.add_observer(retry_loading);
+}
+
+#[derive(Component)]
+struct RetryButton;
+
+const BACKGROUND: Color = Color::BLACK;
+const ERROR_COLOR: Color = Color::srgb(1.0, 0.4, 0.4);
+
+fn any_asset_failed(
+    resource_handles: Res<ResourceHandles>,
+    asset_server: Res<AssetServer>,
+) -> bool {
+    resource_handles.failures(&asset_server).next().is_some()
+}
+
+fn enter_error_screen(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Error);
+}
+
+/// A retry has started loading the failed assets again; the loading screen takes it from here.
+fn leave_error_screen(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Loading);
+}
+
+fn spawn_error_screen(
+    mut commands: Commands,
+    resource_handles: Res<ResourceHandles>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        Name::new("Error Camera"),
+        DespawnOnExit(Screen::Error),
+        Camera2d,
+    ));
+
+    let failures: Vec<String> = resource_handles
+        .failures(&asset_server)
+        .map(|(id, error)| match asset_server.get_path(id) {
+            Some(path) => format!("{path}: {error}"),
+            None => error.to_string(),
+        })
+        .collect();
+
+    commands
+        .spawn((
+            Name::new("Error Screen"),
+            DespawnOnExit(Screen::Error),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: px(8.0),
+                padding: UiRect::all(px(20.0)),
+                ..default()
+            },
+            BackgroundColor(BACKGROUND),
+            TabGroup::new(0),
+            children![(
+                Text::new("Some assets could not be loaded"),
+                TextColor(Color::WHITE),
+            )],
+        ))
+        .with_children(|screen| {
+            for failure in failures {
+                screen.spawn((
+                    Text::new(failure),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(ERROR_COLOR),
+                ));
+            }
+            screen.spawn((
+                Button,
+                RetryButton,
+                focusable(),
+                Text::new("Retry"),
+                TextColor(Color::WHITE),
+                Node {
+                    padding: UiRect::axes(px(10.0), px(4.0)),
+                    ..default()
+                },
+                ButtonStyle,
+            ));
+        });
+}
+
+fn retry_loading(
+    activate: On<Activate>,
+    button_query: Query<(), With<RetryButton>>,
+    resource_handles: Res<ResourceHandles>,
+    asset_server: Res<AssetServer>,
+) {
+    if button_query.contains(activate.entity) {
+        resource_handles.retry_failed(&asset_server);
+    }
+}
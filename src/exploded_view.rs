@@ -0,0 +1,136 @@
+//! An exploded view that moves the case's panels and the installed parts away from its center.
+//!
+//! Nodes of the case model take part when their name starts with [`EXPLODE_PREFIX`], like
+//! `Explode_SidePanel`; installed parts always do. Each moves straight out from the center of
+//! the active case, as seen in the level, however deep in the model the node is.
+
+use bevy::prelude::*;
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    parts::InstalledPart,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ExplodedView>();
+    app.add_systems(OnExit(Screen::Game), collapse_instantly);
+    app.add_systems(
+        Update,
+        (
+            toggle_exploded_view.run_if(key_just_pressed(KeyAction::ExplodeCase)),
+            tag_exploding_nodes,
+            animate_exploded_view,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Toggle exploded view", toggle_exploded_view);
+}
+
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct ExplodedView {
+    /// Where the view is heading: 0 assembled, 1 fully exploded.
+    pub target: f32,
+    /// How far the view has come, from 0 to 1.
+    pub progress: f32,
+}
+
+/// Remembers where a node sits when the case is assembled.
+#[derive(Component, Debug, Clone, Copy)]
+struct Exploding {
+    rest: Vec3,
+    /// The way the node moves out, in its parent's space, as long as a unit is in the level's.
+    direction: Vec3,
+}
+
+const EXPLODE_PREFIX: &str = "Explode_";
+/// How far a node moves out when fully exploded.
+const EXPLODE_DISTANCE: f32 = 250.0;
+/// Seconds to go from assembled to fully exploded.
+const EXPLODE_DURATION: f32 = 0.8;
+
+fn toggle_exploded_view(mut exploded: ResMut<ExplodedView>) {
+    exploded.target = if exploded.target > 0.5 { 0.0 } else { 1.0 };
+}
+
+fn tag_exploding_nodes(
+    mut commands: Commands,
+    active: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    node_query: Query<
+        (Entity, &Name, &Transform, &GlobalTransform, &ChildOf),
+        (Added<Name>, Without<Exploding>),
+    >,
+    part_query: Query<(Entity, &Transform), (Added<InstalledPart>, Without<Exploding>)>,
+    parent_query: Query<&GlobalTransform>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let Ok(level) = level_query.single() else {
+        return;
+    };
+    let center = active.center(&level_assets, &manifests);
+
+    // Nodes of the model can sit anywhere in its hierarchy, so they're placed in the level
+    // through their global transforms, which have been propagated since the scene spawned.
+    for (entity, name, transform, global_transform, child_of) in &node_query {
+        if !name.as_str().starts_with(EXPLODE_PREFIX) {
+            continue;
+        }
+        let Ok(parent) = parent_query.get(child_of.parent()) else {
+            continue;
+        };
+        let position = level
+            .affine()
+            .inverse()
+            .transform_point3(global_transform.translation());
+        let direction = (position - center).normalize_or_zero();
+        if direction != Vec3::ZERO {
+            let world_direction = level.affine().transform_vector3(direction);
+            commands.entity(entity).insert(Exploding {
+                rest: transform.translation,
+                direction: parent.affine().inverse().transform_vector3(world_direction),
+            });
+        }
+    }
+    // Parts are children of the level.
+    for (entity, transform) in &part_query {
+        let rest = transform.translation;
+        let direction = (rest - center).normalize_or_zero();
+        if direction != Vec3::ZERO {
+            commands
+                .entity(entity)
+                .insert(Exploding { rest, direction });
+        }
+    }
+}
+
+fn animate_exploded_view(
+    time: Res<Time>,
+    mut exploded: ResMut<ExplodedView>,
+    mut node_query: Query<(Ref<Exploding>, &mut Transform)>,
+) {
+    let step = time.delta_secs() / EXPLODE_DURATION;
+    let progress = exploded.progress + (exploded.target - exploded.progress).clamp(-step, step);
+    // Others, like the timeline, may set the progress directly.
+    let moved = progress != exploded.progress || exploded.is_changed();
+    if progress != exploded.progress {
+        exploded.progress = progress;
+    }
+
+    let distance = EXPLODE_DISTANCE * EaseFunction::SmoothStep.sample_clamped(progress);
+    for (exploding, mut transform) in &mut node_query {
+        if moved || exploding.is_added() {
+            transform.translation = exploding.rest + exploding.direction * distance;
+        }
+    }
+}
+
+/// A respawned level starts out assembled.
+fn collapse_instantly(mut exploded: ResMut<ExplodedView>) {
+    *exploded = default();
+}
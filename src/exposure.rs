@@ -0,0 +1,108 @@
+//! Camera exposure, either metered automatically or set manually.
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::post_process::auto_exposure::AutoExposurePlugin;
+use bevy::{camera::Exposure, post_process::auto_exposure::AutoExposure, prelude::*};
+
+use crate::{Screen, keybindings::KeyAction, settings::Settings};
+
+pub(super) fn plugin(app: &mut App) {
+    // Auto exposure needs compute shaders, which WebGL2 doesn't have.
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugins(AutoExposurePlugin);
+
+    app.init_resource::<ExposureSettings>();
+    app.add_systems(
+        Update,
+        (
+            adjust_exposure,
+            apply_exposure.run_if(
+                resource_changed::<ExposureSettings>.or(any_match_filter::<Added<Camera3d>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+/// How the camera exposure is chosen.
+#[derive(Reflect, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum ExposureMode {
+    /// Use [`ExposureSettings::ev100`].
+    #[default]
+    Manual,
+    /// Meter the scene and adapt within [`ExposureSettings::auto_range`].
+    Auto,
+}
+
+#[derive(Resource, Reflect, Debug, Clone, PartialEq)]
+#[reflect(Resource)]
+pub struct ExposureSettings {
+    pub mode: ExposureMode,
+    /// Manual exposure value, in EV100.
+    pub ev100: f32,
+    /// The exposure values auto exposure is allowed to settle on.
+    pub auto_range: std::ops::RangeInclusive<f32>,
+}
+
+impl Default for ExposureSettings {
+    fn default() -> Self {
+        Self {
+            mode: ExposureMode::default(),
+            ev100: Exposure::EV100_BLENDER,
+            auto_range: -8.0..=8.0,
+        }
+    }
+}
+
+/// How much a single key press changes the manual exposure, in stops.
+const EV_STEP: f32 = 0.5;
+
+fn adjust_exposure(
+    keys: Res<ButtonInput<KeyCode>>,
+    app_settings: Res<Settings>,
+    mut settings: ResMut<ExposureSettings>,
+) {
+    let bindings = &app_settings.keybindings;
+    #[cfg(not(target_arch = "wasm32"))]
+    if bindings.just_pressed(&keys, KeyAction::AutoExposure) {
+        settings.mode = match settings.mode {
+            ExposureMode::Manual => ExposureMode::Auto,
+            ExposureMode::Auto => ExposureMode::Manual,
+        };
+    }
+
+    if settings.mode == ExposureMode::Manual {
+        // A higher EV lets in less light, so "brighter" lowers it.
+        if bindings.just_pressed(&keys, KeyAction::ExposureUp) {
+            settings.ev100 -= EV_STEP;
+        }
+        if bindings.just_pressed(&keys, KeyAction::ExposureDown) {
+            settings.ev100 += EV_STEP;
+        }
+    }
+}
+
+pub fn apply_exposure(
+    mut commands: Commands,
+    settings: Res<ExposureSettings>,
+    camera_query: Query<Entity, With<Camera3d>>,
+) {
+    for camera in &camera_query {
+        let mut camera = commands.entity(camera);
+        camera.insert(Exposure {
+            ev100: settings.ev100,
+        });
+        match settings.mode {
+            ExposureMode::Manual => {
+                camera.remove::<AutoExposure>();
+            }
+            ExposureMode::Auto => {
+                camera.insert(AutoExposure {
+                    range: settings.auto_range.clone(),
+                    ..default()
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Recoloring the frames and blades of installed fans, each on its own.
+//!
+//! Set the colors of the selected fan from the command palette, as a hex color for the frame
+//! and one for the blades, like `#202020 #ff8800`. They're saved with the build. A mesh of a fan
+//! is part of its blades when its node, or a node between it and the part, is named with
+//! "blade" or "rotor" in it, like the rotors [`fans`](crate::fans) spins; the rest is frame.
+//! Fans without a model are all frame. A logo on a fan's hub, see [`logos`](crate::logos),
+//! keeps its own colors.
+
+use std::collections::BTreeMap;
+
+use bevy::{ecs::system::SystemId, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    LevelAssets, Screen,
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    logos::LogoDecal,
+    part_selection::{HighlightSelectedPart, SelectedPart},
+    parts::{InstalledPart, PartCatalog, PartKind},
+    rgb::{Led, attach_leds},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FanColors>();
+    let set_colors = app.register_system(set_selected_fan_colors);
+    app.insert_resource(FanColorPrompt(set_colors));
+    app.add_systems(
+        Update,
+        (attach_fan_paint, paint_fans)
+            .chain()
+            .after(attach_leds)
+            .before(HighlightSelectedPart)
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(OnExit(Screen::Game), forget_fan_colors);
+    app.add_palette_command(
+        "Set the frame and blade colors of the selected fan",
+        prompt_for_fan_colors,
+    );
+}
+
+/// The colors set on the build's fans, by catalog id.
+#[derive(Resource, Debug, Default)]
+pub struct FanColors(pub BTreeMap<String, FanColor>);
+
+/// Colors in place of a fan's own, as sRGB. Either part keeps its own color while unset.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FanColor {
+    #[serde(default)]
+    pub frame: Option<[f32; 3]>,
+    #[serde(default)]
+    pub blades: Option<[f32; 3]>,
+}
+
+impl FanColor {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Sets the colors of the selected fan from the text it's given.
+#[derive(Resource, Debug)]
+struct FanColorPrompt(SystemId<In<String>>);
+
+/// A mesh of an installed fan, which has a material of its own to paint.
+#[derive(Component, Debug)]
+struct FanPaint {
+    /// Catalog id of the fan.
+    id: String,
+    blades: bool,
+    /// The color the mesh had before any was set.
+    original: Color,
+}
+
+const BLADE_WORDS: [&str; 2] = ["blade", "rotor"];
+
+/// Reads a fan's colors like `#202020 #ff8800`, the frame's then the blades'. A single color
+/// sets both, `-` keeps the fan's own, and nothing sets neither.
+pub fn parse_fan_color(text: &str) -> Option<FanColor> {
+    let color = |word: &str| match word {
+        "-" => Some(None),
+        hex => Srgba::hex(hex)
+            .ok()
+            .map(|color| Some([color.red, color.green, color.blue])),
+    };
+    match text.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => Some(FanColor::default()),
+        [both] => {
+            let both = color(both)?;
+            Some(FanColor {
+                frame: both,
+                blades: both,
+            })
+        }
+        [frame, blades] => Some(FanColor {
+            frame: color(frame)?,
+            blades: color(blades)?,
+        }),
+        _ => None,
+    }
+}
+
+/// Runs every frame, since a fan's model may finish spawning well after it was installed.
+fn attach_fan_paint(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    part_query: Query<(Entity, &InstalledPart)>,
+    children_query: Query<&Children>,
+    parent_query: Query<&ChildOf>,
+    name_query: Query<&Name>,
+    mut mesh_query: Query<
+        (&mut MeshMaterial3d<StandardMaterial>, Has<Led>),
+        (Without<FanPaint>, Without<LogoDecal>),
+    >,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    for (part, installed) in &part_query {
+        if catalog
+            .get(&installed.id)
+            .is_none_or(|spec| spec.kind != PartKind::Fan)
+        {
+            continue;
+        }
+        for entity in std::iter::once(part).chain(children_query.iter_descendants(part)) {
+            let Ok((mut material, has_led)) = mesh_query.get_mut(entity) else {
+                continue;
+            };
+            let Some(own) = materials.get(&material.0).cloned() else {
+                continue;
+            };
+            let original = own.base_color;
+            // LEDs already have a material of their own, which they glow through.
+            if !has_led {
+                material.0 = materials.add(own);
+            }
+            let blades = std::iter::once(entity)
+                .chain(parent_query.iter_ancestors(entity))
+                .take_while(|&node| node != part)
+                .filter_map(|node| name_query.get(node).ok())
+                .any(|name| {
+                    let name = name.as_str().to_lowercase();
+                    BLADE_WORDS.iter().any(|word| name.contains(word))
+                });
+            commands.entity(entity).insert(FanPaint {
+                id: installed.id.clone(),
+                blades,
+                original,
+            });
+        }
+    }
+}
+
+/// Paints whichever material each fan mesh shows, keeping its alpha for see-through planned
+/// parts.
+fn paint_fans(
+    fan_colors: Res<FanColors>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    paint_query: Query<(&FanPaint, &MeshMaterial3d<StandardMaterial>)>,
+) {
+    for (paint, material) in &paint_query {
+        let set = fan_colors.0.get(&paint.id).and_then(|color| {
+            if paint.blades {
+                color.blades
+            } else {
+                color.frame
+            }
+        });
+        let color = set.map_or(paint.original, |[red, green, blue]| {
+            Color::srgb(red, green, blue)
+        });
+        let Some(current) = materials
+            .get(&material.0)
+            .map(|material| material.base_color)
+        else {
+            continue;
+        };
+        let color = color.with_alpha(current.alpha());
+        if current != color
+            && let Some(material) = materials.get_mut(&material.0)
+        {
+            material.base_color = color;
+        }
+    }
+}
+
+fn prompt_for_fan_colors(mut commands: Commands, prompt: Res<FanColorPrompt>) {
+    commands.trigger(OpenPrompt {
+        label: "Frame and blade colors, like #202020 #ff8800, - to keep one".to_string(),
+        submit: prompt.0,
+    });
+}
+
+fn set_selected_fan_colors(
+    In(text): In<String>,
+    selected: Res<SelectedPart>,
+    mut fan_colors: ResMut<FanColors>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    parent_query: Query<&ChildOf>,
+    part_query: Query<&InstalledPart>,
+) {
+    let catalog = catalogs.get(&level_assets.catalog);
+    let fan = selected.0.and_then(|selected| {
+        std::iter::once(selected)
+            .chain(parent_query.iter_ancestors(selected))
+            .find_map(|entity| part_query.get(entity).ok())
+            .filter(|part| {
+                catalog
+                    .and_then(|catalog| catalog.get(&part.id))
+                    .is_some_and(|spec| spec.kind == PartKind::Fan)
+            })
+    });
+    let Some(fan) = fan else {
+        info!("Select an installed fan to set its colors first");
+        return;
+    };
+    let Some(color) = parse_fan_color(&text) else {
+        info!("\"{text}\" is not two hex colors like #202020 #ff8800");
+        return;
+    };
+    if color.is_default() {
+        fan_colors.0.remove(&fan.id);
+    } else {
+        fan_colors.0.insert(fan.id.clone(), color);
+    }
+}
+
+fn forget_fan_colors(mut fan_colors: ResMut<FanColors>) {
+    fan_colors.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_and_blades() {
+        let orange = Some([1.0, 136.0 / 255.0, 0.0]);
+        let black = Some([0.0; 3]);
+        assert_eq!(
+            parse_fan_color("#000000 #ff8800"),
+            Some(FanColor {
+                frame: black,
+                blades: orange,
+            })
+        );
+        assert_eq!(
+            parse_fan_color("- #ff8800"),
+            Some(FanColor {
+                frame: None,
+                blades: orange,
+            })
+        );
+        assert_eq!(
+            parse_fan_color("#000"),
+            Some(FanColor {
+                frame: black,
+                blades: black,
+            })
+        );
+        assert_eq!(parse_fan_color(" "), Some(FanColor::default()));
+        assert_eq!(parse_fan_color("#000 #fff #888"), None);
+        assert_eq!(parse_fan_color("black"), None);
+    }
+}
@@ -0,0 +1,440 @@
+//! Spinning fan rotors, with an RPM slider per group of fans.
+//!
+//! Next to each slider, the panel shows the temperature of the zones the group cools, colored
+//! from blue to red, and below them how loud the fans are, both following the rotors live as
+//! the sliders move (see [`thermals`](crate::thermals)). A focused slider steps by
+//! [`RPM_STEP`] with Left and Right, on the keyboard or the D-pad.
+//!
+//! A node of a model spins when its name starts with [`ROTOR_PREFIX`], and the word after the
+//! prefix names its group: `fan_rotor_front_1` and `fan_rotor_front_2` both belong to "front".
+//! How loud the fans are is estimated from their speeds with [`noise_level`].
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    input_focus::{InputFocus, tab_navigation::TabGroup},
+    platform::collections::HashMap,
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
+
+use crate::{
+    Screen,
+    actions::Actions,
+    rgb::temperature_color,
+    thermals::{Temperatures, zone_group},
+    ui_navigation::{Adjustable, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FanSpeeds>();
+    app.add_systems(OnEnter(Screen::Game), spawn_fan_panel);
+    app.add_systems(
+        Update,
+        (
+            tag_rotors,
+            drag_rpm_sliders,
+            step_focused_slider,
+            spin_rotors,
+            rebuild_fan_rows
+                .run_if(any_match_filter::<Added<Rotor>>.or(any_component_removed::<Rotor>)),
+            update_sliders,
+            update_readouts,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+/// A fan rotor, spinning around its local [`ROTOR_AXIS`].
+#[derive(Component)]
+pub struct Rotor {
+    pub group: String,
+    /// The rotation the rotor was modeled with.
+    rest: Quat,
+    angle: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct FanSpeeds {
+    /// Groups that haven't been touched in the panel spin at [`DEFAULT_RPM`].
+    pub groups: HashMap<String, FanSpeed>,
+}
+
+impl Rotor {
+    /// The way the rotor blows the air, in world space, turned with its fan and its case.
+    pub fn airflow_direction(transform: &GlobalTransform) -> Vec3 {
+        (transform.affine().matrix3 * Vec3A::from(ROTOR_AXIS))
+            .normalize_or_zero()
+            .into()
+    }
+}
+
+impl FanSpeeds {
+    /// The speed the rotors of `group` spin at.
+    pub fn rpm(&self, group: &str) -> f32 {
+        self.groups
+            .get(group)
+            .map_or(DEFAULT_RPM, |speed| speed.rpm)
+    }
+
+    /// The speed set for `group`, which its rotors may still be easing towards.
+    pub fn target_rpm(&self, group: &str) -> f32 {
+        self.groups
+            .get(group)
+            .map_or(DEFAULT_RPM, |speed| speed.target_rpm)
+    }
+}
+
+/// Roughly how loud fans spinning at `rpms` are together, in dB(A), or `None` if none spin.
+///
+/// Each fan is [`REFERENCE_NOISE`] at [`REFERENCE_RPM`] and follows the fan laws, gaining
+/// 50 log10 of its speed up; their sound powers add up.
+pub fn noise_level(rpms: impl IntoIterator<Item = f32>) -> Option<f32> {
+    let power: f32 = rpms
+        .into_iter()
+        .filter(|rpm| *rpm > 0.0)
+        .map(|rpm| {
+            let level = REFERENCE_NOISE + 50.0 * (rpm / REFERENCE_RPM).log10();
+            10f32.powf(level / 10.0)
+        })
+        .sum();
+    (power > 0.0).then(|| 10.0 * power.log10())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FanSpeed {
+    /// The speed set on the slider.
+    pub target_rpm: f32,
+    /// The speed the rotors spin at, easing towards `target_rpm`.
+    pub rpm: f32,
+}
+
+impl Default for FanSpeed {
+    fn default() -> Self {
+        Self {
+            target_rpm: DEFAULT_RPM,
+            rpm: DEFAULT_RPM,
+        }
+    }
+}
+
+#[derive(Component)]
+struct FanPanel;
+
+/// The container the per-group rows are spawned into.
+#[derive(Component)]
+struct FanList;
+
+/// Sets the target RPM of the group it names to where it is pressed, or steps it while focused.
+#[derive(Component)]
+struct RpmSlider(String);
+
+/// The part of an [`RpmSlider`] that fills up.
+#[derive(Component)]
+struct RpmSliderFill(String);
+
+/// Shows the speed of the group it names.
+#[derive(Component)]
+struct RpmLabel(String);
+
+/// Shows the temperature of the hottest zone the group it names cools.
+#[derive(Component)]
+struct TemperatureLabel(String);
+
+/// Shows how loud the fans are.
+#[derive(Component)]
+struct NoiseLabel;
+
+const ROTOR_PREFIX: &str = "fan_rotor_";
+/// Rotors spin around this local axis, which the model should point along the airflow.
+const ROTOR_AXIS: Vec3 = Vec3::Z;
+const DEFAULT_RPM: f32 = 800.0;
+const MAX_RPM: f32 = 2000.0;
+/// How far Left or Right moves a focused slider.
+const RPM_STEP: f32 = 100.0;
+/// How quickly the rotors ease to a new speed; higher is snappier.
+const RPM_SMOOTHING: f32 = 2.0;
+/// How loud a typical 120 mm fan is at [`REFERENCE_RPM`], in dB(A).
+const REFERENCE_NOISE: f32 = 22.0;
+const REFERENCE_RPM: f32 = 1000.0;
+
+const SLIDER_WIDTH: f32 = 120.0;
+const SLIDER_BACKGROUND: Color = Color::srgb(0.2, 0.2, 0.2);
+const SLIDER_FILL: Color = Color::srgb(0.8, 0.8, 0.8);
+/// Lightens the temperature colors, so the blue stays readable on the panel.
+const TEMPERATURE_LIGHTENING: f32 = 0.15;
+
+fn tag_rotors(mut commands: Commands, node_query: Query<(Entity, &Name, &Transform), Added<Name>>) {
+    for (entity, name, transform) in &node_query {
+        if let Some(group) = name.as_str().strip_prefix(ROTOR_PREFIX)
+            && let Some(group) = group.split('_').next()
+            && !group.is_empty()
+        {
+            commands.entity(entity).insert(Rotor {
+                group: group.to_string(),
+                rest: transform.rotation,
+                angle: 0.0,
+            });
+        }
+    }
+}
+
+fn drag_rpm_sliders(
+    slider_query: Query<(&RpmSlider, &Interaction, &RelativeCursorPosition)>,
+    mut speeds: ResMut<FanSpeeds>,
+) {
+    for (slider, interaction, cursor) in &slider_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        // `normalized` is relative to the slider's center.
+        let target_rpm = (position.x + 0.5).clamp(0.0, 1.0) * MAX_RPM;
+        let speed = speeds.groups.entry(slider.0.clone()).or_default();
+        if speed.target_rpm != target_rpm {
+            speed.target_rpm = target_rpm;
+        }
+    }
+}
+
+fn step_focused_slider(
+    actions: Res<Actions>,
+    focus: Res<InputFocus>,
+    slider_query: Query<&RpmSlider>,
+    mut speeds: ResMut<FanSpeeds>,
+) {
+    if actions.adjust == 0.0 {
+        return;
+    }
+    let Some(slider) = focus.get().and_then(|entity| slider_query.get(entity).ok()) else {
+        return;
+    };
+    let speed = speeds.groups.entry(slider.0.clone()).or_default();
+    speed.target_rpm = (speed.target_rpm + actions.adjust * RPM_STEP).clamp(0.0, MAX_RPM);
+}
+
+fn spin_rotors(
+    time: Res<Time>,
+    mut speeds: ResMut<FanSpeeds>,
+    mut rotor_query: Query<(&mut Rotor, &mut Transform)>,
+) {
+    let delta = time.delta_secs();
+    for speed in speeds.groups.values_mut() {
+        speed
+            .rpm
+            .smooth_nudge(&speed.target_rpm, RPM_SMOOTHING, delta);
+    }
+
+    for (mut rotor, mut transform) in &mut rotor_query {
+        let rpm = speeds.rpm(&rotor.group);
+        rotor.angle = (rotor.angle + rpm / 60.0 * TAU * delta).rem_euclid(TAU);
+        transform.rotation = rotor.rest * Quat::from_axis_angle(ROTOR_AXIS, rotor.angle);
+    }
+}
+
+fn spawn_fan_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Fan Panel"),
+        FanPanel,
+        DespawnOnExit(Screen::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            top: percent(50.0),
+            right: px(5.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(4.0),
+            padding: UiRect::all(px(6.0)),
+            // Shown once the scene has rotors.
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(PANEL_BACKGROUND),
+        TabGroup::new(11),
+        children![
+            (Text::new("Fans"), TextColor(Color::WHITE)),
+            (
+                Name::new("Fan List"),
+                FanList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    ..default()
+                },
+            ),
+            (
+                NoiseLabel,
+                Text::default(),
+                TextFont::from_font_size(FONT_SIZE),
+                TextColor(Color::WHITE),
+            ),
+        ],
+    ));
+}
+
+/// Respawns one row per group in the scene, in alphabetical order.
+fn rebuild_fan_rows(
+    mut commands: Commands,
+    mut panel_query: Query<&mut Node, With<FanPanel>>,
+    list_query: Query<Entity, With<FanList>>,
+    rotor_query: Query<&Rotor>,
+) {
+    let Ok(list) = list_query.single() else {
+        return;
+    };
+
+    let mut groups: Vec<&str> = rotor_query
+        .iter()
+        .map(|rotor| rotor.group.as_str())
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+
+    for mut node in &mut panel_query {
+        node.display = if groups.is_empty() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+
+    commands
+        .entity(list)
+        .despawn_children()
+        .with_children(|list| {
+            for group in groups {
+                list.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(6.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(group),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn((
+                        Button,
+                        RpmSlider(group.to_string()),
+                        focusable(),
+                        Adjustable,
+                        RelativeCursorPosition::default(),
+                        Node {
+                            width: px(SLIDER_WIDTH),
+                            height: px(10.0),
+                            ..default()
+                        },
+                        BackgroundColor(SLIDER_BACKGROUND),
+                        children![(
+                            RpmSliderFill(group.to_string()),
+                            Node {
+                                width: percent(0.0),
+                                height: percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(SLIDER_FILL),
+                        )],
+                    ));
+                    row.spawn((
+                        RpmLabel(group.to_string()),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn((
+                        TemperatureLabel(group.to_string()),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                });
+            }
+        });
+}
+
+/// Keeps the slider fills and RPM labels in sync with the target speeds.
+fn update_sliders(
+    speeds: Res<FanSpeeds>,
+    mut fill_query: Query<(&RpmSliderFill, &mut Node)>,
+    mut label_query: Query<(&RpmLabel, &mut Text)>,
+) {
+    for (fill, mut node) in &mut fill_query {
+        let width = percent(speeds.target_rpm(&fill.0) / MAX_RPM * 100.0);
+        if node.width != width {
+            node.width = width;
+        }
+    }
+
+    for (label, mut text) in &mut label_query {
+        let rpm = format!("{:.0} RPM", speeds.target_rpm(&label.0));
+        if text.0 != rpm {
+            text.0 = rpm;
+        }
+    }
+}
+
+/// Updates the temperatures and the noise every frame, as the rotors ease to their speeds and
+/// the zones heat up and cool down.
+fn update_readouts(
+    speeds: Res<FanSpeeds>,
+    temperatures: Res<Temperatures>,
+    rotor_query: Query<&Rotor>,
+    mut temperature_query: Query<
+        (&TemperatureLabel, &mut Text, &mut TextColor),
+        Without<NoiseLabel>,
+    >,
+    mut noise_query: Query<&mut Text, With<NoiseLabel>>,
+) {
+    for (label, mut text, mut color) in &mut temperature_query {
+        let hottest = temperatures
+            .zones
+            .iter()
+            .filter(|(zone, _)| zone_group(zone) == label.0)
+            .map(|(_, celsius)| *celsius)
+            .reduce(f32::max);
+        let (readout, readout_color) = match hottest {
+            Some(celsius) => (
+                format!("{celsius:.0} °C"),
+                temperature_color(celsius).lighter(TEMPERATURE_LIGHTENING),
+            ),
+            None => (String::new(), Color::WHITE),
+        };
+        if text.0 != readout {
+            text.0 = readout;
+        }
+        color.set_if_neq(TextColor(readout_color));
+    }
+
+    let noise = noise_level(rotor_query.iter().map(|rotor| speeds.rpm(&rotor.group)));
+    let readout = match noise {
+        Some(noise) => format!("Noise {noise:.0} dB(A)"),
+        None => "Silent".to_string(),
+    };
+    for mut text in &mut noise_query {
+        if text.0 != readout {
+            text.0.clone_from(&readout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_adds_up() {
+        assert_eq!(noise_level([]), None);
+        assert_eq!(noise_level([0.0]), None);
+        let one = noise_level([REFERENCE_RPM]).unwrap();
+        assert!((one - REFERENCE_NOISE).abs() < 0.01);
+        // Two fans are 3 dB louder than one, and twice the speed is 15 dB louder.
+        let two = noise_level([REFERENCE_RPM; 2]).unwrap();
+        assert!((two - one - 3.01).abs() < 0.01, "{two}");
+        let fast = noise_level([REFERENCE_RPM * 2.0]).unwrap();
+        assert!((fast - one - 15.05).abs() < 0.01, "{fast}");
+    }
+}
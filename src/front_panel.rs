@@ -0,0 +1,261 @@
+//! A guide to wiring the case's front panel: the motherboard's front-panel headers marked and
+//! labeled, with a suggested path for each of the case's front I/O cables to its header.
+//!
+//! Turn it on and off from the command palette. Headers sit where ATX boards put them: the HD
+//! audio, USB 2.0 and front panel (power switch, reset and LEDs) headers along the bottom edge,
+//! from the back to the front, and the USB 3.0 and USB-C ones on the front edge. Each cable of
+//! the case takes the next free header of its kind, as the compatibility checks count them, and
+//! runs from the front I/O behind the motherboard tray, then out through the grommet by the
+//! nearest edge of the board to the header. Headers no cable takes are dimmed.
+
+use bevy::{
+    color::palettes::css::{GRAY, LIGHT_GRAY, LIME, ORANGE, SKY_BLUE, TURQUOISE},
+    prelude::*,
+};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    labels::BillboardLabel,
+    parts::{Connector, InstalledPart, PartCatalog, PartKind},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FrontPanelGuide>();
+    app.add_systems(
+        Update,
+        (
+            sync_header_markers.run_if(
+                resource_changed::<FrontPanelGuide>
+                    .or(resource_changed::<ActiveCase>)
+                    .or(any_match_filter::<Added<InstalledPart>>)
+                    .or(any_component_removed::<InstalledPart>),
+            ),
+            draw_cable_paths.run_if(|guide: Res<FrontPanelGuide>| guide.shown),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(OnExit(Screen::Game), hide_guide);
+    app.add_palette_command(
+        "Front panel: show or hide the headers and cable paths",
+        |mut guide: ResMut<FrontPanelGuide>| guide.shown = !guide.shown,
+    );
+}
+
+/// Whether the front panel guide is shown.
+#[derive(Resource, Debug, Default)]
+struct FrontPanelGuide {
+    shown: bool,
+}
+
+/// A header of the motherboard it's a child of, with the case's cable for it if there is one.
+#[derive(Component, Debug, Clone, Copy)]
+struct HeaderMarker {
+    connector: Connector,
+    cabled: bool,
+}
+
+const LABEL_GROUP: &str = "Front panel headers";
+/// How far behind the motherboard, in millimeters, the cables run along the tray.
+const TRAY_DEPTH: f32 = 20.0;
+/// How far past the edge of the motherboard, in millimeters, the grommets cables come through
+/// are.
+const GROMMET_GAP: f32 = 15.0;
+/// The size of a header's marker, in millimeters.
+const MARKER_SIZE: Vec3 = Vec3::new(4.0, 8.0, 16.0);
+
+fn connector_color(connector: Connector) -> Srgba {
+    match connector {
+        Connector::UsbC => TURQUOISE,
+        Connector::Usb3 => SKY_BLUE,
+        Connector::Usb2 => LIGHT_GRAY,
+        Connector::Audio => LIME,
+        Connector::FrontPanel => ORANGE,
+    }
+}
+
+/// Where the `nth` header of `connector` sits on a motherboard of `size`, from its middle, in
+/// its own space: a little in front of the board, along the bottom or the front edge.
+pub fn header_offset(size: Vec3, connector: Connector, nth: usize) -> Vec3 {
+    let half = size / 2.0;
+    let nth = nth as f32;
+    let (y, z) = match connector {
+        Connector::Audio => (-half.y + 8.0, -half.z + 20.0 + 25.0 * nth),
+        Connector::Usb2 => (-half.y + 8.0, -10.0 + 25.0 * nth),
+        Connector::FrontPanel => (-half.y + 8.0, half.z - 30.0 - 25.0 * nth),
+        Connector::Usb3 => (-half.y + 60.0 + 25.0 * nth, half.z - 8.0),
+        Connector::UsbC => (-half.y + 120.0 + 20.0 * nth, half.z - 8.0),
+    };
+    Vec3::new(half.x + MARKER_SIZE.x / 2.0, y, z)
+}
+
+/// The suggested path of a cable from `front_io` to the `header` of a motherboard of `size`,
+/// both in the board's space: behind the board to the height of the grommet by the edge the
+/// header is nearest, along the tray to the grommet, and out through it to the header.
+pub fn cable_path(size: Vec3, header: Vec3, front_io: Vec3) -> Vec<Vec3> {
+    let half = size / 2.0;
+    let grommet = if header.y + half.y < half.z - header.z {
+        Vec3::new(header.x, -half.y - GROMMET_GAP, header.z)
+    } else {
+        Vec3::new(header.x, header.y, half.z + GROMMET_GAP)
+    };
+    let behind = |point: Vec3| point.with_x(-half.x - TRAY_DEPTH);
+    vec![
+        front_io,
+        behind(front_io),
+        behind(front_io.with_y(grommet.y)),
+        behind(grommet),
+        grommet,
+        header,
+    ]
+}
+
+/// Marks the headers of the installed motherboards while the guide is shown.
+fn sync_header_markers(
+    mut commands: Commands,
+    guide: Res<FrontPanelGuide>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    part_query: Query<(Entity, &InstalledPart)>,
+    marker_query: Query<Entity, With<HeaderMarker>>,
+) {
+    for marker in &marker_query {
+        commands.entity(marker).despawn();
+    }
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    if !guide.shown {
+        return;
+    }
+    let front_io = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+        .map_or(&[][..], |case| &case.front_io);
+
+    for (board, part) in &part_query {
+        let Some(spec) = catalog
+            .get(&part.id)
+            .filter(|spec| spec.kind == PartKind::Motherboard)
+        else {
+            continue;
+        };
+        for (index, &connector) in spec.io_headers.iter().enumerate() {
+            let nth = spec.io_headers[..index]
+                .iter()
+                .filter(|&&other| other == connector)
+                .count();
+            let cables = front_io.iter().filter(|&&other| other == connector).count();
+            commands.spawn((
+                Name::new(format!("{} Header", connector.name())),
+                HeaderMarker {
+                    connector,
+                    cabled: nth < cables,
+                },
+                Transform::from_translation(header_offset(Vec3::from(spec.size), connector, nth)),
+                BillboardLabel {
+                    text: connector.name().to_string(),
+                    group: LABEL_GROUP.to_string(),
+                    offset: Vec3::ZERO,
+                },
+                ChildOf(board),
+            ));
+        }
+    }
+}
+
+fn draw_cable_paths(
+    mut gizmos: Gizmos,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    marker_query: Query<(&HeaderMarker, &Transform, &ChildOf)>,
+    board_query: Query<(&InstalledPart, &GlobalTransform)>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    let (Some(catalog), Ok(level)) = (catalogs.get(&level_assets.catalog), level_query.single())
+    else {
+        return;
+    };
+    let front_io = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0))
+        .map(|case| level.transform_point(case.front_io_position()));
+
+    for (marker, transform, child_of) in &marker_query {
+        let Some((spec, board)) = board_query
+            .get(child_of.parent())
+            .ok()
+            .and_then(|(part, board)| Some((catalog.get(&part.id)?, board)))
+        else {
+            continue;
+        };
+        let color = if marker.cabled {
+            connector_color(marker.connector)
+        } else {
+            GRAY
+        };
+        gizmos.cube(
+            board.mul_transform(transform.with_scale(MARKER_SIZE)),
+            color,
+        );
+        if let Some(front_io) = front_io.filter(|_| marker.cabled) {
+            let from = board.affine().inverse().transform_point3(front_io);
+            let path = cable_path(Vec3::from(spec.size), transform.translation, from);
+            gizmos.linestrip(
+                path.into_iter().map(|point| board.transform_point(point)),
+                color,
+            );
+        }
+    }
+}
+
+fn hide_guide(mut guide: ResMut<FrontPanelGuide>) {
+    guide.shown = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: Vec3 = Vec3::new(4.0, 305.0, 244.0);
+
+    #[test]
+    fn headers_on_the_edges() {
+        let audio = header_offset(BOARD, Connector::Audio, 0);
+        let panel = header_offset(BOARD, Connector::FrontPanel, 0);
+        let usb3 = header_offset(BOARD, Connector::Usb3, 0);
+        // In front of the board, the audio header at the back of the bottom edge and the front
+        // panel one at its front, and the USB 3.0 one on the front edge.
+        assert!(audio.x > BOARD.x / 2.0);
+        assert_eq!(audio.y, panel.y);
+        assert!(audio.z < 0.0 && panel.z > 0.0);
+        assert_eq!(usb3.z, BOARD.z / 2.0 - 8.0);
+        assert!(
+            header_offset(BOARD, Connector::Usb2, 1).z > header_offset(BOARD, Connector::Usb2, 0).z
+        );
+    }
+
+    #[test]
+    fn cables_run_behind_the_tray() {
+        let front_io = Vec3::new(60.0, 200.0, 200.0);
+        let header = header_offset(BOARD, Connector::Audio, 0);
+        let path = cable_path(BOARD, header, front_io);
+        assert_eq!(path.first(), Some(&front_io));
+        assert_eq!(path.last(), Some(&header));
+        // Behind the board all the way to the grommet under its bottom edge.
+        assert!(path[1..4].iter().all(|point| point.x < -BOARD.x / 2.0));
+        assert_eq!(path[3].y, -BOARD.y / 2.0 - GROMMET_GAP);
+
+        // The USB 3.0 header comes through the grommet by the front edge.
+        let header = header_offset(BOARD, Connector::Usb3, 0);
+        let path = cable_path(BOARD, header, front_io);
+        assert_eq!(path[4].z, BOARD.z / 2.0 + GROMMET_GAP);
+        assert_eq!(path[4].y, header.y);
+    }
+}
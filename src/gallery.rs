@@ -0,0 +1,325 @@
+//! A gallery of the captures saved to `screenshots/`, to look back at them without leaving the
+//! app.
+//!
+//! Each capture is saved with a `.ron` file next to it holding the build, camera and lighting
+//! preset it was taken with. The gallery, opened from the command palette, lists the captures
+//! newest first with a thumbnail each. Reopen puts the build, camera and lighting back as they
+//! were, so a shot can be taken again; Delete removes the capture and its file. Only on native,
+//! as the web build downloads its captures instead.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    input_focus::tab_navigation::TabGroup,
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    capture::CAPTURE_FOLDER,
+    cases::CaseManifest,
+    command_palette::AddPaletteCommand,
+    lighting::ActiveLightingPreset,
+    persistence::{SavedBuild, apply_build},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, OVERLAY_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Gallery>();
+    app.add_systems(OnEnter(Screen::Game), spawn_gallery_panel);
+    app.add_systems(
+        Update,
+        update_gallery_panel
+            .run_if(resource_changed::<Gallery>.or(any_match_filter::<Added<GalleryList>>))
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_gallery_button);
+    app.add_palette_command("Open or close the screenshot gallery", toggle_gallery);
+}
+
+/// Whether the gallery is shown. Marked changed to list the captures again.
+#[derive(Resource, Debug, Default)]
+pub struct Gallery {
+    shown: bool,
+}
+
+/// What a capture was taken of, kept next to it so it can be taken again.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShotInfo {
+    pub build: SavedBuild,
+    /// The lighting preset the scene was lit with.
+    pub lighting: String,
+}
+
+#[derive(Component)]
+struct GalleryList;
+
+#[derive(Component, Debug, Clone)]
+struct GalleryButton {
+    /// The capture's image.
+    path: PathBuf,
+    action: GalleryAction,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum GalleryAction {
+    Reopen,
+    Delete,
+}
+
+const HINT_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+/// Width of a capture's thumbnail in the gallery, in pixels.
+const THUMBNAIL_WIDTH: u32 = 160;
+/// How many captures are listed, newest first.
+const MAX_SHOWN: usize = 8;
+
+/// Where the info of the capture at `image` is kept.
+fn info_path(image: &Path) -> PathBuf {
+    image.with_extension("ron")
+}
+
+/// Writes the info of the capture saved as `file_name` in the captures folder.
+pub fn save_shot_info(file_name: &str, info: &ShotInfo) {
+    let path = info_path(&Path::new(CAPTURE_FOLDER).join(file_name));
+    let result = ron::ser::to_string_pretty(info, default())
+        .map_err(io::Error::other)
+        .and_then(|contents| fs::write(&path, contents));
+    if let Err(error) = result {
+        error!("Could not save {}: {error}", path.display());
+    }
+}
+
+fn load_shot_info(image: &Path) -> Option<ShotInfo> {
+    let path = info_path(image);
+    let contents = fs::read_to_string(&path).ok()?;
+    ron::from_str(&contents)
+        .inspect_err(|error| warn!("Could not read {}: {error}", path.display()))
+        .ok()
+}
+
+/// The captures in the captures folder, newest first.
+fn list_captures() -> Vec<PathBuf> {
+    let entries = match fs::read_dir(CAPTURE_FOLDER) {
+        Ok(entries) => entries,
+        Err(error) => {
+            if error.kind() != io::ErrorKind::NotFound {
+                warn!("Could not list {CAPTURE_FOLDER}: {error}");
+            }
+            return Vec::new();
+        }
+    };
+    let mut captures: Vec<_> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "png"))
+        .collect();
+    // Capture names end in the time they were taken.
+    captures.sort_unstable_by(|a, b| b.cmp(a));
+    captures
+}
+
+/// A small copy of the capture at `path`, to show in the gallery.
+fn load_thumbnail(path: &Path) -> Option<Image> {
+    let pixels = image::open(path)
+        .inspect_err(|error| warn!("Could not read {}: {error}", path.display()))
+        .ok()?
+        .to_rgba8();
+    let (width, height) = pixels.dimensions();
+    let thumbnail_height = (height * THUMBNAIL_WIDTH / width.max(1)).max(1);
+    let thumbnail = image::imageops::thumbnail(&pixels, THUMBNAIL_WIDTH, thumbnail_height);
+    Some(Image::new(
+        Extent3d {
+            width: THUMBNAIL_WIDTH,
+            height: thumbnail_height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        thumbnail.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ))
+}
+
+fn toggle_gallery(mut gallery: ResMut<Gallery>) {
+    gallery.shown = !gallery.shown;
+}
+
+/// Lists the captures again, as one was taken or deleted.
+pub fn refresh_gallery(mut gallery: ResMut<Gallery>) {
+    gallery.set_changed();
+}
+
+fn spawn_gallery_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Gallery Panel"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(40.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            GalleryList,
+            Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                max_width: percent(80.0),
+                column_gap: px(8.0),
+                row_gap: px(8.0),
+                padding: UiRect::all(px(6.0)),
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(OVERLAY_BACKGROUND),
+            TabGroup::new(10),
+        )],
+    ));
+}
+
+/// Lists the newest captures, keeping the thumbnails that were loaded before in `thumbnails`.
+fn update_gallery_panel(
+    mut commands: Commands,
+    mut thumbnails: Local<HashMap<PathBuf, Handle<Image>>>,
+    gallery: Res<Gallery>,
+    mut images: ResMut<Assets<Image>>,
+    mut list_query: Query<(Entity, &mut Node), With<GalleryList>>,
+) {
+    let Ok((list, mut node)) = list_query.single_mut() else {
+        return;
+    };
+    node.display = if gallery.shown {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if !gallery.shown {
+        return;
+    }
+
+    let captures = list_captures();
+    thumbnails.retain(|path, _| captures.contains(path));
+    commands
+        .entity(list)
+        .despawn_children()
+        .with_children(|list| {
+            if captures.is_empty() {
+                list.spawn((
+                    Text::new("No captures yet, press F12 to take one"),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(HINT_COLOR),
+                ));
+            }
+            for path in captures.into_iter().take(MAX_SHOWN) {
+                let thumbnail = match thumbnails.get(&path) {
+                    Some(thumbnail) => Some(thumbnail.clone()),
+                    None => load_thumbnail(&path).map(|image| {
+                        let handle = images.add(image);
+                        thumbnails.insert(path.clone(), handle.clone());
+                        handle
+                    }),
+                };
+                let name = path
+                    .file_stem()
+                    .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+                let reopenable = info_path(&path).exists();
+                list.spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(2.0),
+                    ..default()
+                })
+                .with_children(|card| {
+                    if let Some(thumbnail) = thumbnail {
+                        card.spawn((
+                            ImageNode::new(thumbnail),
+                            Node {
+                                width: px(THUMBNAIL_WIDTH as f32),
+                                ..default()
+                            },
+                        ));
+                    }
+                    card.spawn((
+                        Text::new(name),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    card.spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: px(4.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        if reopenable {
+                            row.spawn(button(&path, GalleryAction::Reopen, "Reopen"));
+                        }
+                        row.spawn(button(&path, GalleryAction::Delete, "Delete"));
+                    });
+                });
+            }
+        });
+}
+
+fn button(path: &Path, action: GalleryAction, label: &str) -> impl Bundle {
+    (
+        Button,
+        GalleryButton {
+            path: path.to_path_buf(),
+            action,
+        },
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn activate_gallery_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    button_query: Query<&GalleryButton>,
+    mut gallery: ResMut<Gallery>,
+    mut lighting: ResMut<ActiveLightingPreset>,
+    mut camera_query: Query<&mut OrbitCamera>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+) {
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+
+    match button.action {
+        GalleryAction::Reopen => {
+            let Some(info) = load_shot_info(&button.path) else {
+                return;
+            };
+            let manifest = manifests.get(&level_assets.cases);
+            apply_build(&mut commands, &info.build, manifest, &mut camera_query);
+            lighting.set_if_neq(ActiveLightingPreset(info.lighting));
+            gallery.shown = false;
+        }
+        GalleryAction::Delete => {
+            for path in [button.path.clone(), info_path(&button.path)] {
+                match fs::remove_file(&path) {
+                    Ok(()) => info!("Deleted {}", path.display()),
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                    Err(error) => error!("Could not delete {}: {error}", path.display()),
+                }
+            }
+            gallery.set_changed();
+        }
+    }
+}
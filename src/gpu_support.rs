@@ -0,0 +1,107 @@
+//! Adjustable GPU support stands, which hold up the far end of a sagging graphics card.
+//!
+//! A support stands with its foot at its mount point: its catalog position on the case floor,
+//! or an anchor of the case such as one on a PSU shroud. Its post is stretched up to the
+//! underside of the lowest graphics card over the foot, within the `reach` the catalog gives
+//! it, and fitted again whenever parts are installed or removed.
+
+use bevy::prelude::*;
+
+use crate::{
+    LevelAssets, Screen,
+    labels::BillboardLabel,
+    parts::{InstalledPart, PartCatalog, PartKind},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        fit_gpu_supports
+            .run_if(
+                any_match_filter::<Added<InstalledPart>>.or(any_component_removed::<InstalledPart>),
+            )
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+/// The post of a GPU support, a child of the part, which stands on the part's foot.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GpuSupportPost;
+
+/// How tall a support with its foot at `foot` has to be to meet the lowest of `cards` over it,
+/// given as their centers and sizes, within `reach`. With no card over it, it's as short as it
+/// goes.
+pub fn support_height(
+    foot: Vec3,
+    cards: impl IntoIterator<Item = (Vec3, Vec3)>,
+    reach: [f32; 2],
+) -> f32 {
+    let [shortest, tallest] = reach;
+    cards
+        .into_iter()
+        .filter(|(center, size)| {
+            (foot.x - center.x).abs() <= size.x / 2.0 && (foot.z - center.z).abs() <= size.z / 2.0
+        })
+        .map(|(center, size)| center.y - size.y / 2.0)
+        .filter(|underside| *underside >= foot.y)
+        .reduce(f32::min)
+        .map_or(shortest, |underside| {
+            (underside - foot.y).clamp(shortest, tallest)
+        })
+}
+
+fn fit_gpu_supports(
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    mut part_query: Query<(&InstalledPart, &mut BillboardLabel)>,
+    mut post_query: Query<(&ChildOf, &mut Transform), With<GpuSupportPost>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let cards: Vec<_> = part_query
+        .iter()
+        .filter_map(|(part, _)| catalog.get(&part.id).map(|spec| (part.mount, spec)))
+        .filter(|(_, spec)| spec.kind == PartKind::Gpu)
+        .map(|(mount, spec)| (mount, Vec3::from(spec.size)))
+        .collect();
+
+    for (child_of, mut transform) in &mut post_query {
+        let Ok((part, mut label)) = part_query.get_mut(child_of.parent()) else {
+            continue;
+        };
+        let Some(spec) = catalog.get(&part.id) else {
+            continue;
+        };
+        let modeled = spec.size[1];
+        let height = support_height(
+            part.mount,
+            cards.iter().copied(),
+            spec.reach.unwrap_or([modeled; 2]),
+        );
+        transform.translation = Vec3::Y * height / 2.0;
+        transform.scale.y = height / modeled;
+        label.offset = Vec3::Y * height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meets_the_lowest_card_over_it() {
+        let foot = Vec3::new(0.0, 10.0, 100.0);
+        let card = |y: f32, z: f32| (Vec3::new(0.0, y, z), Vec3::new(40.0, 120.0, 300.0));
+        let reach = [20.0, 200.0];
+        assert_eq!(support_height(foot, [card(180.0, 0.0)], reach), 110.0);
+        assert_eq!(
+            support_height(foot, [card(180.0, 0.0), card(250.0, 0.0)], reach),
+            110.0
+        );
+        // Too short to reach, or with the card ending before the foot.
+        assert_eq!(support_height(foot, [card(400.0, 0.0)], reach), 200.0);
+        assert_eq!(support_height(foot, [card(180.0, -100.0)], reach), 20.0);
+        assert_eq!(support_height(foot, [], reach), 20.0);
+    }
+}
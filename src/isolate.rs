@@ -0,0 +1,110 @@
+//! Isolating the selected part: hiding everything else in the level and dimming the background,
+//! with the camera framing the part, to look at it without taking the case apart.
+//!
+//! H, or the command palette, isolates the selected part and shows everything again. A mesh of
+//! an installed part isolates the whole part; a mesh of the case isolates only that mesh.
+
+use bevy::prelude::*;
+
+use crate::{
+    Level, OrbitCamera, Screen,
+    camera_focus::frame_selection,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::SelectedPart,
+    parts::InstalledPart,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Isolation>();
+    app.add_systems(
+        Update,
+        toggle_isolation
+            .run_if(key_just_pressed(KeyAction::Isolate))
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(OnExit(Screen::Game), forget_isolation);
+    app.add_palette_command("Isolate the selected part", toggle_isolation);
+}
+
+/// Whether a part is isolated.
+#[derive(Resource, Debug, Default)]
+struct Isolation {
+    active: bool,
+}
+
+/// Holds on to the visibility of an entity hidden by the isolation.
+#[derive(Component)]
+struct HiddenByIsolation {
+    previous: Visibility,
+}
+
+/// How much of the background's brightness is left while a part is isolated.
+const BACKGROUND_DIMMING: f32 = 0.25;
+
+fn toggle_isolation(
+    mut commands: Commands,
+    mut isolation: ResMut<Isolation>,
+    selected: Res<SelectedPart>,
+    clear_color: Res<ClearColor>,
+    part_query: Query<(), With<InstalledPart>>,
+    level_query: Query<(), With<Level>>,
+    parent_query: Query<&ChildOf>,
+    children_query: Query<&Children>,
+    mut visibility_query: Query<&mut Visibility>,
+    hidden_query: Query<(Entity, &HiddenByIsolation)>,
+    mut camera_query: Query<&mut Camera, With<OrbitCamera>>,
+) {
+    if isolation.active {
+        for (entity, hidden) in &hidden_query {
+            if let Ok(mut visibility) = visibility_query.get_mut(entity) {
+                *visibility = hidden.previous;
+            }
+            commands.entity(entity).remove::<HiddenByIsolation>();
+        }
+        for mut camera in &mut camera_query {
+            camera.clear_color = ClearColorConfig::Default;
+        }
+        isolation.active = false;
+        return;
+    }
+
+    let Some(selected) = selected.0 else {
+        info!("Select a part to isolate first");
+        return;
+    };
+    // The whole installed part the mesh belongs to, if it belongs to one.
+    let kept = std::iter::once(selected)
+        .chain(parent_query.iter_ancestors(selected))
+        .find(|&entity| part_query.contains(entity))
+        .unwrap_or(selected);
+    // Hides the siblings of the kept entity and of each of its ancestors up to the level.
+    let mut keep = kept;
+    for parent in parent_query.iter_ancestors(kept) {
+        for &sibling in children_query.get(parent).into_iter().flatten() {
+            if sibling == keep {
+                continue;
+            }
+            if let Ok(mut visibility) = visibility_query.get_mut(sibling) {
+                commands.entity(sibling).insert(HiddenByIsolation {
+                    previous: *visibility,
+                });
+                *visibility = Visibility::Hidden;
+            }
+        }
+        if level_query.contains(parent) {
+            break;
+        }
+        keep = parent;
+    }
+    let dimmed = clear_color.0.to_linear() * BACKGROUND_DIMMING;
+    for mut camera in &mut camera_query {
+        camera.clear_color = ClearColorConfig::Custom(dimmed.with_alpha(1.0).into());
+    }
+    isolation.active = true;
+    commands.run_system_cached(frame_selection);
+}
+
+fn forget_isolation(mut isolation: ResMut<Isolation>) {
+    isolation.active = false;
+}
@@ -0,0 +1,584 @@
+//! Keys for the scene's controls that can be changed, on a screen opened from the settings.
+//!
+//! Modules run their key controls with [`key_just_pressed`] for a [`KeyAction`] rather than a
+//! fixed key, so the keys can be moved, for keyboard layouts other than QWERTY among others.
+//! The bindings that differ from the defaults are saved with the rest of the settings. The keys
+//! the UI itself is driven with can't be bound to other actions.
+
+use std::collections::BTreeMap;
+
+use bevy::{input::InputSystems, input_focus::tab_navigation::TabGroup, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Menu,
+    actions::{menu_pressed, read_actions},
+    settings::Settings,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, OVERLAY_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>();
+    app.add_systems(OnEnter(Menu::Keybindings), spawn_keybindings_screen);
+    app.add_systems(OnExit(Menu::Keybindings), stop_rebinding);
+    app.add_systems(
+        PreUpdate,
+        capture_key
+            .after(InputSystems)
+            // So the key that's bound isn't also read as an action.
+            .before(read_actions)
+            .run_if(in_state(Menu::Keybindings)),
+    );
+    app.add_systems(
+        Update,
+        (
+            close_keybindings.run_if(menu_pressed),
+            update_key_labels.run_if(
+                resource_changed::<Settings>
+                    .or(resource_changed::<Rebinding>)
+                    .or(any_match_filter::<Added<KeyLabel>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Menu::Keybindings)),
+    );
+    app.add_observer(activate_keybindings_button);
+}
+
+/// A control of the scene that can be bound to a key.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum KeyAction {
+    OrbitLeft,
+    OrbitRight,
+    ZoomIn,
+    ZoomOut,
+    ResetView,
+    FlipChamber,
+    FramePart,
+    Isolate,
+    NextCase,
+    ExplodeCase,
+    RemovePanels,
+    SwitchViews,
+    BlueprintViews,
+    SplitView,
+    ShowAllLabels,
+    CycleStereo,
+    XRay,
+    ToggleLights,
+    AutoExposure,
+    ExposureUp,
+    ExposureDown,
+    DetachCameraLight,
+    AddLight,
+    NextLight,
+    RemoveLight,
+    MoveLightForward,
+    MoveLightBack,
+    MoveLightLeft,
+    MoveLightRight,
+    RaiseLight,
+    LowerLight,
+    RgbPanel,
+    Timeline,
+    PreviousFrame,
+    NextFrame,
+    Turntable,
+    Capture,
+    PresentationWindow,
+    RespawnLevel,
+    AnchorAuthoring,
+    Measure,
+    MeasureUnits,
+    /// With Ctrl held.
+    CommandPalette,
+    Kiosk,
+}
+
+impl KeyAction {
+    const ALL: [Self; 44] = [
+        Self::OrbitLeft,
+        Self::OrbitRight,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ResetView,
+        Self::FlipChamber,
+        Self::FramePart,
+        Self::Isolate,
+        Self::NextCase,
+        Self::ExplodeCase,
+        Self::RemovePanels,
+        Self::SwitchViews,
+        Self::BlueprintViews,
+        Self::SplitView,
+        Self::ShowAllLabels,
+        Self::CycleStereo,
+        Self::XRay,
+        Self::ToggleLights,
+        Self::AutoExposure,
+        Self::ExposureUp,
+        Self::ExposureDown,
+        Self::DetachCameraLight,
+        Self::AddLight,
+        Self::NextLight,
+        Self::RemoveLight,
+        Self::MoveLightForward,
+        Self::MoveLightBack,
+        Self::MoveLightLeft,
+        Self::MoveLightRight,
+        Self::RaiseLight,
+        Self::LowerLight,
+        Self::RgbPanel,
+        Self::Timeline,
+        Self::PreviousFrame,
+        Self::NextFrame,
+        Self::Turntable,
+        Self::Capture,
+        Self::PresentationWindow,
+        Self::RespawnLevel,
+        Self::AnchorAuthoring,
+        Self::Measure,
+        Self::MeasureUnits,
+        Self::CommandPalette,
+        Self::Kiosk,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::OrbitLeft => "Orbit left",
+            Self::OrbitRight => "Orbit right",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::ResetView => "Reset the view",
+            Self::FlipChamber => "Flip to the rear chamber",
+            Self::FramePart => "Frame the selected part",
+            Self::Isolate => "Isolate the selected part",
+            Self::NextCase => "Next case",
+            Self::ExplodeCase => "Explode the case",
+            Self::RemovePanels => "Take the panels off",
+            Self::SwitchViews => "Top and front views",
+            Self::BlueprintViews => "Blueprint views",
+            Self::SplitView => "Split view layouts",
+            Self::ShowAllLabels => "Show all part labels",
+            Self::CycleStereo => "Stereo modes",
+            Self::XRay => "See through the case",
+            Self::ToggleLights => "Lights off",
+            Self::AutoExposure => "Auto exposure",
+            Self::ExposureUp => "Exposure up",
+            Self::ExposureDown => "Exposure down",
+            Self::DetachCameraLight => "Detach the camera light",
+            Self::AddLight => "Add a light",
+            Self::NextLight => "Select the next light",
+            Self::RemoveLight => "Remove the selected light",
+            Self::MoveLightForward => "Move the light forward",
+            Self::MoveLightBack => "Move the light back",
+            Self::MoveLightLeft => "Move the light left",
+            Self::MoveLightRight => "Move the light right",
+            Self::RaiseLight => "Raise the light",
+            Self::LowerLight => "Lower the light",
+            Self::RgbPanel => "RGB lighting panel",
+            Self::Timeline => "Animation timeline",
+            Self::PreviousFrame => "Previous frame",
+            Self::NextFrame => "Next frame",
+            Self::Turntable => "Record a turntable",
+            Self::Capture => "Capture the view",
+            Self::PresentationWindow => "Presentation window",
+            Self::RespawnLevel => "Respawn the level",
+            Self::AnchorAuthoring => "Place part anchors",
+            Self::Measure => "Measure",
+            Self::MeasureUnits => "Measurement units",
+            Self::CommandPalette => "Command palette (Ctrl)",
+            Self::Kiosk => "Kiosk mode",
+        }
+    }
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Self::OrbitLeft => KeyCode::KeyA,
+            Self::OrbitRight => KeyCode::KeyD,
+            Self::ZoomIn => KeyCode::KeyW,
+            Self::ZoomOut => KeyCode::KeyS,
+            Self::ResetView => KeyCode::Home,
+            Self::FlipChamber => KeyCode::KeyR,
+            Self::FramePart => KeyCode::KeyF,
+            Self::Isolate => KeyCode::KeyH,
+            Self::NextCase => KeyCode::KeyC,
+            Self::ExplodeCase => KeyCode::KeyX,
+            Self::RemovePanels => KeyCode::KeyP,
+            Self::SwitchViews => KeyCode::KeyV,
+            Self::BlueprintViews => KeyCode::KeyO,
+            Self::SplitView => KeyCode::KeyJ,
+            Self::ShowAllLabels => KeyCode::KeyI,
+            Self::CycleStereo => KeyCode::KeyB,
+            Self::XRay => KeyCode::KeyZ,
+            Self::ToggleLights => KeyCode::KeyL,
+            Self::AutoExposure => KeyCode::KeyE,
+            Self::ExposureUp => KeyCode::Equal,
+            Self::ExposureDown => KeyCode::Minus,
+            Self::DetachCameraLight => KeyCode::KeyK,
+            Self::AddLight => KeyCode::KeyN,
+            Self::NextLight => KeyCode::BracketRight,
+            Self::RemoveLight => KeyCode::Delete,
+            Self::MoveLightForward => KeyCode::ArrowUp,
+            Self::MoveLightBack => KeyCode::ArrowDown,
+            Self::MoveLightLeft => KeyCode::ArrowLeft,
+            Self::MoveLightRight => KeyCode::ArrowRight,
+            Self::RaiseLight => KeyCode::PageUp,
+            Self::LowerLight => KeyCode::PageDown,
+            Self::RgbPanel => KeyCode::KeyG,
+            Self::Timeline => KeyCode::KeyY,
+            Self::PreviousFrame => KeyCode::Comma,
+            Self::NextFrame => KeyCode::Period,
+            Self::Turntable => KeyCode::KeyT,
+            Self::Capture => KeyCode::F12,
+            Self::PresentationWindow => KeyCode::F2,
+            Self::RespawnLevel => KeyCode::F5,
+            Self::AnchorAuthoring => KeyCode::F6,
+            Self::Measure => KeyCode::KeyM,
+            Self::MeasureUnits => KeyCode::KeyU,
+            Self::CommandPalette => KeyCode::KeyK,
+            Self::Kiosk => KeyCode::F9,
+        }
+    }
+
+    /// Whether `key` can be bound to the action: any key but the [`RESERVED_KEYS`], which only
+    /// the actions they're the defaults of can take.
+    fn can_take(self, key: KeyCode) -> bool {
+        !RESERVED_KEYS.contains(&key) || key == self.default_key()
+    }
+}
+
+/// The keys bound to actions other than their default ones.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Keybindings(BTreeMap<KeyAction, KeyCode>);
+
+impl Keybindings {
+    pub fn key(&self, action: KeyAction) -> KeyCode {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    pub fn pressed(&self, keys: &ButtonInput<KeyCode>, action: KeyAction) -> bool {
+        keys.pressed(self.key(action))
+    }
+
+    pub fn just_pressed(&self, keys: &ButtonInput<KeyCode>, action: KeyAction) -> bool {
+        keys.just_pressed(self.key(action))
+    }
+
+    /// Binds `key` to `action`. The action it was bound to before, if any, gets the key
+    /// `action` had, so no two actions share a key. The command palette's key is pressed with
+    /// Ctrl, so it can share one with an action pressed without.
+    ///
+    /// Nothing changes if either action would get one of the [`RESERVED_KEYS`] it can't take,
+    /// and that action is returned as the error.
+    fn bind(&mut self, action: KeyAction, key: KeyCode) -> Result<(), KeyAction> {
+        let previous = self.key(action);
+        let with_ctrl = |action| action == KeyAction::CommandPalette;
+        let other = KeyAction::ALL.into_iter().find(|&other| {
+            other != action && with_ctrl(other) == with_ctrl(action) && self.key(other) == key
+        });
+        if !action.can_take(key) {
+            return Err(action);
+        }
+        if let Some(other) = other
+            && !other.can_take(previous)
+        {
+            return Err(other);
+        }
+        self.set(action, key);
+        if let Some(other) = other {
+            self.set(other, previous);
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, action: KeyAction, key: KeyCode) {
+        if key == action.default_key() {
+            self.0.remove(&action);
+        } else {
+            self.0.insert(action, key);
+        }
+    }
+}
+
+/// A run condition for a control bound to a key, like
+/// [`input_just_pressed`](bevy::input::common_conditions::input_just_pressed) for a fixed one.
+pub fn key_just_pressed(
+    action: KeyAction,
+) -> impl FnMut(Res<ButtonInput<KeyCode>>, Res<Settings>) -> bool + Clone {
+    move |keys, settings| settings.keybindings.just_pressed(&keys, action)
+}
+
+/// Keys that drive the UI, and Ctrl for the command palette. They can't be bound but to the
+/// actions they're the defaults of, like the arrows that also move the selected light.
+const RESERVED_KEYS: [KeyCode; 15] = [
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Enter,
+    KeyCode::NumpadEnter,
+    KeyCode::Space,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+];
+
+/// The action waiting for the next key pressed to be bound to it.
+#[derive(Resource, Debug, Default)]
+struct Rebinding(Option<KeyAction>);
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum KeybindingsButton {
+    /// Waits for a key to bind to the action, and shows the one that's bound.
+    Rebind(KeyAction),
+    ResetAll,
+    Back,
+}
+
+/// Shows the key bound to the action.
+#[derive(Component, Debug, Clone, Copy)]
+struct KeyLabel(KeyAction);
+
+const LABEL_WIDTH: f32 = 180.0;
+const KEY_WIDTH: f32 = 100.0;
+
+/// A key's name the way it's printed on the key, more or less.
+pub fn key_name(key: KeyCode) -> String {
+    let symbol = match key {
+        KeyCode::Minus => "-",
+        KeyCode::Equal => "=",
+        KeyCode::BracketLeft => "[",
+        KeyCode::BracketRight => "]",
+        KeyCode::Comma => ",",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::Backslash => "\\",
+        KeyCode::Semicolon => ";",
+        KeyCode::Quote => "'",
+        KeyCode::Backquote => "`",
+        _ => "",
+    };
+    if !symbol.is_empty() {
+        return symbol.to_string();
+    }
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .or_else(|| name.strip_prefix("Arrow"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+fn close_keybindings(rebinding: Res<Rebinding>, mut next_menu: ResMut<NextState<Menu>>) {
+    // Escape while waiting for a key only stops waiting.
+    if rebinding.0.is_none() {
+        next_menu.set(Menu::Settings);
+    }
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+fn capture_key(
+    mut rebinding: ResMut<Rebinding>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(&key) = keys.get_just_pressed().next() else {
+        return;
+    };
+    keys.reset_all();
+    if key == KeyCode::Escape {
+        rebinding.0 = None;
+        return;
+    }
+    match settings.keybindings.bind(action, key) {
+        Ok(()) => rebinding.0 = None,
+        Err(blocked) if blocked == action => info!("{} is kept for the UI", key_name(key)),
+        Err(blocked) => info!(
+            "{} is bound to \"{}\", which can't move to {}, as it's kept for the UI",
+            key_name(key),
+            blocked.label(),
+            key_name(settings.keybindings.key(action))
+        ),
+    }
+}
+
+fn spawn_keybindings_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Keybindings Screen"),
+            DespawnOnExit(Menu::Keybindings),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: px(8.0),
+                padding: UiRect::all(px(20.0)),
+                ..default()
+            },
+            BackgroundColor(OVERLAY_BACKGROUND),
+            GlobalZIndex(1),
+            TabGroup::modal(),
+            children![(Text::new("Key bindings"), TextColor(Color::WHITE))],
+        ))
+        .with_children(|screen| {
+            // Two columns of actions, so they all fit on the screen.
+            screen
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: vec![
+                        GridTrack::px(LABEL_WIDTH),
+                        GridTrack::px(KEY_WIDTH),
+                        GridTrack::px(LABEL_WIDTH),
+                        GridTrack::px(KEY_WIDTH),
+                    ],
+                    align_items: AlignItems::Center,
+                    column_gap: px(8.0),
+                    row_gap: px(4.0),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    for action in KeyAction::ALL {
+                        grid.spawn((
+                            Text::new(action.label()),
+                            TextFont::from_font_size(FONT_SIZE),
+                            TextColor(Color::WHITE),
+                        ));
+                        grid.spawn((
+                            button("", KeybindingsButton::Rebind(action)),
+                            KeyLabel(action),
+                        ));
+                    }
+                });
+            screen
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(8.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(button("Reset to defaults", KeybindingsButton::ResetAll));
+                    row.spawn(button("Back (Esc)", KeybindingsButton::Back));
+                });
+        });
+}
+
+fn button(label: impl Into<String>, action: KeybindingsButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn update_key_labels(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    mut label_query: Query<(&KeyLabel, &mut Text)>,
+) {
+    for (label, mut text) in &mut label_query {
+        text.0 = if rebinding.0 == Some(label.0) {
+            "Press a key".to_string()
+        } else {
+            key_name(settings.keybindings.key(label.0))
+        };
+    }
+}
+
+fn activate_keybindings_button(
+    activate: On<Activate>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    button_query: Query<&KeybindingsButton>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(KeybindingsButton::Rebind(action)) => rebinding.0 = Some(*action),
+        Ok(KeybindingsButton::ResetAll) => {
+            rebinding.0 = None;
+            settings.keybindings = Keybindings::default();
+        }
+        Ok(KeybindingsButton::Back) => next_menu.set(Menu::Settings),
+        Err(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_swaps_keys() {
+        let mut bindings = Keybindings::default();
+        assert_eq!(bindings.bind(KeyAction::OrbitLeft, KeyCode::KeyQ), Ok(()));
+        assert_eq!(bindings.key(KeyAction::OrbitLeft), KeyCode::KeyQ);
+        // The action that had the key gets the one the other had.
+        assert_eq!(bindings.bind(KeyAction::ZoomIn, KeyCode::KeyQ), Ok(()));
+        assert_eq!(bindings.key(KeyAction::ZoomIn), KeyCode::KeyQ);
+        assert_eq!(bindings.key(KeyAction::OrbitLeft), KeyCode::KeyW);
+
+        // Going back to the defaults leaves nothing to save.
+        assert_eq!(bindings.bind(KeyAction::ZoomIn, KeyCode::KeyW), Ok(()));
+        assert_eq!(bindings.bind(KeyAction::OrbitLeft, KeyCode::KeyA), Ok(()));
+        assert_eq!(bindings, Keybindings::default());
+    }
+
+    #[test]
+    fn palette_shares_its_key() {
+        let mut bindings = Keybindings::default();
+        assert_eq!(
+            bindings.bind(KeyAction::CommandPalette, KeyCode::KeyP),
+            Ok(())
+        );
+        // Ctrl+P and P are different shortcuts.
+        assert_eq!(bindings.key(KeyAction::RemovePanels), KeyCode::KeyP);
+        assert_eq!(bindings.key(KeyAction::DetachCameraLight), KeyCode::KeyK);
+    }
+
+    #[test]
+    fn reserved_keys() {
+        let mut bindings = Keybindings::default();
+        assert_eq!(
+            bindings.bind(KeyAction::OrbitLeft, KeyCode::Tab),
+            Err(KeyAction::OrbitLeft)
+        );
+        // Orbiting left would get Page Up, which only raises the light.
+        assert_eq!(
+            bindings.bind(KeyAction::RaiseLight, KeyCode::KeyA),
+            Err(KeyAction::OrbitLeft)
+        );
+        assert_eq!(bindings, Keybindings::default());
+
+        // An action can have its own reserved key back.
+        assert_eq!(bindings.bind(KeyAction::RaiseLight, KeyCode::KeyQ), Ok(()));
+        assert_eq!(
+            bindings.bind(KeyAction::RaiseLight, KeyCode::PageUp),
+            Ok(())
+        );
+        assert_eq!(bindings, Keybindings::default());
+    }
+}
@@ -0,0 +1,274 @@
+//! A kiosk mode for store displays and expo booths.
+//!
+//! Kiosk mode hides the UI, turns the case around on its own and ignores the keyboard and
+//! gamepad buttons, so visitors can orbit and zoom but can't change or lose the build. When
+//! nobody has touched the app for a while, it shows the builds in `assets/builds/presets.ron`
+//! one after another, and leaving kiosk mode puts back the build they replaced.
+//!
+//! Start the app with `--kiosk` (or `?kiosk` in the page URL on the web) to launch into kiosk
+//! mode. Its key, F9 unless it's bound to another, enters and leaves it.
+
+use bevy::{
+    input::{
+        InputSystems,
+        mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll},
+    },
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    actions::{gamepad_used, read_actions},
+    asset_tracking::InitRonAsset,
+    cases::{ActiveCase, SwitchCase},
+    command_palette::AddPaletteCommand,
+    keybindings::KeyAction,
+    orbit_camera_system,
+    parts::{InstallParts, InstalledPart, Planned},
+    settings::Settings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<PresetBuilds>(&["presets.ron"]);
+    app.insert_resource(Kiosk {
+        active: requested_at_launch(),
+        ..default()
+    });
+    app.add_systems(
+        PreUpdate,
+        ignore_keyboard
+            .after(InputSystems)
+            .before(read_actions)
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(
+        Update,
+        (
+            (
+                hide_ui,
+                auto_rotate.before(orbit_camera_system),
+                play_presets,
+            )
+                .run_if(kiosk_active),
+            show_ui.run_if(not(kiosk_active)),
+        )
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Enter kiosk mode", toggle_kiosk);
+}
+
+/// Builds for kiosk mode to show off.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct PresetBuilds {
+    pub builds: Vec<PresetBuild>,
+}
+
+impl PresetBuilds {
+    pub fn get(&self, name: &str) -> Option<&PresetBuild> {
+        self.builds.iter().find(|build| build.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PresetBuild {
+    pub name: String,
+    /// The model of the case, as listed in `models/cases.ron`.
+    pub case_model: String,
+    /// Catalog ids of the parts to install.
+    pub parts: Vec<String>,
+    /// The ids in `parts` to show as planned.
+    #[serde(default)]
+    pub planned: Vec<String>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Kiosk {
+    pub active: bool,
+    /// Seconds without mouse or touch input, wound back each time a preset is shown.
+    idle: f32,
+    /// Index of the preset to show next.
+    next_preset: usize,
+    /// The build the presets replaced, taken before the first one is shown.
+    previous: Option<UserBuild>,
+}
+
+impl Kiosk {
+    /// Enters or leaves kiosk mode, putting back the user's build on the way out.
+    fn toggle(&mut self, commands: &mut Commands) {
+        if let Some(previous) = self.previous.take() {
+            commands.trigger(SwitchCase(previous.case_model));
+            commands.trigger(InstallParts {
+                parts: previous.parts,
+                planned: previous.planned,
+            });
+        }
+        *self = Kiosk {
+            active: !self.active,
+            ..default()
+        };
+        info!("Kiosk mode {}", if self.active { "on" } else { "off" });
+    }
+}
+
+/// The build that was up when the presets started playing.
+#[derive(Debug)]
+struct UserBuild {
+    case_model: String,
+    parts: Vec<String>,
+    planned: Vec<String>,
+}
+
+impl PresetBuild {
+    /// Switches to the build's case and installs its parts in place of the current ones.
+    pub fn apply(&self, commands: &mut Commands) {
+        commands.trigger(SwitchCase(self.case_model.clone()));
+        commands.trigger(InstallParts {
+            parts: self.parts.clone(),
+            planned: self.planned.clone(),
+        });
+    }
+}
+
+/// Remembers the visibility of a UI root that kiosk mode hid.
+#[derive(Component)]
+struct KioskHidden(Visibility);
+
+const KIOSK_FLAG: &str = "--kiosk";
+/// Radians per second the camera turns around the case by itself.
+const AUTO_ROTATE_SPEED: f32 = 0.25;
+/// Seconds of inactivity before the presets start playing.
+const IDLE_DELAY: f32 = 30.0;
+/// Seconds each preset is shown for.
+const PRESET_DURATION: f32 = 20.0;
+
+pub fn kiosk_active(kiosk: Res<Kiosk>) -> bool {
+    kiosk.active
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn requested_at_launch() -> bool {
+    std::env::args().skip(1).any(|arg| arg == KIOSK_FLAG)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn requested_at_launch() -> bool {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .is_some_and(|search| {
+            search
+                .trim_start_matches('?')
+                .split('&')
+                .any(|param| param == KIOSK_FLAG.trim_start_matches('-'))
+        })
+}
+
+fn toggle_kiosk(mut commands: Commands, mut kiosk: ResMut<Kiosk>) {
+    kiosk.toggle(&mut commands);
+}
+
+/// Swallows every key but the one that leaves kiosk mode, and every gamepad button, before any
+/// other system sees them. The sticks and triggers still move the camera.
+fn ignore_keyboard(
+    mut commands: Commands,
+    mut kiosk: ResMut<Kiosk>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut gamepad_query: Query<&mut Gamepad>,
+) {
+    if settings.keybindings.just_pressed(&keys, KeyAction::Kiosk) {
+        kiosk.toggle(&mut commands);
+    }
+    if kiosk.active {
+        keys.reset_all();
+        for mut gamepad in &mut gamepad_query {
+            gamepad.digital_mut().reset_all();
+        }
+    }
+}
+
+fn hide_ui(
+    mut commands: Commands,
+    mut root_query: Query<
+        (Entity, &mut Visibility),
+        (With<Node>, Without<ChildOf>, Without<KioskHidden>),
+    >,
+) {
+    for (entity, mut visibility) in &mut root_query {
+        commands.entity(entity).insert(KioskHidden(*visibility));
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn show_ui(mut commands: Commands, mut root_query: Query<(Entity, &KioskHidden, &mut Visibility)>) {
+    for (entity, hidden, mut visibility) in &mut root_query {
+        *visibility = hidden.0;
+        commands.entity(entity).remove::<KioskHidden>();
+    }
+}
+
+fn auto_rotate(
+    time: Res<Time>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mut camera_query: Query<&mut OrbitCamera>,
+) {
+    // Visitors orbiting by hand get the camera to themselves.
+    if mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    for mut orbit in &mut camera_query {
+        orbit.yaw += AUTO_ROTATE_SPEED * time.delta_secs();
+    }
+}
+
+fn play_presets(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut kiosk: ResMut<Kiosk>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    touches: Res<Touches>,
+    gamepad_query: Query<&Gamepad>,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<PresetBuilds>>,
+    active_case: Res<ActiveCase>,
+    part_query: Query<(&InstalledPart, Has<Planned>)>,
+) {
+    let used = mouse_buttons.get_pressed().next().is_some()
+        || mouse_motion.delta != Vec2::ZERO
+        || mouse_scroll.delta != Vec2::ZERO
+        || touches.iter().next().is_some()
+        || gamepad_query.iter().any(gamepad_used);
+    if used {
+        kiosk.idle = 0.0;
+        return;
+    }
+    kiosk.idle += time.delta_secs();
+    if kiosk.idle < IDLE_DELAY {
+        return;
+    }
+    // The next preset comes up once this one has been shown for its duration.
+    kiosk.idle -= PRESET_DURATION;
+
+    let Some(builds) = presets
+        .get(&level_assets.presets)
+        .map(|presets| &presets.builds)
+        .filter(|builds| !builds.is_empty())
+    else {
+        return;
+    };
+    kiosk.previous.get_or_insert_with(|| UserBuild {
+        case_model: active_case.0.clone(),
+        parts: part_query.iter().map(|(part, _)| part.id.clone()).collect(),
+        planned: part_query
+            .iter()
+            .filter(|(_, planned)| *planned)
+            .map(|(part, _)| part.id.clone())
+            .collect(),
+    });
+    let build = &builds[kiosk.next_preset % builds.len()];
+    kiosk.next_preset = (kiosk.next_preset + 1) % builds.len();
+    info!("Showing the preset \"{}\"", build.name);
+    build.apply(&mut commands);
+}
@@ -0,0 +1,344 @@
+//! Text labels pinned to points in the scene, always facing the viewer.
+//!
+//! Every installed part is labeled with its name, and a note can be pinned to the selected
+//! part from the command palette. Each label belongs to a group that can be shown or hidden
+//! from the palette. Labels are drawn for every camera with [`ShowsLabels`], which includes
+//! view captures, so they can be exported as annotated diagrams.
+//!
+//! Labels of entities with [`ShownOnHover`], like the part names, only show while the mouse is
+//! over them, unless I shows them all for an annotated overview. Hovering any other mesh of the
+//! level shows the name of its glTF node at the mouse.
+
+use bevy::{
+    ecs::system::SystemId,
+    picking::{hover::HoverMap, pointer::PointerId},
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use crate::{
+    Level, Screen,
+    command_palette::{AddPaletteCommand, OpenPrompt, PaletteCommands},
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::SelectedPart,
+    ui_navigation::FONT_SIZE,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let add_note = app.register_system(add_note);
+    app.insert_resource(LabelGroups {
+        hidden: HashSet::new(),
+        known: HashSet::new(),
+        add_note,
+        show_all: false,
+        hovered: None,
+    });
+    app.add_systems(OnExit(Screen::Game), forget_hovered_label);
+    app.add_systems(
+        Update,
+        (
+            toggle_all_labels.run_if(key_just_pressed(KeyAction::ShowAllLabels)),
+            track_hovered_mesh,
+            list_label_groups.run_if(any_match_filter::<Changed<BillboardLabel>>),
+            sync_label_nodes,
+            update_label_text,
+            position_label_nodes,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Labels: add a note to the selected part", prompt_for_note);
+    app.add_palette_command(
+        "Labels: show all part labels or only hovered ones",
+        toggle_all_labels,
+    );
+}
+
+/// Labels the entity with `text`, drawn at `offset` from it in its own space.
+#[derive(Component, Debug, Clone)]
+pub struct BillboardLabel {
+    pub text: String,
+    /// Labels are shown and hidden by group.
+    pub group: String,
+    pub offset: Vec3,
+}
+
+/// Only shows the entity's [`BillboardLabel`] while the mouse is over one of its meshes, or all
+/// labels are shown.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct ShownOnHover;
+
+/// Draws the [`BillboardLabel`]s in the scene over this camera's view.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct ShowsLabels;
+
+#[derive(Resource, Debug)]
+pub struct LabelGroups {
+    pub hidden: HashSet<String>,
+    /// Groups that have a palette command to toggle them.
+    known: HashSet<String>,
+    /// Pins the text it's given to the selected part.
+    add_note: SystemId<In<String>>,
+    /// Shows the labels with [`ShownOnHover`] whether they're hovered or not.
+    show_all: bool,
+    /// The entity with [`ShownOnHover`] the mouse is over.
+    hovered: Option<Entity>,
+}
+
+/// The label of `label` drawn over the view of `camera`.
+#[derive(Component)]
+struct LabelNode {
+    label: Entity,
+    camera: Entity,
+}
+
+/// Names the glTF node of the mesh under the mouse.
+#[derive(Component)]
+struct HoverTooltip;
+
+/// The group of the notes pinned from the palette.
+const NOTE_GROUP: &str = "Notes";
+/// The group of the name shown over a hovered mesh.
+const TOOLTIP_GROUP: &str = "Hover tooltips";
+const LABEL_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+fn forget_hovered_label(mut groups: ResMut<LabelGroups>) {
+    groups.hovered = None;
+}
+
+fn toggle_all_labels(mut groups: ResMut<LabelGroups>) {
+    groups.show_all = !groups.show_all;
+}
+
+/// Finds the nearest mesh of the level under the mouse, and either notes its labeled entity or
+/// names its glTF node in the tooltip.
+fn track_hovered_mesh(
+    mut commands: Commands,
+    mut groups: ResMut<LabelGroups>,
+    hover_map: Res<HoverMap>,
+    mesh_query: Query<(), With<Mesh3d>>,
+    node_query: Query<(), With<Node>>,
+    parent_query: Query<&ChildOf>,
+    level_query: Query<(), With<Level>>,
+    shown_on_hover_query: Query<(), With<ShownOnHover>>,
+    name_query: Query<&Name>,
+    mut tooltip_query: Query<(Entity, &mut BillboardLabel, &mut Transform), With<HoverTooltip>>,
+) {
+    let hits = hover_map.get(&PointerId::Mouse);
+    // Nothing in the scene is hovered while the mouse is over the UI.
+    let over_ui = hits.is_some_and(|hits| hits.keys().any(|entity| node_query.contains(*entity)));
+    let hit = hits
+        .filter(|_| !over_ui)
+        .into_iter()
+        .flatten()
+        .filter(|(entity, _)| {
+            mesh_query.contains(**entity)
+                && parent_query
+                    .iter_ancestors(**entity)
+                    .any(|ancestor| level_query.contains(ancestor))
+        })
+        .min_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth));
+
+    // Parts without a model are a mesh of their own.
+    let labeled = hit.and_then(|(mesh, _)| {
+        std::iter::once(*mesh)
+            .chain(parent_query.iter_ancestors(*mesh))
+            .find(|entity| shown_on_hover_query.contains(*entity))
+    });
+    if groups.hovered != labeled {
+        groups.hovered = labeled;
+    }
+
+    // A labeled entity shows its own label instead.
+    let tooltip = hit
+        .filter(|_| labeled.is_none())
+        .and_then(|(mesh, hit_data)| {
+            // The mesh's entity is named after the glTF mesh and its material, the node above it
+            // after the glTF node.
+            let name = parent_query
+                .iter_ancestors(*mesh)
+                .take_while(|ancestor| !level_query.contains(*ancestor))
+                .find_map(|ancestor| name_query.get(ancestor).ok())
+                .or_else(|| name_query.get(*mesh).ok())?;
+            Some((name.to_string(), hit_data.position?))
+        });
+
+    match (tooltip, tooltip_query.single_mut()) {
+        (Some((text, position)), Ok((_, mut label, mut transform))) => {
+            if label.text != text {
+                label.text = text;
+            }
+            transform.translation = position;
+        }
+        (Some((text, position)), Err(_)) => {
+            commands.spawn((
+                Name::new("Hover Tooltip"),
+                HoverTooltip,
+                DespawnOnExit(Screen::Game),
+                Transform::from_translation(position),
+                BillboardLabel {
+                    text,
+                    group: TOOLTIP_GROUP.to_string(),
+                    offset: Vec3::ZERO,
+                },
+            ));
+        }
+        (None, Ok((tooltip, ..))) => {
+            commands.entity(tooltip).despawn();
+        }
+        (None, Err(_)) => {}
+    }
+}
+
+/// Adds a palette command for each group the first time a label of it shows up.
+fn list_label_groups(
+    mut commands: Commands,
+    mut groups: ResMut<LabelGroups>,
+    mut palette_commands: ResMut<PaletteCommands>,
+    label_query: Query<&BillboardLabel, Changed<BillboardLabel>>,
+) {
+    for label in &label_query {
+        if groups.known.contains(&label.group) {
+            continue;
+        }
+        groups.known.insert(label.group.clone());
+        let group = label.group.clone();
+        let system = commands.register_system(move |mut groups: ResMut<LabelGroups>| {
+            if !groups.hidden.remove(&group) {
+                groups.hidden.insert(group.clone());
+            }
+        });
+        palette_commands.add(format!("Labels: show or hide {}", label.group), system);
+    }
+}
+
+fn prompt_for_note(mut commands: Commands, groups: Res<LabelGroups>, selected: Res<SelectedPart>) {
+    if selected.0.is_none() {
+        info!("Select a part to pin a note to first");
+        return;
+    }
+    commands.trigger(OpenPrompt {
+        label: "Note".to_string(),
+        submit: groups.add_note,
+    });
+}
+
+/// Pins `text` to the selected part, or takes its note off if `text` is empty.
+fn add_note(In(text): In<String>, mut commands: Commands, selected: Res<SelectedPart>) {
+    let Some(part) = selected.0 else {
+        return;
+    };
+    if text.is_empty() {
+        commands.entity(part).remove::<BillboardLabel>();
+    } else {
+        commands.entity(part).insert(BillboardLabel {
+            text,
+            group: NOTE_GROUP.to_string(),
+            offset: Vec3::ZERO,
+        });
+    }
+}
+
+/// Spawns a label node for every pair of label and camera, and despawns the ones whose label
+/// or camera has gone.
+fn sync_label_nodes(
+    mut commands: Commands,
+    node_query: Query<(Entity, &LabelNode)>,
+    label_query: Query<(Entity, &BillboardLabel)>,
+    camera_query: Query<Entity, (With<ShowsLabels>, With<Camera>)>,
+) {
+    let mut existing = HashMap::new();
+    for (node, label_node) in &node_query {
+        if label_query.contains(label_node.label) && camera_query.contains(label_node.camera) {
+            existing.insert((label_node.label, label_node.camera), node);
+        } else {
+            commands.entity(node).despawn();
+        }
+    }
+
+    for camera in &camera_query {
+        for (label, billboard) in &label_query {
+            if existing.contains_key(&(label, camera)) {
+                continue;
+            }
+            commands.spawn((
+                Name::new(format!("Label \"{}\"", billboard.text)),
+                LabelNode { label, camera },
+                DespawnOnExit(Screen::Game),
+                UiTargetCamera(camera),
+                Node {
+                    position_type: PositionType::Absolute,
+                    padding: UiRect::axes(px(4.0), px(1.0)),
+                    // Until it's been placed.
+                    display: Display::None,
+                    ..default()
+                },
+                // Centered above the point it labels.
+                UiTransform::from_translation(Val2::percent(-50.0, -100.0)),
+                BackgroundColor(LABEL_BACKGROUND),
+                Text::new(billboard.text.clone()),
+                TextFont::from_font_size(FONT_SIZE),
+                TextColor(Color::WHITE),
+                // Labels shouldn't catch clicks meant for the scene.
+                Pickable::IGNORE,
+            ));
+        }
+    }
+}
+
+fn update_label_text(
+    label_query: Query<&BillboardLabel, Changed<BillboardLabel>>,
+    mut node_query: Query<(&LabelNode, &mut Text)>,
+) {
+    for (label_node, mut text) in &mut node_query {
+        if let Ok(label) = label_query.get(label_node.label)
+            && text.0 != label.text
+        {
+            text.0 = label.text.clone();
+        }
+    }
+}
+
+/// Moves each label node over the point it labels, as seen by its camera.
+fn position_label_nodes(
+    groups: Res<LabelGroups>,
+    mut node_query: Query<(&LabelNode, &mut Node)>,
+    label_query: Query<(&BillboardLabel, &GlobalTransform, &InheritedVisibility)>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    shown_on_hover_query: Query<(), With<ShownOnHover>>,
+) {
+    for (label_node, mut node) in &mut node_query {
+        let hovered = groups.show_all
+            || groups.hovered == Some(label_node.label)
+            || !shown_on_hover_query.contains(label_node.label);
+        let position = label_query
+            .get(label_node.label)
+            .ok()
+            .filter(|(label, _, visibility)| {
+                hovered && visibility.get() && !groups.hidden.contains(&label.group)
+            })
+            .zip(camera_query.get(label_node.camera).ok())
+            .and_then(|((label, transform, _), (camera, camera_transform))| {
+                let anchor = transform.transform_point(label.offset);
+                let position = camera.world_to_viewport(camera_transform, anchor).ok()?;
+                // The viewport may only cover part of the window, as in stereo mode.
+                let origin = camera
+                    .logical_viewport_rect()
+                    .map_or(Vec2::ZERO, |rect| rect.min);
+                Some(origin + position)
+            });
+
+        let Some(position) = position else {
+            if node.display != Display::None {
+                node.display = Display::None;
+            }
+            continue;
+        };
+        let (left, top) = (px(position.x), px(position.y));
+        if node.display != Display::Flex || node.left != left || node.top != top {
+            node.display = Display::Flex;
+            node.left = left;
+            node.top = top;
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! A panel listing every light in the rig, with controls to tweak, add and remove them, and
+//! the lighting presets to choose from.
+
+use bevy::{input_focus::tab_navigation::TabGroup, prelude::*};
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    light_rig::{LightEnabled, RigLight, SelectedLight, free_light},
+    lighting::{ActiveLightingPreset, LightingPresets},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Game), spawn_light_panel);
+    app.add_systems(
+        Update,
+        (
+            rebuild_light_rows.run_if(
+                any_match_filter::<Changed<RigLight>>.or(any_component_removed::<RigLight>),
+            ),
+            update_button_labels,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_light_panel_button);
+}
+
+/// The container the per-light rows are spawned into.
+#[derive(Component)]
+struct LightList;
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum LightPanelButton {
+    AddLight,
+    /// Switches to the lighting preset at this index.
+    Preset(usize),
+    /// Applies `action` to the `light` entity.
+    Light {
+        light: Entity,
+        action: LightAction,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum LightAction {
+    Select,
+    ToggleEnabled,
+    Dim,
+    Brighten,
+    NextColor,
+    ToggleShadows,
+    Remove,
+}
+
+/// Shows the intensity of the light entity it points at.
+#[derive(Component)]
+struct IntensityLabel(Entity);
+
+/// The colors [`LightAction::NextColor`] cycles through.
+const LIGHT_COLORS: [Color; 6] = [
+    Color::WHITE,
+    Color::srgb(1.0, 0.85, 0.7),
+    Color::srgb(0.7, 0.85, 1.0),
+    Color::srgb(1.0, 0.3, 0.3),
+    Color::srgb(0.3, 1.0, 0.4),
+    Color::srgb(0.4, 0.5, 1.0),
+];
+
+/// How much a single dim/brighten click scales the intensity by.
+const INTENSITY_STEP: f32 = 1.5;
+const MIN_INTENSITY: f32 = 10_000.0;
+const MAX_INTENSITY: f32 = 50_000_000.0;
+
+fn spawn_light_panel(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<LightingPresets>>,
+) {
+    let preset_count = presets
+        .get(&level_assets.lighting)
+        .map_or(0, |presets| presets.presets.len());
+
+    commands.spawn((
+        Name::new("Light Panel"),
+        DespawnOnExit(Screen::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(5.0),
+            right: px(5.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(4.0),
+            padding: UiRect::all(px(6.0)),
+            ..default()
+        },
+        BackgroundColor(PANEL_BACKGROUND),
+        TabGroup::new(0),
+        children![
+            (Text::new("Lights"), TextColor(Color::WHITE)),
+            (
+                Name::new("Lighting Presets"),
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: px(4.0),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(
+                    (0..preset_count).map(|index| button("", LightPanelButton::Preset(index)))
+                )),
+            ),
+            (
+                Name::new("Light List"),
+                LightList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    ..default()
+                },
+            ),
+            button("Add light", LightPanelButton::AddLight),
+        ],
+    ));
+}
+
+fn button(label: impl Into<String>, action: LightPanelButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+/// Respawns one row per light, the camera-mounted light first.
+fn rebuild_light_rows(
+    mut commands: Commands,
+    list_query: Query<Entity, With<LightList>>,
+    light_query: Query<(Entity, &RigLight)>,
+) {
+    let Ok(list) = list_query.single() else {
+        return;
+    };
+
+    let mut lights: Vec<(Entity, RigLight)> = light_query
+        .iter()
+        .map(|(light, rig)| (light, *rig))
+        .collect();
+    lights.sort_by_key(|(light, rig)| (*rig != RigLight::CameraMounted, *light));
+
+    commands
+        .entity(list)
+        .despawn_children()
+        .with_children(|list| {
+            for (light, rig) in lights {
+                let control = |action| LightPanelButton::Light { light, action };
+                let mut row = list.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(4.0),
+                    ..default()
+                });
+                row.with_children(|row| {
+                    row.spawn(button("", control(LightAction::Select)));
+                    row.spawn(button("", control(LightAction::ToggleEnabled)));
+                    row.spawn(button("-", control(LightAction::Dim)));
+                    row.spawn((
+                        IntensityLabel(light),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn(button("+", control(LightAction::Brighten)));
+                    row.spawn(button("Color", control(LightAction::NextColor)));
+                    row.spawn(button("", control(LightAction::ToggleShadows)));
+                    if rig == RigLight::Free {
+                        row.spawn(button("Remove", control(LightAction::Remove)));
+                    }
+                });
+            }
+        });
+}
+
+/// Keeps the button and intensity labels in sync with the lights they control.
+fn update_button_labels(
+    selected: Res<SelectedLight>,
+    active_preset: Res<ActiveLightingPreset>,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<LightingPresets>>,
+    light_query: Query<(&Name, &RigLight, &SpotLight, &LightEnabled)>,
+    mut button_query: Query<(&LightPanelButton, &mut Text, &mut TextColor)>,
+    mut intensity_query: Query<(&IntensityLabel, &mut Text), Without<LightPanelButton>>,
+) {
+    let presets = presets.get(&level_assets.lighting);
+    let active_preset = presets.and_then(|presets| presets.get(&active_preset.0));
+    for (button, mut text, mut text_color) in &mut button_query {
+        if let LightPanelButton::Preset(index) = *button {
+            let Some(preset) = presets.and_then(|presets| presets.presets.get(index)) else {
+                continue;
+            };
+            let label = if active_preset.is_some_and(|active| active.name == preset.name) {
+                format!("> {}", preset.name)
+            } else {
+                preset.name.clone()
+            };
+            if text.0 != label {
+                text.0 = label;
+            }
+            continue;
+        }
+        let LightPanelButton::Light { light, action } = *button else {
+            continue;
+        };
+        let Ok((name, rig, spot_light, enabled)) = light_query.get(light) else {
+            continue;
+        };
+
+        if action == LightAction::NextColor {
+            text_color.set_if_neq(TextColor(spot_light.color));
+            continue;
+        }
+
+        let label = match action {
+            LightAction::Select if selected.0 == Some(light) => format!("> {name}"),
+            LightAction::Select if *rig == RigLight::CameraMounted => format!("{name} (mounted)"),
+            LightAction::Select => name.to_string(),
+            LightAction::ToggleEnabled if enabled.0 => "On".to_string(),
+            LightAction::ToggleEnabled => "Off".to_string(),
+            LightAction::ToggleShadows if spot_light.shadows_enabled => "Shadows on".to_string(),
+            LightAction::ToggleShadows => "Shadows off".to_string(),
+            LightAction::Dim
+            | LightAction::Brighten
+            | LightAction::NextColor
+            | LightAction::Remove => {
+                continue;
+            }
+        };
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+
+    for (label, mut text) in &mut intensity_query {
+        let Ok((_, _, spot_light, _)) = light_query.get(label.0) else {
+            continue;
+        };
+        let intensity = format!("{:.0}k lm", spot_light.intensity / 1000.0);
+        if text.0 != intensity {
+            text.0 = intensity;
+        }
+    }
+}
+
+fn activate_light_panel_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    mut selected: ResMut<SelectedLight>,
+    mut active_preset: ResMut<ActiveLightingPreset>,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<LightingPresets>>,
+    button_query: Query<&LightPanelButton>,
+    camera_query: Query<&GlobalTransform, With<OrbitCamera>>,
+    mut light_query: Query<(&mut SpotLight, &mut LightEnabled)>,
+) {
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+
+    match *button {
+        LightPanelButton::AddLight => {
+            if let Ok(camera) = camera_query.single() {
+                let light = commands.spawn(free_light(camera.translation())).id();
+                selected.0 = Some(light);
+            }
+        }
+        LightPanelButton::Preset(index) => {
+            if let Some(preset) = presets
+                .get(&level_assets.lighting)
+                .and_then(|presets| presets.presets.get(index))
+                && active_preset.0 != preset.name
+            {
+                active_preset.0 = preset.name.clone();
+            }
+        }
+        LightPanelButton::Light {
+            light,
+            action: LightAction::Select,
+        } => {
+            selected.0 = Some(light);
+        }
+        LightPanelButton::Light {
+            light,
+            action: LightAction::Remove,
+        } => {
+            commands.entity(light).despawn();
+            if selected.0 == Some(light) {
+                selected.0 = None;
+            }
+        }
+        LightPanelButton::Light { light, action } => {
+            let Ok((mut spot_light, mut enabled)) = light_query.get_mut(light) else {
+                return;
+            };
+            match action {
+                LightAction::ToggleEnabled => enabled.0 = !enabled.0,
+                LightAction::Dim => {
+                    spot_light.intensity =
+                        (spot_light.intensity / INTENSITY_STEP).max(MIN_INTENSITY);
+                }
+                LightAction::Brighten => {
+                    spot_light.intensity =
+                        (spot_light.intensity * INTENSITY_STEP).min(MAX_INTENSITY);
+                }
+                LightAction::NextColor => {
+                    let next = LIGHT_COLORS
+                        .iter()
+                        .position(|&color| color == spot_light.color)
+                        .map_or(0, |index| (index + 1) % LIGHT_COLORS.len());
+                    spot_light.color = LIGHT_COLORS[next];
+                }
+                LightAction::ToggleShadows => {
+                    spot_light.shadows_enabled = !spot_light.shadows_enabled;
+                }
+                LightAction::Select | LightAction::Remove => {}
+            }
+        }
+    }
+}
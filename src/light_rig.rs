@@ -0,0 +1,307 @@
+//! The light rig: spot lights that are either mounted on the orbit camera or placed freely.
+
+use bevy::{color::palettes::css::YELLOW, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    OrbitCamera, Screen, keybindings::KeyAction, lighting::LightingEnvironment,
+    orbit_camera_system, settings::Settings, ui_navigation::ui_not_focused,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SelectedLight>();
+    app.add_systems(OnExit(Screen::Game), forget_selected_light);
+    app.add_systems(
+        Update,
+        (
+            (
+                toggle_camera_light_mount,
+                add_free_light,
+                remove_selected_light,
+                select_next_light,
+            ),
+            // The arrow keys navigate the UI while it has focus.
+            move_selected_light.run_if(ui_not_focused),
+            aim_rig_lights.after(orbit_camera_system),
+            draw_light_gizmos,
+            sync_light_visibility.run_if(
+                resource_changed::<LightingEnvironment>
+                    .or(any_match_filter::<Changed<LightEnabled>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+/// How a light in the rig is positioned. Every rig light is aimed at the orbit target.
+#[derive(Component, Reflect, Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[reflect(Component)]
+#[require(LightEnabled)]
+pub enum RigLight {
+    /// Parented to the orbit camera and moves with it.
+    CameraMounted,
+    /// Stays wherever it was placed.
+    Free,
+}
+
+/// Whether a rig light is switched on. It still only shines in the studio environment.
+#[derive(Component, Reflect, Debug, Clone, Copy, Eq, PartialEq)]
+#[reflect(Component)]
+pub struct LightEnabled(pub bool);
+
+impl Default for LightEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// The free light that responds to the placement keys.
+#[derive(Resource, Debug, Default)]
+pub struct SelectedLight(pub Option<Entity>);
+
+/// Where a camera-mounted light sits relative to the camera.
+const CAMERA_LIGHT_OFFSET: Vec3 = Vec3::new(0.0, 50.0, 0.0);
+
+/// How fast the selected light moves, in world units per second.
+const LIGHT_MOVE_SPEED: f32 = 400.0;
+
+/// A free light placed at `position`.
+pub fn free_light(position: Vec3) -> impl Bundle {
+    (
+        Name::new("Free Light"),
+        DespawnOnExit(Screen::Game),
+        RigLight::Free,
+        spot_light(),
+        Transform::from_translation(position),
+    )
+}
+
+/// The spot light every rig light starts out as.
+pub fn spot_light() -> SpotLight {
+    SpotLight {
+        intensity: 500_000.0,
+        range: 5000.0,
+        inner_angle: 0.35,
+        outer_angle: 0.6,
+        shadows_enabled: true,
+        ..default()
+    }
+}
+
+/// Detaches the camera-mounted light where it stands, or mounts the selected light on the camera.
+fn toggle_camera_light_mount(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut selected: ResMut<SelectedLight>,
+    camera_query: Query<Entity, With<OrbitCamera>>,
+    mut light_query: Query<(Entity, &mut RigLight, &mut Transform)>,
+) {
+    if !settings
+        .keybindings
+        .just_pressed(&keys, KeyAction::DetachCameraLight)
+    {
+        return;
+    }
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+
+    if let Some((light, mut rig, _)) = light_query
+        .iter_mut()
+        .find(|(_, rig, _)| **rig == RigLight::CameraMounted)
+    {
+        *rig = RigLight::Free;
+        commands.entity(light).remove_parent_in_place();
+        selected.0 = Some(light);
+    } else if let Some(light) = selected.0
+        && let Ok((_, mut rig, mut transform)) = light_query.get_mut(light)
+    {
+        *rig = RigLight::CameraMounted;
+        *transform = Transform::from_translation(CAMERA_LIGHT_OFFSET);
+        commands.entity(camera).add_child(light);
+    }
+}
+
+/// The lights are despawned with the level.
+fn forget_selected_light(mut selected: ResMut<SelectedLight>) {
+    selected.0 = None;
+}
+
+/// Adds a free light at the camera's position, so it lights what is currently in view.
+fn add_free_light(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut selected: ResMut<SelectedLight>,
+    camera_query: Query<&GlobalTransform, With<OrbitCamera>>,
+) {
+    if !settings
+        .keybindings
+        .just_pressed(&keys, KeyAction::AddLight)
+    {
+        return;
+    }
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+
+    let light = commands.spawn(free_light(camera.translation())).id();
+    selected.0 = Some(light);
+}
+
+fn remove_selected_light(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut selected: ResMut<SelectedLight>,
+    light_query: Query<&RigLight>,
+) {
+    if !settings
+        .keybindings
+        .just_pressed(&keys, KeyAction::RemoveLight)
+    {
+        return;
+    }
+
+    if let Some(light) = selected.0
+        && matches!(light_query.get(light), Ok(RigLight::Free))
+    {
+        commands.entity(light).despawn();
+        selected.0 = None;
+    }
+}
+
+/// Cycles the selection through the free lights.
+fn select_next_light(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut selected: ResMut<SelectedLight>,
+    light_query: Query<(Entity, &RigLight)>,
+) {
+    if !settings
+        .keybindings
+        .just_pressed(&keys, KeyAction::NextLight)
+    {
+        return;
+    }
+
+    let mut free_lights: Vec<Entity> = light_query
+        .iter()
+        .filter(|(_, rig)| **rig == RigLight::Free)
+        .map(|(entity, _)| entity)
+        .collect();
+    free_lights.sort();
+
+    let next = match selected
+        .0
+        .and_then(|light| free_lights.iter().position(|&e| e == light))
+    {
+        Some(index) => free_lights.get(index + 1).or(free_lights.first()),
+        None => free_lights.first(),
+    };
+    selected.0 = next.copied();
+}
+
+/// Moves the selected free light relative to the camera's view: the arrows, by default, move it
+/// across the floor plane and Page Up/Down move it vertically.
+fn move_selected_light(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    selected: Res<SelectedLight>,
+    camera_query: Query<&Transform, With<OrbitCamera>>,
+    mut light_query: Query<(&RigLight, &mut Transform), Without<OrbitCamera>>,
+) {
+    let Some(light) = selected.0 else {
+        return;
+    };
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+    let Ok((RigLight::Free, mut transform)) = light_query.get_mut(light) else {
+        return;
+    };
+
+    let forward = camera.forward().as_vec3().with_y(0.0).normalize_or_zero();
+    let right = camera.right().as_vec3().with_y(0.0).normalize_or_zero();
+
+    let pressed = |action| settings.keybindings.pressed(&keys, action);
+    let mut direction = Vec3::ZERO;
+    if pressed(KeyAction::MoveLightForward) {
+        direction += forward;
+    }
+    if pressed(KeyAction::MoveLightBack) {
+        direction -= forward;
+    }
+    if pressed(KeyAction::MoveLightRight) {
+        direction += right;
+    }
+    if pressed(KeyAction::MoveLightLeft) {
+        direction -= right;
+    }
+    if pressed(KeyAction::RaiseLight) {
+        direction += Vec3::Y;
+    }
+    if pressed(KeyAction::LowerLight) {
+        direction -= Vec3::Y;
+    }
+
+    transform.translation += direction * LIGHT_MOVE_SPEED * time.delta_secs();
+}
+
+fn aim_rig_lights(
+    camera_query: Query<(&GlobalTransform, &OrbitCamera)>,
+    mut light_query: Query<(&RigLight, &mut Transform, &GlobalTransform)>,
+) {
+    if let Ok((camera_global, orbit)) = camera_query.single() {
+        for (rig, mut local_transform, light_global) in &mut light_query {
+            let light_pos = light_global.translation();
+            let dir = orbit.target - light_pos;
+
+            if dir.length_squared() > 0.0001 {
+                let rotation = Quat::from_rotation_arc(Vec3::NEG_Z, dir.normalize());
+                local_transform.rotation = match rig {
+                    // A mounted light's rotation is relative to the camera.
+                    RigLight::CameraMounted => camera_global.rotation().inverse() * rotation,
+                    RigLight::Free => rotation,
+                };
+            }
+        }
+    }
+}
+
+fn draw_light_gizmos(
+    mut gizmos: Gizmos,
+    selected: Res<SelectedLight>,
+    light_query: Query<(Entity, &RigLight, &GlobalTransform)>,
+) {
+    for (light, rig, transform) in &light_query {
+        if *rig != RigLight::Free {
+            continue;
+        }
+
+        let color = if selected.0 == Some(light) {
+            Color::from(YELLOW)
+        } else {
+            Color::WHITE
+        };
+        let position = transform.translation();
+        gizmos.sphere(Isometry3d::from_translation(position), 15.0, color);
+        gizmos.arrow(position, position + transform.forward() * 120.0, color);
+    }
+}
+
+fn sync_light_visibility(
+    environment: Res<LightingEnvironment>,
+    mut light_query: Query<(&LightEnabled, &mut Visibility), With<RigLight>>,
+) {
+    for (enabled, mut visibility) in &mut light_query {
+        *visibility = if enabled.0 && *environment == LightingEnvironment::Studio {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,328 @@
+//! Lighting environments the case can be previewed in.
+//!
+//! The scene is lit by one of the presets in `assets/environments/lighting.ron`: each sets the
+//! background and ambient light, may light the scene with an environment map, and spawns its
+//! own lights into the rig. Pick a preset from the light panel or the command palette. Lights
+//! added by hand stay when the preset changes; the preset's own lights are replaced. The preset,
+//! and whether the lights are off, are saved with the build.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{
+        Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    asset_tracking::InitRonAsset,
+    command_palette::{AddPaletteCommand, PaletteCommands},
+    keybindings::{KeyAction, key_just_pressed},
+    light_rig::{RigLight, SelectedLight, spot_light},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<LightingPresets>(&["lighting.ron"]);
+    app.init_resource::<LightingEnvironment>();
+    app.init_resource::<ActiveLightingPreset>();
+    app.add_systems(OnEnter(Screen::Game), list_preset_commands);
+    app.add_systems(
+        Update,
+        (
+            toggle_lighting_environment.run_if(key_just_pressed(KeyAction::ToggleLights)),
+            spawn_preset_lights.run_if(
+                resource_changed::<ActiveLightingPreset>.or(any_match_filter::<Added<OrbitCamera>>),
+            ),
+            apply_lighting_environment.run_if(
+                resource_changed::<LightingEnvironment>
+                    .or(resource_changed::<ActiveLightingPreset>)
+                    .or(any_match_filter::<Added<Camera3d>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Toggle lights off", toggle_lighting_environment);
+}
+
+/// The environment the scene is currently lit with.
+#[derive(Resource, Reflect, Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub enum LightingEnvironment {
+    /// The active lighting preset and the rig lights are on.
+    #[default]
+    Studio,
+    /// "Lights off": only emissive materials (RGB lighting, monitor glow) remain visible.
+    Dark,
+}
+
+impl LightingEnvironment {
+    fn toggled(self) -> Self {
+        match self {
+            Self::Studio => Self::Dark,
+            Self::Dark => Self::Studio,
+        }
+    }
+}
+
+/// Name of the lighting preset the studio environment uses. Names that aren't in the presets
+/// fall back to the first one.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct ActiveLightingPreset(pub String);
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct LightingPresets {
+    pub presets: Vec<LightingPreset>,
+}
+
+impl LightingPresets {
+    /// The preset named `name`, or the first one if there's none by that name.
+    pub fn get(&self, name: &str) -> Option<&LightingPreset> {
+        self.presets
+            .iter()
+            .find(|preset| preset.name == name)
+            .or(self.presets.first())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LightingPreset {
+    pub name: String,
+    /// The color behind the case, in sRGB.
+    background: [f32; 3],
+    /// Brightness of the ambient light, in cd/m².
+    ambient_brightness: f32,
+    #[serde(default)]
+    environment_map: Option<EnvironmentMap>,
+    #[serde(default)]
+    lights: Vec<LightSpec>,
+}
+
+/// Light that reaches the case from its surroundings, with reflections to match.
+#[derive(Debug, Clone, Deserialize)]
+enum EnvironmentMap {
+    /// A cubemap image, such as an HDRI converted to KTX2. Its size must be a power of two.
+    Image { path: String, intensity: f32 },
+    /// A gradient from the sky overhead to the ground below, generated at runtime.
+    Gradient {
+        sky: [f32; 3],
+        ground: [f32; 3],
+        intensity: f32,
+    },
+}
+
+/// A spot light of a preset, aimed at the orbit target like every rig light.
+#[derive(Debug, Clone, Deserialize)]
+struct LightSpec {
+    name: String,
+    mount: RigLight,
+    /// In millimeters, relative to the camera for camera-mounted lights.
+    position: [f32; 3],
+    /// In sRGB.
+    #[serde(default = "white")]
+    color: [f32; 3],
+    /// In lumens.
+    intensity: f32,
+    #[serde(default)]
+    shadows: bool,
+}
+
+fn white() -> [f32; 3] {
+    [1.0; 3]
+}
+
+/// A light spawned by the active preset, replaced when the preset changes.
+#[derive(Component)]
+struct PresetLight;
+
+/// Width and height, in pixels, of each face of a generated environment map.
+const GRADIENT_MAP_SIZE: u32 = 64;
+
+fn toggle_lighting_environment(mut environment: ResMut<LightingEnvironment>) {
+    *environment = environment.toggled();
+}
+
+/// Adds a palette command to switch to each preset.
+fn list_preset_commands(
+    mut commands: Commands,
+    mut listed: Local<bool>,
+    mut palette_commands: ResMut<PaletteCommands>,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<LightingPresets>>,
+) {
+    let Some(presets) = presets.get(&level_assets.lighting) else {
+        return;
+    };
+    if *listed {
+        return;
+    }
+    *listed = true;
+    for preset in &presets.presets {
+        let name = preset.name.clone();
+        let system = commands.register_system(move |mut active: ResMut<ActiveLightingPreset>| {
+            active.set_if_neq(ActiveLightingPreset(name.clone()));
+        });
+        palette_commands.add(format!("Lighting: {}", preset.name), system);
+    }
+}
+
+fn spawn_preset_lights(
+    mut commands: Commands,
+    active: Res<ActiveLightingPreset>,
+    mut selected: ResMut<SelectedLight>,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<LightingPresets>>,
+    light_query: Query<Entity, With<PresetLight>>,
+    camera_query: Query<Entity, With<OrbitCamera>>,
+) {
+    for light in &light_query {
+        commands.entity(light).despawn();
+        if selected.0 == Some(light) {
+            selected.0 = None;
+        }
+    }
+    let Some(preset) = presets
+        .get(&level_assets.lighting)
+        .and_then(|presets| presets.get(&active.0))
+    else {
+        return;
+    };
+    let camera = camera_query.single().ok();
+
+    for light in &preset.lights {
+        let mut entity = commands.spawn((
+            Name::new(light.name.clone()),
+            PresetLight,
+            // Only needed once detached; while mounted it goes with the camera.
+            DespawnOnExit(Screen::Game),
+            light.mount,
+            SpotLight {
+                color: Color::srgb_from_array(light.color),
+                intensity: light.intensity,
+                shadows_enabled: light.shadows,
+                ..spot_light()
+            },
+            Transform::from_translation(Vec3::from(light.position)),
+        ));
+        if light.mount == RigLight::CameraMounted
+            && let Some(camera) = camera
+        {
+            entity.insert(ChildOf(camera));
+        }
+    }
+}
+
+fn apply_lighting_environment(
+    mut commands: Commands,
+    // The gradient last generated, and the preset it was for, so new cameras can share it.
+    mut gradient: Local<Option<(String, Handle<Image>)>>,
+    environment: Res<LightingEnvironment>,
+    active: Res<ActiveLightingPreset>,
+    level_assets: Res<LevelAssets>,
+    presets: Res<Assets<LightingPresets>>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut ambient_light: ResMut<GlobalAmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+    camera_query: Query<Entity, With<Camera3d>>,
+) {
+    let preset = presets
+        .get(&level_assets.lighting)
+        .and_then(|presets| presets.get(&active.0))
+        .filter(|_| *environment == LightingEnvironment::Studio);
+
+    let (ambient, clear) = match preset {
+        Some(preset) => (
+            GlobalAmbientLight {
+                brightness: preset.ambient_brightness,
+                ..default()
+            },
+            ClearColor(Color::srgb_from_array(preset.background)),
+        ),
+        None => (GlobalAmbientLight::NONE, ClearColor(Color::BLACK)),
+    };
+    *ambient_light = ambient;
+    *clear_color = clear;
+
+    let environment_map = preset
+        .and_then(|preset| Some(preset).zip(preset.environment_map.as_ref()))
+        .map(|(preset, map)| match map {
+            EnvironmentMap::Image { path, intensity } => GeneratedEnvironmentMapLight {
+                environment_map: asset_server.load(path),
+                intensity: *intensity,
+                ..default()
+            },
+            EnvironmentMap::Gradient {
+                sky,
+                ground,
+                intensity,
+            } => {
+                let image = match &*gradient {
+                    Some((name, image)) if *name == preset.name => image.clone(),
+                    _ => {
+                        let image = images.add(gradient_cubemap(
+                            Color::srgb_from_array(*sky),
+                            Color::srgb_from_array(*ground),
+                        ));
+                        *gradient = Some((preset.name.clone(), image.clone()));
+                        image
+                    }
+                };
+                GeneratedEnvironmentMapLight {
+                    environment_map: image,
+                    intensity: *intensity,
+                    ..default()
+                }
+            }
+        });
+    for camera in &camera_query {
+        match &environment_map {
+            Some(environment_map) => commands.entity(camera).insert(environment_map.clone()),
+            None => commands
+                .entity(camera)
+                .remove::<(GeneratedEnvironmentMapLight, EnvironmentMapLight)>(),
+        };
+    }
+}
+
+/// A cubemap fading from `sky` straight up to `ground` straight down.
+fn gradient_cubemap(sky: Color, ground: Color) -> Image {
+    let size = GRADIENT_MAP_SIZE;
+    let mut data = Vec::with_capacity((size * size * 6 * 4) as usize);
+    // Faces in the order +X, -X, +Y, -Y, +Z, -Z; rows of every side face run top to bottom.
+    for face in 0..6 {
+        for row in 0..size {
+            for column in 0..size {
+                let u = (column as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let v = (row as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let up = match face {
+                    2 => 1.0,
+                    3 => -1.0,
+                    _ => -v,
+                };
+                let height = up / (1.0 + u * u + v * v).sqrt();
+                let color = ground.mix(&sky, height * 0.5 + 0.5).to_srgba();
+                data.extend(color.to_u8_array());
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    image
+}
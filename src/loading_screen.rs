@@ -0,0 +1,169 @@
+//! A loading screen with a progress bar, and a fade from it into the level.
+
+use bevy::prelude::*;
+
+use crate::{
+    Screen, asset_tracking::ResourceHandles, pipeline_warmup::PipelineWarmup,
+    ui_navigation::FONT_SIZE,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Loading), spawn_loading_screen);
+    app.add_systems(
+        Update,
+        update_loading_progress.run_if(in_state(Screen::Loading)),
+    );
+    app.add_systems(OnEnter(Screen::Game), spawn_fade_in);
+    app.add_systems(Update, fade_in.run_if(in_state(Screen::Game)));
+}
+
+/// The part of the progress bar that fills up.
+#[derive(Component)]
+struct ProgressBarFill;
+
+/// Names the asset that is being waited on.
+#[derive(Component)]
+struct ProgressLabel;
+
+/// Covers the level as it appears, fading out over [`FADE_DURATION`].
+#[derive(Component)]
+struct FadeIn {
+    elapsed: f32,
+}
+
+const PROGRESS_BAR_WIDTH: f32 = 300.0;
+const PROGRESS_BAR_BACKGROUND: Color = Color::srgb(0.2, 0.2, 0.2);
+const PROGRESS_BAR_FILL: Color = Color::srgb(0.8, 0.8, 0.8);
+const BACKGROUND: Color = Color::BLACK;
+/// Seconds the level takes to fade in.
+const FADE_DURATION: f32 = 0.5;
+
+fn spawn_loading_screen(mut commands: Commands) {
+    // The level's camera only exists in `Screen::Game`.
+    commands.spawn((
+        Name::new("Loading Camera"),
+        DespawnOnExit(Screen::Loading),
+        Camera2d,
+    ));
+    commands.spawn((
+        Name::new("Loading Screen"),
+        DespawnOnExit(Screen::Loading),
+        Node {
+            width: percent(100.0),
+            height: percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: px(8.0),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND),
+        children![
+            (Text::new("Loading"), TextColor(Color::WHITE)),
+            (
+                Node {
+                    width: px(PROGRESS_BAR_WIDTH),
+                    height: px(8.0),
+                    ..default()
+                },
+                BackgroundColor(PROGRESS_BAR_BACKGROUND),
+                children![(
+                    ProgressBarFill,
+                    Node {
+                        width: percent(0.0),
+                        height: percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(PROGRESS_BAR_FILL),
+                )],
+            ),
+            (
+                ProgressLabel,
+                Text::default(),
+                TextFont::from_font_size(FONT_SIZE),
+                TextColor(Color::WHITE),
+            ),
+        ],
+    ));
+}
+
+fn update_loading_progress(
+    resource_handles: Res<ResourceHandles>,
+    asset_server: Res<AssetServer>,
+    warmup: Res<PipelineWarmup>,
+    mut fill_query: Query<&mut Node, With<ProgressBarFill>>,
+    mut label_query: Query<&mut Text, With<ProgressLabel>>,
+) {
+    let mut total = 0;
+    let mut loaded = 0;
+    let mut current = None;
+    for (id, is_loaded) in resource_handles.dependency_progress(&asset_server) {
+        total += 1;
+        if is_loaded {
+            loaded += 1;
+        } else if current.is_none() {
+            current = Some(id);
+        }
+    }
+
+    let fraction = if total == 0 {
+        1.0
+    } else {
+        loaded as f32 / total as f32
+    };
+    let width = percent(fraction * 100.0);
+    for mut node in &mut fill_query {
+        if node.width != width {
+            node.width = width;
+        }
+    }
+
+    let label = match current.and_then(|id| asset_server.get_path(id)) {
+        Some(path) => format!("{path} ({loaded}/{total})"),
+        // The assets are in, and the pipeline warmup runs before the level is entered.
+        None if loaded == total && !warmup.is_done() => match warmup.waiting() {
+            0 => "Preparing shaders".to_string(),
+            waiting => format!("Compiling shaders ({waiting} left)"),
+        },
+        None => format!("{loaded}/{total}"),
+    };
+    for mut text in &mut label_query {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
+
+fn spawn_fade_in(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Fade In"),
+        FadeIn { elapsed: 0.0 },
+        DespawnOnExit(Screen::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100.0),
+            height: percent(100.0),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND),
+        // Above every panel, but without swallowing their clicks.
+        GlobalZIndex(i32::MAX),
+        Pickable::IGNORE,
+    ));
+}
+
+fn fade_in(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fade_query: Query<(Entity, &mut FadeIn, &mut BackgroundColor)>,
+) {
+    for (entity, mut fade, mut background) in &mut fade_query {
+        fade.elapsed += time.delta_secs();
+        let alpha = 1.0 - fade.elapsed / FADE_DURATION;
+        if alpha <= 0.0 {
+            commands.entity(entity).despawn();
+        } else {
+            background.0 = BACKGROUND.with_alpha(alpha);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Custom logos on the hubs of fans and the pump caps of liquid coolers, as system integrators
+//! brand the builds they sell.
+//!
+//! Set the logo of the selected fan, or of the liquid cooler whose radiator is selected, from
+//! the command palette, as the path of an image in the assets, like `logos/studio.png`. An
+//! image in an extra asset root works too, see [`asset_roots`](crate::asset_roots). A fan's
+//! logo sits on the middle of the side that faces into the case, and a cooler's on the cap of
+//! its pump on the CPU, see [`pump_cap`]. They're saved with the build, show in captures and
+//! turntables like the rest of the build, and are listed in the workspace's export.
+
+use std::collections::BTreeMap;
+
+use bevy::{ecs::system::SystemId, prelude::*};
+
+use crate::{
+    LevelAssets, Screen,
+    aio::pump_cap,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    part_selection::SelectedPart,
+    parts::{InstalledPart, PartCatalog, PartKind},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PartLogos>();
+    let set_logo = app.register_system(set_selected_logo);
+    app.insert_resource(LogoPrompt(set_logo));
+    app.add_systems(
+        Update,
+        sync_logo_decals
+            .run_if(
+                resource_changed::<PartLogos>
+                    .or(resource_changed::<ActiveCase>)
+                    .or(any_match_filter::<Added<InstalledPart>>)
+                    .or(any_component_removed::<InstalledPart>),
+            )
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(OnExit(Screen::Game), forget_logos);
+    app.add_palette_command(
+        "Set the logo on the selected fan hub or pump cap",
+        prompt_for_logo,
+    );
+}
+
+/// The images set as logos on the build's fans and liquid coolers, by catalog id, as paths in
+/// the assets.
+#[derive(Resource, Debug, Default)]
+pub struct PartLogos(pub BTreeMap<String, String>);
+
+/// Sets the logo of the selected part from the path it's given.
+#[derive(Resource, Debug)]
+struct LogoPrompt(SystemId<In<String>>);
+
+/// The disc a logo is shown on, a child of the fan it's on or of the CPU under the pump.
+#[derive(Component, Debug)]
+pub struct LogoDecal;
+
+/// How far off the surface it's on a logo is shown, in millimeters, so it doesn't flicker
+/// through it.
+const DECAL_LIFT: f32 = 0.5;
+/// How wide a fan's hub is, as a share of the fan's size.
+const HUB_SHARE: f32 = 0.36;
+/// How wide a logo on a pump cap is, in millimeters.
+const PUMP_LOGO_SIZE: f32 = 45.0;
+
+/// Where a logo sits on the hub of a fan of `size`, in the fan's own space: in the middle of
+/// the side it's thinnest across that faces the way `inward` points, with the disc's front
+/// facing out of that side and scaled to the width of the hub.
+pub fn hub_logo(size: Vec3, inward: Vec3) -> Transform {
+    let axis = if size.x <= size.y.min(size.z) {
+        Vec3::X
+    } else if size.y <= size.z {
+        Vec3::Y
+    } else {
+        Vec3::Z
+    };
+    let normal = if inward.dot(axis) < 0.0 { -axis } else { axis };
+    decal(
+        normal * (size.dot(axis) / 2.0 + DECAL_LIFT),
+        normal,
+        size.max_element() * HUB_SHARE,
+    )
+}
+
+/// A disc of `width` at `position`, facing `normal`, with the top of its image up where it can
+/// be.
+fn decal(position: Vec3, normal: Vec3, width: f32) -> Transform {
+    let up = if normal.y.abs() > 0.9 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    Transform::from_translation(position)
+        .looking_to(-normal, up)
+        .with_scale(Vec3::splat(width / 2.0))
+}
+
+/// Shows the logos of the installed parts, each on a disc of its own.
+fn sync_logo_decals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    logos: Res<PartLogos>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    part_query: Query<(Entity, &InstalledPart, &Transform)>,
+    decal_query: Query<Entity, With<LogoDecal>>,
+) {
+    for decal in &decal_query {
+        commands.entity(decal).despawn();
+    }
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let center = active_case.center(&level_assets, &manifests);
+    let cpu = part_query.iter().find(|(_, part, _)| {
+        catalog
+            .get(&part.id)
+            .is_some_and(|spec| spec.kind == PartKind::Cpu)
+    });
+
+    for (entity, part, transform) in &part_query {
+        let (Some(logo), Some(spec)) = (logos.0.get(&part.id), catalog.get(&part.id)) else {
+            continue;
+        };
+        let (parent, placement) = match spec.kind {
+            PartKind::Fan => {
+                let inward = transform.rotation.inverse() * (center - transform.translation);
+                (entity, hub_logo(Vec3::from(spec.size), inward))
+            }
+            PartKind::Radiator if let Some((cpu, ..)) = cpu => {
+                let cap = pump_cap(Vec3::ZERO, Quat::IDENTITY);
+                (
+                    cpu,
+                    decal(cap + Vec3::X * DECAL_LIFT, Vec3::X, PUMP_LOGO_SIZE),
+                )
+            }
+            _ => continue,
+        };
+        commands.spawn((
+            Name::new(format!("{} Logo", spec.name)),
+            LogoDecal,
+            Mesh3d(meshes.add(Circle::new(1.0))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color_texture: Some(asset_server.load(logo.clone())),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            })),
+            placement,
+            ChildOf(parent),
+        ));
+    }
+}
+
+fn prompt_for_logo(mut commands: Commands, prompt: Res<LogoPrompt>) {
+    commands.trigger(OpenPrompt {
+        label: "Logo image in the assets, like logos/studio.png, or nothing to remove it"
+            .to_string(),
+        submit: prompt.0,
+    });
+}
+
+fn set_selected_logo(
+    In(text): In<String>,
+    selected: Res<SelectedPart>,
+    mut logos: ResMut<PartLogos>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    parent_query: Query<&ChildOf>,
+    part_query: Query<&InstalledPart>,
+) {
+    let catalog = catalogs.get(&level_assets.catalog);
+    let part = selected.0.and_then(|selected| {
+        std::iter::once(selected)
+            .chain(parent_query.iter_ancestors(selected))
+            .find_map(|entity| part_query.get(entity).ok())
+            .filter(|part| {
+                catalog
+                    .and_then(|catalog| catalog.get(&part.id))
+                    .is_some_and(|spec| matches!(spec.kind, PartKind::Fan | PartKind::Radiator))
+            })
+    });
+    let Some(part) = part else {
+        info!("Select an installed fan or liquid cooler radiator to set its logo first");
+        return;
+    };
+    match text.trim() {
+        "" => logos.0.remove(&part.id),
+        path => logos.0.insert(part.id.clone(), path.to_string()),
+    };
+}
+
+fn forget_logos(mut logos: ResMut<PartLogos>) {
+    logos.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logos_face_into_the_case() {
+        let fan = Vec3::new(120.0, 120.0, 25.0);
+        // A front fan, with the inside of the case behind it.
+        let logo = hub_logo(fan, Vec3::new(-40.0, -100.0, -200.0));
+        assert_eq!(logo.translation, Vec3::Z * -(12.5 + DECAL_LIFT));
+        assert!(logo.back().dot(Vec3::NEG_Z) > 0.999);
+        assert_eq!(logo.scale, Vec3::splat(120.0 * HUB_SHARE / 2.0));
+
+        // A top fan, lying flat with the case below it.
+        let logo = hub_logo(Vec3::new(120.0, 25.0, 120.0), Vec3::NEG_Y);
+        assert_eq!(logo.translation, Vec3::Y * -(12.5 + DECAL_LIFT));
+        assert!(logo.back().dot(Vec3::NEG_Y) > 0.999);
+    }
+}
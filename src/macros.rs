@@ -0,0 +1,145 @@
+//! Recording a sequence of command palette commands as a named macro, to replay them later.
+//!
+//! Start recording from the palette, run the commands to record from it, then stop recording
+//! and name the macro. It's listed in the palette as "Macro: run <name>" for the rest of the
+//! session.
+
+use bevy::{ecs::system::SystemId, prelude::*};
+
+use crate::{
+    Screen,
+    command_palette::{AddPaletteCommand, OpenPrompt, PaletteCommandRun, PaletteCommands},
+    ui_navigation::FONT_SIZE,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let save = app.register_system(save_macro);
+    app.insert_resource(MacroRecorder {
+        steps: None,
+        save,
+        saved: 0,
+    });
+    app.add_palette_command("Macro: start recording", start_recording);
+    app.add_palette_command("Macro: stop recording", stop_recording);
+    app.add_observer(record_command);
+    app.add_systems(OnEnter(Screen::Game), spawn_recording_label);
+    app.add_systems(
+        Update,
+        update_recording_label
+            .run_if(resource_changed::<MacroRecorder>.or(any_match_filter::<Added<RecordingLabel>>))
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+#[derive(Resource, Debug)]
+struct MacroRecorder {
+    /// The names of the commands recorded so far, while recording.
+    steps: Option<Vec<String>>,
+    /// Names and stores the recorded steps.
+    save: SystemId<In<String>>,
+    /// How many macros have been saved, for naming the ones left unnamed.
+    saved: usize,
+}
+
+#[derive(Component)]
+struct RecordingLabel;
+
+/// Starts the names of the commands that control macros, which are never recorded.
+const MACRO_PREFIX: &str = "Macro:";
+const RECORDING_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+
+fn start_recording(mut recorder: ResMut<MacroRecorder>) {
+    recorder.steps = Some(Vec::new());
+}
+
+fn stop_recording(mut commands: Commands, mut recorder: ResMut<MacroRecorder>) {
+    match recorder.steps.as_deref() {
+        None => {}
+        Some([]) => {
+            info!("Nothing was recorded, so no macro was saved");
+            recorder.steps = None;
+        }
+        Some(_) => commands.trigger(OpenPrompt {
+            label: "Macro name".to_string(),
+            submit: recorder.save,
+        }),
+    }
+}
+
+fn record_command(run: On<PaletteCommandRun>, mut recorder: ResMut<MacroRecorder>) {
+    if run.0.starts_with(MACRO_PREFIX) {
+        return;
+    }
+    if let Some(steps) = &mut recorder.steps {
+        steps.push(run.0.clone());
+    }
+}
+
+fn save_macro(
+    In(name): In<String>,
+    mut commands: Commands,
+    mut recorder: ResMut<MacroRecorder>,
+    mut palette_commands: ResMut<PaletteCommands>,
+) {
+    let Some(steps) = recorder.steps.take() else {
+        return;
+    };
+    recorder.saved += 1;
+    let name = if name.is_empty() {
+        format!("Macro {}", recorder.saved)
+    } else {
+        name
+    };
+
+    info!("Saved macro \"{name}\" with {} steps", steps.len());
+    let replay = commands.register_system(
+        move |mut commands: Commands, palette_commands: Res<PaletteCommands>| {
+            for step in &steps {
+                match palette_commands.get(step) {
+                    Some(system) => commands.run_system(system),
+                    None => warn!("Skipping \"{step}\", which is no longer a command"),
+                }
+            }
+        },
+    );
+    palette_commands.add(format!("{MACRO_PREFIX} run {name}"), replay);
+}
+
+fn spawn_recording_label(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Macro Recording Label"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the label, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            // Below the capture panel.
+            top: px(40.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            RecordingLabel,
+            Text::default(),
+            TextFont::from_font_size(FONT_SIZE),
+            TextColor(RECORDING_COLOR),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn update_recording_label(
+    recorder: Res<MacroRecorder>,
+    mut label_query: Query<&mut Text, With<RecordingLabel>>,
+) {
+    let label = match &recorder.steps {
+        Some(steps) => format!("Recording a macro ({} steps)", steps.len()),
+        None => String::new(),
+    };
+    for mut text in &mut label_query {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
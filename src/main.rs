@@ -1,10 +1,97 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+mod actions;
+mod aio;
+mod anchor_authoring;
+#[cfg(not(target_arch = "wasm32"))]
+mod asset_roots;
 mod asset_tracking;
+mod attract_mode;
+mod blueprint;
+mod board_armor;
+mod build_wizard;
+mod camera_focus;
+mod capture;
+mod case_panels;
+mod cases;
+mod command_palette;
+mod compat;
+mod compatibility;
+mod dust;
+mod error_screen;
+mod exploded_view;
+mod exposure;
+mod fan_colors;
+mod fans;
+mod front_panel;
+#[cfg(not(target_arch = "wasm32"))]
+mod gallery;
+mod gpu_support;
+mod isolate;
+mod keybindings;
+mod kiosk;
+mod labels;
+mod light_panel;
+mod light_rig;
+mod lighting;
+mod loading_screen;
+mod logos;
+mod macros;
+mod main_menu;
+mod measure;
+mod model_cache;
+mod model_import;
+#[cfg(not(target_arch = "wasm32"))]
+mod packs;
+mod part_notes;
+mod part_selection;
+mod parts;
+mod performance;
+mod persistence;
+mod pip_views;
+mod pipeline_warmup;
+mod placeholders;
+mod post_processing;
+mod presentation_window;
+mod quality_guard;
+mod report;
+mod rgb;
+mod settings;
+mod size_compare;
+mod split_view;
+mod stereo;
+mod storage;
+mod thermal_comparison;
+mod thermals;
+#[cfg(not(target_arch = "wasm32"))]
+mod thumbnail_cache;
+mod thumbnails;
+mod timeline;
+mod touch;
+mod turntable;
+mod ui_navigation;
+#[cfg(not(target_arch = "wasm32"))]
+mod validate_pack;
+mod workspace;
+mod xray;
+#[cfg(not(target_arch = "wasm32"))]
+mod zip_archive;
 
-use bevy::{asset::AssetMetaCheck, prelude::*};
+use bevy::{
+    asset::AssetMetaCheck,
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    picking::{hover::HoverMap, pointer::PointerId},
+    prelude::*,
+    scene::SceneInstanceReady,
+};
 use crate::asset_tracking::{LoadResource, ResourceHandles};
+use crate::command_palette::AddPaletteCommand;
+use crate::keybindings::{KeyAction, key_just_pressed};
 
 fn main() -> AppExit {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(pack) = validate_pack::requested() {
+        return validate_pack::run(&pack);
+    }
     App::new().add_plugins(AppPlugin).run()
 }
 
@@ -12,6 +99,10 @@ pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
+        // Extra asset roots have to be in place before the asset plugin is added.
+        #[cfg(not(target_arch = "wasm32"))]
+        asset_roots::register_asset_roots(app);
+
         // Add Bevy plugins.
         app.add_plugins(
             DefaultPlugins
@@ -34,15 +125,88 @@ impl Plugin for AppPlugin {
         );
 
         // Add other plugins.
-        app.add_plugins(
-            asset_tracking::plugin
-        );
+        app.add_plugins((
+            actions::plugin,
+            aio::plugin,
+            anchor_authoring::plugin,
+            asset_tracking::plugin,
+            attract_mode::plugin,
+            blueprint::plugin,
+            board_armor::plugin,
+            build_wizard::plugin,
+            camera_focus::plugin,
+            capture::plugin,
+            case_panels::plugin,
+            cases::plugin,
+            command_palette::plugin,
+            compatibility::plugin,
+            dust::plugin,
+        ));
+        app.add_plugins((
+            error_screen::plugin,
+            exploded_view::plugin,
+            exposure::plugin,
+            fan_colors::plugin,
+            fans::plugin,
+            front_panel::plugin,
+            gpu_support::plugin,
+            isolate::plugin,
+            keybindings::plugin,
+            kiosk::plugin,
+            labels::plugin,
+            light_panel::plugin,
+            light_rig::plugin,
+            lighting::plugin,
+            loading_screen::plugin,
+        ));
+        app.add_plugins((
+            logos::plugin,
+            macros::plugin,
+            main_menu::plugin,
+            measure::plugin,
+            model_cache::plugin,
+            model_import::plugin,
+            part_notes::plugin,
+            part_selection::plugin,
+            parts::plugin,
+            performance::plugin,
+            persistence::plugin,
+            pip_views::plugin,
+            pipeline_warmup::plugin,
+            placeholders::plugin,
+            post_processing::plugin,
+        ));
+        app.add_plugins((
+            presentation_window::plugin,
+            quality_guard::plugin,
+            report::plugin,
+            rgb::plugin,
+            settings::plugin,
+            size_compare::plugin,
+            split_view::plugin,
+            stereo::plugin,
+            thermal_comparison::plugin,
+            thermals::plugin,
+            thumbnails::plugin,
+            timeline::plugin,
+            touch::plugin,
+            turntable::plugin,
+            ui_navigation::plugin,
+        ));
+        app.add_plugins((workspace::plugin, xray::plugin));
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugins((gallery::plugin, packs::plugin));
         app.load_resource::<LevelAssets>();
         app.init_state::<Screen>();
+        app.add_sub_state::<Menu>();
 
-        app.add_systems(Update, enter_gameplay_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)));
+        app.add_systems(Update, enter_gameplay_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded).and(pipeline_warmup::pipelines_warmed_up)));
         app.add_systems(OnEnter(Screen::Game), (init_spawn, spawn_text_in_ui, sync_orbit_camera_on_spawn).chain());
-        app.add_systems(Update, (orbit_camera_system, aim_camera_light).chain().run_if(in_state(Screen::Game)));
+        app.add_systems(Update, (orbit_camera_system, respawn_level.run_if(key_just_pressed(KeyAction::RespawnLevel))).run_if(in_state(Screen::Game)));
+        app.add_systems(Update, update_help_text.run_if(in_state(Screen::Game).and(resource_changed::<settings::Settings>.or(any_match_filter::<Added<HelpText>>))));
+        app.add_observer(reveal_level);
+        app.add_palette_command("Respawn the level", respawn_level);
+        app.add_palette_command("Main menu", open_main_menu);
     }
 }
 
@@ -50,156 +214,331 @@ impl Plugin for AppPlugin {
 enum Screen {
     #[default]
     Loading,
-    Game
+    /// Start, load a build, open the settings or quit, once the assets have loaded.
+    MainMenu,
+    Game,
+    /// Some assets failed to load.
+    Error,
 }
 
-fn enter_gameplay_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Game);
+/// A menu open over the main menu or the level, which stay as they are behind it.
+#[derive(SubStates, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[source(Screen = Screen::MainMenu | Screen::Game)]
+enum Menu {
+    #[default]
+    Closed,
+    /// The settings, opened from the level with Escape or from the main menu.
+    Settings,
+    /// Changing the keys of the scene's controls, opened from the settings.
+    Keybindings,
+    /// Describing an existing PC step by step, opened from the main menu.
+    Wizard,
+}
+
+/// Marks a trip through `Screen::Loading` as a respawn, which goes back to the level.
+#[derive(Resource, Debug)]
+struct Respawning;
+
+/// Loading leads to the main menu; respawns and kiosk mode go straight to the level.
+fn enter_gameplay_screen(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    kiosk: Res<kiosk::Kiosk>,
+    respawning: Option<Res<Respawning>>,
+) {
+    if respawning.is_some() {
+        commands.remove_resource::<Respawning>();
+        next_screen.set(Screen::Game);
+    } else if kiosk.active {
+        // Visitors shouldn't land in a menu; the saved build is shown instead.
+        commands.insert_resource(persistence::LoadSavedBuild);
+        next_screen.set(Screen::Game);
+    } else {
+        next_screen.set(Screen::MainMenu);
+    }
 }
 
 fn all_assets_loaded(resource_handles: Res<ResourceHandles>) -> bool {
     resource_handles.is_all_done()
 }
 
+/// Tears the level down and spawns it again. Going through `Screen::Loading` runs the
+/// `OnExit(Screen::Game)` cleanup, and loading finishes right away since the assets are cached.
+fn respawn_level(mut commands: Commands, mut next_screen: ResMut<NextState<Screen>>) {
+    commands.insert_resource(Respawning);
+    next_screen.set(Screen::Loading);
+}
+
+/// Leaves the level for the main menu, to start another build or load one.
+fn open_main_menu(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::MainMenu);
+}
+
+/// The case model shown at startup, relative to the assets folder.
+const PC_CASE_MODEL: &str = "models/pc_case.glb";
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct LevelAssets {
+    /// The active case; swapped out by [`cases::SwitchCase`].
     #[dependency]
-    pc_case: Handle<Scene>
+    pc_case: Handle<Scene>,
+    #[dependency]
+    cases: Handle<cases::CaseManifest>,
+    #[dependency]
+    catalog: Handle<parts::PartCatalog>,
+    #[dependency]
+    presets: Handle<kiosk::PresetBuilds>,
+    #[dependency]
+    playlist: Handle<attract_mode::AttractPlaylist>,
+    #[dependency]
+    lighting: Handle<lighting::LightingPresets>,
+    #[dependency]
+    thermals: Handle<thermals::ThermalModel>,
 }
 
 impl FromWorld for LevelAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
         Self {
-            pc_case: assets.load(GltfAssetLabel::Scene(0).from_asset("models/pc_case.glb")),
+            pc_case: assets.load(GltfAssetLabel::Scene(0).from_asset(PC_CASE_MODEL)),
+            catalog: assets.load("parts/catalog.ron"),
+            cases: assets.load("models/cases.ron"),
+            presets: assets.load("builds/presets.ron"),
+            playlist: assets.load("attract/playlist.ron"),
+            lighting: assets.load("environments/lighting.ron"),
+            thermals: assets.load("simulation/thermals.ron"),
         }
     }
 }
 
+/// The root of the spawned case scene.
 #[derive(Component)]
+pub struct Level;
+
+#[derive(Component, Clone)]
 pub struct OrbitCamera {
     pub radius: f32,
     pub yaw: f32,
     pub pitch: f32,
     pub speed: f32,
     pub target: Vec3,
+    /// The radius the camera is zooming towards; `radius` follows it smoothly.
+    pub desired_radius: f32,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// How quickly `radius` catches up with `desired_radius`. Higher is snappier.
+    pub zoom_smoothing: f32,
 }
 
+impl OrbitCamera {
+    /// Where the camera sits for its yaw, pitch and radius, looking at the target.
+    pub fn transform(&self) -> Transform {
+        let x = self.radius * self.yaw.cos() * self.pitch.cos();
+        let z = self.radius * self.yaw.sin() * self.pitch.cos();
+        let y = self.radius * self.pitch.sin();
+
+        Transform::from_translation(self.target + Vec3::new(x, y, z)).looking_at(self.target, Vec3::Y)
+    }
+}
+
+/// Radians of orbit per pixel of mouse movement.
+const MOUSE_ORBIT_SENSITIVITY: f32 = 0.005;
+/// Fraction of the radius panned per pixel of mouse movement.
+const MOUSE_PAN_SENSITIVITY: f32 = 0.001;
+/// Factor the radius is divided by per scroll wheel line.
+const ZOOM_STEP: f32 = 1.1;
+/// Touchpads scroll in pixels rather than lines.
+const SCROLL_PIXELS_PER_LINE: f32 = 16.0;
+/// Fraction of the radius panned per second with a gamepad stick pushed all the way.
+const GAMEPAD_PAN_SPEED: f32 = 0.8;
+/// Scroll wheel lines zoomed per second with a gamepad trigger pulled all the way, or a zoom
+/// key held.
+const GAMEPAD_ZOOM_SPEED: f32 = 8.0;
+
 fn init_spawn(mut commands: Commands, level_assets: Res<LevelAssets>) {
     commands.spawn((
         Name::new("Camera"),
+        DespawnOnExit(Screen::Game),
         Camera3d::default(),
+        // Other cameras render on top of this one, so the UI has to be pinned to it.
+        IsDefaultUiCamera,
+        labels::ShowsLabels,
         OrbitCamera {
             radius: 900.0,
             yaw: 0.7,
             pitch: 0.4,
             speed: 1.5,
             target: Vec3::new(0.0, 200.0, 0.0),
+            desired_radius: 900.0,
+            min_radius: 300.0,
+            max_radius: 2500.0,
+            zoom_smoothing: 12.0,
         },
         Transform::default(),
-        children![
-        (
-            Name::new("Camera Light"),
-            SpotLight {
-                intensity: 500_000.0,
-                range: 5000.0,
-                inner_angle: 0.35,
-                outer_angle: 0.6,
-                shadows_enabled: true,
-                ..default()
-            },
-            Transform::from_xyz(0.0, 50.0, 0.0), // smaller offset
-        )
-    ]
     ));
 
     commands.spawn((
         Name::new("Level"),
+        Level,
+        DespawnOnExit(Screen::Game),
         Transform::default(),
-        Visibility::default(),
+        // Revealed by `reveal_level` once the scene has finished spawning.
+        Visibility::Hidden,
         children![
             SceneRoot(level_assets.pc_case.clone()),
         ],
     ));
 }
 
+fn reveal_level(
+    ready: On<SceneInstanceReady>,
+    scene_query: Query<&ChildOf, With<SceneRoot>>,
+    mut level_query: Query<&mut Visibility, With<Level>>,
+) {
+    let Ok(child_of) = scene_query.get(ready.entity) else {
+        return;
+    };
+    if let Ok(mut visibility) = level_query.get_mut(child_of.parent()) {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+/// The controls, listed over the level with the keys they're bound to.
+#[derive(Component)]
+struct HelpText;
+
 fn spawn_text_in_ui(mut commands: Commands) {
     commands.spawn((
+        HelpText,
+        DespawnOnExit(Screen::Game),
         Node {
             position_type: PositionType::Absolute,
             top: px(5.0),
             left: px(5.0),
             ..default()
         },
-        Text::new("Use 'A' and 'D' to rotate the object."),
+        Text::default(),
         TextColor(Color::WHITE),
         TextLayout::new_with_justify(Justify::Center),
     ));
 }
 
+fn update_help_text(settings: Res<settings::Settings>, mut text_query: Query<&mut Text, With<HelpText>>) {
+    for mut text in &mut text_query {
+        text.0 = help_text(&settings.keybindings);
+    }
+}
+
+fn help_text(bindings: &keybindings::Keybindings) -> String {
+    let name = |action| keybindings::key_name(bindings.key(action));
+    // Letters and symbols are quoted, named keys like Home aren't.
+    let key = |action| {
+        let name = name(action);
+        if name.chars().count() == 1 { format!("'{name}'") } else { name }
+    };
+    [
+        format!("Use {} and {} to rotate the object, {} and {} to zoom.", key(KeyAction::OrbitLeft), key(KeyAction::OrbitRight), key(KeyAction::ZoomIn), key(KeyAction::ZoomOut)),
+        "Drag with the left mouse button to orbit, the right or middle one to pan, and scroll to zoom.".to_string(),
+        format!("Press {} to toggle the lights.", key(KeyAction::ToggleLights)),
+        format!("Press {} to toggle auto exposure, {} and {} to adjust it.", key(KeyAction::AutoExposure), key(KeyAction::ExposureDown), key(KeyAction::ExposureUp)),
+        format!("Press {} to detach the camera light, {} to add a light, {} to select the next one.", key(KeyAction::DetachCameraLight), key(KeyAction::AddLight), key(KeyAction::NextLight)),
+        format!("Move the selected light with {}, {}, {} and {}, and {} and {} vertically, remove it with {}.", key(KeyAction::MoveLightForward), key(KeyAction::MoveLightLeft), key(KeyAction::MoveLightBack), key(KeyAction::MoveLightRight), key(KeyAction::RaiseLight), key(KeyAction::LowerLight), key(KeyAction::RemoveLight)),
+        "Press Tab to move through the light panel, Enter to use a button and Escape to leave it.".to_string(),
+        "Click a part to select it, and click it again or click empty space to deselect it.".to_string(),
+        format!("Hover a part to see its name, or press {} to label all of them.", key(KeyAction::ShowAllLabels)),
+        format!("Press {} to frame the selected part, {} to isolate it and {} to reset the view.", key(KeyAction::FramePart), key(KeyAction::Isolate), key(KeyAction::ResetView)),
+        format!("Press {} to flip the camera to the rear chamber of a dual-chamber case and back.", key(KeyAction::FlipChamber)),
+        format!("Press {} to switch to the next case, {} to explode it, {} or a click on a panel to take the panels off.", key(KeyAction::NextCase), key(KeyAction::ExplodeCase), key(KeyAction::RemovePanels)),
+        format!("Press {} to toggle the top and front views, {} to cycle the stereo modes, {} to see through the case.", key(KeyAction::SwitchViews), key(KeyAction::CycleStereo), key(KeyAction::XRay)),
+        format!("Press {} to step through the orthographic blueprint views of the case, {} to split the window into more views.", key(KeyAction::BlueprintViews), key(KeyAction::SplitView)),
+        format!("Press {} to open the RGB lighting panel.", key(KeyAction::RgbPanel)),
+        format!("Press {} to open a presentation window, {} to respawn the level, {} to capture the view, {} to record a turntable.", key(KeyAction::PresentationWindow), key(KeyAction::RespawnLevel), key(KeyAction::Capture), key(KeyAction::Turntable)),
+        format!("Press {} to open the animation timeline, {} and {} to step through its frames.", key(KeyAction::Timeline), key(KeyAction::PreviousFrame), key(KeyAction::NextFrame)),
+        format!("Press Ctrl+{} to search every action in the command palette, {} to enter or leave kiosk mode.", name(KeyAction::CommandPalette), key(KeyAction::Kiosk)),
+        format!("Press {} to place part anchors on the case by clicking it.", key(KeyAction::AnchorAuthoring)),
+        format!("Press {} to measure between two clicked points, {} to switch between millimeters and inches.", key(KeyAction::Measure), key(KeyAction::MeasureUnits)),
+        "Press Escape to open the settings, where the keys can be changed from these.".to_string(),
+        "On a gamepad, the right stick orbits, the left stick pans, the triggers zoom and the D-pad moves through the UI.".to_string(),
+        "On a touch screen, drag one finger to orbit, pinch to zoom and drag two fingers to pan.".to_string(),
+    ]
+    .join("\n")
+}
+
 fn sync_orbit_camera_on_spawn(
     mut query: Query<(&OrbitCamera, &mut Transform)>,
 ) {
     for (orbit, mut transform) in &mut query {
-        let x = orbit.radius * orbit.yaw.cos() * orbit.pitch.cos();
-        let z = orbit.radius * orbit.yaw.sin() * orbit.pitch.cos();
-        let y = orbit.radius * orbit.pitch.sin();
-
-        transform.translation = orbit.target + Vec3::new(x, y, z);
-        transform.look_at(orbit.target, Vec3::Y);
+        *transform = orbit.transform();
     }
 }
 
 fn orbit_camera_system(
     time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<actions::Actions>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    hover_map: Res<HoverMap>,
+    settings: Res<settings::Settings>,
+    split_view: Res<split_view::SplitView>,
+    node_query: Query<(), With<Node>>,
     mut query: Query<(&mut OrbitCamera, &mut Transform)>,
 ) {
+    // Leave the mouse to the UI while it is over a panel or button, and to the split view's
+    // other views while it is over one of them.
+    let mouse_over_ui = hover_map
+        .get(&PointerId::Mouse)
+        .is_some_and(|hits| hits.keys().any(|entity| node_query.contains(*entity)));
+    let (motion, scroll) = if mouse_over_ui || split_view.has_mouse() {
+        (Vec2::ZERO, 0.0)
+    } else {
+        let scroll = match mouse_scroll.unit {
+            MouseScrollUnit::Line => mouse_scroll.delta.y,
+            MouseScrollUnit::Pixel => mouse_scroll.delta.y / SCROLL_PIXELS_PER_LINE,
+        };
+        (mouse_motion.delta, scroll)
+    };
+    let scroll = scroll + actions.zoom_steps + actions.zoom * GAMEPAD_ZOOM_SPEED * time.delta_secs();
+    let mut orbit_drag = actions.orbit_drag;
+    if mouse_buttons.pressed(MouseButton::Left) {
+        orbit_drag += motion;
+    }
+    let mut pan_drag = actions.pan_drag;
+    if mouse_buttons.any_pressed([MouseButton::Right, MouseButton::Middle]) {
+        pan_drag += motion;
+    }
+
     for (mut orbit, mut transform) in &mut query {
-        // Input
-        let mut direction = 0.0;
-        if keys.pressed(KeyCode::KeyA) {
-            direction += 1.0;
-        }
-        if keys.pressed(KeyCode::KeyD) {
-            direction -= 1.0;
-        }
+        // Keys and sticks orbit at a steady speed
+        let orbit_speed = orbit.speed * settings.camera_speed * time.delta_secs();
+        orbit.yaw += actions.orbit.x * orbit_speed;
+        orbit.pitch += actions.orbit.y * orbit_speed;
 
-        // Update yaw
-        orbit.yaw += direction * orbit.speed * time.delta_secs();
+        // Left-drag orbits, and so does dragging one finger
+        let orbit_motion = settings.orbit_motion(orbit_drag);
+        orbit.yaw += orbit_motion.x * MOUSE_ORBIT_SENSITIVITY;
+        orbit.pitch += orbit_motion.y * MOUSE_ORBIT_SENSITIVITY;
 
-        // Clamp pitch so we never flip
-        orbit.pitch = orbit.pitch.clamp(0.05, 1.2);
+        // Right- or middle-drag pans, as does dragging two fingers, so the scene moves along
+        let pan = pan_drag * settings.mouse_sensitivity * orbit.radius * MOUSE_PAN_SENSITIVITY;
+        orbit.target += transform.up() * pan.y - transform.right() * pan.x;
 
-        // Spherical → Cartesian
-        let cos_pitch = orbit.pitch.cos();
-        let sin_pitch = orbit.pitch.sin();
+        // The left stick moves the target the way it's pushed
+        let pan = actions.pan * settings.camera_speed * orbit.radius * GAMEPAD_PAN_SPEED * time.delta_secs();
+        orbit.target += transform.right() * pan.x + transform.up() * pan.y;
 
-        let x = orbit.radius * orbit.yaw.cos() * cos_pitch;
-        let z = orbit.radius * orbit.yaw.sin() * cos_pitch;
-        let y = orbit.radius * sin_pitch;
+        // Scrolling zooms, easing the radius towards where the wheel left it
+        orbit.desired_radius = (orbit.desired_radius / ZOOM_STEP.powf(scroll))
+            .clamp(orbit.min_radius, orbit.max_radius);
+        let desired_radius = orbit.desired_radius;
+        let zoom_smoothing = orbit.zoom_smoothing;
+        orbit.radius.smooth_nudge(&desired_radius, zoom_smoothing, time.delta_secs());
 
-        // Apply transform
-        transform.translation = orbit.target + Vec3::new(x, y, z);
-        transform.look_at(orbit.target, Vec3::Y);
-    }
-}
+        // Clamp pitch so we never flip
+        orbit.pitch = orbit.pitch.clamp(0.05, 1.2);
 
-fn aim_camera_light(
-    camera_query: Query<(&GlobalTransform, &OrbitCamera)>,
-    mut light_query: Query<(&mut Transform, &GlobalTransform), With<SpotLight>>,
-) {
-    if let Ok((camera_global, orbit)) = camera_query.single() {
-        for (mut local_transform, light_global) in &mut light_query {
-            let light_pos = light_global.translation();
-            let dir = orbit.target - light_pos;
-
-            if dir.length_squared() > 0.0001 {
-                let rotation = Quat::from_rotation_arc(Vec3::NEG_Z, dir.normalize());
-                local_transform.rotation =
-                    camera_global.rotation().inverse() * rotation;
-            }
-        }
+        // Spherical → Cartesian
+        *transform = orbit.transform();
     }
 }
@@ -0,0 +1,126 @@
+//! The main menu, shown once the assets have loaded and before the level, and again when the
+//! level is left for it from the settings or the command palette.
+//!
+//! Start opens the level with the default case and no parts, Load build opens it with the saved
+//! build, Describe my PC opens the [`build_wizard`](crate::build_wizard) to start from an
+//! existing PC instead, Settings opens the settings over the menu, which is put back when
+//! they're closed, and Quit closes the app. The web build has no Quit, since a page can't close
+//! itself.
+
+use bevy::{input_focus::tab_navigation::TabGroup, prelude::*};
+
+use crate::{
+    Menu, Screen,
+    persistence::LoadSavedBuild,
+    ui_navigation::{Activate, ButtonStyle, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::MainMenu), spawn_main_menu_camera);
+    // Taken down while the settings are open, so it can't be tabbed to behind them.
+    app.add_systems(
+        OnEnter(Menu::Closed),
+        spawn_main_menu.run_if(in_state(Screen::MainMenu)),
+    );
+    app.add_observer(activate_menu_button);
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum MenuButton {
+    Start,
+    LoadBuild,
+    DescribePc,
+    Settings,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    Quit,
+}
+
+const BACKGROUND: Color = Color::BLACK;
+const BUTTON_WIDTH: f32 = 200.0;
+const TITLE_FONT_SIZE: f32 = 32.0;
+
+fn spawn_main_menu_camera(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Main Menu Camera"),
+        DespawnOnExit(Screen::MainMenu),
+        Camera2d,
+    ));
+}
+
+fn spawn_main_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Main Menu"),
+            DespawnOnExit(Screen::MainMenu),
+            DespawnOnExit(Menu::Closed),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: px(8.0),
+                padding: UiRect::all(px(20.0)),
+                ..default()
+            },
+            BackgroundColor(BACKGROUND),
+            TabGroup::new(0),
+            children![(
+                Text::new("PC Case Visualizer"),
+                TextFont::from_font_size(TITLE_FONT_SIZE),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(px(12.0)),
+                    ..default()
+                },
+            )],
+        ))
+        .with_children(|menu| {
+            menu.spawn(button("Start", MenuButton::Start));
+            menu.spawn(button("Load build", MenuButton::LoadBuild));
+            menu.spawn(button("Describe my PC", MenuButton::DescribePc));
+            menu.spawn(button("Settings", MenuButton::Settings));
+            #[cfg(not(target_arch = "wasm32"))]
+            menu.spawn(button("Quit", MenuButton::Quit));
+        });
+}
+
+fn button(label: impl Into<String>, action: MenuButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Center),
+        Node {
+            width: px(BUTTON_WIDTH),
+            padding: UiRect::axes(px(10.0), px(4.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn activate_menu_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    mut app_exit: MessageWriter<AppExit>,
+    button_query: Query<&MenuButton>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(MenuButton::Start) => next_screen.set(Screen::Game),
+        Ok(MenuButton::LoadBuild) => {
+            commands.insert_resource(LoadSavedBuild);
+            next_screen.set(Screen::Game);
+        }
+        Ok(MenuButton::DescribePc) => next_menu.set(Menu::Wizard),
+        Ok(MenuButton::Settings) => next_menu.set(Menu::Settings),
+        Ok(MenuButton::Quit) => {
+            app_exit.write(AppExit::Success);
+        }
+        Err(_) => {}
+    }
+}
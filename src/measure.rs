@@ -0,0 +1,202 @@
+//! A ruler for measuring the model, to check whether an aftermarket part would fit.
+//!
+//! Press M to start measuring, then click two points on the case or its parts: a dimension
+//! line is drawn between them, labeled with its length. Press U to switch every length in the
+//! app between millimeters and inches, as the units setting does. Measurements stay on the
+//! model until they're cleared from the command palette. Clicks don't select parts while
+//! measuring.
+
+use bevy::{
+    color::palettes::css::{AQUA, WHITE},
+    prelude::*,
+};
+
+use crate::{
+    Level, Screen,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    labels::BillboardLabel,
+    part_selection::{PressPosition, Unselectable},
+    settings::{Settings, save_settings},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Ruler>();
+    app.add_systems(OnExit(Screen::Game), drop_measurement_start);
+    app.add_observer(place_measurement_point);
+    app.add_systems(
+        Update,
+        (
+            toggle_measuring.run_if(key_just_pressed(KeyAction::Measure)),
+            toggle_units.run_if(key_just_pressed(KeyAction::MeasureUnits)),
+            sync_measuring_mode
+                .run_if(resource_changed::<Ruler>.or(any_match_filter::<Added<Level>>)),
+            update_measurement_labels
+                .run_if(resource_changed::<Settings>.or(any_match_filter::<Added<Measurement>>)),
+            draw_measurements,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Measure: start or stop", toggle_measuring);
+    app.add_palette_command(
+        "Measure: switch between millimeters and inches",
+        toggle_units,
+    );
+    app.add_palette_command("Measure: clear the measurements", clear_measurements);
+}
+
+#[derive(Resource, Debug, Default)]
+struct Ruler {
+    active: bool,
+    /// The first point of the measurement being taken, in the level's space.
+    start: Option<Vec3>,
+}
+
+/// A dimension line between two points, in the space of the level it's a child of. The
+/// entity sits halfway between them, where the label goes.
+#[derive(Component, Debug, Clone, Copy)]
+struct Measurement {
+    start: Vec3,
+    end: Vec3,
+}
+
+const LABEL_GROUP: &str = "Measurements";
+const LINE_COLOR: Srgba = AQUA;
+const POINT_COLOR: Srgba = WHITE;
+/// Radius, in millimeters, of the marker drawn at each end of a line.
+const POINT_RADIUS: f32 = 3.0;
+
+fn toggle_measuring(mut ruler: ResMut<Ruler>) {
+    ruler.active = !ruler.active;
+    ruler.start = None;
+    if ruler.active {
+        info!("Click two points on the model to measure between them");
+    }
+}
+
+fn toggle_units(mut commands: Commands, mut settings: ResMut<Settings>) {
+    settings.units = settings.units.next();
+    commands.run_system_cached(save_settings);
+}
+
+/// Measurements are despawned with the level, so one half taken can't be finished on the next.
+fn drop_measurement_start(mut ruler: ResMut<Ruler>) {
+    ruler.start = None;
+}
+
+fn clear_measurements(
+    mut commands: Commands,
+    mut ruler: ResMut<Ruler>,
+    measurement_query: Query<Entity, With<Measurement>>,
+) {
+    ruler.start = None;
+    for entity in &measurement_query {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Keeps clicks on the level from selecting parts while measuring.
+fn sync_measuring_mode(
+    mut commands: Commands,
+    mut was_active: Local<bool>,
+    ruler: Res<Ruler>,
+    level_query: Query<Entity, With<Level>>,
+) {
+    for level in &level_query {
+        if ruler.active {
+            commands.entity(level).insert(Unselectable);
+        } else if *was_active {
+            // Only when measuring stops, so anchor authoring keeps the level unselectable.
+            commands.entity(level).remove::<Unselectable>();
+        }
+    }
+    *was_active = ruler.active;
+}
+
+fn place_measurement_point(
+    click: On<Pointer<Click>>,
+    mut commands: Commands,
+    mut ruler: ResMut<Ruler>,
+    press_position: Res<PressPosition>,
+    parent_query: Query<&ChildOf>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    if !ruler.active
+        || !press_position.is_click(&click)
+        || click.entity != click.original_event_target()
+    {
+        return;
+    }
+    let Some(hit) = click.hit.position else {
+        return;
+    };
+    let Some((level_entity, level)) = parent_query
+        .iter_ancestors(click.entity)
+        .find_map(|ancestor| Some(ancestor).zip(level_query.get(ancestor).ok()))
+    else {
+        return;
+    };
+
+    let point = level.affine().inverse().transform_point3(hit);
+    let Some(start) = ruler.start.take() else {
+        ruler.start = Some(point);
+        return;
+    };
+    commands.spawn((
+        Name::new("Measurement"),
+        Measurement { start, end: point },
+        Transform::from_translation(start.midpoint(point)),
+        BillboardLabel {
+            text: String::new(),
+            group: LABEL_GROUP.to_string(),
+            offset: Vec3::ZERO,
+        },
+        ChildOf(level_entity),
+    ));
+}
+
+fn update_measurement_labels(
+    settings: Res<Settings>,
+    mut measurement_query: Query<(&Measurement, &mut BillboardLabel)>,
+) {
+    for (measurement, mut label) in &mut measurement_query {
+        let text = settings.format_length(measurement.start.distance(measurement.end));
+        if label.text != text {
+            label.text = text;
+        }
+    }
+}
+
+fn draw_measurements(
+    mut gizmos: Gizmos,
+    ruler: Res<Ruler>,
+    measurement_query: Query<(&Measurement, &ChildOf)>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    for (measurement, child_of) in &measurement_query {
+        let Ok(level) = level_query.get(child_of.parent()) else {
+            continue;
+        };
+        let start = level.transform_point(measurement.start);
+        let end = level.transform_point(measurement.end);
+        gizmos.line(start, end, LINE_COLOR);
+        for point in [start, end] {
+            gizmos.sphere(
+                Isometry3d::from_translation(point),
+                POINT_RADIUS,
+                POINT_COLOR,
+            );
+        }
+    }
+
+    // The first point of the next measurement, until the second one is clicked.
+    if let (Some(start), Ok(level)) = (ruler.start, level_query.single()) {
+        let start = level.transform_point(start);
+        gizmos.sphere(
+            Isometry3d::from_translation(start),
+            POINT_RADIUS,
+            LINE_COLOR,
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Part models kept loaded after they're last used, within a memory budget.
+//!
+//! Parts load their models through [`PartModels`], which holds on to them once the part is
+//! taken out or its thumbnail is done, so putting the part back is instant. Each model is
+//! tracked with how much memory its meshes and textures take. While the models add up to more
+//! than [`MEMORY_BUDGET`], the least recently used ones that aren't in the scene are let go of,
+//! and Bevy frees them with everything only they used. Models in the scene, like those of the
+//! installed parts, are never let go of, so a large build can still go over the budget.
+
+use bevy::{
+    image::TextureFormatPixelInfo,
+    mesh::Indices,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use crate::{Screen, command_palette::AddPaletteCommand};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PartModels>();
+    app.add_systems(
+        Update,
+        (measure_models, evict_models)
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Show the memory used by part models", log_model_memory);
+}
+
+/// How many bytes of meshes and textures the part models may take before unused ones are let
+/// go of.
+const MEMORY_BUDGET: usize = 512 * MEBIBYTE;
+const MEBIBYTE: usize = 1024 * 1024;
+
+/// The part models loaded so far, by path.
+#[derive(Resource, Debug, Default)]
+pub struct PartModels {
+    models: HashMap<String, CachedModel>,
+    /// Counts the uses of every model, to tell which was used last.
+    uses: u64,
+}
+
+#[derive(Debug)]
+struct CachedModel {
+    scene: Handle<Scene>,
+    /// When the model was last used, as a count of [`PartModels::uses`].
+    last_used: u64,
+    /// Bytes of the model's meshes and textures, once it has loaded.
+    bytes: Option<usize>,
+}
+
+impl PartModels {
+    /// The scene of the glTF file at `path`, from memory if it's still loaded.
+    pub fn load(&mut self, asset_server: &AssetServer, path: &str) -> Handle<Scene> {
+        self.uses += 1;
+        let last_used = self.uses;
+        let model = self
+            .models
+            .entry(path.to_string())
+            .or_insert_with(|| CachedModel {
+                scene: asset_server.load(GltfAssetLabel::Scene(0).from_asset(path.to_string())),
+                last_used,
+                bytes: None,
+            });
+        model.last_used = last_used;
+        model.scene.clone()
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.models.values().filter_map(|model| model.bytes).sum()
+    }
+}
+
+/// Adds up the meshes and textures of the models that have finished loading. Assets shared
+/// between models are counted for each of them.
+fn measure_models(
+    mut part_models: ResMut<PartModels>,
+    asset_server: Res<AssetServer>,
+    scenes: Res<Assets<Scene>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    images: Res<Assets<Image>>,
+) {
+    for model in part_models.models.values_mut() {
+        if model.bytes.is_some() || !asset_server.is_loaded_with_dependencies(&model.scene) {
+            continue;
+        }
+        let Some(scene) = scenes.get(&model.scene) else {
+            continue;
+        };
+        model.bytes = Some(scene_bytes(scene, &meshes, &materials, &images));
+    }
+}
+
+fn scene_bytes(
+    scene: &Scene,
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+    images: &Assets<Image>,
+) -> usize {
+    let mut mesh_ids = HashSet::new();
+    let mut image_ids = HashSet::new();
+    let world = &scene.world;
+    if let Some(mut query) =
+        world.try_query::<(Option<&Mesh3d>, Option<&MeshMaterial3d<StandardMaterial>>)>()
+    {
+        for (mesh, material) in query.iter(world) {
+            if let Some(mesh) = mesh {
+                mesh_ids.insert(mesh.id());
+            }
+            let Some(material) = material.and_then(|material| materials.get(material)) else {
+                continue;
+            };
+            let textures = [
+                &material.base_color_texture,
+                &material.emissive_texture,
+                &material.metallic_roughness_texture,
+                &material.normal_map_texture,
+                &material.occlusion_texture,
+            ];
+            image_ids.extend(textures.into_iter().flatten().map(Handle::id));
+        }
+    }
+
+    let mesh_bytes: usize = mesh_ids
+        .into_iter()
+        .filter_map(|id| meshes.get(id))
+        .map(|mesh| {
+            let indices = mesh.indices().map_or(0, |indices| match indices {
+                Indices::U16(indices) => indices.len() * 2,
+                Indices::U32(indices) => indices.len() * 4,
+            });
+            mesh.count_vertices() * mesh.get_vertex_size() as usize + indices
+        })
+        .sum();
+    let image_bytes: usize = image_ids
+        .into_iter()
+        .filter_map(|id| images.get(id))
+        .map(|image| {
+            image.data.as_ref().map_or_else(
+                // Images only kept on the GPU, which take about as much there.
+                || {
+                    let size = image.texture_descriptor.size;
+                    let pixel_size = image.texture_descriptor.format.pixel_size().unwrap_or(4);
+                    (size.width * size.height * size.depth_or_array_layers) as usize * pixel_size
+                },
+                Vec::len,
+            )
+        })
+        .sum();
+    mesh_bytes + image_bytes
+}
+
+/// Lets go of the least recently used models that aren't in the scene, until the rest fit in
+/// the budget.
+fn evict_models(mut part_models: ResMut<PartModels>, scene_query: Query<&SceneRoot>) {
+    // Installed parts and the thumbnail being rendered count as used right now.
+    let in_use: HashSet<AssetId<Scene>> = scene_query.iter().map(|scene| scene.0.id()).collect();
+    let part_models = &mut *part_models;
+    for model in part_models.models.values_mut() {
+        if in_use.contains(&model.scene.id()) {
+            part_models.uses += 1;
+            model.last_used = part_models.uses;
+        }
+    }
+
+    let mut total = part_models.total_bytes();
+    if total <= MEMORY_BUDGET {
+        return;
+    }
+    let mut unused: Vec<(String, u64, usize)> = part_models
+        .models
+        .iter()
+        .filter(|(_, model)| !in_use.contains(&model.scene.id()))
+        .map(|(path, model)| (path.clone(), model.last_used, model.bytes.unwrap_or(0)))
+        .collect();
+    unused.sort_by_key(|(_, last_used, _)| *last_used);
+    for (path, _, bytes) in unused {
+        if total <= MEMORY_BUDGET {
+            break;
+        }
+        part_models.models.remove(&path);
+        total -= bytes;
+        debug!(
+            "Let go of {path} ({:.1} MiB) to keep the part models within the memory budget",
+            bytes as f32 / MEBIBYTE as f32
+        );
+    }
+}
+
+fn log_model_memory(part_models: Res<PartModels>) {
+    let loading = part_models
+        .models
+        .values()
+        .filter(|model| model.bytes.is_none())
+        .count();
+    info!(
+        "{} part models take {:.1} MiB of the {} MiB budget, {loading} more aren't loaded",
+        part_models.models.len() - loading,
+        part_models.total_bytes() as f32 / MEBIBYTE as f32,
+        MEMORY_BUDGET / MEBIBYTE
+    );
+}
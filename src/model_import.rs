@@ -0,0 +1,402 @@
+//! Catching models that were exported in the wrong units or with the wrong up axis.
+//!
+//! Once a case or part model has spawned, its size is compared with the one the case manifest
+//! or part catalog gives for it. A model that fits much better scaled from centimeters, meters
+//! or inches, or turned from Z up to Y up, gets a notice offering to normalize it. Normalized
+//! and ignored models are remembered for the rest of the session, so the model comes in right
+//! the next time it's spawned.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    camera::primitives::Aabb, input_focus::tab_navigation::TabGroup,
+    platform::collections::HashMap, prelude::*, scene::SceneInstanceReady,
+};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::AddPaletteCommand,
+    parts::{InstalledPart, PartCatalog},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ModelFixes>();
+    app.add_observer(queue_model_check);
+    app.add_observer(activate_import_button);
+    app.add_systems(OnEnter(Screen::Game), spawn_import_panel);
+    app.add_systems(
+        Update,
+        (
+            apply_known_fixes,
+            check_models,
+            rebuild_import_panel
+                .run_if(resource_changed::<ModelFixes>.or(any_match_filter::<Added<ImportPanel>>)),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Normalize the imported models", normalize_all);
+}
+
+/// What's known about the models spawned so far, by path.
+#[derive(Resource, Debug, Default)]
+struct ModelFixes {
+    /// Models that look off, in the order they were found.
+    suggested: Vec<(String, ModelFix)>,
+    /// Models that were normalized, or ignored with [`ModelFix::NONE`].
+    decided: HashMap<String, ModelFix>,
+}
+
+impl ModelFixes {
+    fn decide(&mut self, index: usize, accept: bool) -> Option<(String, ModelFix)> {
+        if index >= self.suggested.len() {
+            return None;
+        }
+        let (model, fix) = self.suggested.remove(index);
+        let fix = if accept { fix } else { ModelFix::NONE };
+        self.decided.insert(model.clone(), fix);
+        Some((model, fix))
+    }
+}
+
+/// How a model has to be turned and scaled to be in millimeters with Y up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelFix {
+    units: ModelUnits,
+    z_up: bool,
+}
+
+impl ModelFix {
+    const NONE: Self = Self {
+        units: ModelUnits::Millimeters,
+        z_up: false,
+    };
+
+    fn transform(self) -> Transform {
+        let rotation = if self.z_up {
+            Quat::from_rotation_x(-FRAC_PI_2)
+        } else {
+            Quat::IDENTITY
+        };
+        Transform::from_rotation(rotation).with_scale(Vec3::splat(self.units.millimeters()))
+    }
+
+    /// The size a model of `size` has once fixed.
+    fn apply(self, size: Vec3) -> Vec3 {
+        let size = if self.z_up { size.xzy() } else { size };
+        size * self.units.millimeters()
+    }
+
+    pub fn describe(self, model: &str) -> String {
+        let units = self.units.name();
+        match (self.units, self.z_up) {
+            (ModelUnits::Millimeters, _) => format!("{model} looks like it's modeled with Z up"),
+            (_, false) => format!("{model} looks like it's modeled in {units}"),
+            (_, true) => format!("{model} looks like it's modeled in {units} with Z up"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum ModelUnits {
+    Millimeters,
+    Centimeters,
+    Meters,
+    Inches,
+}
+
+impl ModelUnits {
+    const ALL: [Self; 4] = [
+        Self::Millimeters,
+        Self::Centimeters,
+        Self::Meters,
+        Self::Inches,
+    ];
+
+    fn millimeters(self) -> f32 {
+        match self {
+            Self::Millimeters => 1.0,
+            Self::Centimeters => 10.0,
+            Self::Meters => 1000.0,
+            Self::Inches => 25.4,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Millimeters => "millimeters",
+            Self::Centimeters => "centimeters",
+            Self::Meters => "meters",
+            Self::Inches => "inches",
+        }
+    }
+}
+
+/// A model whose size hasn't been checked yet.
+#[derive(Component)]
+struct UncheckedModel;
+
+#[derive(Component)]
+struct ImportPanel;
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum ImportButton {
+    /// Normalizes the suggested model at this index.
+    Normalize(usize),
+    Ignore(usize),
+}
+
+/// How much closer to its expected size a fix has to bring a model to be suggested: the sum
+/// over the three axes of how many times too large or small each is, on a log scale.
+const FIX_THRESHOLD: f32 = 1.5;
+
+/// The path of the model a scene was loaded from.
+fn model_path(scene: &SceneRoot) -> Option<String> {
+    scene
+        .0
+        .path()
+        .map(|path| path.path().to_string_lossy().into_owned())
+}
+
+/// Its meshes only have bounds once they have been computed, after the scene has spawned.
+fn queue_model_check(ready: On<SceneInstanceReady>, mut commands: Commands) {
+    commands.entity(ready.entity).insert(UncheckedModel);
+}
+
+/// Turns and scales models that were normalized before as soon as they spawn again.
+fn apply_known_fixes(
+    fixes: Res<ModelFixes>,
+    mut scene_query: Query<(&SceneRoot, &mut Transform), Added<SceneRoot>>,
+) {
+    for (scene, mut transform) in &mut scene_query {
+        if let Some(fix) = model_path(scene).and_then(|model| fixes.decided.get(&model)) {
+            *transform = fix.transform();
+        }
+    }
+}
+
+fn check_models(
+    mut commands: Commands,
+    mut fixes: ResMut<ModelFixes>,
+    active_case: Res<ActiveCase>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+    catalogs: Res<Assets<PartCatalog>>,
+    scene_query: Query<(Entity, &SceneRoot, &GlobalTransform, &ChildOf), With<UncheckedModel>>,
+    children_query: Query<&Children>,
+    mesh_query: Query<(Option<&Aabb>, &GlobalTransform), With<Mesh3d>>,
+    level_query: Query<(), With<Level>>,
+    part_query: Query<&InstalledPart>,
+) {
+    for (entity, scene, scene_transform, child_of) in &scene_query {
+        let meshes: Vec<_> = children_query
+            .iter_descendants(entity)
+            .filter_map(|descendant| mesh_query.get(descendant).ok())
+            .collect();
+        if meshes.iter().any(|(aabb, _)| aabb.is_none()) {
+            continue;
+        }
+        commands.entity(entity).remove::<UncheckedModel>();
+
+        let Some(model) = model_path(scene) else {
+            continue;
+        };
+        if fixes.decided.contains_key(&model)
+            || fixes
+                .suggested
+                .iter()
+                .any(|(suggested, _)| *suggested == model)
+        {
+            continue;
+        }
+        let expected = if level_query.contains(child_of.parent()) {
+            manifests
+                .get(&level_assets.cases)
+                .and_then(|manifest| manifest.get(&active_case.0))
+                .map(|case| case.dimensions)
+        } else {
+            part_query
+                .get(child_of.parent())
+                .ok()
+                .zip(catalogs.get(&level_assets.catalog))
+                .and_then(|(part, catalog)| catalog.get(&part.id))
+                .map(|spec| spec.size)
+        };
+        let Some(expected) = expected else {
+            continue;
+        };
+
+        // The model's bounds in its own space, as it was exported.
+        let to_model = scene_transform.affine().inverse();
+        let (min, max) = meshes
+            .iter()
+            .filter_map(|(aabb, transform)| Some((aabb.as_ref()?, *transform)))
+            .flat_map(|(aabb, transform)| {
+                let (center, half) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+                [-1.0, 1.0].into_iter().flat_map(move |x| {
+                    [-1.0, 1.0].into_iter().flat_map(move |y| {
+                        [-1.0, 1.0].into_iter().map(move |z| {
+                            let corner = center + half * Vec3::new(x, y, z);
+                            to_model.transform_point3(transform.transform_point(corner))
+                        })
+                    })
+                })
+            })
+            .fold((Vec3::MAX, Vec3::MIN), |(min, max), corner| {
+                (min.min(corner), max.max(corner))
+            });
+        if let Some(fix) = suggest_fix(max - min, Vec3::from(expected)) {
+            info!("{}", fix.describe(&model));
+            fixes.suggested.push((model, fix));
+        }
+    }
+}
+
+/// The fix that best brings a model of `size` to the `expected` size, if that's clearly better
+/// than leaving it as it is.
+pub fn suggest_fix(size: Vec3, expected: Vec3) -> Option<ModelFix> {
+    if size.max_element() <= 0.0 {
+        return None;
+    }
+    // How many times too large or small the fixed model is on each axis, on a log scale.
+    // Flat models, like a fan seen from the side, are measured by their other axes alone.
+    let error = |fix: ModelFix| {
+        let fixed = fix.apply(size);
+        (0..3)
+            .filter(|&axis| fixed[axis] > 0.0 && expected[axis] > 0.0)
+            .map(|axis| (fixed[axis] / expected[axis]).ln().abs())
+            .sum::<f32>()
+    };
+    let (best, best_error) = ModelUnits::ALL
+        .into_iter()
+        .flat_map(|units| [false, true].map(|z_up| ModelFix { units, z_up }))
+        .map(|fix| (fix, error(fix)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    (best != ModelFix::NONE && error(ModelFix::NONE) - best_error > FIX_THRESHOLD).then_some(best)
+}
+
+fn normalize_all(
+    mut fixes: ResMut<ModelFixes>,
+    mut scene_query: Query<(&SceneRoot, &mut Transform)>,
+) {
+    while let Some((model, fix)) = fixes.decide(0, true) {
+        normalize(&model, fix, &mut scene_query);
+    }
+}
+
+fn normalize(model: &str, fix: ModelFix, scene_query: &mut Query<(&SceneRoot, &mut Transform)>) {
+    info!("Normalized {model}");
+    for (scene, mut transform) in scene_query.iter_mut() {
+        if model_path(scene).as_deref() == Some(model) {
+            *transform = fix.transform();
+        }
+    }
+}
+
+fn activate_import_button(
+    activate: On<Activate>,
+    mut fixes: ResMut<ModelFixes>,
+    button_query: Query<&ImportButton>,
+    mut scene_query: Query<(&SceneRoot, &mut Transform)>,
+) {
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+    match *button {
+        ImportButton::Normalize(index) => {
+            if let Some((model, fix)) = fixes.decide(index, true) {
+                normalize(&model, fix, &mut scene_query);
+            }
+        }
+        ImportButton::Ignore(index) => {
+            fixes.decide(index, false);
+        }
+    }
+}
+
+fn spawn_import_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Import Panel"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            // Below the anchor authoring panel.
+            top: px(120.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            ImportPanel,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                // Shown while there are models to normalize.
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(7),
+        )],
+    ));
+}
+
+/// Lists a row per suggested fix, and hides the panel while there are none.
+fn rebuild_import_panel(
+    mut commands: Commands,
+    fixes: Res<ModelFixes>,
+    mut panel_query: Query<(Entity, &mut Node), With<ImportPanel>>,
+) {
+    for (panel, mut node) in &mut panel_query {
+        node.display = if fixes.suggested.is_empty() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+        commands
+            .entity(panel)
+            .despawn_children()
+            .with_children(|panel| {
+                for (index, (model, fix)) in fixes.suggested.iter().enumerate() {
+                    panel.spawn((
+                        Node {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: px(4.0),
+                            ..default()
+                        },
+                        children![
+                            (
+                                Text::new(fix.describe(model)),
+                                TextFont::from_font_size(FONT_SIZE),
+                                TextColor(Color::WHITE),
+                            ),
+                            button(ImportButton::Normalize(index), "Normalize"),
+                            button(ImportButton::Ignore(index), "Ignore"),
+                        ],
+                    ));
+                }
+            });
+    }
+}
+
+fn button(action: ImportButton, label: &str) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
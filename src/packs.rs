@@ -0,0 +1,133 @@
+//! Merges the parts and cases of the asset packs in `packs/` into the app's own.
+//!
+//! `asset_roots` finds the packs at startup. Each pack's `parts/catalog.ron` and
+//! `models/cases.ron`, where it has them, load with the app's assets, and their parts and
+//! cases are added to the app's catalog and case list, again whenever either is reloaded. A
+//! part or case the app or an earlier pack already has, by id or by model, is left out with a
+//! warning, so a pack can't replace what a build refers to.
+
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use crate::{
+    LevelAssets, asset_roots::packs, asset_tracking::LoadResource, cases::CaseManifest,
+    parts::PartCatalog,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.load_resource::<PackAssets>();
+    app.add_systems(
+        Update,
+        merge_packs.run_if(
+            resource_exists::<LevelAssets>
+                .and(resource_exists::<PackAssets>)
+                .and(
+                    resource_added::<LevelAssets>
+                        .or(resource_added::<PackAssets>)
+                        .or(on_message::<AssetEvent<PartCatalog>>)
+                        .or(on_message::<AssetEvent<CaseManifest>>),
+                ),
+        ),
+    );
+}
+
+const CATALOG_PATH: &str = "parts/catalog.ron";
+const CASES_PATH: &str = "models/cases.ron";
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+struct PackAssets {
+    #[dependency]
+    catalogs: Vec<Handle<PartCatalog>>,
+    #[dependency]
+    cases: Vec<Handle<CaseManifest>>,
+}
+
+impl FromWorld for PackAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            catalogs: load_all(assets, CATALOG_PATH),
+            cases: load_all(assets, CASES_PATH),
+        }
+    }
+}
+
+/// The file at `path` of every pack that has one.
+fn load_all<A: Asset>(assets: &AssetServer, path: &str) -> Vec<Handle<A>> {
+    packs()
+        .iter()
+        .filter(|pack| pack.has(path))
+        .map(|pack| assets.load(format!("{}://{path}", pack.source)))
+        .collect()
+}
+
+/// Adds the packs' parts and cases the app's catalog and case list don't have, again after
+/// either is reloaded. Only touches them when there's something to add, as changing them sends
+/// the event this runs on. `handled` holds the ids and models seen before, so a clash is only
+/// warned about once.
+fn merge_packs(
+    mut handled: Local<HashSet<String>>,
+    level_assets: Res<LevelAssets>,
+    pack_assets: Res<PackAssets>,
+    mut catalogs: ResMut<Assets<PartCatalog>>,
+    mut manifests: ResMut<Assets<CaseManifest>>,
+) {
+    if let Some(catalog) = catalogs.get(&level_assets.catalog) {
+        let mut ids: Vec<&str> = catalog.parts.iter().map(|part| part.id.as_str()).collect();
+        let mut new_parts = Vec::new();
+        for part in pack_assets
+            .catalogs
+            .iter()
+            .filter_map(|handle| catalogs.get(handle))
+            .flat_map(|pack_catalog| &pack_catalog.parts)
+        {
+            let first_seen = handled.insert(part.id.clone());
+            if ids.contains(&part.id.as_str()) {
+                if first_seen {
+                    warn!("Leaving out the pack part {}, as its id is taken", part.id);
+                }
+                continue;
+            }
+            ids.push(&part.id);
+            new_parts.push(part.clone());
+        }
+        if !new_parts.is_empty()
+            && let Some(catalog) = catalogs.get_mut(&level_assets.catalog)
+        {
+            catalog.parts.extend(new_parts);
+        }
+    }
+
+    if let Some(manifest) = manifests.get(&level_assets.cases) {
+        let mut models: Vec<&str> = manifest
+            .cases
+            .iter()
+            .map(|case| case.model.as_str())
+            .collect();
+        let mut new_cases = Vec::new();
+        for case in pack_assets
+            .cases
+            .iter()
+            .filter_map(|handle| manifests.get(handle))
+            .flat_map(|pack_manifest| &pack_manifest.cases)
+        {
+            let first_seen = handled.insert(case.model.clone());
+            if models.contains(&case.model.as_str()) {
+                if first_seen {
+                    warn!(
+                        "Leaving out the pack case {}, as its model is taken",
+                        case.name
+                    );
+                }
+                continue;
+            }
+            models.push(&case.model);
+            new_cases.push(case.clone());
+        }
+        if !new_cases.is_empty()
+            && let Some(manifest) = manifests.get_mut(&level_assets.cases)
+        {
+            manifest.cases.extend(new_cases);
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Notes on the parts of a build: free text, when each part was bought and how long its
+//! warranty lasts, for keeping track of an owned system.
+//!
+//! Set them for the selected part from the command palette, as a purchase date, a warranty in
+//! months and the note, like `2026-03-14 36 Bought at the local shop`. Each of them is
+//! optional, and entering nothing clears the notes. They're saved with the build, and the build
+//! report lists them with when each warranty ends, warning about the ones that end soon or
+//! ended.
+
+use std::{collections::BTreeMap, fmt};
+
+use bevy::{ecs::system::SystemId, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Screen,
+    capture::timestamp,
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    part_selection::SelectedPart,
+    parts::InstalledPart,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PartNotes>();
+    let set_note = app.register_system(set_selected_note);
+    app.insert_resource(NotePrompt(set_note));
+    app.add_systems(OnExit(Screen::Game), forget_notes);
+    app.add_palette_command("Set the notes of the selected part", prompt_for_note);
+}
+
+/// The notes on the build's parts, by catalog id.
+#[derive(Resource, Debug, Default)]
+pub struct PartNotes(pub BTreeMap<String, PartNote>);
+
+/// Sets the note of the selected part from the text it's given.
+#[derive(Resource, Debug)]
+struct NotePrompt(SystemId<In<String>>);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PartNote {
+    /// Free text, like where the part was bought.
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub purchased: Option<Date>,
+    /// How long the warranty lasts from the purchase, in months.
+    #[serde(default)]
+    pub warranty_months: Option<u32>,
+}
+
+impl PartNote {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The day the warranty ends, if both the purchase date and the warranty are known.
+    pub fn warranty_end(&self) -> Option<Date> {
+        Some(self.purchased?.add_months(self.warranty_months?))
+    }
+}
+
+/// Where a part's warranty stands on a given day, see [`warranty_status`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WarrantyStatus {
+    /// Ends on this day, more than [`WARRANTY_WARNING_DAYS`] away.
+    Active(Date),
+    /// Ends on this day, in the given number of days.
+    EndingSoon(Date, i64),
+    /// Ended on this day.
+    Ended(Date),
+}
+
+/// A day, saved as `YYYY-MM-DD`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+/// How close to its end, in days, a warranty is warned about.
+pub const WARRANTY_WARNING_DAYS: i64 = 30;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+impl Date {
+    /// Reads a date like `2026-03-14`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut fields = text.splitn(3, '-');
+        let year = fields.next()?.parse().ok()?;
+        let month = fields.next()?.parse().ok()?;
+        let day = fields.next()?.parse().ok()?;
+        ((1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day))
+            .then_some(Self { year, month, day })
+    }
+
+    /// Today, in UTC.
+    pub fn today() -> Self {
+        Self::from_days((timestamp() / SECONDS_PER_DAY) as i64)
+    }
+
+    /// The date `days` after 1970-01-01, after Howard Hinnant's `civil_from_days`.
+    fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Counting months from March, so the leap day comes last.
+        let march_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * march_month + 2) / 5 + 1) as u32;
+        let month = if march_month < 10 {
+            march_month + 3
+        } else {
+            march_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+
+    /// Days since 1970-01-01, after Howard Hinnant's `days_from_civil`.
+    fn days(self) -> i64 {
+        let year = self.year - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let march_month = i64::from((self.month + 9) % 12);
+        let day_of_year = (153 * march_month + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// The same day `months` later, or the last day of that month if it's shorter.
+    pub fn add_months(self, months: u32) -> Self {
+        let months = i64::from(self.month - 1) + i64::from(months);
+        let year = self.year + months / 12;
+        let month = (months % 12) as u32 + 1;
+        Self {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text).ok_or_else(|| format!("\"{text}\" is not a date like 2026-03-14"))
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Where the warranty of `note` stands on `today`, if it has one with a purchase date.
+pub fn warranty_status(note: &PartNote, today: Date) -> Option<WarrantyStatus> {
+    let end = note.warranty_end()?;
+    let days_left = end.days() - today.days();
+    Some(if days_left < 0 {
+        WarrantyStatus::Ended(end)
+    } else if days_left <= WARRANTY_WARNING_DAYS {
+        WarrantyStatus::EndingSoon(end, days_left)
+    } else {
+        WarrantyStatus::Active(end)
+    })
+}
+
+/// Reads a note like `2026-03-14 36 Bought at the local shop`: an optional purchase date, an
+/// optional warranty in months, then the free text.
+pub fn parse_note(text: &str) -> PartNote {
+    let mut note = PartNote::default();
+    let mut rest = text.trim();
+    let (word, after) = first_word(rest);
+    if let Some(date) = Date::parse(word) {
+        note.purchased = Some(date);
+        rest = after;
+    }
+    let (word, after) = first_word(rest);
+    if let Ok(months) = word.parse() {
+        note.warranty_months = Some(months);
+        rest = after;
+    }
+    note.text = rest.to_string();
+    note
+}
+
+/// Splits the first word off `text`, which starts with one.
+fn first_word(text: &str) -> (&str, &str) {
+    let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    (word, rest.trim_start())
+}
+
+fn prompt_for_note(mut commands: Commands, prompt: Res<NotePrompt>) {
+    commands.trigger(OpenPrompt {
+        label: "Purchase date, warranty in months and note, like 2026-03-14 36 Bought online"
+            .to_string(),
+        submit: prompt.0,
+    });
+}
+
+fn set_selected_note(
+    In(text): In<String>,
+    selected: Res<SelectedPart>,
+    mut notes: ResMut<PartNotes>,
+    parent_query: Query<&ChildOf>,
+    part_query: Query<&InstalledPart>,
+) {
+    let part = selected.0.and_then(|selected| {
+        std::iter::once(selected)
+            .chain(parent_query.iter_ancestors(selected))
+            .find_map(|entity| part_query.get(entity).ok())
+    });
+    let Some(part) = part else {
+        info!("Select an installed part to set its notes first");
+        return;
+    };
+    let note = parse_note(&text);
+    if note.is_empty() {
+        notes.0.remove(&part.id);
+    } else {
+        notes.0.insert(part.id.clone(), note);
+    }
+}
+
+fn forget_notes(mut notes: ResMut<PartNotes>) {
+    notes.0.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Date {
+        Date::parse(text).unwrap()
+    }
+
+    #[test]
+    fn days_round_trip() {
+        assert_eq!(Date::from_days(0), date("1970-01-01"));
+        assert_eq!(date("2000-03-01").days(), 11_017);
+        for days in [-1, 59, 365, 11_016, 20_000, 30_000] {
+            assert_eq!(Date::from_days(days).days(), days);
+        }
+        assert_eq!(Date::parse("2025-02-29"), None);
+        assert_eq!(Date::parse("2024-02-29"), Some(date("2024-02-29")));
+    }
+
+    #[test]
+    fn warranty_ends() {
+        let note = parse_note("2024-01-31 25 Bought online");
+        assert_eq!(note.text, "Bought online");
+        // February 2026 has no 31st.
+        assert_eq!(note.warranty_end(), Some(date("2026-02-28")));
+        let status = |today| warranty_status(&note, date(today));
+        assert_eq!(
+            status("2026-01-01"),
+            Some(WarrantyStatus::Active(date("2026-02-28")))
+        );
+        assert_eq!(
+            status("2026-02-18"),
+            Some(WarrantyStatus::EndingSoon(date("2026-02-28"), 10))
+        );
+        assert_eq!(
+            status("2026-03-01"),
+            Some(WarrantyStatus::Ended(date("2026-02-28")))
+        );
+    }
+
+    #[test]
+    fn partial_notes() {
+        assert_eq!(parse_note("  "), PartNote::default());
+        let note = parse_note("Second hand");
+        assert_eq!((note.purchased, note.text.as_str()), (None, "Second hand"));
+        let note = parse_note("2026-03-14");
+        assert_eq!(note.purchased, Some(date("2026-03-14")));
+        assert_eq!(warranty_status(&note, date("2026-03-14")), None);
+    }
+}
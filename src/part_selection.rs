@@ -0,0 +1,171 @@
+//! Selecting parts of the case by clicking on them.
+//!
+//! Clicking a mesh of the level selects it and tints it, clicking it again or clicking empty
+//! space clears the selection. Double-clicking a part keeps it selected and triggers
+//! [`PartDoubleClicked`]. Other systems read the selection from [`SelectedPart`]. Meshes below
+//! an [`Unselectable`] entity are left to whatever their click does instead.
+
+use std::time::Duration;
+
+use bevy::{color::palettes::css::ORANGE, picking::mesh_picking::MeshPickingPlugin, prelude::*};
+
+use crate::{Level, Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(MeshPickingPlugin);
+    app.init_resource::<SelectedPart>();
+    app.init_resource::<PressPosition>();
+    app.init_resource::<LastClick>();
+    app.add_observer(record_press_position);
+    app.add_observer(select_clicked_part);
+    app.add_systems(OnExit(Screen::Game), clear_selection);
+    app.add_systems(
+        Update,
+        highlight_selected_part
+            .in_set(HighlightSelectedPart)
+            .run_if(resource_changed::<SelectedPart>)
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+/// The mesh entity of the level that the user clicked on last.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SelectedPart(pub Option<Entity>);
+
+/// A part of the level was double-clicked.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PartDoubleClicked(pub Entity);
+
+/// Swaps the materials of the selected part, for systems that must not race it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HighlightSelectedPart;
+
+/// Keeps the meshes below this entity from being selected.
+#[derive(Component, Debug, Default)]
+pub struct Unselectable;
+
+/// Where the primary button went down, to tell clicks apart from orbiting drags.
+#[derive(Resource, Default)]
+pub struct PressPosition(Vec2);
+
+impl PressPosition {
+    /// Whether `click` is a primary click, rather than the end of a drag.
+    pub fn is_click(&self, click: &Pointer<Click>) -> bool {
+        click.button == PointerButton::Primary
+            && click.pointer_location.position.distance(self.0) <= CLICK_TOLERANCE
+    }
+}
+
+/// The part that was clicked last and when, to tell double clicks apart.
+#[derive(Resource, Default)]
+struct LastClick {
+    part: Option<Entity>,
+    time: Duration,
+}
+
+/// Holds on to the part's own material while it wears the highlight.
+#[derive(Component)]
+struct Highlighted {
+    original: Handle<StandardMaterial>,
+}
+
+/// How far, in logical pixels, the pointer may move between press and release for a click.
+const CLICK_TOLERANCE: f32 = 4.0;
+/// The longest a double click may take from one click to the other.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+const HIGHLIGHT_EMISSIVE: Srgba = ORANGE;
+
+fn record_press_position(press: On<Pointer<Press>>, mut press_position: ResMut<PressPosition>) {
+    // Presses bubble up the hierarchy; the first one is enough.
+    if press.button == PointerButton::Primary && press.entity == press.original_event_target() {
+        press_position.0 = press.pointer_location.position;
+    }
+}
+
+fn select_clicked_part(
+    click: On<Pointer<Click>>,
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    press_position: Res<PressPosition>,
+    mut last_click: ResMut<LastClick>,
+    mut selected: ResMut<SelectedPart>,
+    part_query: Query<(), With<MeshMaterial3d<StandardMaterial>>>,
+    window_query: Query<(), With<Window>>,
+    parent_query: Query<&ChildOf>,
+    level_query: Query<(), With<Level>>,
+    unselectable_query: Query<(), With<Unselectable>>,
+) {
+    if !press_position.is_click(&click) || click.entity != click.original_event_target() {
+        return;
+    }
+
+    let entity = click.entity;
+    let is_part = part_query.contains(entity)
+        && parent_query
+            .iter_ancestors(entity)
+            .any(|ancestor| level_query.contains(ancestor))
+        && !parent_query
+            .iter_ancestors(entity)
+            .any(|ancestor| unselectable_query.contains(ancestor));
+
+    if is_part {
+        let now = time.elapsed();
+        let double_click = last_click.part == Some(entity)
+            && now.saturating_sub(last_click.time) <= DOUBLE_CLICK_TIME;
+        if double_click {
+            // The first click selected the part already, so this one mustn't deselect it.
+            selected.set_if_neq(SelectedPart(Some(entity)));
+            commands.trigger(PartDoubleClicked(entity));
+            // A third click starts over.
+            last_click.part = None;
+        } else {
+            let part = (selected.0 != Some(entity)).then_some(entity);
+            selected.set_if_neq(SelectedPart(part));
+            *last_click = LastClick {
+                part: Some(entity),
+                time: now,
+            };
+        }
+    } else if window_query.contains(entity) {
+        // Nothing but the window was under the pointer.
+        selected.set_if_neq(SelectedPart(None));
+    }
+}
+
+fn clear_selection(mut selected: ResMut<SelectedPart>) {
+    selected.set_if_neq(SelectedPart(None));
+}
+
+fn highlight_selected_part(
+    mut commands: Commands,
+    selected: Res<SelectedPart>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut highlighted_query: Query<(Entity, &Highlighted, &mut MeshMaterial3d<StandardMaterial>)>,
+    mut part_query: Query<&mut MeshMaterial3d<StandardMaterial>, Without<Highlighted>>,
+) {
+    for (entity, highlighted, mut material) in &mut highlighted_query {
+        if selected.0 != Some(entity) {
+            material.0 = highlighted.original.clone();
+            commands.entity(entity).remove::<Highlighted>();
+        }
+    }
+
+    let Some(entity) = selected.0 else {
+        return;
+    };
+    let Ok(mut material) = part_query.get_mut(entity) else {
+        return;
+    };
+    // Materials are shared between meshes of the scene, so the part gets its own tinted copy.
+    let Some(mut tinted) = materials.get(&material.0).cloned() else {
+        return;
+    };
+    tinted.emissive = HIGHLIGHT_EMISSIVE.into();
+    // Keep the tint equally visible whatever the camera exposure is.
+    tinted.emissive_exposure_weight = 0.0;
+
+    commands.entity(entity).insert(Highlighted {
+        original: material.0.clone(),
+    });
+    material.0 = materials.add(tinted);
+}
@@ -0,0 +1,1336 @@
+//! A data-driven catalog of parts that can be installed into the case, and a panel to do so.
+//!
+//! The catalog lives in `assets/parts/catalog.ron`, which native builds keep an index of in
+//! `thumbnail_cache` so it's only parsed again once it changes. A part's model only starts
+//! loading once the part is installed, or its thumbnail is rendered, and stays loaded for a
+//! while after as `model_cache` allows; parts without a model are shown as boxes of their
+//! size. Placeholder parts, made from dimensions the user entered, are boxes too, see-through
+//! and labeled.
+//!
+//! A part mounts to the first free anchor of its kind that the active case lists in
+//! `assets/models/cases.ron`, or at its catalog position if there is none. Switching cases
+//! mounts the parts again. A GPU support stands at its mount point instead, and reaches up to
+//! the graphics card above it, see [`gpu_support`](crate::gpu_support). In an inverted case,
+//! catalog positions are laid out like the anchors, and parts are turned upside down with the
+//! motherboard.
+//!
+//! For planning upgrades, installed parts can be marked as [`Planned`] rather than owned.
+//! Planned parts are drawn see-through, and the panel adds up what the owned and the planned
+//! parts cost. Prices are in US dollars in the catalog and shown in the currency set in
+//! the settings.
+//!
+//! Graphics cards are listed with the expansion slots they take, which a water-blocked card
+//! cuts down to the block's, its active backplate sitting on the back of the card rather than
+//! over the next slot. Water-blocked cards without a model are shown as their block and
+//! backplate.
+//!
+//! Parts with a vendor `url` have a Link button that opens it in the browser and a Copy link
+//! button that puts it on the clipboard, and their SKU is shown next to the price. Both are in
+//! the workspace's CSV export too.
+//!
+//! Hovering a graphics card or cooler in the panel that isn't installed, or focusing one of
+//! its buttons, shows it see-through where it would mount in place of the installed one of its
+//! kind, which is hidden meanwhile and outlined, to compare their sizes before swapping.
+//!
+//! Parts marked as favorites in the panel are kept in the settings and listed first, and the
+//! panel can list only them.
+
+use bevy::{
+    color::palettes::css::WHITE,
+    ecs::system::EntityCommands,
+    input_focus::{InputFocus, tab_navigation::TabGroup},
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(target_arch = "wasm32")]
+use crate::asset_tracking::InitRonAsset;
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest, CaseSpec},
+    command_palette::PaletteCommands,
+    gpu_support::GpuSupportPost,
+    labels::{BillboardLabel, ShownOnHover},
+    model_cache::PartModels,
+    part_selection::{HighlightSelectedPart, SelectedPart},
+    placeholders::{PLACEHOLDER_ALPHA, PlaceholderPart},
+    rgb::RgbZone,
+    settings::{Settings, save_settings},
+    thumbnails::thumbnail,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    #[cfg(not(target_arch = "wasm32"))]
+    app.init_asset::<PartCatalog>()
+        .register_asset_loader(crate::thumbnail_cache::CatalogLoader);
+    #[cfg(target_arch = "wasm32")]
+    app.init_ron_asset::<PartCatalog>(&["catalog.ron"]);
+    app.init_resource::<PartFilter>();
+    app.add_systems(OnEnter(Screen::Game), spawn_parts_panel);
+    app.add_systems(
+        Update,
+        (
+            update_part_buttons.run_if(
+                any_match_filter::<Or<(Added<InstalledPart>, Changed<Planned>)>>
+                    .or(any_component_removed::<InstalledPart>)
+                    .or(any_component_removed::<Planned>),
+            ),
+            update_favorites.run_if(
+                resource_changed::<Settings>
+                    .or(resource_changed::<PartFilter>)
+                    .or(any_match_filter::<Added<PartRow>>),
+            ),
+            update_part_prices
+                .run_if(resource_changed::<Settings>.or(any_match_filter::<Added<PartPrice>>)),
+            update_build_cost.run_if(
+                any_match_filter::<Or<(Added<InstalledPart>, Changed<Planned>, Added<BuildCost>)>>
+                    .or(any_component_removed::<InstalledPart>)
+                    .or(any_component_removed::<Planned>)
+                    .or(resource_changed::<Settings>),
+            ),
+            remount_parts
+                .run_if(resource_changed::<ActiveCase>.and(not(resource_added::<ActiveCase>))),
+            preview_hovered_swap,
+            draw_swap_outline,
+            // Ghosts the part's own materials, not the selection tint that's being taken off.
+            ghost_planned_parts.after(HighlightSelectedPart),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_part_button);
+    app.add_observer(install_parts);
+    app.add_observer(toggle_part);
+    app.add_systems(
+        OnEnter(Screen::Game),
+        add_part_commands.after(spawn_parts_panel).run_if(run_once),
+    );
+}
+
+#[derive(Asset, TypePath, Debug, Serialize, Deserialize)]
+pub struct PartCatalog {
+    pub parts: Vec<PartSpec>,
+}
+
+impl PartCatalog {
+    pub fn get(&self, id: &str) -> Option<&PartSpec> {
+        self.parts.iter().find(|part| part.id == id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartSpec {
+    pub id: String,
+    pub name: String,
+    pub kind: PartKind,
+    /// Path to a glTF file, relative to the assets folder.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Bounding box in millimeters.
+    pub size: [f32; 3],
+    /// Where the center of the part sits in cases without an anchor for it, in millimeters.
+    pub position: [f32; 3],
+    /// The RGB lighting zone the part's LEDs belong to, if it has any.
+    #[serde(default)]
+    pub rgb_zone: Option<String>,
+    /// In US dollars.
+    #[serde(default)]
+    pub price: f32,
+    /// The CPU socket of a CPU or motherboard, like `"AM5"`.
+    #[serde(default)]
+    pub socket: Option<String>,
+    /// The chipset of a motherboard, like `"B650"`.
+    #[serde(default)]
+    pub chipset: Option<String>,
+    /// The motherboard chipsets a CPU runs on.
+    #[serde(default)]
+    pub chipsets: Vec<String>,
+    /// The sockets a cooler has mounting brackets for.
+    #[serde(default)]
+    pub bracket_sockets: Vec<String>,
+    /// The 4-pin fan headers of a motherboard, for fans and the fans of air coolers.
+    #[serde(default)]
+    pub fan_headers: u32,
+    /// The pump headers of a motherboard, for the pumps of liquid coolers. Fans can take
+    /// them as well.
+    #[serde(default)]
+    pub pump_headers: u32,
+    /// The addressable RGB headers of a motherboard, for parts with an RGB zone.
+    #[serde(default)]
+    pub argb_headers: u32,
+    /// The internal headers of a motherboard the case's front-panel connectors plug into,
+    /// one entry per header.
+    #[serde(default)]
+    pub io_headers: Vec<Connector>,
+    /// The M.2 slots of a motherboard.
+    #[serde(default)]
+    pub m2_slots: u32,
+    /// The pieces of a motherboard's armor that come off, such as the heatsinks over its M.2
+    /// slots.
+    #[serde(default)]
+    pub armor: Vec<Armor>,
+    /// The water block a graphics card is cooled by in a custom loop, in place of its fans.
+    #[serde(default)]
+    pub water_block: Option<WaterBlock>,
+    /// The bay or slot a drive goes in.
+    #[serde(default)]
+    pub drive_size: Option<DriveSize>,
+    /// What the part draws from the power supply under load, in watts.
+    #[serde(default)]
+    pub power_draw: f32,
+    /// What a power supply delivers, in watts.
+    #[serde(default)]
+    pub wattage: f32,
+    /// How fast a CPU or graphics card is, relative to a mainstream one at 100.
+    #[serde(default)]
+    pub performance: Option<f32>,
+    /// The shortest and the tallest a GPU support adjusts to, in millimeters. Supports without
+    /// one stay as tall as their `size`.
+    #[serde(default)]
+    pub reach: Option<[f32; 2]>,
+    /// A page to buy the part from.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The vendor's stock keeping unit, to find the exact part with.
+    #[serde(default)]
+    pub sku: Option<String>,
+    /// Made from dimensions the user entered rather than listed in the catalog, see
+    /// [`placeholders`](crate::placeholders).
+    #[serde(skip)]
+    pub placeholder: bool,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum PartKind {
+    Motherboard,
+    Cpu,
+    Gpu,
+    Cooler,
+    Psu,
+    Fan,
+    Drive,
+    Radiator,
+    /// A stand that holds up the far end of a graphics card.
+    GpuSupport,
+}
+
+impl PartKind {
+    pub const ALL: [Self; 9] = [
+        Self::Motherboard,
+        Self::Cpu,
+        Self::Gpu,
+        Self::Cooler,
+        Self::Psu,
+        Self::Fan,
+        Self::Drive,
+        Self::Radiator,
+        Self::GpuSupport,
+    ];
+
+    /// The name of the kind in anchor names, like `fan` in `fan_2`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Motherboard => "motherboard",
+            Self::Cpu => "cpu",
+            Self::Gpu => "gpu",
+            Self::Cooler => "cooler",
+            Self::Psu => "psu",
+            Self::Fan => "fan",
+            Self::Drive => "drive",
+            Self::Radiator => "radiator",
+            Self::GpuSupport => "support",
+        }
+    }
+
+    /// The color of the box shown for parts without a model.
+    pub fn placeholder_color(self) -> Color {
+        match self {
+            Self::Motherboard => Color::srgb(0.15, 0.25, 0.3),
+            Self::Cpu => Color::srgb(0.75, 0.7, 0.55),
+            Self::Gpu => Color::srgb(0.2, 0.6, 0.3),
+            Self::Cooler => Color::srgb(0.7, 0.7, 0.75),
+            Self::Psu => Color::srgb(0.15, 0.15, 0.15),
+            Self::Fan => Color::srgb(0.3, 0.4, 0.8),
+            Self::Drive => Color::srgb(0.6, 0.3, 0.2),
+            Self::Radiator => Color::srgb(0.25, 0.25, 0.3),
+            Self::GpuSupport => Color::srgb(0.55, 0.55, 0.6),
+        }
+    }
+}
+
+/// The water block and active backplate of a graphics card. The card's size takes both in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterBlock {
+    /// How thick the active backplate, with coolant running through it as well, is on the back
+    /// of the card, towards the motherboard, in millimeters.
+    pub backplate: f32,
+}
+
+impl PartSpec {
+    /// How many expansion slots a graphics card takes, or `None` for other parts. A water
+    /// block's active backplate is on the back of the card and doesn't cover the next slot.
+    pub fn expansion_slots(&self) -> Option<u32> {
+        let backplate = self
+            .water_block
+            .as_ref()
+            .map_or(0.0, |block| block.backplate);
+        (self.kind == PartKind::Gpu)
+            .then(|| ((self.size[0] - backplate) / SLOT_PITCH).ceil().max(1.0) as u32)
+    }
+}
+
+/// A front-panel connector of a case, or the motherboard header it plugs into.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Connector {
+    UsbC,
+    Usb3,
+    Usb2,
+    Audio,
+    /// The power switch, reset switch and LEDs, on one header.
+    FrontPanel,
+}
+
+impl Connector {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UsbC => "USB-C",
+            Self::Usb3 => "USB 3.0",
+            Self::Usb2 => "USB 2.0",
+            Self::Audio => "HD audio",
+            Self::FrontPanel => "front panel",
+        }
+    }
+}
+
+/// A piece of a motherboard's armor, over the front of the board.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Armor {
+    pub kind: ArmorKind,
+    /// Where the middle of the piece sits from the middle of the board, in millimeters.
+    pub offset: [f32; 3],
+    /// Bounding box in millimeters.
+    pub size: [f32; 3],
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ArmorKind {
+    /// A heatsink over M.2 slots, which hides the drives in them.
+    M2Heatsink,
+    /// The heatsink over the chipset.
+    Chipset,
+}
+
+impl ArmorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::M2Heatsink => "M.2 heatsink",
+            Self::Chipset => "chipset armor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DriveSize {
+    /// A 3.5" drive, which takes a 3.5" bay.
+    ThreeAndHalf,
+    /// A 2.5" drive, which takes a 2.5" bay or a free 3.5" one.
+    TwoAndHalf,
+    /// An M.2 drive, which takes one of the motherboard's M.2 slots.
+    M2,
+}
+
+/// A part from the catalog, installed as a child of the [`Level`].
+#[derive(Component, Debug, Clone)]
+pub struct InstalledPart {
+    pub id: String,
+    /// Where the center of the part is mounted, in the level's space, or the foot of a GPU
+    /// support.
+    pub mount: Vec3,
+}
+
+/// An installed part the user doesn't own yet.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Planned;
+
+/// A see-through preview of the catalog part with this id, while its row in the panel is
+/// hovered.
+#[derive(Component, Debug, Clone)]
+struct SwapPreview {
+    id: String,
+    /// The installed part of the same kind it stands in for, hidden meanwhile.
+    replaces: Option<Entity>,
+}
+
+/// Holds on to a mesh's own material while its part is [`Planned`] or a [`SwapPreview`].
+#[derive(Component)]
+struct Ghosted {
+    original: Handle<StandardMaterial>,
+}
+
+/// Replaces the installed parts with the catalog parts with these ids.
+#[derive(Event, Debug, Clone)]
+pub struct InstallParts {
+    pub parts: Vec<String>,
+    /// The ids in `parts` to mark as [`Planned`].
+    pub planned: Vec<String>,
+}
+
+/// Installs the catalog part with this id, or removes it if it is installed.
+#[derive(Event, Debug, Clone)]
+pub struct TogglePart(pub String);
+
+/// Applies `action` to the catalog part with this id.
+#[derive(Component, Debug, Clone)]
+struct PartButton {
+    id: String,
+    action: PartAction,
+}
+
+/// The row of the catalog part with this id in the panel.
+#[derive(Component, Debug, Clone)]
+struct PartRow {
+    id: String,
+    /// Where the part is in the catalog, which rows are listed in after the favorites.
+    index: usize,
+}
+
+/// The list of [`PartRow`]s.
+#[derive(Component)]
+struct PartList;
+
+/// Toggles listing only the favorite parts.
+#[derive(Component)]
+struct FavoritesOnlyButton;
+
+/// Whether the panel lists only the favorite parts.
+#[derive(Resource, Debug, Default)]
+struct PartFilter {
+    favorites_only: bool,
+}
+
+/// Shows what the installed parts cost, owned and planned.
+#[derive(Component)]
+struct BuildCost;
+
+/// Shows a catalog price, in US dollars, in the currency of the settings.
+#[derive(Component)]
+struct PartPrice(f32);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum PartAction {
+    /// Installs the part, or removes it if it is installed.
+    Install,
+    TogglePlanned,
+    ToggleFavorite,
+    /// Opens the part's vendor page.
+    OpenLink,
+    /// Copies the address of the part's vendor page.
+    CopyLink,
+}
+
+/// How far apart expansion slots are, in millimeters.
+pub const SLOT_PITCH: f32 = 20.32;
+const BLOCK_COLOR: Color = Color::srgb(0.75, 0.78, 0.8);
+const BACKPLATE_COLOR: Color = Color::srgb(0.12, 0.12, 0.14);
+/// How close, in millimeters, a part has to be to an anchor to take it up.
+pub const MOUNT_TOLERANCE: f32 = 1.0;
+const PRICE_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+/// The kinds of parts hovering one in the panel previews a swap for.
+const SWAP_PREVIEW_KINDS: [PartKind; 2] = [PartKind::Gpu, PartKind::Cooler];
+const SWAP_OUTLINE_COLOR: Srgba = WHITE;
+/// How much of the window's height the list of parts may take up, in percent.
+const PART_LIST_MAX_HEIGHT: f32 = 60.0;
+/// The label group the part names are shown in.
+const LABEL_GROUP: &str = "Parts";
+/// Opacity of planned parts.
+const GHOST_ALPHA: f32 = 0.3;
+
+fn spawn_parts_panel(
+    mut commands: Commands,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+
+    commands
+        .spawn((
+            Name::new("Parts Panel"),
+            DespawnOnExit(Screen::Game),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: px(5.0),
+                left: px(5.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(1),
+            children![(
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(8.0),
+                    ..default()
+                },
+                children![
+                    (Text::new("Parts"), TextColor(Color::WHITE)),
+                    (
+                        Button,
+                        FavoritesOnlyButton,
+                        focusable(),
+                        Text::new("All parts"),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                        Node {
+                            padding: UiRect::axes(px(6.0), px(2.0)),
+                            ..default()
+                        },
+                        ButtonStyle,
+                    ),
+                ],
+            )],
+        ))
+        .with_children(|panel| {
+            panel
+                .spawn((
+                    PartList,
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        // Long catalogs go on in a second column rather than off the window.
+                        flex_wrap: FlexWrap::Wrap,
+                        max_height: vh(PART_LIST_MAX_HEIGHT),
+                        row_gap: px(4.0),
+                        column_gap: px(12.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|list| {
+                    for (index, part) in catalog.parts.iter().enumerate() {
+                        list.spawn((
+                            PartRow {
+                                id: part.id.clone(),
+                                index,
+                            },
+                            RelativeCursorPosition::default(),
+                            Node {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                column_gap: px(4.0),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|row| {
+                            row.spawn(thumbnail(&part.id));
+                            row.spawn(button(part.id.clone(), PartAction::ToggleFavorite));
+                            row.spawn(button(part.id.clone(), PartAction::Install));
+                            row.spawn(button(part.id.clone(), PartAction::TogglePlanned));
+                            row.spawn((
+                                Text::new(&part.name),
+                                TextFont::from_font_size(FONT_SIZE),
+                                TextColor(Color::WHITE),
+                            ));
+                            row.spawn((
+                                PartPrice(part.price),
+                                Text::default(),
+                                TextFont::from_font_size(FONT_SIZE),
+                                TextColor(PRICE_COLOR),
+                            ));
+                            if let Some(slots) = part.expansion_slots() {
+                                let plural = if slots == 1 { "" } else { "s" };
+                                row.spawn((
+                                    Text::new(format!("{slots} slot{plural}")),
+                                    TextFont::from_font_size(FONT_SIZE),
+                                    TextColor(PRICE_COLOR),
+                                ));
+                            }
+                            if let Some(sku) = &part.sku {
+                                row.spawn((
+                                    Text::new(format!("SKU {sku}")),
+                                    TextFont::from_font_size(FONT_SIZE),
+                                    TextColor(PRICE_COLOR),
+                                ));
+                            }
+                            if part.url.is_some() {
+                                row.spawn(button(part.id.clone(), PartAction::OpenLink));
+                                row.spawn(button(part.id.clone(), PartAction::CopyLink));
+                            }
+                        });
+                    }
+                });
+            panel.spawn((
+                BuildCost,
+                Text::default(),
+                TextFont::from_font_size(FONT_SIZE),
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+fn button(id: String, action: PartAction) -> impl Bundle {
+    (
+        Button,
+        PartButton { id, action },
+        focusable(),
+        Text::default(),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+/// Also hides the owned/planned toggle of parts that aren't installed.
+fn update_part_buttons(
+    installed_query: Query<(&InstalledPart, Has<Planned>)>,
+    mut button_query: Query<(&PartButton, &mut Text, &mut Node)>,
+) {
+    for (button, mut text, mut node) in &mut button_query {
+        let installed = installed_query
+            .iter()
+            .find(|(part, _)| part.id == button.id);
+        let (label, display) = match (button.action, installed) {
+            (PartAction::Install, Some(_)) => ("Remove", Display::Flex),
+            (PartAction::Install, None) => ("Add", Display::Flex),
+            (PartAction::TogglePlanned, Some((_, true))) => ("Planned", Display::Flex),
+            (PartAction::TogglePlanned, Some((_, false))) => ("Owned", Display::Flex),
+            (PartAction::TogglePlanned, None) => ("", Display::None),
+            (PartAction::OpenLink, _) => ("Link", Display::Flex),
+            (PartAction::CopyLink, _) => ("Copy link", Display::Flex),
+            // Labeled by `update_favorites`.
+            (PartAction::ToggleFavorite, _) => continue,
+        };
+        if text.0 != label {
+            text.0 = label.to_string();
+        }
+        if node.display != display {
+            node.display = display;
+        }
+    }
+}
+
+/// Lists the favorite parts first, or alone, and labels their buttons.
+fn update_favorites(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    filter: Res<PartFilter>,
+    list_query: Query<(Entity, &Children), With<PartList>>,
+    mut row_query: Query<(&PartRow, &mut Node)>,
+    mut button_query: Query<(&PartButton, &mut Text), Without<FavoritesOnlyButton>>,
+    mut filter_query: Query<&mut Text, With<FavoritesOnlyButton>>,
+) {
+    let is_favorite = |id: &str| settings.favorite_parts.contains(id);
+    for (list, children) in &list_query {
+        let mut rows: Vec<_> = children
+            .iter()
+            .filter_map(|row| {
+                let (part, _) = row_query.get(row).ok()?;
+                Some((!is_favorite(&part.id), part.index, row))
+            })
+            .collect();
+        rows.sort_unstable();
+        let sorted: Vec<_> = rows.iter().map(|&(_, _, row)| row).collect();
+        if sorted[..] != children[..] {
+            commands.entity(list).insert_children(0, &sorted);
+        }
+    }
+    for (part, mut node) in &mut row_query {
+        let display = if filter.favorites_only && !is_favorite(&part.id) {
+            Display::None
+        } else {
+            Display::Flex
+        };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+    for (button, mut text) in &mut button_query {
+        if button.action == PartAction::ToggleFavorite {
+            text.0 = if is_favorite(&button.id) {
+                "Unfavorite"
+            } else {
+                "Favorite"
+            }
+            .to_string();
+        }
+    }
+    for mut text in &mut filter_query {
+        text.0 = if filter.favorites_only {
+            "Favorites only"
+        } else {
+            "All parts"
+        }
+        .to_string();
+    }
+}
+
+fn update_part_prices(settings: Res<Settings>, mut price_query: Query<(&PartPrice, &mut Text)>) {
+    for (price, mut text) in &mut price_query {
+        text.0 = settings.format_price(price.0);
+    }
+}
+
+fn update_build_cost(
+    settings: Res<Settings>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    installed_query: Query<(&InstalledPart, Has<Planned>)>,
+    mut cost_query: Query<&mut Text, With<BuildCost>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let (mut owned, mut planned) = (0.0, 0.0);
+    for (part, is_planned) in &installed_query {
+        let price = catalog.get(&part.id).map_or(0.0, |spec| spec.price);
+        if is_planned {
+            planned += price;
+        } else {
+            owned += price;
+        }
+    }
+    let label = format!(
+        "Owned {}, planned {}, total {}",
+        settings.format_price(owned),
+        settings.format_price(planned),
+        settings.format_price(owned + planned)
+    );
+    for mut text in &mut cost_query {
+        text.0.clone_from(&label);
+    }
+}
+
+fn activate_part_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    button_query: Query<&PartButton>,
+    filter_button_query: Query<(), With<FavoritesOnlyButton>>,
+    installed_query: Query<(Entity, &InstalledPart, Has<Planned>)>,
+    mut selected: ResMut<SelectedPart>,
+    mut settings: ResMut<Settings>,
+    mut filter: ResMut<PartFilter>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    if filter_button_query.contains(activate.entity) {
+        filter.favorites_only = !filter.favorites_only;
+        return;
+    }
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+
+    match button.action {
+        PartAction::Install => commands.trigger(TogglePart(button.id.clone())),
+        PartAction::TogglePlanned => {
+            let Some((installed, _, planned)) = installed_query
+                .iter()
+                .find(|(_, part, _)| part.id == button.id)
+            else {
+                return;
+            };
+            // The selection highlight swaps materials too, and would restore the wrong one.
+            selected.set_if_neq(SelectedPart(None));
+            if planned {
+                commands.entity(installed).remove::<Planned>();
+            } else {
+                commands.entity(installed).insert(Planned);
+            }
+        }
+        PartAction::ToggleFavorite => {
+            if !settings.favorite_parts.remove(&button.id) {
+                settings.favorite_parts.insert(button.id.clone());
+            }
+            commands.run_system_cached(save_settings);
+        }
+        PartAction::OpenLink | PartAction::CopyLink => {
+            let url = catalogs
+                .get(&level_assets.catalog)
+                .and_then(|catalog| catalog.get(&button.id))
+                .and_then(|spec| spec.url.as_deref());
+            match (url, button.action) {
+                (Some(url), PartAction::OpenLink) => open_url(url),
+                (Some(url), _) => copy_to_clipboard(url),
+                (None, _) => {}
+            }
+        }
+    }
+}
+
+/// Opens `url` in the default browser.
+#[cfg(not(target_arch = "wasm32"))]
+fn open_url(url: &str) {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        // The empty title keeps `start` from taking the quoted URL for one.
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    match command.arg(url).spawn() {
+        Ok(_) => info!("Opened {url}"),
+        Err(error) => error!("Could not open {url}: {error}"),
+    }
+}
+
+/// Opens `url` in a new browser tab.
+#[cfg(target_arch = "wasm32")]
+fn open_url(url: &str) {
+    let opened = web_sys::window().map(|window| window.open_with_url_and_target(url, "_blank"));
+    match opened {
+        Some(Ok(_)) => info!("Opened {url}"),
+        Some(Err(error)) => error!("Could not open {url}: {error:?}"),
+        None => error!("Could not open {url}: no window"),
+    }
+}
+
+/// Puts `text` on the clipboard, with the first of the system's clipboard tools that runs.
+#[cfg(not(target_arch = "wasm32"))]
+fn copy_to_clipboard(text: &str) {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    let tools: &[(&str, &[&str])] = if cfg!(target_os = "windows") {
+        &[("clip", &[])]
+    } else if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+    for (tool, args) in tools {
+        let Ok(mut child) = Command::new(tool).args(*args).stdin(Stdio::piped()).spawn() else {
+            continue;
+        };
+        // The tool reads until its input is closed, which dropping it does.
+        let written = child
+            .stdin
+            .take()
+            .map(|mut stdin| stdin.write_all(text.as_bytes()));
+        match (written, child.wait()) {
+            (Some(Ok(())), Ok(status)) if status.success() => info!("Copied {text}"),
+            _ => error!("Could not copy {text} with {tool}"),
+        }
+        return;
+    }
+    error!("Could not copy {text}: no clipboard tool found");
+}
+
+/// Puts `text` on the clipboard, once the browser allows it.
+#[cfg(target_arch = "wasm32")]
+fn copy_to_clipboard(text: &str) {
+    match web_sys::window() {
+        Some(window) => {
+            // The promise is left to settle on its own, as nothing waits for it.
+            let _ = window.navigator().clipboard().write_text(text);
+            info!("Copied {text}");
+        }
+        None => error!("Could not copy {text}: no window"),
+    }
+}
+
+fn toggle_part(
+    toggle: On<TogglePart>,
+    mut commands: Commands,
+    installed_query: Query<(Entity, &InstalledPart)>,
+    level_query: Query<Entity, With<Level>>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    active_case: Res<ActiveCase>,
+    manifests: Res<Assets<CaseManifest>>,
+    asset_server: Res<AssetServer>,
+    mut part_models: ResMut<PartModels>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if let Some((installed, _)) = installed_query.iter().find(|(_, part)| part.id == toggle.0) {
+        commands.entity(installed).despawn();
+        return;
+    }
+
+    let (Ok(level), Some(catalog)) = (level_query.single(), catalogs.get(&level_assets.catalog))
+    else {
+        return;
+    };
+    let case = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0));
+    if let Some(spec) = catalog.get(&toggle.0) {
+        let taken: Vec<_> = installed_query.iter().map(|(_, part)| part.mount).collect();
+        spawn_part(
+            &mut commands,
+            level,
+            spec,
+            mount_point(spec, case, &taken),
+            case.map_or(Quat::IDENTITY, CaseSpec::part_rotation),
+            false,
+            &asset_server,
+            &mut part_models,
+            &mut meshes,
+            &mut materials,
+        );
+    }
+}
+
+/// Lists adding or removing each catalog part in the command palette.
+fn add_part_commands(
+    mut commands: Commands,
+    mut palette_commands: ResMut<PaletteCommands>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    for spec in &catalog.parts {
+        let id = spec.id.clone();
+        let system = commands.register_system(move |mut commands: Commands| {
+            commands.trigger(TogglePart(id.clone()));
+        });
+        palette_commands.add(format!("Add or remove the {}", spec.name), system);
+    }
+}
+
+/// Replaces every installed part with the catalog parts listed, skipping unknown ids.
+fn install_parts(
+    install: On<InstallParts>,
+    mut commands: Commands,
+    installed_query: Query<Entity, With<InstalledPart>>,
+    level_query: Query<Entity, With<Level>>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    active_case: Res<ActiveCase>,
+    manifests: Res<Assets<CaseManifest>>,
+    asset_server: Res<AssetServer>,
+    mut part_models: ResMut<PartModels>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let (Ok(level), Some(catalog)) = (level_query.single(), catalogs.get(&level_assets.catalog))
+    else {
+        return;
+    };
+
+    let case = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0));
+
+    for installed in &installed_query {
+        commands.entity(installed).despawn();
+    }
+    let mut taken = Vec::new();
+    for id in &install.parts {
+        match catalog.get(id) {
+            Some(spec) => {
+                let mount = mount_point(spec, case, &taken);
+                taken.push(mount);
+                spawn_part(
+                    &mut commands,
+                    level,
+                    spec,
+                    mount,
+                    case.map_or(Quat::IDENTITY, CaseSpec::part_rotation),
+                    install.planned.contains(id),
+                    &asset_server,
+                    &mut part_models,
+                    &mut meshes,
+                    &mut materials,
+                );
+            }
+            None => warn!("Skipping part \"{id}\", which is not in the catalog"),
+        }
+    }
+}
+
+/// Mounts the installed parts again, as the active case or its anchors changed.
+pub fn remount_parts(mut commands: Commands, part_query: Query<(&InstalledPart, Has<Planned>)>) {
+    if part_query.is_empty() {
+        return;
+    }
+    let parts = part_query.iter().map(|(part, _)| part.id.clone()).collect();
+    let planned = part_query
+        .iter()
+        .filter(|(_, planned)| *planned)
+        .map(|(part, _)| part.id.clone())
+        .collect();
+    commands.trigger(InstallParts { parts, planned });
+}
+
+/// Where `spec` mounts in `case`: at the first of the case's anchors for its kind that no part
+/// in `taken` is mounted at, or else at its catalog position, laid out in the case.
+pub fn mount_point(spec: &PartSpec, case: Option<&CaseSpec>, taken: &[Vec3]) -> Vec3 {
+    case.into_iter()
+        .flat_map(|case| case.anchors_for(spec.kind))
+        .map(|anchor| Vec3::from(anchor.position))
+        .find(|position| {
+            !taken
+                .iter()
+                .any(|mount| mount.distance(*position) < MOUNT_TOLERANCE)
+        })
+        .unwrap_or_else(|| {
+            let position = Vec3::from(spec.position);
+            case.map_or(position, |case| case.lay_out(position))
+        })
+}
+
+fn spawn_part(
+    commands: &mut Commands,
+    level: Entity,
+    spec: &PartSpec,
+    mount: Vec3,
+    rotation: Quat,
+    planned: bool,
+    asset_server: &AssetServer,
+    part_models: &mut PartModels,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let mut part = commands.spawn((
+        Name::new(spec.name.clone()),
+        InstalledPart {
+            id: spec.id.clone(),
+            mount,
+        },
+        BillboardLabel {
+            text: spec.name.clone(),
+            group: LABEL_GROUP.to_string(),
+            offset: Vec3::Y * spec.size[1] / 2.0,
+        },
+        Transform::from_translation(mount).with_rotation(rotation),
+        ChildOf(level),
+    ));
+    // Placeholders are always labeled, as there's no model to tell what they are.
+    if spec.placeholder {
+        part.insert(PlaceholderPart { size: spec.size });
+    } else {
+        part.insert(ShownOnHover);
+    }
+    if planned {
+        part.insert(Planned);
+    }
+    if let Some(zone) = &spec.rgb_zone {
+        part.insert(RgbZone(zone.clone()));
+    }
+    // A support's post is a child of its own, stretched up from its foot to the graphics card.
+    let mut part = if spec.kind == PartKind::GpuSupport {
+        let foot = part.id();
+        commands.spawn((GpuSupportPost, Transform::default(), ChildOf(foot)))
+    } else {
+        part
+    };
+    insert_part_model(
+        &mut part,
+        spec,
+        asset_server,
+        part_models,
+        meshes,
+        materials,
+    );
+}
+
+/// Shows `part` as the model of `spec`, or a box of its size if it has none.
+fn insert_part_model(
+    part: &mut EntityCommands,
+    spec: &PartSpec,
+    asset_server: &AssetServer,
+    part_models: &mut PartModels,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    match &spec.model {
+        Some(model) => {
+            // A child of its own, to be turned and scaled if the model needs normalizing.
+            part.with_child(SceneRoot(part_models.load(asset_server, model)));
+        }
+        None if spec.placeholder => {
+            let color = spec.kind.placeholder_color();
+            part.insert((
+                Mesh3d(meshes.add(Cuboid::from_size(Vec3::from(spec.size)))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: color.with_alpha(PLACEHOLDER_ALPHA),
+                    emissive: color.to_linear() * 0.5,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                })),
+            ));
+        }
+        None if let Some(block) = &spec.water_block => {
+            // The backplate on the side towards the motherboard, and the block over the rest.
+            let size = Vec3::from(spec.size);
+            let backplate = Vec3::new(block.backplate, size.y, size.z);
+            let block = size.with_x(size.x - block.backplate);
+            let metal = |color: Color| StandardMaterial {
+                base_color: color,
+                metallic: 0.8,
+                perceptual_roughness: 0.3,
+                ..default()
+            };
+            part.with_children(|card| {
+                card.spawn((
+                    Mesh3d(meshes.add(Cuboid::from_size(backplate))),
+                    MeshMaterial3d(materials.add(metal(BACKPLATE_COLOR))),
+                    Transform::from_xyz((backplate.x - size.x) / 2.0, 0.0, 0.0),
+                ));
+                card.spawn((
+                    Mesh3d(meshes.add(Cuboid::from_size(block))),
+                    MeshMaterial3d(materials.add(metal(BLOCK_COLOR))),
+                    Transform::from_xyz(backplate.x / 2.0, 0.0, 0.0),
+                ));
+            });
+        }
+        None => {
+            part.insert((
+                Mesh3d(meshes.add(Cuboid::from_size(Vec3::from(spec.size)))),
+                MeshMaterial3d(materials.add(spec.kind.placeholder_color())),
+            ));
+        }
+    }
+}
+
+/// Previews swapping in the graphics card or cooler whose row is hovered, or whose button is
+/// focused, if it isn't installed.
+fn preview_hovered_swap(
+    mut commands: Commands,
+    focus: Res<InputFocus>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    active_case: Res<ActiveCase>,
+    manifests: Res<Assets<CaseManifest>>,
+    asset_server: Res<AssetServer>,
+    mut part_models: ResMut<PartModels>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    row_query: Query<(&PartRow, &RelativeCursorPosition)>,
+    button_query: Query<&PartButton>,
+    installed_query: Query<(Entity, &InstalledPart)>,
+    preview_query: Query<(Entity, &SwapPreview)>,
+    level_query: Query<Entity, With<Level>>,
+) {
+    let (Ok(level), Some(catalog)) = (level_query.single(), catalogs.get(&level_assets.catalog))
+    else {
+        return;
+    };
+    let hovered = row_query
+        .iter()
+        .find(|(_, cursor)| cursor.cursor_over)
+        .map(|(row, _)| row.id.as_str())
+        .or_else(|| {
+            let button = button_query.get(focus.get()?).ok()?;
+            Some(button.id.as_str())
+        });
+    let spec = hovered.and_then(|id| catalog.get(id)).filter(|spec| {
+        SWAP_PREVIEW_KINDS.contains(&spec.kind)
+            && !installed_query.iter().any(|(_, part)| part.id == spec.id)
+    });
+    if let Ok((_, preview)) = preview_query.single()
+        && spec.is_some_and(|spec| spec.id == preview.id)
+    {
+        return;
+    }
+
+    for (entity, preview) in &preview_query {
+        commands.entity(entity).despawn();
+        if let Some(replaced) = preview.replaces {
+            commands.entity(replaced).try_insert(Visibility::Inherited);
+        }
+    }
+    let Some(spec) = spec else {
+        return;
+    };
+    let case = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0));
+    let replaces = installed_query
+        .iter()
+        .find(|(_, part)| {
+            catalog
+                .get(&part.id)
+                .is_some_and(|other| other.kind == spec.kind)
+        })
+        .map(|(entity, _)| entity);
+    // Where it would mount once swapped in, with the anchor of the part it replaces free.
+    let taken: Vec<_> = installed_query
+        .iter()
+        .filter(|(entity, _)| Some(*entity) != replaces)
+        .map(|(_, part)| part.mount)
+        .collect();
+    let mount = mount_point(spec, case, &taken);
+    let mut preview = commands.spawn((
+        Name::new(format!("{} Preview", spec.name)),
+        SwapPreview {
+            id: spec.id.clone(),
+            replaces,
+        },
+        Transform::from_translation(mount)
+            .with_rotation(case.map_or(Quat::IDENTITY, CaseSpec::part_rotation)),
+        Pickable::IGNORE,
+        ChildOf(level),
+    ));
+    insert_part_model(
+        &mut preview,
+        spec,
+        &asset_server,
+        &mut part_models,
+        &mut meshes,
+        &mut materials,
+    );
+    if let Some(replaced) = replaces {
+        commands.entity(replaced).insert(Visibility::Hidden);
+    }
+}
+
+/// Outlines the installed part a swap preview stands in for.
+fn draw_swap_outline(
+    mut gizmos: Gizmos,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    preview_query: Query<&SwapPreview>,
+    part_query: Query<(&InstalledPart, &GlobalTransform)>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    for preview in &preview_query {
+        let Some((spec, transform)) = preview
+            .replaces
+            .and_then(|replaced| part_query.get(replaced).ok())
+            .and_then(|(part, transform)| Some((catalog.get(&part.id)?, transform)))
+        else {
+            continue;
+        };
+        gizmos.cube(
+            transform.mul_transform(Transform::from_scale(Vec3::from(spec.size))),
+            SWAP_OUTLINE_COLOR,
+        );
+    }
+}
+
+/// Swaps the materials of planned parts and swap previews for see-through copies, and back
+/// once the parts are owned.
+///
+/// Runs every frame, since a part's model may finish spawning well after it was installed.
+fn ghost_planned_parts(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    part_query: Query<
+        (Entity, Has<Planned>, Has<SwapPreview>),
+        Or<(With<InstalledPart>, With<SwapPreview>)>,
+    >,
+    children_query: Query<&Children>,
+    mut mesh_query: Query<(&mut MeshMaterial3d<StandardMaterial>, Option<&Ghosted>)>,
+) {
+    for (part, planned, preview) in &part_query {
+        let ghost = planned || preview;
+        for entity in std::iter::once(part).chain(children_query.iter_descendants(part)) {
+            let Ok((mut material, ghosted)) = mesh_query.get_mut(entity) else {
+                continue;
+            };
+            match (ghost, ghosted) {
+                (true, None) => {
+                    let Some(mut ghost) = materials.get(&material.0).cloned() else {
+                        continue;
+                    };
+                    ghost
+                        .base_color
+                        .set_alpha(ghost.base_color.alpha() * GHOST_ALPHA);
+                    ghost.alpha_mode = AlphaMode::Blend;
+                    commands.entity(entity).insert(Ghosted {
+                        original: material.0.clone(),
+                    });
+                    material.0 = materials.add(ghost);
+                }
+                (false, Some(ghosted)) => {
+                    material.0 = ghosted.original.clone();
+                    commands.entity(entity).remove::<Ghosted>();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expansion_slots() {
+        let card = |size: &str, extra: &str| -> PartSpec {
+            ron::de::from_str(&format!(
+                "(id: \"gpu\", name: \"GPU\", kind: Gpu, size: {size}, \
+                 position: (0.0, 0.0, 0.0), {extra})"
+            ))
+            .unwrap()
+        };
+        assert_eq!(card("(40.0, 120.0, 300.0)", "").expansion_slots(), Some(2));
+        assert_eq!(card("(60.0, 135.0, 340.0)", "").expansion_slots(), Some(3));
+        // The block takes one slot, the backplate none.
+        let blocked = card(
+            "(28.0, 120.0, 280.0)",
+            "water_block: Some((backplate: 8.0))",
+        );
+        assert_eq!(blocked.expansion_slots(), Some(1));
+        let mut fan = card("(25.0, 120.0, 120.0)", "");
+        fan.kind = PartKind::Fan;
+        assert_eq!(fan.expansion_slots(), None);
+    }
+
+    #[test]
+    fn mounts_at_free_anchors_then_the_catalog_position() {
+        let fan: PartSpec = ron::de::from_str(
+            "(id: \"fan\", name: \"Fan\", kind: Fan, size: (120.0, 120.0, 25.0), \
+             position: (0.0, 100.0, 200.0))",
+        )
+        .unwrap();
+        let case: CaseSpec = ron::de::from_str(
+            "(name: \"Case\", model: \"case.glb\", form_factor: \"ATX\", \
+             dimensions: (200.0, 400.0, 400.0), anchors: [\
+                (name: \"Rear fan\", kind: Fan, position: (0.0, 300.0, -180.0)), \
+                (name: \"GPU\", kind: Gpu, position: (0.0, 150.0, 0.0)), \
+                (name: \"Front fan\", kind: Fan, position: (0.0, 150.0, 180.0))])",
+        )
+        .unwrap();
+        let rear = Vec3::new(0.0, 300.0, -180.0);
+        let front = Vec3::new(0.0, 150.0, 180.0);
+        let catalog = Vec3::new(0.0, 100.0, 200.0);
+
+        assert_eq!(mount_point(&fan, Some(&case), &[]), rear);
+        // Parts within the tolerance of an anchor take it up.
+        assert_eq!(mount_point(&fan, Some(&case), &[rear + 0.5]), front);
+        assert_eq!(mount_point(&fan, Some(&case), &[rear, front]), catalog);
+        assert_eq!(mount_point(&fan, None, &[]), catalog);
+
+        let inverted = CaseSpec {
+            inverted: true,
+            ..case
+        };
+        assert_eq!(
+            mount_point(&fan, Some(&inverted), &[]),
+            Vec3::new(0.0, 100.0, -180.0)
+        );
+        assert_eq!(
+            mount_point(
+                &fan,
+                Some(&inverted),
+                &[rear, front].map(|anchor| inverted.lay_out(anchor))
+            ),
+            Vec3::new(0.0, 300.0, 200.0)
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! A view comparing what the catalog's CPUs and graphics cards cost with how fast they are, to
+//! sanity-check a build's balance before ordering.
+//!
+//! CPUs and graphics cards carry a `performance` score in the catalog, relative to a mainstream
+//! part at 100. The view, opened from the command palette, rates the installed pair as a tier,
+//! says whether one holds the other back, and lists every CPU and graphics card with its score
+//! for the money. Installed parts are highlighted.
+
+use bevy::prelude::*;
+
+use crate::{
+    LevelAssets, Screen,
+    command_palette::AddPaletteCommand,
+    parts::{InstalledPart, PartCatalog, PartKind, PartSpec},
+    settings::Settings,
+    ui_navigation::{FONT_SIZE, OVERLAY_BACKGROUND},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PerformanceView>();
+    app.add_systems(OnEnter(Screen::Game), spawn_performance_panel);
+    app.add_systems(
+        Update,
+        update_performance_panel
+            .run_if(
+                resource_changed::<PerformanceView>
+                    .or(resource_changed::<Settings>)
+                    .or(any_match_filter::<Added<InstalledPart>>)
+                    .or(any_component_removed::<InstalledPart>)
+                    .or(any_match_filter::<Added<PerformanceTable>>),
+            )
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command(
+        "Compare cost and performance",
+        |mut view: ResMut<PerformanceView>| view.shown = !view.shown,
+    );
+}
+
+#[derive(Resource, Debug, Default)]
+struct PerformanceView {
+    shown: bool,
+}
+
+#[derive(Component)]
+struct PerformanceTable;
+
+/// How a build performs overall, from its [`build_score`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Tier {
+    Entry,
+    Mainstream,
+    HighEnd,
+    Enthusiast,
+}
+
+impl Tier {
+    pub fn from_score(score: f32) -> Self {
+        match score {
+            score if score < 80.0 => Self::Entry,
+            score if score < 140.0 => Self::Mainstream,
+            score if score < 220.0 => Self::HighEnd,
+            _ => Self::Enthusiast,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Entry => "Entry",
+            Self::Mainstream => "Mainstream",
+            Self::HighEnd => "High-end",
+            Self::Enthusiast => "Enthusiast",
+        }
+    }
+}
+
+/// How much the graphics card counts towards a build's score, which is mostly about games.
+const GPU_WEIGHT: f32 = 0.7;
+/// How many times faster than the CPU the graphics card may score before the CPU holds it
+/// back, and the other way around.
+const BALANCE_RATIO: f32 = 2.0;
+const INSTALLED_COLOR: Color = Color::srgb(0.5, 0.9, 1.0);
+const TEXT_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const NAME_WIDTH: f32 = 200.0;
+const COLUMN_WIDTH: f32 = 90.0;
+
+/// The score of a build with a CPU and a graphics card of these scores.
+pub fn build_score(cpu: f32, gpu: f32) -> f32 {
+    cpu * (1.0 - GPU_WEIGHT) + gpu * GPU_WEIGHT
+}
+
+/// Which of the CPU and graphics card holds the other back, if either does.
+pub fn imbalance(cpu: f32, gpu: f32) -> Option<&'static str> {
+    if gpu > cpu * BALANCE_RATIO {
+        Some("The CPU may hold the graphics card back")
+    } else if cpu > gpu * BALANCE_RATIO {
+        Some("The graphics card is the weak half of the build")
+    } else {
+        None
+    }
+}
+
+fn spawn_performance_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Performance Panel"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(40.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            PerformanceTable,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(2.0),
+                padding: UiRect::all(px(6.0)),
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(OVERLAY_BACKGROUND),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn update_performance_panel(
+    mut commands: Commands,
+    view: Res<PerformanceView>,
+    settings: Res<Settings>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    installed_query: Query<&InstalledPart>,
+    mut table_query: Query<(Entity, &mut Node), With<PerformanceTable>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let installed = |spec: &PartSpec| installed_query.iter().any(|part| part.id == spec.id);
+    let installed_score = |kind| {
+        catalog
+            .parts
+            .iter()
+            .filter(|spec| spec.kind == kind && installed(spec))
+            .find_map(|spec| spec.performance)
+    };
+    let mut summary = Vec::new();
+    match installed_score(PartKind::Cpu).zip(installed_score(PartKind::Gpu)) {
+        Some((cpu, gpu)) => {
+            let score = build_score(cpu, gpu);
+            summary.push(format!(
+                "{} build, scoring {score:.0}",
+                Tier::from_score(score).name()
+            ));
+            summary.extend(imbalance(cpu, gpu).map(str::to_string));
+        }
+        None => summary.push("Install a CPU and a graphics card to rate the build".to_string()),
+    }
+
+    for (table, mut node) in &mut table_query {
+        node.display = if view.shown {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        commands
+            .entity(table)
+            .despawn_children()
+            .with_children(|table| {
+                table.spawn((Text::new("Cost and performance"), TextColor(Color::WHITE)));
+                for line in &summary {
+                    table.spawn((
+                        Text::new(line.clone()),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                }
+                let header = [
+                    "Part".to_string(),
+                    "Score".to_string(),
+                    "Price".to_string(),
+                    format!("Per {}", settings.format_price(100.0)),
+                ];
+                spawn_row(table, header, TEXT_COLOR);
+                for spec in catalog
+                    .parts
+                    .iter()
+                    .filter(|spec| matches!(spec.kind, PartKind::Cpu | PartKind::Gpu))
+                {
+                    let Some(score) = spec.performance else {
+                        continue;
+                    };
+                    let per_hundred = if spec.price > 0.0 {
+                        format!("{:.0}", score / spec.price * 100.0)
+                    } else {
+                        "-".to_string()
+                    };
+                    let cells = [
+                        spec.name.clone(),
+                        format!("{score:.0}"),
+                        settings.format_price(spec.price),
+                        per_hundred,
+                    ];
+                    let color = if installed(spec) {
+                        INSTALLED_COLOR
+                    } else {
+                        TEXT_COLOR
+                    };
+                    spawn_row(table, cells, color);
+                }
+            });
+    }
+}
+
+fn spawn_row(table: &mut ChildSpawnerCommands, cells: [String; 4], color: Color) {
+    table
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            Pickable::IGNORE,
+        ))
+        .with_children(|row| {
+            for (column, cell) in cells.into_iter().enumerate() {
+                let width = if column == 0 {
+                    NAME_WIDTH
+                } else {
+                    COLUMN_WIDTH
+                };
+                row.spawn((
+                    Text::new(cell),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(color),
+                    Node {
+                        width: px(width),
+                        ..default()
+                    },
+                    Pickable::IGNORE,
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers() {
+        assert_eq!(
+            Tier::from_score(build_score(100.0, 100.0)),
+            Tier::Mainstream
+        );
+        assert_eq!(Tier::from_score(build_score(160.0, 210.0)), Tier::HighEnd);
+        assert_eq!(Tier::from_score(50.0), Tier::Entry);
+    }
+
+    #[test]
+    fn balance() {
+        assert_eq!(imbalance(100.0, 150.0), None);
+        assert!(imbalance(100.0, 210.0).unwrap().contains("CPU"));
+        assert!(imbalance(210.0, 100.0).unwrap().contains("graphics card"));
+    }
+}
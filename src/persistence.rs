@@ -0,0 +1,496 @@
+//! Saving the current build to a RON file and loading it back.
+//!
+//! The saved build is loaded from the main menu, and can be saved or loaded again from the
+//! build panel, which also holds the tabs of the [`workspace`]. Native builds write `build.ron`
+//! next to the app, the web build keeps the file in `localStorage`. The build keeps the notes
+//! on its parts, see [`part_notes`], the colors set on its fans, see [`fan_colors`], the logos
+//! on its fans and pumps, see [`logos`], and how it looks: the settings of its RGB zones, the
+//! x-ray view and the lighting.
+//!
+//! [`workspace`]: crate::workspace
+//! [`part_notes`]: crate::part_notes
+//! [`fan_colors`]: crate::fan_colors
+//! [`logos`]: crate::logos
+
+use std::collections::BTreeMap;
+
+use bevy::{ecs::system::SystemParam, input_focus::tab_navigation::TabGroup, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    cases::{ActiveCase, CaseManifest, SwitchCase},
+    command_palette::AddPaletteCommand,
+    fan_colors::{FanColor, FanColors},
+    init_spawn,
+    lighting::{ActiveLightingPreset, LightingEnvironment},
+    logos::PartLogos,
+    part_notes::{PartNote, PartNotes},
+    parts::{InstallParts, InstalledPart, Planned},
+    report::{BuildReport, toggle_report},
+    rgb::{RgbSettings, ZoneSettings},
+    storage,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+    workspace::WorkspaceTabs,
+    xray::{XRay, set_xray},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(Screen::Game),
+        (
+            spawn_build_panel,
+            (load_saved_build, forget_build_request)
+                .chain()
+                .after(init_spawn)
+                .run_if(resource_exists::<LoadSavedBuild>),
+        ),
+    );
+    app.add_observer(activate_build_button);
+    app.add_palette_command("Save build", save_current_build);
+    app.add_palette_command("Load saved build", load_saved_build);
+}
+
+/// Loads the saved build once the game screen is entered.
+#[derive(Resource, Debug)]
+pub struct LoadSavedBuild;
+
+/// Bumped whenever [`SavedBuild`] changes in a way older files can't be read as. Version 2
+/// added notes to the parts, version 3 the look of the build.
+const BUILD_VERSION: u32 = 3;
+/// Where the build is saved, see [`storage`].
+pub const BUILD_KEY: &str = "build";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedBuild {
+    pub version: u32,
+    /// The case model the build was made in.
+    pub case_model: String,
+    /// The installed parts.
+    pub parts: Vec<SavedPart>,
+    /// The ids in `parts` that are planned rather than owned.
+    #[serde(default)]
+    pub planned: Vec<String>,
+    pub camera: SavedCamera,
+    /// How the build looks. Builds saved before version 3 leave the look as it is.
+    #[serde(default)]
+    pub look: Option<SavedLook>,
+}
+
+impl SavedBuild {
+    /// The catalog ids of the installed parts.
+    pub fn part_ids(&self) -> Vec<String> {
+        self.parts.iter().map(|part| part.id.clone()).collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "SavedPartFile")]
+pub struct SavedPart {
+    /// The part's catalog id.
+    pub id: String,
+    #[serde(skip_serializing_if = "PartNote::is_empty")]
+    pub note: PartNote,
+    /// The frame and blade colors set on a fan.
+    #[serde(skip_serializing_if = "FanColor::is_default")]
+    pub fan_color: FanColor,
+    /// The image of the logo on a fan's hub or a liquid cooler's pump cap.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+}
+
+/// How a [`SavedPart`] is read, as version 1 saved only its id.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedPartFile {
+    Id(String),
+    Part {
+        id: String,
+        #[serde(default)]
+        note: PartNote,
+        #[serde(default)]
+        fan_color: FanColor,
+        #[serde(default)]
+        logo: Option<String>,
+    },
+}
+
+impl From<SavedPartFile> for SavedPart {
+    fn from(part: SavedPartFile) -> Self {
+        match part {
+            SavedPartFile::Id(id) => Self {
+                id,
+                note: PartNote::default(),
+                fan_color: FanColor::default(),
+                logo: None,
+            },
+            SavedPartFile::Part {
+                id,
+                note,
+                fan_color,
+                logo,
+            } => Self {
+                id,
+                note,
+                fan_color,
+                logo,
+            },
+        }
+    }
+}
+
+/// The settings of the RGB zones, the x-ray view and the lighting a build is shown with.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SavedLook {
+    /// Zones that aren't listed use [`ZoneSettings::default`].
+    #[serde(default)]
+    pub zones: BTreeMap<String, ZoneSettings>,
+    #[serde(default)]
+    pub xray: bool,
+    #[serde(default)]
+    pub lighting: LightingEnvironment,
+    /// The name of the lighting preset.
+    #[serde(default)]
+    pub lighting_preset: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct SavedCamera {
+    radius: f32,
+    yaw: f32,
+    pitch: f32,
+    target: [f32; 3],
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum BuildButton {
+    Save,
+    Load,
+    Compare,
+}
+
+fn spawn_build_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Build Panel"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: px(5.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Row,
+                column_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(2),
+            children![
+                (
+                    WorkspaceTabs,
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: px(4.0),
+                        ..default()
+                    },
+                ),
+                button("Save build", BuildButton::Save),
+                button("Load build", BuildButton::Load),
+                button("Compare", BuildButton::Compare),
+            ],
+        )],
+    ));
+}
+
+fn button(label: impl Into<String>, action: BuildButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn activate_build_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    button_query: Query<&BuildButton>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(BuildButton::Save) => commands.run_system_cached(save_current_build),
+        Ok(BuildButton::Load) => commands.run_system_cached(load_saved_build),
+        Ok(BuildButton::Compare) => commands.run_system_cached(toggle_report),
+        Err(_) => {}
+    }
+}
+
+fn save_current_build(current: CurrentBuild, mut report: ResMut<BuildReport>) {
+    if let Some(build) = current.get() {
+        save_build(BUILD_KEY, &build);
+        report.saved = Some(build);
+    }
+}
+
+/// What the build in the level is saved from.
+#[derive(SystemParam)]
+pub struct CurrentBuild<'w, 's> {
+    part_query: Query<'w, 's, (&'static InstalledPart, Has<Planned>)>,
+    camera_query: Query<'w, 's, &'static OrbitCamera>,
+    active_case: Res<'w, ActiveCase>,
+    notes: Res<'w, PartNotes>,
+    fan_colors: Res<'w, FanColors>,
+    logos: Res<'w, PartLogos>,
+    rgb: Res<'w, RgbSettings>,
+    xray: Res<'w, XRay>,
+    lighting: Res<'w, LightingEnvironment>,
+    lighting_preset: Res<'w, ActiveLightingPreset>,
+}
+
+impl CurrentBuild<'_, '_> {
+    /// The build in the level, as it would be saved.
+    pub fn get(&self) -> Option<SavedBuild> {
+        let orbit = self.camera_query.single().ok()?;
+        Some(SavedBuild {
+            version: BUILD_VERSION,
+            case_model: self.active_case.0.clone(),
+            parts: self
+                .part_query
+                .iter()
+                .map(|(part, _)| SavedPart {
+                    id: part.id.clone(),
+                    note: self.notes.0.get(&part.id).cloned().unwrap_or_default(),
+                    fan_color: self.fan_colors.0.get(&part.id).copied().unwrap_or_default(),
+                    logo: self.logos.0.get(&part.id).cloned(),
+                })
+                .collect(),
+            planned: self
+                .part_query
+                .iter()
+                .filter(|(_, planned)| *planned)
+                .map(|(part, _)| part.id.clone())
+                .collect(),
+            camera: SavedCamera {
+                radius: orbit.desired_radius,
+                yaw: orbit.yaw,
+                pitch: orbit.pitch,
+                target: orbit.target.to_array(),
+            },
+            look: Some(SavedLook {
+                zones: self
+                    .rgb
+                    .zones
+                    .iter()
+                    .map(|(zone, settings)| (zone.clone(), *settings))
+                    .collect(),
+                xray: self.xray.active,
+                lighting: *self.lighting,
+                lighting_preset: self.lighting_preset.0.clone(),
+            }),
+        })
+    }
+}
+
+fn load_saved_build(
+    mut commands: Commands,
+    mut camera_query: Query<&mut OrbitCamera>,
+    mut report: ResMut<BuildReport>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+) {
+    report.saved = load_build(BUILD_KEY);
+    if let Some(build) = &report.saved {
+        let manifest = manifests.get(&level_assets.cases);
+        apply_build(&mut commands, build, manifest, &mut camera_query);
+    }
+}
+
+fn forget_build_request(mut commands: Commands) {
+    commands.remove_resource::<LoadSavedBuild>();
+}
+
+pub fn apply_build(
+    commands: &mut Commands,
+    build: &SavedBuild,
+    manifest: Option<&CaseManifest>,
+    camera_query: &mut Query<&mut OrbitCamera>,
+) {
+    if manifest.is_some_and(|manifest| manifest.get(&build.case_model).is_some()) {
+        commands.trigger(SwitchCase(build.case_model.clone()));
+    } else {
+        warn!(
+            "The saved build was made in \"{}\", which is not in the case list",
+            build.case_model
+        );
+    }
+
+    commands.trigger(InstallParts {
+        parts: build.part_ids(),
+        planned: build.planned.clone(),
+    });
+    commands.insert_resource(PartNotes(
+        build
+            .parts
+            .iter()
+            .filter(|part| !part.note.is_empty())
+            .map(|part| (part.id.clone(), part.note.clone()))
+            .collect(),
+    ));
+    commands.insert_resource(FanColors(
+        build
+            .parts
+            .iter()
+            .filter(|part| !part.fan_color.is_default())
+            .map(|part| (part.id.clone(), part.fan_color))
+            .collect(),
+    ));
+    commands.insert_resource(PartLogos(
+        build
+            .parts
+            .iter()
+            .filter_map(|part| Some((part.id.clone(), part.logo.clone()?)))
+            .collect(),
+    ));
+    if let Some(look) = &build.look {
+        commands.insert_resource(RgbSettings {
+            zones: look
+                .zones
+                .iter()
+                .map(|(zone, settings)| (zone.clone(), *settings))
+                .collect(),
+        });
+        commands.run_system_cached_with(set_xray, look.xray);
+        commands.insert_resource(look.lighting);
+        commands.insert_resource(ActiveLightingPreset(look.lighting_preset.clone()));
+    }
+    if let Ok(mut orbit) = camera_query.single_mut() {
+        let camera = build.camera;
+        orbit.desired_radius = camera.radius.clamp(orbit.min_radius, orbit.max_radius);
+        orbit.radius = orbit.desired_radius;
+        orbit.yaw = camera.yaw;
+        orbit.pitch = camera.pitch;
+        orbit.target = Vec3::from(camera.target);
+    }
+}
+
+/// Saves `build` under `key`, see [`storage`].
+pub fn save_build(key: &str, build: &SavedBuild) {
+    let contents = match ron::ser::to_string_pretty(build, default()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            error!("Could not serialize the build: {error}");
+            return;
+        }
+    };
+    match storage::write(key, &contents) {
+        Ok(()) => info!("Saved the build as \"{key}\""),
+        Err(error) => error!("Could not save the build as \"{key}\": {error}"),
+    }
+}
+
+/// Reads the build saved under `key`, if there is one that this version of the app understands.
+pub fn load_build(key: &str) -> Option<SavedBuild> {
+    let contents = match storage::read(key) {
+        Ok(contents) => contents?,
+        Err(error) => {
+            error!("Could not read the build saved as \"{key}\": {error}");
+            return None;
+        }
+    };
+    let build = match ron::de::from_str::<SavedBuild>(&contents) {
+        Ok(build) => build,
+        Err(error) => {
+            error!("Could not parse the build saved as \"{key}\": {error}");
+            return None;
+        }
+    };
+    if build.version > BUILD_VERSION {
+        error!(
+            "The build saved as \"{key}\" is from a newer version of the app (version {})",
+            build.version
+        );
+        return None;
+    }
+    Some(build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_version_1_parts() {
+        let build: SavedBuild = ron::from_str(
+            r#"(
+                version: 1,
+                case_model: "mid_tower",
+                parts: ["gpu_dual_slot", "fan_front_120"],
+                camera: (radius: 1.0, yaw: 0.0, pitch: 0.0, target: (0.0, 0.0, 0.0)),
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(build.part_ids(), ["gpu_dual_slot", "fan_front_120"]);
+        assert!(build.parts.iter().all(|part| part.note.is_empty()));
+        assert!(build.parts.iter().all(|part| part.fan_color.is_default()));
+        assert!(build.parts.iter().all(|part| part.logo.is_none()));
+        assert_eq!(build.look, None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let build = SavedBuild {
+            version: BUILD_VERSION,
+            case_model: "mid_tower".to_string(),
+            parts: vec![
+                SavedPart {
+                    id: "gpu_dual_slot".to_string(),
+                    note: crate::part_notes::parse_note("2026-03-14 36 Bought online"),
+                    fan_color: FanColor::default(),
+                    logo: None,
+                },
+                SavedPart {
+                    id: "fan_front_120".to_string(),
+                    note: PartNote::default(),
+                    fan_color: crate::fan_colors::parse_fan_color("- #ff8800").unwrap(),
+                    logo: Some("logos/studio.png".to_string()),
+                },
+            ],
+            planned: Vec::new(),
+            camera: SavedCamera {
+                radius: 1.0,
+                yaw: 0.0,
+                pitch: 0.0,
+                target: [0.0; 3],
+            },
+            look: Some(SavedLook {
+                zones: BTreeMap::from([(
+                    "Front".to_string(),
+                    ZoneSettings {
+                        effect: crate::rgb::RgbEffect::Strobe,
+                        speed: 2.0,
+                        color: 3,
+                    },
+                )]),
+                xray: true,
+                lighting: LightingEnvironment::Dark,
+                lighting_preset: "Showroom".to_string(),
+            }),
+        };
+        let contents = ron::ser::to_string_pretty(&build, default()).unwrap();
+        let read: SavedBuild = ron::from_str(&contents).unwrap();
+        assert_eq!(read.parts, build.parts);
+        assert_eq!(read.look, build.look);
+    }
+}
@@ -0,0 +1,142 @@
+//! Small picture-in-picture viewports with fixed orthographic top and front views, so the
+//! case's orientation stays clear while orbiting the main camera.
+
+use bevy::{
+    camera::{ScalingMode, Viewport},
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    OrbitCamera, Screen,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Game), spawn_pip_cameras);
+    app.add_systems(
+        Update,
+        (
+            toggle_pip_views.run_if(key_just_pressed(KeyAction::SwitchViews)),
+            layout_pip_viewports,
+            follow_orbit_target,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Toggle top and front views", toggle_pip_views);
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum PipView {
+    Top,
+    Front,
+}
+
+impl PipView {
+    /// Position of this view's slot, counted from the bottom-right corner of the window.
+    fn slot(self) -> u32 {
+        match self {
+            Self::Front => 0,
+            Self::Top => 1,
+        }
+    }
+
+    /// Where the camera sits relative to the orbit target, and which way is up on screen.
+    fn offset_and_up(self) -> (Vec3, Vec3) {
+        match self {
+            Self::Top => (Vec3::Y * PIP_CAMERA_DISTANCE, Vec3::NEG_Z),
+            Self::Front => (Vec3::Z * PIP_CAMERA_DISTANCE, Vec3::Y),
+        }
+    }
+}
+
+/// Far enough out to stay clear of the case; an orthographic view doesn't shrink with distance.
+const PIP_CAMERA_DISTANCE: f32 = 2000.0;
+/// How much of the scene, in world units, fits vertically into a viewport.
+const PIP_VIEW_HEIGHT: f32 = 800.0;
+/// Gap between the viewports and the window edge, in physical pixels.
+const PIP_MARGIN: u32 = 10;
+const PIP_BACKGROUND: Color = Color::srgb(0.1, 0.1, 0.1);
+
+fn spawn_pip_cameras(mut commands: Commands) {
+    for (view, order) in [(PipView::Top, 1), (PipView::Front, 2)] {
+        commands.spawn((
+            Name::new(format!("{view:?} View Camera")),
+            view,
+            DespawnOnExit(Screen::Game),
+            Camera3d::default(),
+            Camera {
+                order,
+                clear_color: ClearColorConfig::Custom(PIP_BACKGROUND),
+                ..default()
+            },
+            Projection::from(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: PIP_VIEW_HEIGHT,
+                },
+                far: PIP_CAMERA_DISTANCE * 2.0,
+                ..OrthographicProjection::default_3d()
+            }),
+            Transform::default(),
+        ));
+    }
+}
+
+fn toggle_pip_views(mut camera_query: Query<&mut Camera, With<PipView>>) {
+    for mut camera in &mut camera_query {
+        camera.is_active = !camera.is_active;
+    }
+}
+
+/// Keeps the viewports square and docked to the bottom-right corner as the window resizes.
+fn layout_pip_viewports(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&PipView, &mut Camera)>,
+) {
+    let window_size = window.physical_size();
+    let side = window_size.x.min(window_size.y) / 4;
+    if side == 0 {
+        // A minimized window has no room for the viewports; keep the last layout.
+        return;
+    }
+
+    for (view, mut camera) in &mut camera_query {
+        let physical_size = UVec2::splat(side);
+        let physical_position = UVec2::new(
+            window_size
+                .x
+                .saturating_sub((view.slot() + 1) * (side + PIP_MARGIN)),
+            window_size.y.saturating_sub(side + PIP_MARGIN),
+        );
+
+        let unchanged = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_size == physical_size
+                && viewport.physical_position == physical_position
+        });
+        if !unchanged {
+            camera.viewport = Some(Viewport {
+                physical_position,
+                physical_size,
+                ..default()
+            });
+        }
+    }
+}
+
+fn follow_orbit_target(
+    orbit_query: Query<&OrbitCamera>,
+    mut camera_query: Query<(&PipView, &mut Transform)>,
+) {
+    let Ok(orbit) = orbit_query.single() else {
+        return;
+    };
+
+    for (view, mut transform) in &mut camera_query {
+        let (offset, up) = view.offset_and_up();
+        let target_transform =
+            Transform::from_translation(orbit.target + offset).looking_at(orbit.target, up);
+        transform.set_if_neq(target_transform);
+    }
+}
@@ -0,0 +1,256 @@
+//! Compiles the render pipelines of the scene's materials while the loading screen is still up,
+//! so the level doesn't hitch the first time see-through, emissive or glass materials show.
+//!
+//! Once the assets have loaded, the case and a few stand-ins for the parts are rendered offscreen
+//! with the same post-processing as the level: once as they are, and once in the see-through
+//! materials that planned parts and the X-ray view put on. The level is entered once no
+//! pipelines have been waiting to compile for a few frames. Pipelines stay compiled for the rest
+//! of the session, so this only happens on the first load.
+
+use bevy::{
+    camera::RenderTarget,
+    prelude::*,
+    render::{
+        MainWorld, RenderApp,
+        render_resource::{PipelineCache, TextureFormat},
+    },
+    scene::SceneInstanceReady,
+};
+
+use crate::{LevelAssets, Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PipelineWarmup>();
+    app.add_systems(
+        Update,
+        (
+            spawn_warmup_scene.run_if(resource_exists::<LevelAssets>.and(warmup_not_started)),
+            wait_for_pipelines,
+        )
+            .chain()
+            .run_if(in_state(Screen::Loading)),
+    );
+    app.add_observer(make_see_through_copy);
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app.add_systems(ExtractSchedule, count_waiting_pipelines);
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct PipelineWarmup {
+    state: WarmupState,
+    /// How many pipelines the renderer has yet to compile, as of the last frame.
+    waiting: usize,
+}
+
+impl PipelineWarmup {
+    /// Whether the pipelines are compiled, or the warmup gave up on them.
+    pub fn is_done(&self) -> bool {
+        self.state == WarmupState::Done
+    }
+
+    /// How many pipelines are still compiling while the warmup runs.
+    pub fn waiting(&self) -> usize {
+        self.waiting
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum WarmupState {
+    #[default]
+    NotStarted,
+    Running {
+        /// Scenes that haven't spawned yet.
+        scenes_left: usize,
+        /// Frames in a row without a pipeline waiting, once the scenes have spawned.
+        settled_frames: u32,
+        elapsed: f32,
+    },
+    Done,
+}
+
+/// A scene rendered for the warmup. See-through ones have their materials swapped for
+/// transparent copies once they've spawned.
+#[derive(Component, Debug, Clone, Copy)]
+struct WarmupScene {
+    see_through: bool,
+}
+
+/// Frames without a pipeline waiting before the warmup counts as done.
+const SETTLED_FRAMES: u32 = 5;
+/// The longest to keep the loading screen up for the warmup, in case pipelines keep coming.
+const MAX_DURATION: f32 = 20.0;
+/// Side of the offscreen image, in pixels; the pipelines don't depend on its size.
+const TARGET_SIZE: u32 = 256;
+const SEE_THROUGH_ALPHA: f32 = 0.3;
+
+/// A run condition for entering the level once the warmup is done.
+pub fn pipelines_warmed_up(warmup: Res<PipelineWarmup>) -> bool {
+    warmup.is_done()
+}
+
+fn warmup_not_started(warmup: Res<PipelineWarmup>) -> bool {
+    warmup.state == WarmupState::NotStarted
+}
+
+fn spawn_warmup_scene(
+    mut commands: Commands,
+    mut warmup: ResMut<PipelineWarmup>,
+    level_assets: Res<LevelAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let target = images.add(Image::new_target_texture(
+        TARGET_SIZE,
+        TARGET_SIZE,
+        TextureFormat::Rgba8UnormSrgb,
+        None,
+    ));
+    // Far enough out for everything to be in view, since only visible meshes get pipelines.
+    commands.spawn((
+        Name::new("Warmup Camera"),
+        DespawnOnExit(Screen::Loading),
+        Camera3d::default(),
+        Camera {
+            order: -10,
+            ..default()
+        },
+        RenderTarget::Image(target.into()),
+        Transform::from_xyz(0.0, 400.0, 1500.0).looking_at(Vec3::Y * 200.0, Vec3::Y),
+    ));
+    // Shadows have pipelines of their own.
+    commands.spawn((
+        Name::new("Warmup Light"),
+        DespawnOnExit(Screen::Loading),
+        SpotLight {
+            intensity: 1_000_000.0,
+            range: 5000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(300.0, 900.0, 600.0).looking_at(Vec3::Y * 200.0, Vec3::Y),
+    ));
+
+    for see_through in [false, true] {
+        commands.spawn((
+            Name::new("Warmup Case"),
+            WarmupScene { see_through },
+            DespawnOnExit(Screen::Loading),
+            SceneRoot(level_assets.pc_case.clone()),
+        ));
+    }
+
+    // Stand-ins for the parts shown as boxes: as they are, glowing like RGB lighting and
+    // see-through like planned parts.
+    let cuboid = meshes.add(Cuboid::from_size(Vec3::splat(100.0)));
+    let stand_ins = [
+        StandardMaterial::default(),
+        StandardMaterial {
+            emissive: LinearRgba::rgb(4.0, 1.0, 2.0),
+            ..default()
+        },
+        StandardMaterial {
+            base_color: Color::WHITE.with_alpha(SEE_THROUGH_ALPHA),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        },
+    ];
+    for (index, material) in stand_ins.into_iter().enumerate() {
+        commands.spawn((
+            Name::new("Warmup Part"),
+            DespawnOnExit(Screen::Loading),
+            Mesh3d(cuboid.clone()),
+            MeshMaterial3d(materials.add(material)),
+            Transform::from_xyz(index as f32 * 150.0 - 150.0, 50.0, 400.0),
+        ));
+    }
+
+    warmup.state = WarmupState::Running {
+        scenes_left: 2,
+        settled_frames: 0,
+        elapsed: 0.0,
+    };
+}
+
+/// Swaps the materials of a see-through scene for transparent copies, like the ones planned
+/// parts and the X-ray view use.
+fn make_see_through_copy(
+    ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    mut warmup: ResMut<PipelineWarmup>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    scene_query: Query<&WarmupScene>,
+    children_query: Query<&Children>,
+    material_query: Query<&MeshMaterial3d<StandardMaterial>>,
+) {
+    let Ok(scene) = scene_query.get(ready.entity) else {
+        return;
+    };
+    if let WarmupState::Running { scenes_left, .. } = &mut warmup.state {
+        *scenes_left = scenes_left.saturating_sub(1);
+    }
+    if !scene.see_through {
+        return;
+    }
+
+    for entity in children_query.iter_descendants(ready.entity) {
+        let Some(material) = material_query
+            .get(entity)
+            .ok()
+            .and_then(|material| materials.get(material))
+        else {
+            continue;
+        };
+        let mut see_through = material.clone();
+        see_through.base_color = see_through.base_color.with_alpha(SEE_THROUGH_ALPHA);
+        see_through.alpha_mode = AlphaMode::Blend;
+        commands
+            .entity(entity)
+            .insert(MeshMaterial3d(materials.add(see_through)));
+    }
+}
+
+fn wait_for_pipelines(mut warmup: ResMut<PipelineWarmup>, time: Res<Time<Real>>) {
+    let WarmupState::Running {
+        scenes_left,
+        mut settled_frames,
+        mut elapsed,
+    } = warmup.state
+    else {
+        return;
+    };
+    elapsed += time.delta_secs();
+    if scenes_left == 0 && warmup.waiting == 0 {
+        settled_frames += 1;
+    } else {
+        settled_frames = 0;
+    }
+
+    warmup.state = if settled_frames >= SETTLED_FRAMES {
+        info!("Compiled the render pipelines in {elapsed:.1} s");
+        WarmupState::Done
+    } else if elapsed >= MAX_DURATION {
+        warn!(
+            "Gave up waiting for {} render pipelines to compile",
+            warmup.waiting
+        );
+        WarmupState::Done
+    } else {
+        WarmupState::Running {
+            scenes_left,
+            settled_frames,
+            elapsed,
+        }
+    };
+}
+
+/// Hands the number of pipelines waiting to compile over from the render world.
+fn count_waiting_pipelines(pipeline_cache: Res<PipelineCache>, mut main_world: ResMut<MainWorld>) {
+    let waiting = pipeline_cache.waiting_pipelines().count();
+    if let Some(mut warmup) = main_world.get_resource_mut::<PipelineWarmup>()
+        && warmup.waiting != waiting
+    {
+        warmup.waiting = waiting;
+    }
+}
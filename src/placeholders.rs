@@ -0,0 +1,289 @@
+//! Placeholder parts for hardware the catalog doesn't have, made from dimensions the user
+//! enters, like a 322 x 137 x 61 mm graphics card.
+//!
+//! A placeholder is added to the catalog as a part without a model, so it's installed, mounted
+//! and checked for clearance and collisions like any other. It's drawn as a see-through box
+//! labeled with its name and size. Placeholders are kept in `placeholders.ron`, so builds that
+//! use them load again next time.
+
+use bevy::{ecs::system::SystemId, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    LevelAssets, Screen,
+    command_palette::{AddPaletteCommand, OpenPrompt, PaletteCommands},
+    labels::BillboardLabel,
+    parts::{InstalledPart, PartCatalog, PartKind, PartSpec, TogglePart},
+    settings::Settings,
+    storage,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let add_placeholder = app.register_system(add_placeholder);
+    app.insert_resource(Placeholders {
+        parts: load_placeholders(),
+        add_placeholder,
+    });
+    app.add_systems(
+        Update,
+        (
+            add_placeholders_to_catalog.run_if(
+                resource_exists::<LevelAssets>
+                    .and(resource_added::<LevelAssets>.or(on_message::<AssetEvent<PartCatalog>>)),
+            ),
+            update_placeholder_labels
+                .run_if(resource_changed::<Settings>.or(any_match_filter::<Added<PlaceholderPart>>))
+                .run_if(in_state(Screen::Game)),
+        ),
+    );
+    app.add_palette_command("Add a placeholder part", prompt_for_placeholder);
+}
+
+/// Where the placeholders are kept, see [`storage`].
+const PLACEHOLDERS_KEY: &str = "placeholders";
+/// The prefix of placeholder ids, which keeps them apart from the catalog's own.
+const ID_PREFIX: &str = "placeholder_";
+/// Opacity of placeholder boxes, so what's behind them stays in view.
+pub const PLACEHOLDER_ALPHA: f32 = 0.6;
+
+#[derive(Resource, Debug)]
+struct Placeholders {
+    parts: Vec<Placeholder>,
+    /// Makes a placeholder from the text it's given.
+    add_placeholder: SystemId<In<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    pub id: String,
+    pub name: String,
+    pub kind: PartKind,
+    /// In millimeters.
+    pub size: [f32; 3],
+}
+
+impl Placeholder {
+    /// The catalog part for the placeholder, mounted at `position` where the case has no anchor
+    /// for it.
+    fn spec(&self, position: [f32; 3]) -> PartSpec {
+        PartSpec {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            kind: self.kind,
+            model: None,
+            size: self.size,
+            position,
+            rgb_zone: None,
+            price: 0.0,
+            socket: None,
+            chipset: None,
+            chipsets: Vec::new(),
+            bracket_sockets: Vec::new(),
+            fan_headers: 0,
+            pump_headers: 0,
+            argb_headers: 0,
+            io_headers: Vec::new(),
+            m2_slots: 0,
+            armor: Vec::new(),
+            water_block: None,
+            drive_size: None,
+            power_draw: 0.0,
+            wattage: 0.0,
+            performance: None,
+            reach: None,
+            url: None,
+            sku: None,
+            placeholder: true,
+        }
+    }
+}
+
+/// Marks an installed placeholder, whose label shows its size.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PlaceholderPart {
+    pub size: [f32; 3],
+}
+
+/// Reads a placeholder from text like `gpu 322x137x61 RTX 4090`: the kind of part, its width,
+/// height and depth in millimeters, and optionally a name. `id` is used as its id.
+pub fn parse_placeholder(text: &str, id: String) -> Result<Placeholder, String> {
+    let mut words = text.split_whitespace();
+    let kind = words.next().ok_or("Enter a kind of part and its size")?;
+    let kind = PartKind::ALL
+        .into_iter()
+        .find(|candidate| candidate.name().eq_ignore_ascii_case(kind))
+        .ok_or_else(|| format!("\"{kind}\" is not a kind of part"))?;
+    let size = words.next().ok_or("Enter the size, like 322x137x61")?;
+    let size: Vec<f32> = size
+        .split(['x', 'X', '×'])
+        .map(|side| side.parse::<f32>().ok().filter(|side| *side > 0.0))
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("\"{size}\" is not a size like 322x137x61"))?;
+    let [width, height, depth] = size[..] else {
+        return Err(format!("\"{text}\" needs a width, height and depth"));
+    };
+    let name = words.collect::<Vec<_>>().join(" ");
+    let name = if name.is_empty() {
+        format!("Placeholder {}", kind.name())
+    } else {
+        name
+    };
+    Ok(Placeholder {
+        id,
+        name,
+        kind,
+        size: [width, height, depth],
+    })
+}
+
+fn prompt_for_placeholder(mut commands: Commands, placeholders: Res<Placeholders>) {
+    let kinds: Vec<_> = PartKind::ALL.iter().map(|kind| kind.name()).collect();
+    commands.trigger(OpenPrompt {
+        label: format!(
+            "Kind ({}), size in mm and name, like gpu 322x137x61 RTX 4090",
+            kinds.join(", ")
+        ),
+        submit: placeholders.add_placeholder,
+    });
+}
+
+/// Makes a placeholder from `text`, adds it to the catalog and installs it.
+fn add_placeholder(
+    In(text): In<String>,
+    mut commands: Commands,
+    mut placeholders: ResMut<Placeholders>,
+    mut palette_commands: ResMut<PaletteCommands>,
+    level_assets: Res<LevelAssets>,
+    mut catalogs: ResMut<Assets<PartCatalog>>,
+) {
+    let number = (1..)
+        .find(|number| {
+            let id = format!("{ID_PREFIX}{number}");
+            placeholders.parts.iter().all(|part| part.id != id)
+        })
+        .unwrap_or_default();
+    let placeholder = match parse_placeholder(&text, format!("{ID_PREFIX}{number}")) {
+        Ok(placeholder) => placeholder,
+        Err(error) => {
+            warn!("{error}");
+            return;
+        }
+    };
+    let Some(catalog) = catalogs.get_mut(&level_assets.catalog) else {
+        return;
+    };
+    catalog
+        .parts
+        .push(placeholder.spec(position_for(catalog, placeholder.kind)));
+
+    let id = placeholder.id.clone();
+    let system = commands.register_system(move |mut commands: Commands| {
+        commands.trigger(TogglePart(id.clone()));
+    });
+    palette_commands.add(format!("Add or remove the {}", placeholder.name), system);
+    commands.trigger(TogglePart(placeholder.id.clone()));
+
+    placeholders.parts.push(placeholder);
+    save_placeholders(&placeholders.parts);
+}
+
+/// Where a placeholder of `kind` goes in cases without an anchor for it: where the catalog's
+/// first part of that kind goes, or the middle of the case.
+fn position_for(catalog: &PartCatalog, kind: PartKind) -> [f32; 3] {
+    catalog
+        .parts
+        .iter()
+        .find(|spec| spec.kind == kind && !spec.placeholder)
+        .map_or([0.0; 3], |spec| spec.position)
+}
+
+/// Adds the placeholders the catalog doesn't have, as it's just loaded or was reloaded.
+fn add_placeholders_to_catalog(
+    placeholders: Res<Placeholders>,
+    level_assets: Res<LevelAssets>,
+    mut catalogs: ResMut<Assets<PartCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let missing: Vec<_> = placeholders
+        .parts
+        .iter()
+        .filter(|placeholder| catalog.get(&placeholder.id).is_none())
+        .map(|placeholder| placeholder.spec(position_for(catalog, placeholder.kind)))
+        .collect();
+    // Changing the catalog sends the event this runs on.
+    if !missing.is_empty()
+        && let Some(catalog) = catalogs.get_mut(&level_assets.catalog)
+    {
+        catalog.parts.extend(missing);
+    }
+}
+
+fn update_placeholder_labels(
+    settings: Res<Settings>,
+    mut part_query: Query<(&Name, &PlaceholderPart, &mut BillboardLabel), With<InstalledPart>>,
+) {
+    for (name, placeholder, mut label) in &mut part_query {
+        label.text = format!("{name}, {}", settings.format_size(placeholder.size));
+    }
+}
+
+fn load_placeholders() -> Vec<Placeholder> {
+    let contents = match storage::read(PLACEHOLDERS_KEY) {
+        Ok(contents) => contents,
+        Err(error) => {
+            error!("Could not read the placeholder parts: {error}");
+            return Vec::new();
+        }
+    };
+    let Some(contents) = contents else {
+        return Vec::new();
+    };
+    ron::de::from_str(&contents).unwrap_or_else(|error| {
+        error!("Could not parse the placeholder parts: {error}");
+        Vec::new()
+    })
+}
+
+fn save_placeholders(parts: &[Placeholder]) {
+    let contents = match ron::ser::to_string_pretty(parts, default()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            error!("Could not serialize the placeholder parts: {error}");
+            return;
+        }
+    };
+    if let Err(error) = storage::write(PLACEHOLDERS_KEY, &contents) {
+        error!("Could not save the placeholder parts: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_kind_size_and_name() {
+        let placeholder = parse_placeholder("GPU 322x137×61 RTX 4090", "id".to_string());
+        assert_eq!(
+            placeholder,
+            Ok(Placeholder {
+                id: "id".to_string(),
+                name: "RTX 4090".to_string(),
+                kind: PartKind::Gpu,
+                size: [322.0, 137.0, 61.0],
+            })
+        );
+        let placeholder = parse_placeholder("fan 120x120x25", "id".to_string()).unwrap();
+        assert_eq!(placeholder.name, "Placeholder fan");
+    }
+
+    #[test]
+    fn rejects_bad_sizes() {
+        assert!(parse_placeholder("gpu 322x137", "id".to_string()).is_err());
+        assert!(parse_placeholder("gpu 322x-1x61", "id".to_string()).is_err());
+        assert!(parse_placeholder("case 1x1x1", "id".to_string()).is_err());
+        assert!(parse_placeholder("", "id".to_string()).is_err());
+    }
+}
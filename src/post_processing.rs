@@ -0,0 +1,95 @@
+//! Bloom, ambient occlusion and tonemapping for the scene's cameras, as set in the settings.
+
+use bevy::{
+    core_pipeline::{
+        prepass::{DepthPrepass, NormalPrepass},
+        tonemapping::Tonemapping,
+    },
+    pbr::{ScreenSpaceAmbientOcclusion, ScreenSpaceAmbientOcclusionQualityLevel},
+    post_process::{auto_exposure::AutoExposure, bloom::Bloom},
+    prelude::*,
+    render::view::Hdr,
+};
+
+use crate::{
+    Screen,
+    exposure::{self, ExposureSettings},
+    quality_guard::QualityOverride,
+    settings::{self, AmbientOcclusionQuality, Settings},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        apply_post_processing
+            // Whether the cameras keep HDR depends on auto exposure, so it goes second.
+            .after(exposure::apply_exposure)
+            .run_if(
+                resource_changed::<Settings>
+                    .or(resource_changed::<QualityOverride>)
+                    .or(resource_changed::<ExposureSettings>)
+                    .or(any_match_filter::<Added<Camera3d>>),
+            )
+            // The pipeline warmup renders with them while loading.
+            .run_if(in_state(Screen::Game).or(in_state(Screen::Loading))),
+    );
+}
+
+fn apply_post_processing(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    quality: Res<QualityOverride>,
+    camera_query: Query<
+        (Entity, Has<AutoExposure>, Has<ScreenSpaceAmbientOcclusion>),
+        With<Camera3d>,
+    >,
+) {
+    let settings = quality.lowered(&settings);
+    let tonemapping = match settings.tonemapping {
+        settings::Tonemapping::None => Tonemapping::None,
+        settings::Tonemapping::Reinhard => Tonemapping::Reinhard,
+        settings::Tonemapping::AcesFitted => Tonemapping::AcesFitted,
+        settings::Tonemapping::AgX => Tonemapping::AgX,
+        settings::Tonemapping::TonyMcMapface => Tonemapping::TonyMcMapface,
+        settings::Tonemapping::BlenderFilmic => Tonemapping::BlenderFilmic,
+    };
+    let quality_level = match settings.ambient_occlusion_quality {
+        AmbientOcclusionQuality::Low => ScreenSpaceAmbientOcclusionQualityLevel::Low,
+        AmbientOcclusionQuality::Medium => ScreenSpaceAmbientOcclusionQualityLevel::Medium,
+        AmbientOcclusionQuality::High => ScreenSpaceAmbientOcclusionQualityLevel::High,
+        AmbientOcclusionQuality::Ultra => ScreenSpaceAmbientOcclusionQualityLevel::Ultra,
+    };
+    // WebGL2 can't run it.
+    let ambient_occlusion = settings.ambient_occlusion && cfg!(not(target_arch = "wasm32"));
+
+    for (camera, auto_exposure, had_ambient_occlusion) in &camera_query {
+        let mut camera = commands.entity(camera);
+        camera.insert(tonemapping);
+        if settings.bloom {
+            camera.insert(Bloom {
+                intensity: settings.bloom_intensity,
+                ..Bloom::NATURAL
+            });
+        } else {
+            camera.remove::<Bloom>();
+            // Auto exposure meters the HDR image too.
+            if !auto_exposure {
+                camera.remove::<Hdr>();
+            }
+        }
+        if ambient_occlusion {
+            camera.insert((
+                ScreenSpaceAmbientOcclusion {
+                    quality_level,
+                    ..default()
+                },
+                // Ambient occlusion doesn't work with multisampling.
+                Msaa::Off,
+            ));
+        } else if had_ambient_occlusion {
+            camera
+                .remove::<(ScreenSpaceAmbientOcclusion, DepthPrepass, NormalPrepass)>()
+                .insert(Msaa::default());
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! A second, UI-free window that mirrors the main view, for a second monitor or a demo booth.
+
+use bevy::{camera::RenderTarget, prelude::*, window::WindowRef};
+
+use crate::{
+    OrbitCamera, Screen,
+    blueprint::apply_blueprint_view,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            toggle_presentation_window.run_if(key_just_pressed(KeyAction::PresentationWindow)),
+            despawn_orphaned_cameras.run_if(any_component_removed::<PresentationWindow>),
+            follow_orbit_camera.after(apply_blueprint_view),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Toggle presentation window", toggle_presentation_window);
+}
+
+#[derive(Component)]
+struct PresentationWindow;
+
+/// Renders into the [`PresentationWindow`] and follows the orbit camera.
+#[derive(Component)]
+struct PresentationCamera {
+    window: Entity,
+}
+
+fn toggle_presentation_window(
+    mut commands: Commands,
+    window_query: Query<Entity, With<PresentationWindow>>,
+) {
+    if let Ok(window) = window_query.single() {
+        // The camera follows in `despawn_orphaned_cameras`.
+        commands.entity(window).despawn();
+        return;
+    }
+
+    let window = commands
+        .spawn((
+            Name::new("Presentation Window"),
+            PresentationWindow,
+            DespawnOnExit(Screen::Game),
+            Window {
+                title: "Pc Case Visualizer - Presentation".to_string(),
+                ..default()
+            },
+        ))
+        .id();
+    commands.spawn((
+        Name::new("Presentation Camera"),
+        PresentationCamera { window },
+        DespawnOnExit(Screen::Game),
+        Camera3d::default(),
+        RenderTarget::Window(WindowRef::Entity(window)),
+        Transform::default(),
+    ));
+}
+
+/// Cleans up after a presentation window that was closed, either by F2 or by the user.
+fn despawn_orphaned_cameras(
+    mut commands: Commands,
+    window_query: Query<(), With<PresentationWindow>>,
+    camera_query: Query<(Entity, &PresentationCamera)>,
+) {
+    for (camera, presentation) in &camera_query {
+        if !window_query.contains(presentation.window) {
+            commands.entity(camera).despawn();
+        }
+    }
+}
+
+fn follow_orbit_camera(
+    orbit_query: Query<&Transform, (With<OrbitCamera>, Without<PresentationCamera>)>,
+    mut camera_query: Query<&mut Transform, With<PresentationCamera>>,
+) {
+    let Ok(orbit_transform) = orbit_query.single() else {
+        return;
+    };
+    for mut transform in &mut camera_query {
+        *transform = *orbit_transform;
+    }
+}
@@ -0,0 +1,203 @@
+//! Lowers the graphics settings while frames take too long, so the scene stays interactive on
+//! weak GPUs, like the integrated ones the web build often runs on.
+//!
+//! The frame time is averaged over the last second or so. Once it has stayed over
+//! [`FRAME_TIME_BUDGET`] for [`SLOW_DURATION`], the most expensive setting still on is lowered
+//! one step: ambient occlusion goes first, then bloom, then the shadows get smaller. The next
+//! step waits for [`STEP_COOLDOWN`], long enough for the frame time to settle. Once frames have
+//! stayed under [`FRAME_TIME_HEADROOM`] for [`HEADROOM_DURATION`], the last step is undone, and
+//! if that makes them too slow again, the next try waits twice as long.
+//!
+//! The steps are kept in [`QualityOverride`] for the session rather than written to the saved
+//! settings, and the systems that apply the settings go by [`QualityOverride::lowered`].
+//! Turning this off in the settings undoes them.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{Screen, settings::Settings};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FrameTimes>();
+    app.init_resource::<QualityOverride>();
+    app.add_systems(OnEnter(Screen::Game), reset_frame_times);
+    app.add_systems(
+        Update,
+        (
+            forget_steps_when_off.run_if(resource_changed::<Settings>),
+            adjust_quality.run_if(
+                in_state(Screen::Game).and(|settings: Res<Settings>| settings.auto_quality),
+            ),
+        ),
+    );
+}
+
+/// How many steps the graphics settings have been lowered for this session.
+#[derive(Resource, Debug, Default, PartialEq)]
+pub struct QualityOverride {
+    steps: usize,
+}
+
+impl QualityOverride {
+    /// The settings with the steps taken so far applied, to render with in place of `settings`.
+    pub fn lowered(&self, settings: &Settings) -> Settings {
+        let mut lowered = settings.clone();
+        if settings.auto_quality {
+            for _ in 0..self.steps {
+                if lower_step(&mut lowered).is_none() {
+                    break;
+                }
+            }
+        }
+        lowered
+    }
+}
+
+/// How long a frame may take before the quality is lowered: 30 frames per second.
+const FRAME_TIME_BUDGET: f32 = 1.0 / 30.0;
+/// How long frames must have been too slow before a step is taken.
+const SLOW_DURATION: f32 = 3.0;
+/// How long to wait after a step before taking the next one.
+const STEP_COOLDOWN: f32 = 4.0;
+/// How short frames must be for the quality to be raised again. Over the refresh interval of a
+/// 60 Hz display, so vsync doesn't hide the headroom.
+const FRAME_TIME_HEADROOM: f32 = 1.0 / 45.0;
+/// How long frames must have been quick before a step is undone.
+const HEADROOM_DURATION: f32 = 10.0;
+/// How long after entering the scene to start measuring, to let loading and shader
+/// compilation pass.
+const WARMUP: f32 = 2.0;
+/// How quickly the average follows the frame time, in the fraction of the difference closed each
+/// second.
+const AVERAGE_RATE: f32 = 2.0;
+
+#[derive(Resource, Debug, Default)]
+struct FrameTimes {
+    /// The moving average of the frame time, in seconds.
+    average: Option<f32>,
+    /// How long the average has been over the budget.
+    slow_for: f32,
+    /// How long the average has been under [`FRAME_TIME_HEADROOM`].
+    quick_for: f32,
+    /// How long frames must be quick for before undoing a step, doubled whenever undoing one
+    /// made them too slow.
+    raise_after: f32,
+    /// Whether the last step was undone rather than taken.
+    raised: bool,
+    /// How long until frames are measured again, after entering the scene or taking a step.
+    wait: f32,
+    /// Whether there was nothing left to lower, to say so only once.
+    exhausted: bool,
+}
+
+fn reset_frame_times(mut frame_times: ResMut<FrameTimes>) {
+    *frame_times = FrameTimes {
+        wait: WARMUP,
+        exhausted: frame_times.exhausted,
+        raise_after: frame_times.raise_after.max(HEADROOM_DURATION),
+        raised: frame_times.raised,
+        ..default()
+    };
+}
+
+fn forget_steps_when_off(settings: Res<Settings>, mut quality: ResMut<QualityOverride>) {
+    if !settings.auto_quality && quality.steps > 0 {
+        quality.steps = 0;
+    }
+}
+
+fn adjust_quality(
+    mut frame_times: ResMut<FrameTimes>,
+    mut quality: ResMut<QualityOverride>,
+    settings: Res<Settings>,
+    time: Res<Time<Real>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+) {
+    let delta = time.delta_secs();
+    // Browsers slow down tabs in the background, which says nothing about the GPU.
+    if !window.focused {
+        frame_times.average = None;
+        frame_times.slow_for = 0.0;
+        frame_times.quick_for = 0.0;
+        return;
+    }
+    if frame_times.wait > 0.0 {
+        frame_times.wait -= delta;
+        return;
+    }
+
+    let average = match frame_times.average {
+        Some(average) => average + (delta - average) * (AVERAGE_RATE * delta).min(1.0),
+        None => delta,
+    };
+    frame_times.average = Some(average);
+    if average < FRAME_TIME_HEADROOM && quality.steps > 0 {
+        frame_times.quick_for += delta;
+    } else {
+        frame_times.quick_for = 0.0;
+    }
+    if frame_times.quick_for >= frame_times.raise_after {
+        take_step(&mut frame_times);
+        frame_times.raised = true;
+        quality.steps -= 1;
+        info!(
+            "Frames took {:.0} ms, raised the graphics settings a step",
+            average * 1000.0
+        );
+        return;
+    }
+    if average <= FRAME_TIME_BUDGET {
+        frame_times.slow_for = 0.0;
+        frame_times.exhausted = false;
+        return;
+    }
+    frame_times.slow_for += delta;
+    if frame_times.slow_for < SLOW_DURATION {
+        return;
+    }
+
+    take_step(&mut frame_times);
+    let Some(lowered) = lower_step(&mut quality.lowered(&settings)) else {
+        if !frame_times.exhausted {
+            frame_times.exhausted = true;
+            info!(
+                "Frames take {:.0} ms, but the graphics settings are as low as they go",
+                average * 1000.0
+            );
+        }
+        return;
+    };
+    if frame_times.raised {
+        frame_times.raised = false;
+        frame_times.raise_after *= 2.0;
+    }
+    quality.steps += 1;
+    info!(
+        "Frames took {:.0} ms, lowered {lowered} to keep the scene smooth",
+        average * 1000.0
+    );
+}
+
+/// Starts measuring afresh after a step, once the frame time has had time to settle.
+fn take_step(frame_times: &mut FrameTimes) {
+    frame_times.slow_for = 0.0;
+    frame_times.quick_for = 0.0;
+    frame_times.average = None;
+    frame_times.wait = STEP_COOLDOWN;
+}
+
+/// Lowers the most expensive setting that's still on one step, saying which it was, or returns
+/// `None` if they're all as low as they go.
+fn lower_step(settings: &mut Settings) -> Option<&'static str> {
+    // The web build never draws ambient occlusion, so turning it off there gains nothing.
+    if settings.ambient_occlusion && cfg!(not(target_arch = "wasm32")) {
+        settings.ambient_occlusion = false;
+        return Some("ambient occlusion");
+    }
+    if settings.bloom {
+        settings.bloom = false;
+        return Some("bloom");
+    }
+    let shadow_quality = settings.shadow_quality.lower()?;
+    settings.shadow_quality = shadow_quality;
+    Some("shadow quality")
+}
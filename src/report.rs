@@ -0,0 +1,310 @@
+//! A report of what changed since the build was last saved: the parts added and removed with
+//! their prices, what that did to the cost, and the compatibility issues it fixed or caused.
+//!
+//! Open it from the build panel or the command palette. It compares the saved build with the
+//! current one, both as [`SavedBuild`]s, so swapping a part shows its consequences before the
+//! swap is saved. The saved build is read from storage as the level is entered and kept in
+//! [`BuildReport`] as it's saved and loaded again.
+//!
+//! Below that, it lists the notes on the installed parts with when their warranties end, in
+//! orange when one ends within a month and in red once it has ended.
+
+use bevy::prelude::*;
+
+use crate::{
+    LevelAssets, Screen,
+    aio::AioTubes,
+    board_armor::BoardArmor,
+    cases::CaseManifest,
+    command_palette::AddPaletteCommand,
+    compat,
+    compatibility::CompatibilityIssues,
+    part_notes::{Date, PartNotes, WarrantyStatus, warranty_status},
+    parts::{PartCatalog, mount_point},
+    persistence::{BUILD_KEY, CurrentBuild, SavedBuild, load_build},
+    settings::{Locale, Settings, Units},
+    ui_navigation::{FONT_SIZE, OVERLAY_BACKGROUND},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<BuildReport>();
+    app.add_systems(
+        OnEnter(Screen::Game),
+        (spawn_report_panel, read_saved_build),
+    );
+    app.add_systems(
+        Update,
+        update_report_panel
+            .run_if(
+                resource_changed::<BuildReport>
+                    .or(resource_changed::<CompatibilityIssues>)
+                    .or(resource_changed::<Settings>)
+                    .or(resource_changed::<PartNotes>)
+                    .or(any_match_filter::<Added<ReportList>>),
+            )
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Compare the build with the saved one", toggle_report);
+}
+
+/// Whether the report is shown, and the saved build it compares with.
+#[derive(Resource, Debug, Default)]
+pub struct BuildReport {
+    pub shown: bool,
+    /// Kept from when the build was last saved or loaded, rather than read from storage again
+    /// every time the report is updated.
+    pub saved: Option<SavedBuild>,
+}
+
+#[derive(Component)]
+struct ReportList;
+
+/// The parts one build has that another doesn't, by catalog id. A part installed twice counts
+/// twice.
+#[derive(Debug, Default, PartialEq)]
+pub struct PartsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+pub fn diff_parts(before: &[String], after: &[String]) -> PartsDiff {
+    let mut removed = before.to_vec();
+    let mut added = Vec::new();
+    for id in after {
+        match removed.iter().position(|other| other == id) {
+            Some(index) => {
+                removed.remove(index);
+            }
+            None => added.push(id.clone()),
+        }
+    }
+    PartsDiff { added, removed }
+}
+
+/// The compatibility issues of `build`, with its parts mounted as installing them would, and
+/// the motherboard's `armor` and a liquid cooler's `tubes` as they are.
+pub fn build_issues(
+    build: &SavedBuild,
+    catalog: &PartCatalog,
+    manifest: Option<&CaseManifest>,
+    armor: BoardArmor,
+    tubes: AioTubes,
+    units: Units,
+    locale: Locale,
+) -> Vec<String> {
+    let case = manifest.and_then(|manifest| manifest.get(&build.case_model));
+    let mut parts = Vec::new();
+    let mut taken = Vec::new();
+    for spec in build.parts.iter().filter_map(|part| catalog.get(&part.id)) {
+        let mount = mount_point(spec, case, &taken);
+        taken.push(mount);
+        parts.push((spec, mount));
+    }
+    compat::check(&parts, case, armor, tubes, units, locale)
+        .into_iter()
+        .map(|issue| issue.message)
+        .collect()
+}
+
+const ADDED_COLOR: Color = Color::srgb(0.5, 1.0, 0.5);
+const REMOVED_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+const WARNING_COLOR: Color = Color::srgb(1.0, 0.75, 0.3);
+const NOTE_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+
+pub fn toggle_report(mut report: ResMut<BuildReport>) {
+    report.shown = !report.shown;
+}
+
+fn read_saved_build(mut report: ResMut<BuildReport>) {
+    report.saved = load_build(BUILD_KEY);
+}
+
+fn spawn_report_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Report Panel"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the panel, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(40.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            ReportList,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: px(2.0),
+                padding: UiRect::all(px(6.0)),
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(OVERLAY_BACKGROUND),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn update_report_panel(
+    mut commands: Commands,
+    report: Res<BuildReport>,
+    settings: Res<Settings>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    armor: Res<BoardArmor>,
+    tubes: Res<AioTubes>,
+    current: CurrentBuild,
+    mut list_query: Query<(Entity, &mut Node), With<ReportList>>,
+) {
+    let Ok((list, mut node)) = list_query.single_mut() else {
+        return;
+    };
+    node.display = if report.shown {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    let (Some(catalog), true) = (catalogs.get(&level_assets.catalog), report.shown) else {
+        return;
+    };
+    let manifest = manifests.get(&level_assets.cases);
+    let current = current.get();
+    let name = |id: &String| catalog.get(id).map_or(id.clone(), |spec| spec.name.clone());
+
+    let mut note_lines = Vec::new();
+    let today = Date::today();
+    for part in current.iter().flat_map(|build| &build.parts) {
+        let (status, color) = match warranty_status(&part.note, today) {
+            Some(WarrantyStatus::Active(end)) => (format!("warranty until {end}"), Color::WHITE),
+            Some(WarrantyStatus::EndingSoon(end, days)) => (
+                format!("warranty ends {end}, in {days} days"),
+                WARNING_COLOR,
+            ),
+            Some(WarrantyStatus::Ended(end)) => (format!("warranty ended {end}"), REMOVED_COLOR),
+            None => match part.note.purchased {
+                Some(purchased) => (format!("bought {purchased}"), NOTE_COLOR),
+                None if part.note.text.is_empty() => continue,
+                None => (String::new(), NOTE_COLOR),
+            },
+        };
+        let mut line = format!("{}: {status}", name(&part.id));
+        if !part.note.text.is_empty() {
+            if !status.is_empty() {
+                line.push_str(", ");
+            }
+            line.push_str(&part.note.text);
+        }
+        note_lines.push((line, color));
+    }
+
+    let mut lines = Vec::new();
+    match (&report.saved, current) {
+        (Some(saved), Some(current)) => {
+            let diff = diff_parts(&saved.part_ids(), &current.part_ids());
+            let price = |id: &String| catalog.get(id).map_or(0.0, |spec| spec.price);
+            for id in &diff.added {
+                let text = format!("+ {} {}", name(id), settings.format_price(price(id)));
+                lines.push((text, ADDED_COLOR));
+            }
+            for id in &diff.removed {
+                let text = format!("- {} {}", name(id), settings.format_price(price(id)));
+                lines.push((text, REMOVED_COLOR));
+            }
+            if diff == PartsDiff::default() {
+                lines.push(("The parts are as saved".to_string(), Color::WHITE));
+            }
+            let change = diff.added.iter().map(price).sum::<f32>()
+                - diff.removed.iter().map(price).sum::<f32>();
+            let sign = if change < 0.0 { "-" } else { "+" };
+            lines.push((
+                format!("Cost {sign}{}", settings.format_price(change.abs())),
+                Color::WHITE,
+            ));
+
+            let issues = |build| {
+                build_issues(
+                    build,
+                    catalog,
+                    manifest,
+                    *armor,
+                    *tubes,
+                    settings.units,
+                    settings.locale,
+                )
+            };
+            let (before, after) = (issues(saved), issues(&current));
+            for issue in after.iter().filter(|issue| !before.contains(issue)) {
+                lines.push((format!("New: {issue}"), REMOVED_COLOR));
+            }
+            for issue in before.iter().filter(|issue| !after.contains(issue)) {
+                lines.push((format!("Fixed: {issue}"), ADDED_COLOR));
+            }
+        }
+        _ => lines.push((
+            "There is no saved build to compare with".to_string(),
+            Color::WHITE,
+        )),
+    }
+
+    commands
+        .entity(list)
+        .despawn_children()
+        .with_children(|list| {
+            list.spawn((
+                Text::new("Changes since the saved build"),
+                TextColor(Color::WHITE),
+                Pickable::IGNORE,
+            ));
+            for (line, color) in lines {
+                list.spawn((
+                    Text::new(line),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(color),
+                    Pickable::IGNORE,
+                ));
+            }
+            if !note_lines.is_empty() {
+                list.spawn((
+                    Text::new("Notes and warranties"),
+                    TextColor(Color::WHITE),
+                    Pickable::IGNORE,
+                ));
+            }
+            for (line, color) in note_lines {
+                list.spawn((
+                    Text::new(line),
+                    TextFont::from_font_size(FONT_SIZE),
+                    TextColor(color),
+                    Pickable::IGNORE,
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn swapped_parts() {
+        let diff = diff_parts(
+            &ids(&["gpu_dual_slot", "fan_front_120", "fan_front_120"]),
+            &ids(&["fan_front_120", "gpu_triple_slot"]),
+        );
+        assert_eq!(diff.added, ids(&["gpu_triple_slot"]));
+        assert_eq!(diff.removed, ids(&["gpu_dual_slot", "fan_front_120"]));
+    }
+
+    #[test]
+    fn same_parts_in_another_order() {
+        let diff = diff_parts(&ids(&["cpu", "gpu"]), &ids(&["gpu", "cpu"]));
+        assert_eq!(diff, PartsDiff::default());
+    }
+}
@@ -0,0 +1,486 @@
+//! Animated RGB lighting on fans and strips, grouped into zones.
+//!
+//! A node of the case model joins a zone when its name starts with [`RGB_PREFIX`]: both
+//! `RGB_Front` and `RGB_Front_Fan1` light up in the "Front" zone. Catalog parts join the zone
+//! set in their `rgb_zone`. Every mesh below such an entity glows with the zone's effect.
+//! Press G to open the panel that picks the effect, speed and color of each zone, and how
+//! bright it may glow over the limit set in the settings. The temperature effect shades a zone
+//! from blue to red with its temperature (see `thermals`). The frames and blades of installed
+//! fans are colored apart from their LEDs, see [`fan_colors`](crate::fan_colors). The zones'
+//! settings are saved with the build.
+
+use std::f32::consts::TAU;
+
+use bevy::{input_focus::tab_navigation::TabGroup, platform::collections::HashMap, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Screen,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::HighlightSelectedPart,
+    settings::{LED_BRIGHTNESS_STEP, Settings, save_settings},
+    thermals::Temperatures,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RgbSettings>();
+    app.add_systems(OnEnter(Screen::Game), spawn_rgb_panel);
+    app.add_systems(
+        Update,
+        (
+            toggle_rgb_panel.run_if(key_just_pressed(KeyAction::RgbPanel)),
+            tag_rgb_nodes,
+            // Before the selection highlight and ghosting copy the material it swaps in.
+            attach_leds.before(HighlightSelectedPart),
+            animate_leds,
+            rebuild_rgb_rows
+                .run_if(any_match_filter::<Added<RgbZone>>.or(any_component_removed::<RgbZone>)),
+            update_button_labels,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_rgb_panel_button);
+    app.add_palette_command("Toggle RGB lighting panel", toggle_rgb_panel);
+}
+
+/// Lights up every mesh below this entity with the effect of the named zone.
+#[derive(Component, Debug, Clone)]
+pub struct RgbZone(pub String);
+
+/// A mesh lit by a zone. Holds its own copy of the mesh's material, so the effect can be
+/// animated without touching meshes that shared the original.
+#[derive(Component)]
+pub struct Led {
+    zone: String,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct RgbSettings {
+    /// Zones that haven't been touched in the panel use [`ZoneSettings::default`].
+    pub zones: HashMap<String, ZoneSettings>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ZoneSettings {
+    pub effect: RgbEffect,
+    /// Effect cycles per second.
+    pub speed: f32,
+    /// Index into [`RGB_COLORS`]; ignored by [`RgbEffect::RainbowWave`] and
+    /// [`RgbEffect::Temperature`].
+    pub color: usize,
+}
+
+impl Default for ZoneSettings {
+    fn default() -> Self {
+        Self {
+            effect: RgbEffect::RainbowWave,
+            speed: 0.5,
+            color: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RgbEffect {
+    Static,
+    Breathing,
+    RainbowWave,
+    Strobe,
+    Temperature,
+}
+
+impl RgbEffect {
+    fn next(self) -> Self {
+        match self {
+            Self::Static => Self::Breathing,
+            Self::Breathing => Self::RainbowWave,
+            Self::RainbowWave => Self::Strobe,
+            Self::Strobe => Self::Temperature,
+            Self::Temperature => Self::Static,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Static => "Static",
+            Self::Breathing => "Breathing",
+            Self::RainbowWave => "Rainbow wave",
+            Self::Strobe => "Strobe",
+            Self::Temperature => "Temperature",
+        }
+    }
+}
+
+#[derive(Component)]
+struct RgbPanel;
+
+/// The container the per-zone rows are spawned into.
+#[derive(Component)]
+struct ZoneList;
+
+#[derive(Component, Debug, Clone)]
+struct RgbPanelButton {
+    zone: String,
+    action: RgbAction,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum RgbAction {
+    NextEffect,
+    Slower,
+    Faster,
+    NextColor,
+    LowerLimit,
+    RaiseLimit,
+}
+
+/// Shows the speed of the zone it names.
+#[derive(Component)]
+struct SpeedLabel(String);
+
+/// Shows the brightness limit of the zone it names.
+#[derive(Component)]
+struct LimitLabel(String);
+
+const RGB_PREFIX: &str = "RGB_";
+/// The colors [`RgbAction::NextColor`] cycles through.
+const RGB_COLORS: [Color; 6] = [
+    Color::srgb(1.0, 0.0, 0.0),
+    Color::srgb(0.0, 1.0, 0.0),
+    Color::srgb(0.0, 0.3, 1.0),
+    Color::srgb(1.0, 0.0, 1.0),
+    Color::srgb(0.0, 1.0, 1.0),
+    Color::WHITE,
+];
+/// Emissive strength at full brightness, and the highest brightness limit. Not scaled by
+/// exposure, so LEDs look alike in every lighting environment.
+pub const LED_BRIGHTNESS: f32 = 2.0;
+/// How many degrees the rainbow's hue shifts per millimeter along the case.
+const RAINBOW_HUE_PER_MM: f32 = 0.8;
+/// Fraction of each strobe cycle the LEDs are lit.
+const STROBE_DUTY: f32 = 0.15;
+/// Temperatures, in degrees Celsius, that [`RgbEffect::Temperature`] shows as fully blue and
+/// fully red.
+const COLD_TEMPERATURE: f32 = 30.0;
+const HOT_TEMPERATURE: f32 = 90.0;
+const SPEED_STEP: f32 = 1.5;
+const MIN_SPEED: f32 = 0.1;
+const MAX_SPEED: f32 = 5.0;
+
+fn tag_rgb_nodes(mut commands: Commands, node_query: Query<(Entity, &Name), Added<Name>>) {
+    for (entity, name) in &node_query {
+        if let Some(zone) = name.as_str().strip_prefix(RGB_PREFIX)
+            && let Some(zone) = zone.split('_').next()
+            && !zone.is_empty()
+        {
+            commands.entity(entity).insert(RgbZone(zone.to_string()));
+        }
+    }
+}
+
+/// Runs every frame, since a zone's meshes may finish spawning well after the zone was tagged.
+pub fn attach_leds(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    zone_query: Query<(Entity, &RgbZone)>,
+    children_query: Query<&Children>,
+    mut mesh_query: Query<&mut MeshMaterial3d<StandardMaterial>, Without<Led>>,
+) {
+    for (root, zone) in &zone_query {
+        for entity in std::iter::once(root).chain(children_query.iter_descendants(root)) {
+            let Ok(mut material) = mesh_query.get_mut(entity) else {
+                continue;
+            };
+            let Some(mut led_material) = materials.get(&material.0).cloned() else {
+                continue;
+            };
+            led_material.emissive_exposure_weight = 0.0;
+            let led_material = materials.add(led_material);
+            material.0 = led_material.clone();
+            commands.entity(entity).insert(Led {
+                zone: zone.0.clone(),
+                material: led_material,
+            });
+        }
+    }
+}
+
+fn animate_leds(
+    time: Res<Time>,
+    settings: Res<RgbSettings>,
+    limits: Res<Settings>,
+    temperatures: Res<Temperatures>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    led_query: Query<(&Led, &GlobalTransform)>,
+) {
+    let elapsed = time.elapsed_secs();
+    for (led, transform) in &led_query {
+        let zone = settings.zones.get(&led.zone).copied().unwrap_or_default();
+        let phase = elapsed * zone.speed;
+        let color = RGB_COLORS[zone.color % RGB_COLORS.len()];
+        let emissive = match zone.effect {
+            RgbEffect::Static => color,
+            RgbEffect::Breathing => {
+                color.with_luminance(color.luminance() * (0.5 - 0.5 * (phase * TAU).cos()))
+            }
+            RgbEffect::RainbowWave => {
+                let hue = phase * 360.0 + transform.translation().x * RAINBOW_HUE_PER_MM;
+                Color::hsl(hue.rem_euclid(360.0), 1.0, 0.5)
+            }
+            RgbEffect::Strobe if phase.fract() < STROBE_DUTY => color,
+            RgbEffect::Strobe => Color::BLACK,
+            RgbEffect::Temperature => temperature_color(temperatures.get(&led.zone)),
+        };
+        if let Some(material) = materials.get_mut(&led.material) {
+            material.emissive = limit_emissive(
+                emissive.to_linear() * LED_BRIGHTNESS,
+                limits.led_brightness_limit(&led.zone),
+            );
+        }
+    }
+}
+
+/// Dims `emissive` until none of its channels is over `limit`, keeping its hue.
+pub fn limit_emissive(emissive: LinearRgba, limit: f32) -> LinearRgba {
+    let brightest = emissive.red.max(emissive.green).max(emissive.blue);
+    if brightest > limit {
+        (emissive * (limit / brightest)).with_alpha(emissive.alpha)
+    } else {
+        emissive
+    }
+}
+
+/// Blue when cold, through green and yellow to red when hot.
+pub fn temperature_color(celsius: f32) -> Color {
+    let heat =
+        ((celsius - COLD_TEMPERATURE) / (HOT_TEMPERATURE - COLD_TEMPERATURE)).clamp(0.0, 1.0);
+    Color::hsl(240.0 * (1.0 - heat), 1.0, 0.5)
+}
+
+fn spawn_rgb_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("RGB Panel"),
+        RgbPanel,
+        DespawnOnExit(Screen::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            top: percent(40.0),
+            left: px(5.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(4.0),
+            padding: UiRect::all(px(6.0)),
+            // Opened with G.
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(PANEL_BACKGROUND),
+        TabGroup::new(3),
+        children![
+            (Text::new("RGB"), TextColor(Color::WHITE)),
+            (
+                Name::new("Zone List"),
+                ZoneList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4.0),
+                    ..default()
+                },
+            ),
+        ],
+    ));
+}
+
+fn toggle_rgb_panel(mut panel_query: Query<&mut Node, With<RgbPanel>>) {
+    for mut node in &mut panel_query {
+        node.display = match node.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+fn button(zone: &str, action: RgbAction, label: &str) -> impl Bundle {
+    (
+        Button,
+        RgbPanelButton {
+            zone: zone.to_string(),
+            action,
+        },
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+/// Respawns one row per zone in the scene, in alphabetical order.
+fn rebuild_rgb_rows(
+    mut commands: Commands,
+    list_query: Query<Entity, With<ZoneList>>,
+    zone_query: Query<&RgbZone>,
+) {
+    let Ok(list) = list_query.single() else {
+        return;
+    };
+
+    let mut zones: Vec<&str> = zone_query.iter().map(|zone| zone.0.as_str()).collect();
+    zones.sort_unstable();
+    zones.dedup();
+
+    commands
+        .entity(list)
+        .despawn_children()
+        .with_children(|list| {
+            for zone in zones {
+                list.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: px(4.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(zone),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn(button(zone, RgbAction::NextEffect, ""));
+                    row.spawn(button(zone, RgbAction::Slower, "-"));
+                    row.spawn((
+                        SpeedLabel(zone.to_string()),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn(button(zone, RgbAction::Faster, "+"));
+                    row.spawn(button(zone, RgbAction::NextColor, "Color"));
+                    row.spawn(button(zone, RgbAction::LowerLimit, "-"));
+                    row.spawn((
+                        LimitLabel(zone.to_string()),
+                        Text::default(),
+                        TextFont::from_font_size(FONT_SIZE),
+                        TextColor(Color::WHITE),
+                    ));
+                    row.spawn(button(zone, RgbAction::RaiseLimit, "+"));
+                });
+            }
+        });
+}
+
+/// Keeps the effect, color, speed and limit labels in sync with the zone settings. Zones showing
+/// their temperature have it in place of the speed.
+fn update_button_labels(
+    settings: Res<RgbSettings>,
+    limits: Res<Settings>,
+    temperatures: Res<Temperatures>,
+    mut button_query: Query<(&RgbPanelButton, &mut Text, &mut TextColor)>,
+    mut speed_query: Query<(&SpeedLabel, &mut Text), Without<RgbPanelButton>>,
+    mut limit_query: Query<
+        (&LimitLabel, &mut Text),
+        (Without<RgbPanelButton>, Without<SpeedLabel>),
+    >,
+) {
+    for (button, mut text, mut text_color) in &mut button_query {
+        let zone = settings
+            .zones
+            .get(&button.zone)
+            .copied()
+            .unwrap_or_default();
+        match button.action {
+            RgbAction::NextEffect => {
+                let label = zone.effect.label();
+                if text.0 != label {
+                    text.0 = label.to_string();
+                }
+            }
+            RgbAction::NextColor => {
+                text_color.set_if_neq(TextColor(RGB_COLORS[zone.color % RGB_COLORS.len()]));
+            }
+            RgbAction::Slower
+            | RgbAction::Faster
+            | RgbAction::LowerLimit
+            | RgbAction::RaiseLimit => {}
+        }
+    }
+
+    for (label, mut text) in &mut speed_query {
+        let zone = settings.zones.get(&label.0).copied().unwrap_or_default();
+        let speed = match zone.effect {
+            RgbEffect::Temperature => format!("{:.0} °C", temperatures.get(&label.0)),
+            _ => format!("{:.1}x", zone.speed),
+        };
+        if text.0 != speed {
+            text.0 = speed;
+        }
+    }
+
+    for (label, mut text) in &mut limit_query {
+        let limit = format!("Max {:.1}", limits.led_brightness_limit(&label.0));
+        if text.0 != limit {
+            text.0 = limit;
+        }
+    }
+}
+
+fn activate_rgb_panel_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    button_query: Query<&RgbPanelButton>,
+    mut settings: ResMut<RgbSettings>,
+    mut limits: ResMut<Settings>,
+) {
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+
+    if let RgbAction::LowerLimit | RgbAction::RaiseLimit = button.action {
+        let step = match button.action {
+            RgbAction::LowerLimit => -LED_BRIGHTNESS_STEP,
+            _ => LED_BRIGHTNESS_STEP,
+        };
+        let limit = (limits.led_brightness_limit(&button.zone) + step)
+            .clamp(LED_BRIGHTNESS_STEP, LED_BRIGHTNESS);
+        // A zone back at the global limit follows it again.
+        if (limit - limits.led_brightness_limit).abs() < LED_BRIGHTNESS_STEP / 2.0 {
+            limits.zone_brightness_limits.remove(&button.zone);
+        } else {
+            limits
+                .zone_brightness_limits
+                .insert(button.zone.clone(), limit);
+        }
+        commands.run_system_cached(save_settings);
+        return;
+    }
+
+    let zone = settings.zones.entry(button.zone.clone()).or_default();
+    match button.action {
+        RgbAction::NextEffect => zone.effect = zone.effect.next(),
+        RgbAction::Slower => zone.speed = (zone.speed / SPEED_STEP).max(MIN_SPEED),
+        RgbAction::Faster => zone.speed = (zone.speed * SPEED_STEP).min(MAX_SPEED),
+        RgbAction::NextColor => zone.color = (zone.color + 1) % RGB_COLORS.len(),
+        RgbAction::LowerLimit | RgbAction::RaiseLimit => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emissive_limit() {
+        let white = LinearRgba::rgb(2.0, 2.0, 2.0);
+        assert_eq!(limit_emissive(white, 1.0), LinearRgba::rgb(1.0, 1.0, 1.0));
+        let red = LinearRgba::rgb(2.0, 0.5, 0.0);
+        assert_eq!(limit_emissive(red, 1.0), LinearRgba::rgb(1.0, 0.25, 0.0));
+        assert_eq!(limit_emissive(red, 3.0), red);
+    }
+}
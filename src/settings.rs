@@ -0,0 +1,860 @@
+//! A settings screen for the camera controls, shadow quality, post-processing, vsync, UI scale,
+//! automatic quality, the units lengths are shown in, the currency prices are shown in and the
+//! locale numbers are written in, how bright RGB lighting may glow, the watermark and
+//! background colors of exported images, and the key bindings, which have a screen of their
+//! own.
+//! The RGB panel overrides the brightness limit for single zones, and the parts panel keeps the
+//! favorite parts here.
+//!
+//! Escape, or Start on a gamepad, opens the screen over the scene and closes it again, and the
+//! main menu has a button for it. Opened over the level, it also leads back to the main menu.
+//! The scene carries on behind the screen, but the keys and sticks are kept from moving it.
+//! The settings are saved when the screen is left and applied on startup: native builds write
+//! `settings.ron` next to the app, the web build keeps the file in `localStorage`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::{
+    input_focus::tab_navigation::TabGroup,
+    light::{DirectionalLightShadowMap, PointLightShadowMap},
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Menu, Screen,
+    actions::{Actions, menu_pressed, read_actions},
+    keybindings::Keybindings,
+    quality_guard::QualityOverride,
+    rgb::LED_BRIGHTNESS,
+    storage,
+    ui_navigation::{
+        Activate, ButtonStyle, FONT_SIZE, OVERLAY_BACKGROUND, focusable, release_focus,
+        ui_not_focused,
+    },
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(load_settings().unwrap_or_default());
+    app.add_systems(
+        Update,
+        apply_settings.run_if(resource_changed::<Settings>.or(resource_changed::<QualityOverride>)),
+    );
+    app.add_systems(
+        Update,
+        open_settings
+            .run_if(
+                in_state(Screen::Game)
+                    .and(in_state(Menu::Closed))
+                    .and(menu_pressed),
+            )
+            .run_if(ui_not_focused)
+            .before(release_focus),
+    );
+    app.add_systems(
+        PreUpdate,
+        hold_scene_input
+            .after(read_actions)
+            .run_if(in_state(Menu::Settings).or(in_state(Menu::Keybindings))),
+    );
+    app.add_systems(OnEnter(Menu::Settings), spawn_settings_screen);
+    app.add_systems(OnExit(Menu::Settings), save_settings);
+    app.add_systems(
+        Update,
+        (
+            close_settings.run_if(menu_pressed),
+            update_value_labels
+                .run_if(resource_changed::<Settings>.or(any_match_filter::<Added<SettingValue>>)),
+        )
+            .chain()
+            .run_if(in_state(Menu::Settings)),
+    );
+    app.add_observer(activate_settings_button);
+}
+
+/// Preferences that outlast the session.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    /// Multiplies the speed the orbit keys and a gamepad's sticks move the camera at.
+    pub camera_speed: f32,
+    /// Multiplies how far dragging the mouse orbits and pans the camera.
+    pub mouse_sensitivity: f32,
+    /// Drags orbit the other way around horizontally.
+    pub invert_x: bool,
+    /// Drags orbit the other way around vertically.
+    pub invert_y: bool,
+    pub shadow_quality: ShadowQuality,
+    /// Makes bright and emissive surfaces, like RGB lighting, glow.
+    pub bloom: bool,
+    pub bloom_intensity: f32,
+    /// Screen-space ambient occlusion, which darkens creases and corners. Not on the web.
+    pub ambient_occlusion: bool,
+    pub ambient_occlusion_quality: AmbientOcclusionQuality,
+    pub tonemapping: Tonemapping,
+    pub vsync: bool,
+    pub ui_scale: f32,
+    /// Lowers the other graphics settings when frames take too long, as on weak integrated
+    /// GPUs.
+    pub auto_quality: bool,
+    pub units: Units,
+    /// The decimal mark and thousands separator of every length and price.
+    pub locale: Locale,
+    pub currency: Currency,
+    /// How much of the currency a US dollar, which the catalog's prices are in, is worth.
+    pub currency_rate: f32,
+    /// The emissive strength RGB lighting is capped at, so bloom doesn't blow out captures.
+    pub led_brightness_limit: f32,
+    /// Limits that replace `led_brightness_limit` for single RGB zones, by zone name.
+    pub zone_brightness_limits: BTreeMap<String, f32>,
+    /// A logo stamped onto captures and turntables, as a path in the assets folder. Set from
+    /// the command palette.
+    pub watermark: Option<String>,
+    pub watermark_position: WatermarkPosition,
+    pub watermark_opacity: f32,
+    /// The sRGB colors at the top and the bottom of exported images' backgrounds, when they're
+    /// painted in rather than the scene's. A solid background uses the top one. Set from the
+    /// command palette.
+    pub export_background: [[f32; 3]; 2],
+    /// The catalog ids of the parts marked as favorites in the parts panel.
+    pub favorite_parts: BTreeSet<String>,
+    pub keybindings: Keybindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            camera_speed: 1.0,
+            mouse_sensitivity: 1.0,
+            invert_x: false,
+            invert_y: false,
+            shadow_quality: ShadowQuality::Medium,
+            bloom: false,
+            bloom_intensity: 0.15,
+            ambient_occlusion: false,
+            ambient_occlusion_quality: AmbientOcclusionQuality::High,
+            tonemapping: Tonemapping::TonyMcMapface,
+            vsync: true,
+            ui_scale: 1.0,
+            auto_quality: true,
+            units: Units::Millimeters,
+            locale: Locale::English,
+            currency: Currency::UsDollar,
+            currency_rate: 1.0,
+            led_brightness_limit: LED_BRIGHTNESS,
+            zone_brightness_limits: BTreeMap::new(),
+            watermark: None,
+            watermark_position: WatermarkPosition::BottomRight,
+            watermark_opacity: 0.5,
+            export_background: [[0.22, 0.23, 0.26], [0.06, 0.06, 0.07]],
+            favorite_parts: BTreeSet::new(),
+            keybindings: Keybindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// How a drag of `motion` pixels should orbit the camera, with the axes inverted as set.
+    pub fn orbit_motion(&self, motion: Vec2) -> Vec2 {
+        let sign = Vec2::new(
+            if self.invert_x { -1.0 } else { 1.0 },
+            if self.invert_y { -1.0 } else { 1.0 },
+        );
+        motion * sign * self.mouse_sensitivity
+    }
+
+    /// The emissive strength the LEDs of `zone` are capped at.
+    pub fn led_brightness_limit(&self, zone: &str) -> f32 {
+        self.zone_brightness_limits
+            .get(zone)
+            .copied()
+            .unwrap_or(self.led_brightness_limit)
+    }
+
+    /// A price of `dollars` in the currency, at its rate, like `$1,012.50` or `11,50 €`.
+    pub fn format_price(&self, dollars: f32) -> String {
+        let amount = self.locale.format_number(dollars * self.currency_rate, 2);
+        match self.currency {
+            Currency::UsDollar => format!("${amount}"),
+            Currency::Pound => format!("£{amount}"),
+            Currency::Euro => format!("{amount} €"),
+            Currency::Zloty => format!("{amount} zł"),
+        }
+    }
+
+    /// A length of `millimeters` in the units and locale, see [`Units::format`].
+    pub fn format_length(&self, millimeters: f32) -> String {
+        self.units.format(millimeters, self.locale)
+    }
+
+    /// A width, height and depth in millimeters, in the units and locale.
+    pub fn format_size(&self, size: [f32; 3]) -> String {
+        self.units.format_size(size, self.locale)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum ShadowQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    fn next(self) -> Self {
+        match self {
+            Self::Low => Self::Medium,
+            Self::Medium => Self::High,
+            Self::High => Self::Low,
+        }
+    }
+
+    /// The next lower quality, if there is one.
+    pub fn lower(self) -> Option<Self> {
+        match self {
+            Self::Low => None,
+            Self::Medium => Some(Self::Low),
+            Self::High => Some(Self::Medium),
+        }
+    }
+
+    /// Width and height of the shadow maps, in texels.
+    fn map_size(self) -> usize {
+        match self {
+            Self::Low => 512,
+            Self::Medium => 1024,
+            Self::High => 2048,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum AmbientOcclusionQuality {
+    Low,
+    Medium,
+    #[default]
+    High,
+    Ultra,
+}
+
+impl AmbientOcclusionQuality {
+    fn next(self) -> Self {
+        match self {
+            Self::Low => Self::Medium,
+            Self::Medium => Self::High,
+            Self::High => Self::Ultra,
+            Self::Ultra => Self::Low,
+        }
+    }
+}
+
+/// How the scene's colors are mapped to the screen's range.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Tonemapping {
+    None,
+    Reinhard,
+    AcesFitted,
+    AgX,
+    #[default]
+    TonyMcMapface,
+    BlenderFilmic,
+}
+
+impl Tonemapping {
+    fn next(self) -> Self {
+        match self {
+            Self::None => Self::Reinhard,
+            Self::Reinhard => Self::AcesFitted,
+            Self::AcesFitted => Self::AgX,
+            Self::AgX => Self::TonyMcMapface,
+            Self::TonyMcMapface => Self::BlenderFilmic,
+            Self::BlenderFilmic => Self::None,
+        }
+    }
+}
+
+/// The unit every length in the app is shown in, see [`Units::format`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Units {
+    #[default]
+    Millimeters,
+    Inches,
+}
+
+const MILLIMETERS_PER_INCH: f32 = 25.4;
+
+impl Units {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Millimeters => Self::Inches,
+            Self::Inches => Self::Millimeters,
+        }
+    }
+
+    /// A length of `millimeters` in these units, written as in `locale`, like `12.5 mm` or
+    /// `0,49 in`.
+    pub fn format(self, millimeters: f32, locale: Locale) -> String {
+        format!("{} {}", self.number(millimeters, locale), self.symbol())
+    }
+
+    /// A width, height and depth in millimeters, like `450 x 210 x 460 mm`.
+    pub fn format_size(self, [width, height, depth]: [f32; 3], locale: Locale) -> String {
+        format!(
+            "{} x {} x {} {}",
+            self.number(width, locale),
+            self.number(height, locale),
+            self.number(depth, locale),
+            self.symbol()
+        )
+    }
+
+    /// Millimeters to a tenth, without a trailing zero, and inches to a hundredth.
+    fn number(self, millimeters: f32, locale: Locale) -> String {
+        match self {
+            Self::Millimeters if (millimeters * 10.0).round() % 10.0 == 0.0 => {
+                locale.format_number(millimeters, 0)
+            }
+            Self::Millimeters => locale.format_number(millimeters, 1),
+            Self::Inches => locale.format_number(millimeters / MILLIMETERS_PER_INCH, 2),
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Millimeters => "mm",
+            Self::Inches => "in",
+        }
+    }
+}
+
+/// How numbers are written, named after a language that writes them so: the mark before the
+/// decimals and the one between groups of thousands.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Locale {
+    /// `1,234.5`
+    #[default]
+    English,
+    /// `1.234,5`
+    German,
+    /// `1 234,5`, as in most of Europe.
+    French,
+    /// `1'234.5`
+    Swiss,
+}
+
+impl Locale {
+    fn next(self) -> Self {
+        match self {
+            Self::English => Self::German,
+            Self::German => Self::French,
+            Self::French => Self::Swiss,
+            Self::Swiss => Self::English,
+        }
+    }
+
+    /// The decimal mark and the thousands separator.
+    fn separators(self) -> (char, char) {
+        match self {
+            Self::English => ('.', ','),
+            Self::German => (',', '.'),
+            Self::French => (',', ' '),
+            Self::Swiss => ('.', '\''),
+        }
+    }
+
+    /// `number` rounded to `decimals` places, with its thousands grouped.
+    pub fn format_number(self, number: f32, decimals: usize) -> String {
+        let (decimal_mark, separator) = self.separators();
+        let plain = format!("{number:.decimals$}");
+        let (sign, digits) = match plain.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", plain.as_str()),
+        };
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let mut formatted = sign.to_string();
+        for (index, digit) in whole.chars().enumerate() {
+            if index > 0 && (whole.len() - index) % 3 == 0 {
+                formatted.push(separator);
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(decimal_mark);
+            formatted.push_str(fraction);
+        }
+        formatted
+    }
+}
+
+/// The currency prices are shown in, see [`Settings::format_price`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Currency {
+    #[default]
+    UsDollar,
+    Euro,
+    Pound,
+    Zloty,
+}
+
+impl Currency {
+    fn next(self) -> Self {
+        match self {
+            Self::UsDollar => Self::Euro,
+            Self::Euro => Self::Pound,
+            Self::Pound => Self::Zloty,
+            Self::Zloty => Self::UsDollar,
+        }
+    }
+
+    /// A rough rate to a US dollar to start from, which the rate setting can then adjust.
+    fn usual_rate(self) -> f32 {
+        match self {
+            Self::UsDollar => 1.0,
+            Self::Euro => 0.92,
+            Self::Pound => 0.79,
+            Self::Zloty => 3.95,
+        }
+    }
+}
+
+/// The corner of exported images the watermark goes in, or their center.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+impl WatermarkPosition {
+    fn next(self) -> Self {
+        match self {
+            Self::TopLeft => Self::TopRight,
+            Self::TopRight => Self::BottomLeft,
+            Self::BottomLeft => Self::BottomRight,
+            Self::BottomRight => Self::Center,
+            Self::Center => Self::TopLeft,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Setting {
+    CameraSpeed,
+    MouseSensitivity,
+    InvertX,
+    InvertY,
+    ShadowQuality,
+    Bloom,
+    BloomIntensity,
+    AmbientOcclusion,
+    AmbientOcclusionQuality,
+    Tonemapping,
+    Vsync,
+    UiScale,
+    AutoQuality,
+    Units,
+    Locale,
+    Currency,
+    CurrencyRate,
+    LedBrightnessLimit,
+    WatermarkPosition,
+    WatermarkOpacity,
+}
+
+impl Setting {
+    fn label(self) -> &'static str {
+        match self {
+            Self::CameraSpeed => "Camera speed",
+            Self::MouseSensitivity => "Mouse sensitivity",
+            Self::InvertX => "Invert horizontal orbit",
+            Self::InvertY => "Invert vertical orbit",
+            Self::ShadowQuality => "Shadow quality",
+            Self::Bloom => "Bloom",
+            Self::BloomIntensity => "Bloom intensity",
+            Self::AmbientOcclusion => "Ambient occlusion",
+            Self::AmbientOcclusionQuality => "Ambient occlusion quality",
+            Self::Tonemapping => "Tonemapping",
+            Self::Vsync => "Vsync",
+            Self::UiScale => "UI scale",
+            Self::AutoQuality => "Lower quality when slow",
+            Self::Units => "Units",
+            Self::Locale => "Number format",
+            Self::Currency => "Currency",
+            Self::CurrencyRate => "Per US dollar",
+            Self::LedBrightnessLimit => "RGB brightness limit",
+            Self::WatermarkPosition => "Watermark position",
+            Self::WatermarkOpacity => "Watermark opacity",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" }.to_string();
+        match self {
+            Self::CameraSpeed => format!("{:.2}x", settings.camera_speed),
+            Self::MouseSensitivity => format!("{:.2}x", settings.mouse_sensitivity),
+            Self::InvertX => on_off(settings.invert_x),
+            Self::InvertY => on_off(settings.invert_y),
+            Self::ShadowQuality => format!("{:?}", settings.shadow_quality),
+            Self::Bloom => on_off(settings.bloom),
+            Self::BloomIntensity => format!("{:.0}%", settings.bloom_intensity * 100.0),
+            Self::AmbientOcclusion => on_off(settings.ambient_occlusion),
+            Self::AmbientOcclusionQuality => format!("{:?}", settings.ambient_occlusion_quality),
+            Self::Tonemapping => format!("{:?}", settings.tonemapping),
+            Self::Vsync => on_off(settings.vsync),
+            Self::UiScale => format!("{:.0}%", settings.ui_scale * 100.0),
+            Self::AutoQuality => on_off(settings.auto_quality),
+            Self::Units => format!("{:?}", settings.units),
+            Self::Locale => settings.locale.format_number(1234.5, 1),
+            Self::Currency => format!("{:?}", settings.currency),
+            Self::CurrencyRate => format!("{:.2}", settings.currency_rate),
+            Self::LedBrightnessLimit => format!("{:.1}", settings.led_brightness_limit),
+            Self::WatermarkPosition => format!("{:?}", settings.watermark_position),
+            Self::WatermarkOpacity => format!("{:.0}%", settings.watermark_opacity * 100.0),
+        }
+    }
+
+    /// Whether the setting is a number adjusted with - and +, rather than switched by clicking.
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Self::CameraSpeed
+                | Self::MouseSensitivity
+                | Self::BloomIntensity
+                | Self::UiScale
+                | Self::CurrencyRate
+                | Self::LedBrightnessLimit
+                | Self::WatermarkOpacity
+        )
+    }
+
+    /// Moves a numeric setting a step up or down, or switches any other one to its next value.
+    fn change(self, settings: &mut Settings, up: bool) {
+        let step = |value: &mut f32, step: f32, min: f32, max: f32| {
+            let delta = if up { step } else { -step };
+            // Rounded so repeated steps don't drift off the grid.
+            *value = (((*value + delta) / step).round() * step).clamp(min, max);
+        };
+        match self {
+            Self::CameraSpeed => step(&mut settings.camera_speed, 0.25, 0.25, 4.0),
+            Self::MouseSensitivity => step(&mut settings.mouse_sensitivity, 0.25, 0.25, 4.0),
+            Self::BloomIntensity => step(&mut settings.bloom_intensity, 0.05, 0.05, 1.0),
+            Self::UiScale => step(&mut settings.ui_scale, 0.1, 0.5, 2.0),
+            Self::CurrencyRate => step(&mut settings.currency_rate, 0.01, 0.01, 100.0),
+            Self::LedBrightnessLimit => step(
+                &mut settings.led_brightness_limit,
+                LED_BRIGHTNESS_STEP,
+                LED_BRIGHTNESS_STEP,
+                LED_BRIGHTNESS,
+            ),
+            Self::WatermarkOpacity => step(&mut settings.watermark_opacity, 0.05, 0.05, 1.0),
+            Self::WatermarkPosition => {
+                settings.watermark_position = settings.watermark_position.next();
+            }
+            Self::InvertX => settings.invert_x = !settings.invert_x,
+            Self::InvertY => settings.invert_y = !settings.invert_y,
+            Self::ShadowQuality => settings.shadow_quality = settings.shadow_quality.next(),
+            Self::Bloom => settings.bloom = !settings.bloom,
+            Self::AmbientOcclusion => settings.ambient_occlusion = !settings.ambient_occlusion,
+            Self::AmbientOcclusionQuality => {
+                settings.ambient_occlusion_quality = settings.ambient_occlusion_quality.next();
+            }
+            Self::Tonemapping => settings.tonemapping = settings.tonemapping.next(),
+            Self::Vsync => settings.vsync = !settings.vsync,
+            Self::AutoQuality => settings.auto_quality = !settings.auto_quality,
+            Self::Units => settings.units = settings.units.next(),
+            Self::Locale => settings.locale = settings.locale.next(),
+            Self::Currency => {
+                settings.currency = settings.currency.next();
+                settings.currency_rate = settings.currency.usual_rate();
+            }
+        }
+    }
+}
+
+/// Where the settings are saved, see [`storage`].
+const SETTINGS_KEY: &str = "settings";
+
+/// How much the RGB brightness limits move per step, here and in the RGB panel.
+pub const LED_BRIGHTNESS_STEP: f32 = 0.1;
+
+const SETTINGS: [Setting; 20] = [
+    Setting::CameraSpeed,
+    Setting::MouseSensitivity,
+    Setting::InvertX,
+    Setting::InvertY,
+    Setting::ShadowQuality,
+    Setting::Bloom,
+    Setting::BloomIntensity,
+    Setting::AmbientOcclusion,
+    Setting::AmbientOcclusionQuality,
+    Setting::Tonemapping,
+    Setting::Vsync,
+    Setting::UiScale,
+    Setting::AutoQuality,
+    Setting::Units,
+    Setting::Locale,
+    Setting::Currency,
+    Setting::CurrencyRate,
+    Setting::LedBrightnessLimit,
+    Setting::WatermarkPosition,
+    Setting::WatermarkOpacity,
+];
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum SettingsButton {
+    Decrease(Setting),
+    Increase(Setting),
+    /// Switches the setting to its next value, and shows the current one.
+    Switch(Setting),
+    Keybindings,
+    /// Leaves the level for the main menu.
+    MainMenu,
+    Back,
+}
+
+/// Shows the current value of the setting.
+#[derive(Component, Debug, Clone, Copy)]
+struct SettingValue(Setting);
+
+const LABEL_WIDTH: f32 = 180.0;
+const VALUE_WIDTH: f32 = 70.0;
+
+fn open_settings(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Settings);
+}
+
+fn close_settings(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Closed);
+}
+
+/// Keeps the keys and sticks from moving the scene behind the menus, leaving them only what
+/// the menus are navigated with. Rebinding a key reads it before this.
+fn hold_scene_input(mut actions: ResMut<Actions>, mut keys: ResMut<ButtonInput<KeyCode>>) {
+    let read = std::mem::take(&mut *actions);
+    *actions = Actions {
+        navigate: read.navigate,
+        activate: read.activate,
+        back: read.back,
+        menu: read.menu,
+        ..default()
+    };
+    keys.reset_all();
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    quality: Res<QualityOverride>,
+    mut ui_scale: ResMut<UiScale>,
+    mut directional_shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut point_shadow_map: ResMut<PointLightShadowMap>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    if ui_scale.0 != settings.ui_scale {
+        ui_scale.0 = settings.ui_scale;
+    }
+    // Spot lights take their shadow map size from the directional one.
+    let size = quality.lowered(&settings).shadow_quality.map_size();
+    directional_shadow_map.size = size;
+    point_shadow_map.size = size;
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
+
+fn spawn_settings_screen(mut commands: Commands, screen: Res<State<Screen>>) {
+    let in_level = *screen.get() == Screen::Game;
+    commands
+        .spawn((
+            Name::new("Settings Screen"),
+            DespawnOnExit(Menu::Settings),
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: px(8.0),
+                padding: UiRect::all(px(20.0)),
+                ..default()
+            },
+            BackgroundColor(OVERLAY_BACKGROUND),
+            // Over the scene's panels, which can't be tabbed to while it's open.
+            GlobalZIndex(1),
+            TabGroup::modal(),
+            children![(Text::new("Settings"), TextColor(Color::WHITE))],
+        ))
+        .with_children(|screen| {
+            for setting in SETTINGS {
+                screen
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: px(4.0),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(setting.label()),
+                            TextFont::from_font_size(FONT_SIZE),
+                            TextColor(Color::WHITE),
+                            Node {
+                                width: px(LABEL_WIDTH),
+                                ..default()
+                            },
+                        ));
+                        if setting.is_numeric() {
+                            row.spawn(button("-", SettingsButton::Decrease(setting)));
+                            row.spawn((
+                                SettingValue(setting),
+                                Text::default(),
+                                TextFont::from_font_size(FONT_SIZE),
+                                TextColor(Color::WHITE),
+                                TextLayout::new_with_justify(Justify::Center),
+                                Node {
+                                    width: px(VALUE_WIDTH),
+                                    ..default()
+                                },
+                            ));
+                            row.spawn(button("+", SettingsButton::Increase(setting)));
+                        } else {
+                            row.spawn((
+                                button("", SettingsButton::Switch(setting)),
+                                SettingValue(setting),
+                            ));
+                        }
+                    });
+            }
+            screen.spawn(button("Key bindings", SettingsButton::Keybindings));
+            if in_level {
+                screen.spawn(button("Main menu", SettingsButton::MainMenu));
+            }
+            screen.spawn(button("Back (Esc)", SettingsButton::Back));
+        });
+}
+
+fn button(label: impl Into<String>, action: SettingsButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn update_value_labels(
+    settings: Res<Settings>,
+    mut value_query: Query<(&SettingValue, &mut Text)>,
+) {
+    for (value, mut text) in &mut value_query {
+        text.0 = value.0.value(&settings);
+    }
+}
+
+fn activate_settings_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    mut next_menu: ResMut<NextState<Menu>>,
+    button_query: Query<&SettingsButton>,
+    mut settings: ResMut<Settings>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(SettingsButton::Decrease(setting)) => setting.change(&mut settings, false),
+        Ok(SettingsButton::Increase(setting) | SettingsButton::Switch(setting)) => {
+            setting.change(&mut settings, true)
+        }
+        Ok(SettingsButton::Keybindings) => next_menu.set(Menu::Keybindings),
+        Ok(SettingsButton::MainMenu) => commands.run_system_cached(crate::open_main_menu),
+        Ok(SettingsButton::Back) => commands.run_system_cached(close_settings),
+        Err(_) => {}
+    }
+}
+
+/// Saves the settings, as leaving the settings screen does.
+pub fn save_settings(settings: Res<Settings>) {
+    let contents = match ron::ser::to_string_pretty(&*settings, default()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            error!("Could not serialize the settings: {error}");
+            return;
+        }
+    };
+    if let Err(error) = storage::write(SETTINGS_KEY, &contents) {
+        error!("Could not save the settings: {error}");
+    }
+}
+
+fn load_settings() -> Option<Settings> {
+    let contents = match storage::read(SETTINGS_KEY) {
+        Ok(contents) => contents?,
+        Err(error) => {
+            error!("Could not read the settings: {error}");
+            return None;
+        }
+    };
+    match ron::de::from_str(&contents) {
+        Ok(settings) => Some(settings),
+        Err(error) => {
+            error!("Could not parse the settings: {error}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_in_each_unit() {
+        let locale = Locale::English;
+        assert_eq!(Units::Millimeters.format(12.0, locale), "12 mm");
+        assert_eq!(Units::Millimeters.format(12.54, locale), "12.5 mm");
+        assert_eq!(Units::Millimeters.format(11.98, locale), "12 mm");
+        assert_eq!(Units::Inches.format(25.4, locale), "1.00 in");
+        assert_eq!(
+            Units::Millimeters.format_size([450.0, 210.0, 460.0], locale),
+            "450 x 210 x 460 mm"
+        );
+        assert_eq!(Units::Inches.format(12.5, Locale::German), "0,49 in");
+    }
+
+    #[test]
+    fn numbers_in_each_locale() {
+        assert_eq!(Locale::English.format_number(1234567.5, 1), "1,234,567.5");
+        assert_eq!(Locale::German.format_number(1234.5, 2), "1.234,50");
+        assert_eq!(Locale::French.format_number(-1234.0, 0), "-1 234");
+        assert_eq!(Locale::Swiss.format_number(999.5, 1), "999.5");
+    }
+
+    #[test]
+    fn prices_in_the_currency() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.format_price(12.5), "$12.50");
+        assert_eq!(settings.format_price(1012.5), "$1,012.50");
+        settings.currency = Currency::Euro;
+        settings.currency_rate = 0.5;
+        settings.locale = Locale::German;
+        assert_eq!(settings.format_price(12.5), "6,25 €");
+    }
+}
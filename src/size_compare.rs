@@ -0,0 +1,202 @@
+//! An overlay comparing the sizes of two parts, like two graphics cards, by drawing their
+//! bounding boxes on top of each other with their dimensions labeled.
+//!
+//! Parts are picked from the command palette, from the whole catalog or as the selected part,
+//! and needn't be installed; only their catalog sizes are drawn, so no model is loaded. The
+//! boxes stand on the same floor, centered where the first part mounts. Picking a third part
+//! replaces the one picked first.
+
+use bevy::{
+    color::palettes::css::{AQUA, ORANGE},
+    math::bounding::Aabb3d,
+    prelude::*,
+};
+
+use crate::{
+    Level, LevelAssets, Screen,
+    cases::{ActiveCase, CaseManifest},
+    command_palette::{AddPaletteCommand, PaletteCommands},
+    compat::{part_bounds, part_center},
+    labels::BillboardLabel,
+    part_selection::SelectedPart,
+    parts::{InstalledPart, PartCatalog, mount_point},
+    settings::Settings,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SizeComparison>();
+    app.add_systems(OnEnter(Screen::Game), add_compare_commands.run_if(run_once));
+    app.add_systems(OnExit(Screen::Game), clear_comparison);
+    app.add_systems(
+        Update,
+        (
+            update_compared_boxes.run_if(
+                resource_changed::<SizeComparison>
+                    .or(resource_changed::<Settings>)
+                    .or(any_match_filter::<Added<Level>>),
+            ),
+            draw_compared_boxes,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command(
+        "Compare sizes: add the selected part",
+        compare_selected_part,
+    );
+    app.add_palette_command("Compare sizes: clear", clear_comparison);
+}
+
+/// The catalog ids of the parts being compared, the one picked first first.
+#[derive(Resource, Debug, Default)]
+struct SizeComparison {
+    parts: Vec<String>,
+}
+
+impl SizeComparison {
+    fn add(&mut self, id: String) {
+        if self.parts.contains(&id) {
+            return;
+        }
+        if self.parts.len() == 2 {
+            self.parts.remove(0);
+        }
+        self.parts.push(id);
+    }
+}
+
+/// One of the compared boxes, as a child of the level, where its label goes.
+#[derive(Component, Debug, Clone, Copy)]
+struct ComparedBox {
+    /// The corners of the box, in the level's space.
+    min: Vec3,
+    max: Vec3,
+    color: Srgba,
+}
+
+const LABEL_GROUP: &str = "Size comparison";
+const BOX_COLORS: [Srgba; 2] = [AQUA, ORANGE];
+
+/// Lists comparing each catalog part in the command palette.
+fn add_compare_commands(
+    mut commands: Commands,
+    mut palette_commands: ResMut<PaletteCommands>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    for spec in &catalog.parts {
+        let id = spec.id.clone();
+        let system = commands.register_system(move |mut comparison: ResMut<SizeComparison>| {
+            comparison.add(id.clone());
+        });
+        palette_commands.add(format!("Compare sizes: {}", spec.name), system);
+    }
+}
+
+fn compare_selected_part(
+    selected: Res<SelectedPart>,
+    mut comparison: ResMut<SizeComparison>,
+    parent_query: Query<&ChildOf>,
+    part_query: Query<&InstalledPart>,
+) {
+    let part = selected.0.and_then(|selected| {
+        std::iter::once(selected)
+            .chain(parent_query.iter_ancestors(selected))
+            .find_map(|entity| part_query.get(entity).ok())
+    });
+    match part {
+        Some(part) => comparison.add(part.id.clone()),
+        None => info!("Select an installed part to compare first"),
+    }
+}
+
+fn clear_comparison(mut comparison: ResMut<SizeComparison>) {
+    comparison.parts.clear();
+}
+
+/// Respawns the boxes for the parts being compared, labeled with their names and sizes.
+fn update_compared_boxes(
+    mut commands: Commands,
+    comparison: Res<SizeComparison>,
+    settings: Res<Settings>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    manifests: Res<Assets<CaseManifest>>,
+    active_case: Res<ActiveCase>,
+    installed_query: Query<&InstalledPart>,
+    box_query: Query<Entity, With<ComparedBox>>,
+    level_query: Query<Entity, With<Level>>,
+) {
+    for entity in &box_query {
+        commands.entity(entity).despawn();
+    }
+    let (Ok(level), Some(catalog)) = (level_query.single(), catalogs.get(&level_assets.catalog))
+    else {
+        return;
+    };
+    let specs: Vec<_> = comparison
+        .parts
+        .iter()
+        .filter_map(|id| catalog.get(id))
+        .collect();
+    let Some(first) = specs.first() else {
+        return;
+    };
+
+    // Where the first part is mounted, or would be.
+    let case = manifests
+        .get(&level_assets.cases)
+        .and_then(|manifest| manifest.get(&active_case.0));
+    let mount = installed_query
+        .iter()
+        .find(|part| part.id == first.id)
+        .map(|part| part.mount)
+        .unwrap_or_else(|| {
+            let taken: Vec<_> = installed_query.iter().map(|part| part.mount).collect();
+            mount_point(first, case, &taken)
+        });
+    let floor = part_bounds(first, mount).0.y;
+
+    for (index, (spec, color)) in specs.iter().zip(BOX_COLORS).enumerate() {
+        let half_size = Vec3::from(spec.size) / 2.0;
+        let center = part_center(first, mount).with_y(floor + half_size.y);
+        let (min, max) = (center - half_size, center + half_size);
+        // The labels go on opposite top corners, so they don't cover each other.
+        let corner = if index == 0 {
+            Vec3::new(min.x, max.y, min.z)
+        } else {
+            max
+        };
+        commands.spawn((
+            Name::new(format!("Size Comparison \"{}\"", spec.name)),
+            ComparedBox { min, max, color },
+            Transform::from_translation(corner),
+            BillboardLabel {
+                text: format!("{}: {}", spec.name, settings.format_size(spec.size)),
+                group: LABEL_GROUP.to_string(),
+                offset: Vec3::ZERO,
+            },
+            ChildOf(level),
+        ));
+    }
+}
+
+fn draw_compared_boxes(
+    mut gizmos: Gizmos,
+    box_query: Query<(&ComparedBox, &ChildOf)>,
+    level_query: Query<&GlobalTransform, With<Level>>,
+) {
+    for (compared, child_of) in &box_query {
+        let Ok(level) = level_query.get(child_of.parent()) else {
+            continue;
+        };
+        gizmos.aabb_3d(
+            Aabb3d::from_min_max(compared.min, compared.max),
+            *level,
+            compared.color,
+        );
+    }
+}
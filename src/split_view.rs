@@ -0,0 +1,474 @@
+//! A split view, with the perspective orbit camera next to orthographic views of the case.
+//!
+//! Press J, or use the panel on the right, to step between the single view, two views side by
+//! side and four views. With four the perspective view keeps the left two thirds of the window
+//! and three orthographic views are stacked on the right, since the UI is laid out over the
+//! orbit camera's part, as in stereo mode. Which way each orthographic view looks at the case
+//! is picked in the panel.
+//!
+//! Every orthographic view has a camera controller of its own: dragging with any mouse button
+//! over it pans it and scrolling zooms it, while the orbit camera only follows the mouse over
+//! its own part of the window. A drag stays with the view it started in.
+
+use bevy::{
+    camera::{ScalingMode, Viewport},
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    input_focus::tab_navigation::TabGroup,
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    OrbitCamera, Screen,
+    blueprint::BlueprintView,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    orbit_camera_system,
+    stereo::{StereoMode, layout_stereo_viewports},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SplitView>();
+    app.add_systems(OnEnter(Screen::Game), spawn_split_view_panel);
+    app.add_systems(OnExit(Screen::Game), release_mouse);
+    app.add_systems(
+        Update,
+        (
+            cycle_layouts.run_if(key_just_pressed(KeyAction::SplitView)),
+            leave_split_view_for_stereo,
+            sync_view_cameras,
+            layout_viewports.after(layout_stereo_viewports),
+            track_mouse.before(orbit_camera_system),
+            control_view_cameras,
+            update_button_labels.run_if(
+                resource_changed::<SplitView>.or(any_match_filter::<Added<SplitViewButton>>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_split_view_button);
+    app.add_palette_command("Split view: cycle the layouts", cycle_layouts);
+}
+
+#[derive(Resource, Debug)]
+pub struct SplitView {
+    layout: SplitLayout,
+    /// Which way each orthographic view looks, by slot.
+    views: [BlueprintView; 3],
+    /// The slot of the orthographic view the mouse is over, or dragging in.
+    mouse_slot: Option<usize>,
+}
+
+impl Default for SplitView {
+    fn default() -> Self {
+        Self {
+            layout: SplitLayout::Single,
+            views: [
+                BlueprintView::Front,
+                BlueprintView::Side,
+                BlueprintView::Top,
+            ],
+            mouse_slot: None,
+        }
+    }
+}
+
+impl SplitView {
+    /// Whether the mouse is left to one of the orthographic views rather than the orbit camera.
+    pub fn has_mouse(&self) -> bool {
+        self.mouse_slot.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum SplitLayout {
+    Single,
+    TwoUp,
+    FourUp,
+}
+
+impl SplitLayout {
+    const ALL: [Self; 3] = [Self::Single, Self::TwoUp, Self::FourUp];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Single => "Single",
+            Self::TwoUp => "2-up",
+            Self::FourUp => "4-up",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Single => Self::TwoUp,
+            Self::TwoUp => Self::FourUp,
+            Self::FourUp => Self::Single,
+        }
+    }
+
+    /// How many orthographic views are shown next to the perspective one.
+    fn view_count(self) -> usize {
+        match self {
+            Self::Single => 0,
+            Self::TwoUp => 1,
+            Self::FourUp => 3,
+        }
+    }
+
+    /// The orbit camera's viewport and those of the orthographic views, or `None` while the
+    /// window is too small to split.
+    fn viewports(self, window_size: UVec2) -> Option<(Viewport, Vec<Viewport>)> {
+        let viewport = |position: UVec2, size: UVec2| Viewport {
+            physical_position: position,
+            physical_size: size,
+            ..default()
+        };
+        let views = self.view_count() as u32;
+        let main_width = match self {
+            Self::Single => return None,
+            Self::TwoUp => window_size.x / 2,
+            Self::FourUp => window_size.x * 2 / 3,
+        };
+        let view_size = UVec2::new(window_size.x - main_width, window_size.y / views);
+        if main_width == 0 || view_size.cmpeq(UVec2::ZERO).any() {
+            return None;
+        }
+
+        let main = viewport(UVec2::ZERO, UVec2::new(main_width, window_size.y));
+        let views = (0..views)
+            .map(|slot| viewport(UVec2::new(main_width, slot * view_size.y), view_size))
+            .collect();
+        Some((main, views))
+    }
+}
+
+/// The camera of an orthographic view, with the state of its controller.
+#[derive(Component, Debug)]
+struct ViewCamera {
+    slot: usize,
+    /// The point the view is centered on.
+    target: Vec3,
+    /// How much of the scene, in world units, fits vertically into the view.
+    height: f32,
+    /// The height the view is zooming towards; `height` follows it smoothly.
+    desired_height: f32,
+}
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum SplitViewButton {
+    Layout(SplitLayout),
+    /// Turns the orthographic view in the slot to look from the next side.
+    View(usize),
+}
+
+/// Far enough out to stay clear of the case; an orthographic view doesn't shrink with distance.
+const CAMERA_DISTANCE: f32 = 2000.0;
+/// How much of the scene, in world units, fits vertically into a view at first.
+const VIEW_HEIGHT: f32 = 800.0;
+const MIN_VIEW_HEIGHT: f32 = 100.0;
+const MAX_VIEW_HEIGHT: f32 = 4000.0;
+/// Factor the view height is divided by per scroll wheel line.
+const ZOOM_STEP: f32 = 1.1;
+/// Touchpads scroll in pixels rather than lines.
+const SCROLL_PIXELS_PER_LINE: f32 = 16.0;
+/// How quickly `height` catches up with `desired_height`. Higher is snappier.
+const ZOOM_SMOOTHING: f32 = 12.0;
+const VIEW_BACKGROUND: Color = Color::srgb(0.08, 0.09, 0.12);
+/// The text of the button for the layout that's shown.
+const ACTIVE_TEXT: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// The views are despawned with the level, and the orbit camera gets the mouse back.
+fn release_mouse(mut split_view: ResMut<SplitView>) {
+    split_view.mouse_slot = None;
+}
+
+fn cycle_layouts(mut split_view: ResMut<SplitView>, mut stereo_mode: ResMut<StereoMode>) {
+    split_view.layout = split_view.layout.next();
+    if split_view.layout != SplitLayout::Single {
+        *stereo_mode = StereoMode::Off;
+    }
+}
+
+/// Both split the window around the orbit camera, so turning stereo on ends the split view.
+fn leave_split_view_for_stereo(mut split_view: ResMut<SplitView>, stereo_mode: Res<StereoMode>) {
+    if stereo_mode.is_changed() && *stereo_mode != StereoMode::Off {
+        split_view.layout = SplitLayout::Single;
+    }
+}
+
+/// Runs every frame rather than on change, so the views come back after a level respawn.
+fn sync_view_cameras(
+    mut commands: Commands,
+    split_view: Res<SplitView>,
+    orbit_query: Query<&OrbitCamera>,
+    camera_query: Query<(Entity, &ViewCamera)>,
+) {
+    let count = split_view.layout.view_count();
+    let mut shown = [false; 3];
+    for (entity, camera) in &camera_query {
+        if camera.slot < count {
+            shown[camera.slot] = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let target = orbit_query
+        .single()
+        .map_or(Vec3::ZERO, |orbit| orbit.target);
+    for slot in (0..count).filter(|&slot| !shown[slot]) {
+        commands.spawn((
+            Name::new(format!("Split View Camera {}", slot + 1)),
+            ViewCamera {
+                slot,
+                target,
+                height: VIEW_HEIGHT,
+                desired_height: VIEW_HEIGHT,
+            },
+            DespawnOnExit(Screen::Game),
+            Camera3d::default(),
+            Camera {
+                // The orbit camera has to stay the one the UI renders on top of.
+                order: -2 - slot as isize,
+                clear_color: ClearColorConfig::Custom(VIEW_BACKGROUND),
+                // Hidden until it has a viewport, rather than covering the whole window.
+                is_active: false,
+                ..default()
+            },
+            Projection::from(OrthographicProjection {
+                scaling_mode: ScalingMode::FixedVertical {
+                    viewport_height: VIEW_HEIGHT,
+                },
+                far: CAMERA_DISTANCE * 2.0,
+                ..OrthographicProjection::default_3d()
+            }),
+            Transform::default(),
+        ));
+    }
+}
+
+fn layout_viewports(
+    split_view: Res<SplitView>,
+    stereo_mode: Res<StereoMode>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut orbit_query: Query<&mut Camera, (With<OrbitCamera>, Without<ViewCamera>)>,
+    mut camera_query: Query<(&ViewCamera, &mut Camera)>,
+) {
+    let viewports = split_view.layout.viewports(window.physical_size());
+    // Stereo mode lays out the orbit camera otherwise.
+    if viewports.is_some() || *stereo_mode == StereoMode::Off {
+        for mut camera in &mut orbit_query {
+            set_viewport(
+                &mut camera,
+                viewports.as_ref().map(|(main, _)| main.clone()),
+            );
+        }
+    }
+
+    for (view_camera, mut camera) in &mut camera_query {
+        let viewport = viewports
+            .as_ref()
+            .and_then(|(_, views)| views.get(view_camera.slot).cloned());
+        camera.is_active = viewport.is_some();
+        if viewport.is_some() {
+            set_viewport(&mut camera, viewport);
+        }
+    }
+}
+
+/// Only touches the camera when the viewport actually changes, to keep change detection quiet.
+fn set_viewport(camera: &mut Mut<Camera>, viewport: Option<Viewport>) {
+    let unchanged = match (&camera.viewport, &viewport) {
+        (None, None) => true,
+        (Some(current), Some(new)) => {
+            current.physical_position == new.physical_position
+                && current.physical_size == new.physical_size
+        }
+        _ => false,
+    };
+    if !unchanged {
+        camera.viewport = viewport;
+    }
+}
+
+/// Finds the orthographic view under the mouse, keeping the one a drag started in until it
+/// ends.
+fn track_mouse(
+    mut split_view: ResMut<SplitView>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&ViewCamera, &Camera)>,
+) {
+    let dragging =
+        mouse_buttons.any_pressed([MouseButton::Left, MouseButton::Right, MouseButton::Middle])
+            && mouse_buttons.get_just_pressed().next().is_none();
+    if dragging {
+        return;
+    }
+
+    let mouse_slot = window.cursor_position().and_then(|cursor| {
+        camera_query
+            .iter()
+            .filter(|(_, camera)| camera.is_active)
+            .find(|(_, camera)| {
+                camera
+                    .logical_viewport_rect()
+                    .is_some_and(|rect| rect.contains(cursor))
+            })
+            .map(|(view_camera, _)| view_camera.slot)
+    });
+    if split_view.mouse_slot != mouse_slot {
+        split_view.mouse_slot = mouse_slot;
+    }
+}
+
+/// Pans the view the mouse is dragging in along the screen and zooms it with the scroll wheel,
+/// then points every view at its target from its side.
+fn control_view_cameras(
+    time: Res<Time>,
+    split_view: Res<SplitView>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    mut camera_query: Query<(&mut ViewCamera, &Camera, &mut Transform, &mut Projection)>,
+) {
+    for (mut view_camera, camera, mut transform, mut projection) in &mut camera_query {
+        let view = split_view.views[view_camera.slot];
+        let (toward_camera, up) = view.toward_camera_and_up();
+
+        if split_view.mouse_slot == Some(view_camera.slot) {
+            // Pans by as much as the mouse moves, so the scene stays under it.
+            let viewport_height = camera
+                .logical_viewport_size()
+                .map_or(1.0, |size| size.y.max(1.0));
+            if mouse_buttons.any_pressed([
+                MouseButton::Left,
+                MouseButton::Right,
+                MouseButton::Middle,
+            ]) {
+                let pan = mouse_motion.delta * view_camera.height / viewport_height;
+                let right = up.cross(toward_camera);
+                view_camera.target += up * pan.y - right * pan.x;
+            }
+
+            let scroll = match mouse_scroll.unit {
+                MouseScrollUnit::Line => mouse_scroll.delta.y,
+                MouseScrollUnit::Pixel => mouse_scroll.delta.y / SCROLL_PIXELS_PER_LINE,
+            };
+            view_camera.desired_height = (view_camera.desired_height / ZOOM_STEP.powf(scroll))
+                .clamp(MIN_VIEW_HEIGHT, MAX_VIEW_HEIGHT);
+        }
+        let desired_height = view_camera.desired_height;
+        view_camera
+            .height
+            .smooth_nudge(&desired_height, ZOOM_SMOOTHING, time.delta_secs());
+
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scaling_mode = ScalingMode::FixedVertical {
+                viewport_height: view_camera.height,
+            };
+        }
+        let target_transform =
+            Transform::from_translation(view_camera.target + toward_camera * CAMERA_DISTANCE)
+                .looking_at(view_camera.target, up);
+        transform.set_if_neq(target_transform);
+    }
+}
+
+fn spawn_split_view_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Split View Panel"),
+            DespawnOnExit(Screen::Game),
+            Node {
+                position_type: PositionType::Absolute,
+                top: percent(25.0),
+                right: px(5.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: px(4.0),
+                padding: UiRect::all(px(6.0)),
+                ..default()
+            },
+            BackgroundColor(PANEL_BACKGROUND),
+            TabGroup::new(9),
+            children![(Text::new("Split view (J)"), TextColor(Color::WHITE))],
+        ))
+        .with_children(|panel| {
+            for layout in SplitLayout::ALL {
+                panel.spawn(button(SplitViewButton::Layout(layout)));
+            }
+            for slot in 0..3 {
+                panel.spawn(button(SplitViewButton::View(slot)));
+            }
+        });
+}
+
+fn button(action: SplitViewButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::default(),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn update_button_labels(
+    split_view: Res<SplitView>,
+    mut button_query: Query<(&SplitViewButton, &mut Text, &mut TextColor, &mut Node)>,
+) {
+    for (button, mut text, mut color, mut node) in &mut button_query {
+        match button {
+            SplitViewButton::Layout(layout) => {
+                text.0 = layout.label().to_string();
+                color.0 = if *layout == split_view.layout {
+                    ACTIVE_TEXT
+                } else {
+                    Color::WHITE
+                };
+            }
+            SplitViewButton::View(slot) => {
+                text.0 = format!("View {}: {}", slot + 2, split_view.views[*slot].label());
+                // Only the views in the layout can be turned.
+                node.display = if *slot < split_view.layout.view_count() {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+            }
+        }
+    }
+}
+
+fn activate_split_view_button(
+    activate: On<Activate>,
+    button_query: Query<&SplitViewButton>,
+    mut split_view: ResMut<SplitView>,
+    mut stereo_mode: ResMut<StereoMode>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(SplitViewButton::Layout(layout)) => {
+            split_view.layout = *layout;
+            if *layout != SplitLayout::Single {
+                *stereo_mode = StereoMode::Off;
+            }
+        }
+        Ok(SplitViewButton::View(slot)) => {
+            let views = &BlueprintView::ALL;
+            let current = views
+                .iter()
+                .position(|view| *view == split_view.views[*slot])
+                .unwrap_or(0);
+            split_view.views[*slot] = views[(current + 1) % views.len()];
+        }
+        Err(_) => {}
+    }
+}
@@ -0,0 +1,156 @@
+//! A side-by-side stereoscopic render mode, for judging depth without VR hardware.
+//!
+//! The orbit camera keeps rendering the left eye and a second camera, offset to the right,
+//! renders the right eye. Cross-eye mode swaps the halves for free-viewing cross-eyed.
+
+use bevy::{camera::Viewport, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    OrbitCamera, Screen,
+    blueprint::apply_blueprint_view,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<StereoMode>();
+    app.add_systems(
+        Update,
+        (
+            cycle_stereo_mode.run_if(key_just_pressed(KeyAction::CycleStereo)),
+            sync_second_eye,
+            layout_stereo_viewports,
+            follow_orbit_camera.after(apply_blueprint_view),
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Cycle stereo mode", cycle_stereo_mode);
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Copy, Eq, PartialEq, Default)]
+#[reflect(Resource)]
+pub enum StereoMode {
+    #[default]
+    Off,
+    /// Left eye on the left half, for parallel viewing.
+    Parallel,
+    /// Left eye on the right half, for cross-eyed viewing.
+    CrossEye,
+}
+
+impl StereoMode {
+    fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Parallel,
+            Self::Parallel => Self::CrossEye,
+            Self::CrossEye => Self::Off,
+        }
+    }
+}
+
+/// The right-eye camera.
+#[derive(Component)]
+pub struct SecondEye;
+
+/// Distance between the eyes in world units (millimeters), roughly a human's.
+const EYE_SEPARATION: f32 = 65.0;
+
+fn cycle_stereo_mode(mut mode: ResMut<StereoMode>) {
+    *mode = mode.next();
+}
+
+/// Runs every frame rather than on change, so the eye comes back after a level respawn.
+fn sync_second_eye(
+    mut commands: Commands,
+    mode: Res<StereoMode>,
+    eye_query: Query<Entity, With<SecondEye>>,
+) {
+    match (*mode, eye_query.single()) {
+        (StereoMode::Off, Ok(eye)) => {
+            commands.entity(eye).despawn();
+        }
+        (StereoMode::Parallel | StereoMode::CrossEye, Err(_)) => {
+            commands.spawn((
+                Name::new("Second Eye Camera"),
+                SecondEye,
+                DespawnOnExit(Screen::Game),
+                Camera3d::default(),
+                Camera {
+                    // The orbit camera has to stay the one the UI renders on top of.
+                    order: -1,
+                    ..default()
+                },
+                Transform::default(),
+            ));
+        }
+        _ => {}
+    }
+}
+
+pub fn layout_stereo_viewports(
+    mode: Res<StereoMode>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut orbit_query: Query<&mut Camera, (With<OrbitCamera>, Without<SecondEye>)>,
+    mut eye_query: Query<&mut Camera, With<SecondEye>>,
+) {
+    let window_size = window.physical_size();
+    let (left_eye, right_eye) = match *mode {
+        StereoMode::Off => (None, None),
+        StereoMode::Parallel => (
+            half_viewport(window_size, false),
+            half_viewport(window_size, true),
+        ),
+        StereoMode::CrossEye => (
+            half_viewport(window_size, true),
+            half_viewport(window_size, false),
+        ),
+    };
+
+    for mut camera in &mut orbit_query {
+        set_viewport(&mut camera, left_eye.clone());
+    }
+    for mut camera in &mut eye_query {
+        set_viewport(&mut camera, right_eye.clone());
+    }
+}
+
+/// The left or right half of the window, or `None` while the window is minimized.
+fn half_viewport(window_size: UVec2, right: bool) -> Option<Viewport> {
+    let half_width = window_size.x / 2;
+    (half_width > 0 && window_size.y > 0).then(|| Viewport {
+        physical_position: UVec2::new(if right { half_width } else { 0 }, 0),
+        physical_size: UVec2::new(half_width, window_size.y),
+        ..default()
+    })
+}
+
+/// Only touches the camera when the viewport actually changes, to keep change detection quiet.
+fn set_viewport(camera: &mut Mut<Camera>, viewport: Option<Viewport>) {
+    let unchanged = match (&camera.viewport, &viewport) {
+        (None, None) => true,
+        (Some(current), Some(new)) => {
+            current.physical_position == new.physical_position
+                && current.physical_size == new.physical_size
+        }
+        _ => false,
+    };
+    if !unchanged {
+        camera.viewport = viewport;
+    }
+}
+
+/// Places the right eye next to the orbit camera, converging on the orbit target.
+fn follow_orbit_camera(
+    orbit_query: Query<(&OrbitCamera, &Transform), Without<SecondEye>>,
+    mut eye_query: Query<&mut Transform, With<SecondEye>>,
+) {
+    let Ok((orbit, orbit_transform)) = orbit_query.single() else {
+        return;
+    };
+
+    for mut transform in &mut eye_query {
+        let position = orbit_transform.translation + orbit_transform.right() * EYE_SEPARATION;
+        *transform = Transform::from_translation(position).looking_at(orbit.target, Vec3::Y);
+    }
+}
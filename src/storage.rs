@@ -0,0 +1,109 @@
+//! Reading and writing small text files that persist between sessions: files in the working
+//! directory on native, localStorage on the web.
+//!
+//! Each file is named by a key, so `"settings"` is `settings.ron` on native and
+//! `pc_case_visualizer.settings` in localStorage. Missing files read as `None`. Keys can group
+//! files in a folder, like `"workspace/Option A"`, whose files [`list`] finds.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{fs, io, path::Path};
+
+    fn path(key: &str) -> String {
+        format!("{key}.ron")
+    }
+
+    pub fn read(key: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(path(key)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn write(key: &str, contents: &str) -> io::Result<()> {
+        let path = path(key);
+        if let Some(folder) = Path::new(&path).parent() {
+            fs::create_dir_all(folder)?;
+        }
+        fs::write(path, contents)
+    }
+
+    pub fn remove(key: &str) -> io::Result<()> {
+        match fs::remove_file(path(key)) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    pub fn list(folder: &str) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(folder) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+        let mut names = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "ron")
+                && let Some(name) = path.file_stem().and_then(|name| name.to_str())
+            {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    fn item_key(key: &str) -> String {
+        format!("pc_case_visualizer.{key}")
+    }
+
+    fn local_storage() -> Result<web_sys::Storage, String> {
+        web_sys::window()
+            .ok_or("no window")?
+            .local_storage()
+            .map_err(|error| format!("{error:?}"))?
+            .ok_or_else(|| "localStorage is unavailable".to_string())
+    }
+
+    pub fn read(key: &str) -> Result<Option<String>, String> {
+        local_storage()?
+            .get_item(&item_key(key))
+            .map_err(|error| format!("{error:?}"))
+    }
+
+    pub fn write(key: &str, contents: &str) -> Result<(), String> {
+        local_storage()?
+            .set_item(&item_key(key), contents)
+            .map_err(|error| format!("{error:?}"))
+    }
+
+    pub fn remove(key: &str) -> Result<(), String> {
+        local_storage()?
+            .remove_item(&item_key(key))
+            .map_err(|error| format!("{error:?}"))
+    }
+
+    pub fn list(folder: &str) -> Result<Vec<String>, String> {
+        let storage = local_storage()?;
+        let prefix = item_key(&format!("{folder}/"));
+        let length = storage.length().map_err(|error| format!("{error:?}"))?;
+        Ok((0..length)
+            .filter_map(|index| storage.key(index).ok().flatten())
+            .filter_map(|key| Some(key.strip_prefix(&prefix)?.to_string()))
+            .collect())
+    }
+}
+
+pub use platform::{read, remove, write};
+
+/// The names of the files in `folder`, as keys relative to it, sorted.
+pub fn list(folder: &str) -> Result<Vec<String>, impl std::fmt::Display> {
+    platform::list(folder).map(|mut names| {
+        names.sort();
+        names
+    })
+}
@@ -0,0 +1,357 @@
+//! Comparing the cooling of two configurations, to see whether adding a fan or speeding them up
+//! is worth the noise.
+//!
+//! From the command palette, snapshot the current build as A, change its fans or parts, and
+//! snapshot it as B. A table then shows, side by side, what the CPU, the GPU, the VRM and each
+//! RGB zone settle at under full load in the simulation (see [`thermals`](crate::thermals)),
+//! heated by the installed parts' power draw, how loud the fans are and how many there are,
+//! with the difference from A to B. The snapshots use the speeds set on the fan sliders, not
+//! the ones the rotors are still easing to.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    LevelAssets, Screen,
+    command_palette::AddPaletteCommand,
+    fans::{FanSpeeds, Rotor, noise_level},
+    parts::{InstalledPart, PartCatalog, PartSpec},
+    rgb::RgbZone,
+    thermals::{ComponentTemperatures, ThermalModel, group_drafts, zone_group},
+    ui_navigation::{FONT_SIZE, OVERLAY_BACKGROUND},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ThermalComparison>();
+    app.add_systems(OnEnter(Screen::Game), spawn_comparison_panel);
+    app.add_systems(OnExit(Screen::Game), clear_comparison);
+    app.add_systems(
+        Update,
+        update_comparison_panel
+            .run_if(
+                resource_changed::<ThermalComparison>
+                    .or(any_match_filter::<Added<ComparisonTable>>),
+            )
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command(
+        "Thermal comparison: snapshot as A",
+        |mut commands: Commands| {
+            commands.run_system_cached_with(take_snapshot, 0);
+        },
+    );
+    app.add_palette_command(
+        "Thermal comparison: snapshot as B",
+        |mut commands: Commands| {
+            commands.run_system_cached_with(take_snapshot, 1);
+        },
+    );
+    app.add_palette_command("Thermal comparison: clear", clear_comparison);
+}
+
+/// The configurations being compared, A and B.
+#[derive(Resource, Debug, Default)]
+struct ThermalComparison {
+    snapshots: [Option<ThermalSnapshot>; 2],
+}
+
+/// What the simulation makes of one configuration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThermalSnapshot {
+    pub components: ComponentTemperatures,
+    /// Degrees Celsius each zone settles at under full load, by zone name.
+    pub zones: BTreeMap<String, f32>,
+    /// In dB(A), if any fan spins.
+    pub noise: Option<f32>,
+    pub fans: usize,
+}
+
+/// A row of the comparison table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonRow {
+    pub label: String,
+    /// In A and in B, if the snapshot has it.
+    pub values: [Option<f32>; 2],
+    pub unit: &'static str,
+    /// Whether a lower value is the better one, which colors the difference.
+    pub lower_is_better: bool,
+}
+
+#[derive(Component)]
+struct ComparisonTable;
+
+const BETTER_COLOR: Color = Color::srgb(0.5, 1.0, 0.5);
+const WORSE_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+const HEADER_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+/// Width of a table column, in pixels.
+const COLUMN_WIDTH: f32 = 90.0;
+/// The workload the snapshots are simulated at.
+const FULL_LOAD: f32 = 1.0;
+
+/// The rows comparing `snapshots`: one per component and zone in either, then the noise and the
+/// fan count.
+pub fn comparison_rows(snapshots: &[Option<ThermalSnapshot>; 2]) -> Vec<ComparisonRow> {
+    let zones: BTreeSet<&String> = snapshots
+        .iter()
+        .flatten()
+        .flat_map(|snapshot| snapshot.zones.keys())
+        .collect();
+    let value = |get: &dyn Fn(&ThermalSnapshot) -> Option<f32>| {
+        [0, 1].map(|index| snapshots[index].as_ref().and_then(get))
+    };
+
+    let components: [(&str, &dyn Fn(&ComponentTemperatures) -> Option<f32>); 3] = [
+        ("CPU", &|components| components.cpu),
+        ("GPU", &|components| components.gpu),
+        ("VRM", &|components| components.vrm),
+    ];
+    let mut rows: Vec<_> = components
+        .into_iter()
+        .map(|(label, get)| {
+            (
+                label.to_string(),
+                value(&|snapshot| get(&snapshot.components)),
+            )
+        })
+        .chain(zones.into_iter().map(|zone| {
+            let values = value(&|snapshot| snapshot.zones.get(zone).copied());
+            (zone.clone(), values)
+        }))
+        .filter(|(_, values)| values.iter().any(Option::is_some))
+        .map(|(label, values)| ComparisonRow {
+            label,
+            values,
+            unit: "°C",
+            lower_is_better: true,
+        })
+        .collect();
+    rows.push(ComparisonRow {
+        label: "Noise".to_string(),
+        values: value(&|snapshot| snapshot.noise),
+        unit: "dB",
+        lower_is_better: true,
+    });
+    rows.push(ComparisonRow {
+        label: "Fans".to_string(),
+        values: value(&|snapshot| Some(snapshot.fans as f32)),
+        unit: "",
+        lower_is_better: false,
+    });
+    rows
+}
+
+/// What the simulation makes of `parts` cooled by the fans at their set speeds.
+fn simulate(
+    model: &ThermalModel,
+    fan_speeds: &FanSpeeds,
+    zone_query: &Query<&RgbZone>,
+    rotor_query: &Query<(&Rotor, &GlobalTransform)>,
+    parts: &[&PartSpec],
+) -> ThermalSnapshot {
+    let load_heat = model.load_heat(parts.iter().copied());
+    let drafts = group_drafts(model, rotor_query);
+    let rpms: Vec<f32> = rotor_query
+        .iter()
+        .map(|(rotor, _)| fan_speeds.target_rpm(&rotor.group))
+        .collect();
+    let airflow = rotor_query
+        .iter()
+        .map(|(rotor, transform)| {
+            let rpm = fan_speeds.target_rpm(&rotor.group);
+            model.airflow(rpm) * model.draft(Rotor::airflow_direction(transform))
+        })
+        .sum();
+    ThermalSnapshot {
+        components: model.component_temperatures(parts, airflow, FULL_LOAD),
+        zones: zone_query
+            .iter()
+            .map(|RgbZone(zone)| {
+                let group = zone_group(zone);
+                let draft = drafts.get(&group).copied().unwrap_or(1.0);
+                let airflow = model.airflow(fan_speeds.target_rpm(&group)) * draft;
+                let temperature = model.zone_temperature(airflow, FULL_LOAD, load_heat);
+                (zone.clone(), temperature)
+            })
+            .collect(),
+        noise: noise_level(rpms.iter().copied()),
+        fans: rpms.len(),
+    }
+}
+
+/// Snapshots the current build as A (`slot` 0) or B (1).
+fn take_snapshot(
+    In(slot): In<usize>,
+    mut comparison: ResMut<ThermalComparison>,
+    fan_speeds: Res<FanSpeeds>,
+    level_assets: Res<LevelAssets>,
+    models: Res<Assets<ThermalModel>>,
+    catalogs: Res<Assets<PartCatalog>>,
+    zone_query: Query<&RgbZone>,
+    rotor_query: Query<(&Rotor, &GlobalTransform)>,
+    part_query: Query<&InstalledPart>,
+) {
+    if let Some(model) = models.get(&level_assets.thermals)
+        && let Some(catalog) = catalogs.get(&level_assets.catalog)
+    {
+        let parts: Vec<_> = part_query
+            .iter()
+            .filter_map(|part| catalog.get(&part.id))
+            .collect();
+        let snapshot = simulate(model, &fan_speeds, &zone_query, &rotor_query, &parts);
+        comparison.snapshots[slot] = Some(snapshot);
+    }
+}
+
+fn clear_comparison(mut comparison: ResMut<ThermalComparison>) {
+    comparison.snapshots = default();
+}
+
+fn spawn_comparison_panel(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Thermal Comparison Panel"),
+        DespawnOnExit(Screen::Game),
+        ComparisonTable,
+        Node {
+            position_type: PositionType::Absolute,
+            top: px(40.0),
+            right: px(5.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: px(2.0),
+            padding: UiRect::all(px(6.0)),
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(OVERLAY_BACKGROUND),
+        Pickable::IGNORE,
+    ));
+}
+
+fn update_comparison_panel(
+    mut commands: Commands,
+    comparison: Res<ThermalComparison>,
+    mut table_query: Query<(Entity, &mut Node), With<ComparisonTable>>,
+) {
+    let Ok((table, mut node)) = table_query.single_mut() else {
+        return;
+    };
+    let shown = comparison.snapshots.iter().any(Option::is_some);
+    node.display = if shown { Display::Flex } else { Display::None };
+    if !shown {
+        return;
+    }
+
+    let cell = |text: String, color: Color| {
+        (
+            Text::new(text),
+            TextFont::from_font_size(FONT_SIZE),
+            TextColor(color),
+            Node {
+                width: px(COLUMN_WIDTH),
+                ..default()
+            },
+            Pickable::IGNORE,
+        )
+    };
+    let row_node = || {
+        (
+            Node {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            Pickable::IGNORE,
+        )
+    };
+    commands
+        .entity(table)
+        .despawn_children()
+        .with_children(|table| {
+            table.spawn((
+                Text::new("Full load, as simulated"),
+                TextColor(Color::WHITE),
+                Pickable::IGNORE,
+            ));
+            table.spawn(row_node()).with_children(|row| {
+                for header in ["", "A", "B", "B - A"] {
+                    row.spawn(cell(header.to_string(), HEADER_COLOR));
+                }
+            });
+            for row in comparison_rows(&comparison.snapshots) {
+                let format = |value: Option<f32>| {
+                    value.map_or("-".to_string(), |value| format!("{value:.0} {}", row.unit))
+                };
+                let (difference, color) = match row.values {
+                    [Some(a), Some(b)] => {
+                        let color = if (b - a).abs() < 0.5 || !row.lower_is_better {
+                            Color::WHITE
+                        } else if b < a {
+                            BETTER_COLOR
+                        } else {
+                            WORSE_COLOR
+                        };
+                        (format!("{:+.0} {}", b - a, row.unit), color)
+                    }
+                    _ => ("-".to_string(), Color::WHITE),
+                };
+                table.spawn(row_node()).with_children(|table_row| {
+                    table_row.spawn(cell(row.label.clone(), HEADER_COLOR));
+                    table_row.spawn(cell(format(row.values[0]), Color::WHITE));
+                    table_row.spawn(cell(format(row.values[1]), Color::WHITE));
+                    table_row.spawn(cell(difference, color));
+                });
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zones_of_either_snapshot() {
+        let a = ThermalSnapshot {
+            components: ComponentTemperatures {
+                cpu: Some(80.0),
+                ..default()
+            },
+            zones: BTreeMap::from([("Front".to_string(), 60.0)]),
+            noise: Some(25.0),
+            fans: 1,
+        };
+        let b = ThermalSnapshot {
+            components: ComponentTemperatures {
+                cpu: Some(75.0),
+                gpu: Some(70.0),
+                vrm: None,
+            },
+            zones: BTreeMap::from([("Front".to_string(), 50.0), ("Rear".to_string(), 55.0)]),
+            noise: Some(28.0),
+            fans: 2,
+        };
+        let rows = comparison_rows(&[Some(a), Some(b)]);
+        let values: Vec<_> = rows
+            .iter()
+            .map(|row| (row.label.as_str(), row.values))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("CPU", [Some(80.0), Some(75.0)]),
+                ("GPU", [None, Some(70.0)]),
+                ("Front", [Some(60.0), Some(50.0)]),
+                ("Rear", [None, Some(55.0)]),
+                ("Noise", [Some(25.0), Some(28.0)]),
+                ("Fans", [Some(1.0), Some(2.0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_a() {
+        let rows = comparison_rows(&[Some(ThermalSnapshot::default()), None]);
+        let noise = rows.iter().find(|row| row.label == "Noise").unwrap();
+        assert_eq!(noise.values, [None, None]);
+        let fans = rows.iter().find(|row| row.label == "Fans").unwrap();
+        assert_eq!(fans.values, [Some(0.0), None]);
+    }
+}
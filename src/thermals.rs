@@ -0,0 +1,414 @@
+//! Temperatures of the RGB zones, for the temperature lighting effect to show.
+//!
+//! By default the temperatures are simulated: a workload that rises and falls every couple of
+//! minutes heats each zone, by the power the installed parts draw, and the fans of the group
+//! named after the zone (the "front" rotors for the "Front" zone) cool it down by the air they
+//! move, a little better when blowing up with the rising heat than down against it, which
+//! follows the fans as an inverted case turns them over. The model's parameters, down to the
+//! fans' airflow curve and how much each kind of part heats the case, are read from
+//! `assets/simulation/thermals.ron`, so they can be calibrated against real measurements
+//! without rebuilding. On desktop, the temperatures can come from this machine's sensors
+//! instead, with every zone showing the hottest reading. The simulation is also run at full
+//! load to compare two configurations, see [`thermal_comparison`](crate::thermal_comparison),
+//! and the fans' airflow also drives the [`dust`](crate::dust) preview.
+
+use std::f32::consts::TAU;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    LevelAssets, Screen,
+    asset_tracking::InitRonAsset,
+    command_palette::AddPaletteCommand,
+    fans::{FanSpeeds, Rotor},
+    parts::{InstalledPart, PartCatalog, PartKind, PartSpec},
+    rgb::RgbZone,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<ThermalModel>(&["thermals.ron"]);
+    app.init_resource::<Temperatures>();
+    app.init_resource::<sensors::Sensors>();
+    app.add_systems(Update, update_temperatures.run_if(in_state(Screen::Game)));
+    app.add_palette_command(
+        "Switch between simulated and live temperatures",
+        toggle_temperature_source,
+    );
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Temperatures {
+    pub source: TemperatureSource,
+    /// Degrees Celsius of each zone in the scene.
+    pub zones: HashMap<String, f32>,
+    /// Degrees Celsius of the room, which zones start out at.
+    pub ambient: f32,
+}
+
+impl Temperatures {
+    pub fn get(&self, zone: &str) -> f32 {
+        self.zones.get(zone).copied().unwrap_or(self.ambient)
+    }
+}
+
+/// The parameters of the simulation, see `assets/simulation/thermals.ron`.
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct ThermalModel {
+    /// In degrees Celsius.
+    pub ambient_temperature: f32,
+    /// Seconds the simulated workload takes to rise and fall again.
+    pub load_period: f32,
+    /// Degrees above ambient a zone heats up to without airflow when idle.
+    pub idle_heat: f32,
+    /// Degrees above ambient a zone heats up to without airflow at full load, on top of the
+    /// idle heat, per watt a part of each kind draws. Kinds that aren't listed don't heat it.
+    pub heat_coefficients: HashMap<PartKind, f32>,
+    /// A fan's airflow in CFM at a few speeds in RPM, from slowest to fastest. It moves no air
+    /// standing still, and no more than at the last speed past it.
+    pub fan_curve: Vec<(f32, f32)>,
+    /// The airflow, in CFM, that halves the heat of a zone.
+    pub half_cooling_airflow: f32,
+    /// How much more a fan's airflow cools blowing straight up, with the rising heat, and how
+    /// much less blowing straight down against it, as a fraction of it.
+    pub convection: f32,
+    /// How much hotter than the air in the case the CPU, the GPU and the VRM run.
+    pub component_heat: ComponentHeat,
+    /// How quickly zones heat up and cool down; higher is snappier.
+    pub smoothing: f32,
+    /// How dust builds up in the case, for the [`dust`](crate::dust) preview.
+    pub dust: DustModel,
+}
+
+impl ThermalModel {
+    /// The air a fan spinning at `rpm` moves, in CFM, along the fan curve.
+    pub fn airflow(&self, rpm: f32) -> f32 {
+        let mut previous = (0.0, 0.0);
+        for &(curve_rpm, cfm) in &self.fan_curve {
+            if rpm <= curve_rpm {
+                let span = (curve_rpm - previous.0).max(f32::EPSILON);
+                return previous
+                    .1
+                    .lerp(cfm, ((rpm - previous.0) / span).clamp(0.0, 1.0));
+            }
+            previous = (curve_rpm, cfm);
+        }
+        previous.1
+    }
+
+    /// Degrees above ambient `parts` heat a zone up to at full load without airflow, by the
+    /// power they draw.
+    pub fn load_heat<'a>(&self, parts: impl IntoIterator<Item = &'a PartSpec>) -> f32 {
+        parts
+            .into_iter()
+            .map(|spec| spec.power_draw * self.heat_coefficient(spec.kind))
+            .sum()
+    }
+
+    pub fn heat_coefficient(&self, kind: PartKind) -> f32 {
+        self.heat_coefficients.get(&kind).copied().unwrap_or(0.0)
+    }
+
+    /// How much the air a fan blows along `direction` counts for, with [`Self::convection`].
+    pub fn draft(&self, direction: Vec3) -> f32 {
+        1.0 + self.convection * direction.normalize_or_zero().y
+    }
+
+    /// The temperature a zone settles at, in degrees Celsius, with its fans moving `airflow`
+    /// CFM and the workload at `load`, from 0 for idle to 1 for full load, heating it by
+    /// `load_heat` at full load (see [`ThermalModel::load_heat`]).
+    pub fn zone_temperature(&self, airflow: f32, load: f32, load_heat: f32) -> f32 {
+        let cooling = 1.0 + airflow / self.half_cooling_airflow;
+        self.ambient_temperature + (self.idle_heat + load_heat * load) / cooling
+    }
+
+    /// The temperatures of the CPU, the GPU and the motherboard's VRM among `parts`, with the
+    /// case's fans moving `airflow` CFM in all and the workload at `load`. Each runs hotter
+    /// than the air in the case by the power it handles: the VRM feeds the CPU.
+    pub fn component_temperatures(
+        &self,
+        parts: &[&PartSpec],
+        airflow: f32,
+        load: f32,
+    ) -> ComponentTemperatures {
+        let air = self.zone_temperature(airflow, load, self.load_heat(parts.iter().copied()));
+        let has = |kind| parts.iter().any(|spec| spec.kind == kind);
+        let draw = |kind| {
+            parts
+                .iter()
+                .filter(|spec| spec.kind == kind)
+                .map(|spec| spec.power_draw * load)
+                .sum::<f32>()
+        };
+        let heat = &self.component_heat;
+        ComponentTemperatures {
+            cpu: has(PartKind::Cpu).then(|| air + draw(PartKind::Cpu) * heat.cpu),
+            gpu: has(PartKind::Gpu).then(|| air + draw(PartKind::Gpu) * heat.gpu),
+            vrm: has(PartKind::Motherboard).then(|| air + draw(PartKind::Cpu) * heat.vrm),
+        }
+    }
+
+    /// The dusty air, in CFM, that gets into a case whose fans draw in `intakes`, each its
+    /// airflow and whether it's filtered, and blow out `exhaust`. Filters let some of the dust
+    /// through, and what the exhaust blows out beyond the intake is drawn in unfiltered through
+    /// the gaps and vents of the case.
+    pub fn dusty_airflow(
+        &self,
+        intakes: impl IntoIterator<Item = (f32, bool)>,
+        exhaust: f32,
+    ) -> f32 {
+        let (mut intake, mut dusty) = (0.0, 0.0);
+        for (airflow, filtered) in intakes {
+            intake += airflow;
+            dusty += if filtered {
+                airflow * self.dust.filter_pass
+            } else {
+                airflow
+            };
+        }
+        dusty + (exhaust - intake).max(0.0)
+    }
+
+    /// How much of the case dust covers, from 0 to 1, after `months` of `dusty_airflow` CFM
+    /// getting in (see [`ThermalModel::dusty_airflow`]).
+    pub fn dust_coverage(&self, dusty_airflow: f32, months: f32) -> f32 {
+        1.0 - (-self.dust.rate * dusty_airflow * months).exp()
+    }
+}
+
+/// How dust gets into the case and settles.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DustModel {
+    /// The fraction of the dust in the air a dust filter lets through.
+    pub filter_pass: f32,
+    /// How quickly dust covers the case, per CFM of dusty air and month.
+    pub rate: f32,
+}
+
+/// Degrees above the air in the case each component runs per watt it handles.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComponentHeat {
+    pub cpu: f32,
+    pub gpu: f32,
+    /// Per watt the CPU draws through it.
+    pub vrm: f32,
+}
+
+/// In degrees Celsius, for the components that are installed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ComponentTemperatures {
+    pub cpu: Option<f32>,
+    pub gpu: Option<f32>,
+    pub vrm: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum TemperatureSource {
+    #[default]
+    Simulated,
+    /// The hottest of this machine's sensors.
+    Live,
+}
+
+/// Seconds between readings of the sensors.
+#[cfg(not(target_arch = "wasm32"))]
+const SENSOR_REFRESH: f32 = 1.0;
+
+fn toggle_temperature_source(mut temperatures: ResMut<Temperatures>) {
+    temperatures.source = match temperatures.source {
+        TemperatureSource::Simulated => TemperatureSource::Live,
+        TemperatureSource::Live => TemperatureSource::Simulated,
+    };
+    match temperatures.source {
+        TemperatureSource::Simulated => info!("Showing simulated temperatures"),
+        TemperatureSource::Live => info!("Showing live temperatures"),
+    }
+}
+
+fn update_temperatures(
+    time: Res<Time>,
+    mut temperatures: ResMut<Temperatures>,
+    mut sensors: ResMut<sensors::Sensors>,
+    fan_speeds: Res<FanSpeeds>,
+    level_assets: Res<LevelAssets>,
+    models: Res<Assets<ThermalModel>>,
+    catalogs: Res<Assets<PartCatalog>>,
+    zone_query: Query<&RgbZone>,
+    part_query: Query<&InstalledPart>,
+    rotor_query: Query<(&Rotor, &GlobalTransform)>,
+) {
+    let Some(model) = models.get(&level_assets.thermals) else {
+        return;
+    };
+    let load_heat = catalogs.get(&level_assets.catalog).map_or(0.0, |catalog| {
+        model.load_heat(part_query.iter().filter_map(|part| catalog.get(&part.id)))
+    });
+    let live = match temperatures.source {
+        TemperatureSource::Simulated => None,
+        TemperatureSource::Live => {
+            let reading = sensors.hottest(time.delta());
+            if reading.is_none() {
+                warn!("No temperature sensors found, going back to simulated temperatures");
+                temperatures.source = TemperatureSource::Simulated;
+            }
+            reading
+        }
+    };
+    let drafts = group_drafts(model, rotor_query.iter());
+    let load = 0.5 - 0.5 * (time.elapsed_secs() * TAU / model.load_period).cos();
+
+    temperatures.ambient = model.ambient_temperature;
+    let zones = &mut temperatures.zones;
+    zones.retain(|zone, _| zone_query.iter().any(|RgbZone(name)| name == zone));
+    for RgbZone(zone) in &zone_query {
+        let target = live.unwrap_or_else(|| {
+            let group = zone_group(zone);
+            let draft = drafts.get(&group).copied().unwrap_or(1.0);
+            let airflow = model.airflow(fan_speeds.rpm(&group)) * draft;
+            model.zone_temperature(airflow, load, load_heat)
+        });
+        let temperature = zones
+            .entry(zone.clone())
+            .or_insert(model.ambient_temperature);
+        temperature.smooth_nudge(&target, model.smoothing, time.delta_secs());
+    }
+}
+
+/// The average [`ThermalModel::draft`] of each group's rotors.
+pub fn group_drafts<'a>(
+    model: &ThermalModel,
+    rotors: impl IntoIterator<Item = (&'a Rotor, &'a GlobalTransform)>,
+) -> HashMap<String, f32> {
+    let mut sums: HashMap<String, (f32, f32)> = HashMap::new();
+    for (rotor, transform) in rotors {
+        let sum = sums.entry(rotor.group.clone()).or_default();
+        sum.0 += model.draft(Rotor::airflow_direction(transform));
+        sum.1 += 1.0;
+    }
+    sums.into_iter()
+        .map(|(group, (drafts, count))| (group, drafts / count))
+        .collect()
+}
+
+/// The fan group that cools `zone`, like "front" for the "Front" zone.
+pub fn zone_group(zone: &str) -> String {
+    zone.to_lowercase()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod sensors {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+    use sysinfo::Components;
+
+    use super::SENSOR_REFRESH;
+
+    #[derive(Resource)]
+    pub struct Sensors {
+        /// Listed the first time live temperatures are asked for.
+        components: Option<Components>,
+        refresh: Timer,
+        reading: Option<f32>,
+    }
+
+    impl Default for Sensors {
+        fn default() -> Self {
+            Self {
+                components: None,
+                refresh: Timer::from_seconds(SENSOR_REFRESH, TimerMode::Repeating),
+                reading: None,
+            }
+        }
+    }
+
+    impl Sensors {
+        /// The hottest sensor in degrees Celsius, or `None` if the machine has no sensors.
+        pub fn hottest(&mut self, delta: Duration) -> Option<f32> {
+            let refresh = self.refresh.tick(delta).just_finished();
+            match &mut self.components {
+                Some(components) if refresh => components.refresh(false),
+                Some(_) => return self.reading,
+                None => {
+                    self.components = Some(Components::new_with_refreshed_list());
+                }
+            }
+            self.reading = self
+                .components
+                .iter()
+                .flatten()
+                .filter_map(|component| component.temperature())
+                .filter(|temperature| temperature.is_finite())
+                .reduce(f32::max);
+            self.reading
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod sensors {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    /// Browsers don't expose temperature sensors.
+    #[derive(Resource, Default)]
+    pub struct Sensors;
+
+    impl Sensors {
+        pub fn hottest(&mut self, _delta: Duration) -> Option<f32> {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn airflow_follows_the_fan_curve() {
+        let model = ThermalModel {
+            ambient_temperature: 25.0,
+            load_period: 120.0,
+            idle_heat: 20.0,
+            heat_coefficients: HashMap::from_iter([(PartKind::Cpu, 0.1)]),
+            fan_curve: vec![(1000.0, 40.0), (2000.0, 70.0)],
+            half_cooling_airflow: 40.0,
+            convection: 0.2,
+            component_heat: ComponentHeat {
+                cpu: 0.3,
+                gpu: 0.2,
+                vrm: 0.1,
+            },
+            smoothing: 0.5,
+            dust: DustModel {
+                filter_pass: 0.1,
+                rate: 0.001,
+            },
+        };
+        assert_eq!(model.airflow(0.0), 0.0);
+        assert_eq!(model.airflow(500.0), 20.0);
+        assert_eq!(model.airflow(1500.0), 55.0);
+        assert_eq!(model.airflow(3000.0), 70.0);
+        // Idle, fans moving 40 CFM halve the idle heat.
+        assert_eq!(model.zone_temperature(40.0, 0.0, 50.0), 35.0);
+        assert_eq!(model.zone_temperature(40.0, 1.0, 50.0), 60.0);
+        assert_eq!(model.draft(Vec3::Y * 2.0), 1.2);
+        assert_eq!(model.draft(Vec3::NEG_Y), 0.8);
+        assert_eq!(model.draft(Vec3::Z), 1.0);
+        assert_eq!(model.heat_coefficient(PartKind::Gpu), 0.0);
+
+        // A filtered front intake and an unfiltered bottom one, with the exhaust drawing 10
+        // CFM more through the gaps.
+        assert_eq!(
+            model.dusty_airflow([(50.0, true), (20.0, false)], 80.0),
+            35.0
+        );
+        // Positive pressure keeps it to what gets through the filter.
+        assert_eq!(model.dusty_airflow([(50.0, true)], 30.0), 5.0);
+        assert_eq!(model.dust_coverage(35.0, 0.0), 0.0);
+        assert!(model.dust_coverage(5.0, 12.0) < model.dust_coverage(35.0, 12.0));
+        assert!(model.dust_coverage(35.0, 1000.0) <= 1.0);
+    }
+}
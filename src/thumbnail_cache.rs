@@ -0,0 +1,205 @@
+//! Part thumbnails and catalogs kept on disk between sessions, so a large part library isn't
+//! rendered or parsed again on every launch.
+//!
+//! The images are PNGs in `cache/thumbnails/`, named after the fingerprint of what they show,
+//! and `index.ron` next to them lists the fingerprint each part was last rendered with. A part
+//! whose fingerprint is unchanged is shown from the cache right away; the others are rendered
+//! as usual and their images replace the old ones.
+//!
+//! Each `catalog.ron`, the app's and every pack's, is indexed in `cache/catalogs/` under the
+//! hash of its path, along with the hash of its contents. While the contents hash the same,
+//! the catalog's parts are read from the index, which has no comments or formatting to skip;
+//! once the catalog changes it's parsed again and indexed in place of the old one.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+    render::render_resource::TextureFormat,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_tracking::RonAssetLoaderError,
+    capture::{encode_png, to_rgba},
+    parts::PartCatalog,
+};
+
+const CACHE_FOLDER: &str = "cache/thumbnails";
+const INDEX_FILE: &str = "index.ron";
+const CATALOG_FOLDER: &str = "cache/catalogs";
+
+/// The fingerprint of the thumbnail each part was last rendered with, by id.
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+pub struct ThumbnailCache(BTreeMap<String, u64>);
+
+impl ThumbnailCache {
+    /// Reads the index, or starts an empty one if there's none yet or it can't be read.
+    pub fn open() -> Self {
+        let path = Path::new(CACHE_FOLDER).join(INDEX_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!("Could not read {}: {error}", path.display());
+                return Self::default();
+            }
+        };
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!("Could not read {}: {error}", path.display());
+            Self::default()
+        })
+    }
+
+    /// The cached thumbnail of the part with `id`, if it was rendered with `fingerprint`, as a
+    /// render target of `size` pixels so it can be rendered into again.
+    pub fn load(&self, id: &str, fingerprint: u64, size: u32) -> Option<Image> {
+        if self.0.get(id) != Some(&fingerprint) {
+            return None;
+        }
+        let path = image_path(fingerprint);
+        let png = match fs::read(&path) {
+            Ok(png) => png,
+            Err(error) => {
+                if error.kind() != io::ErrorKind::NotFound {
+                    warn!("Could not read {}: {error}", path.display());
+                }
+                return None;
+            }
+        };
+        let pixels = match image::load_from_memory_with_format(&png, image::ImageFormat::Png) {
+            Ok(pixels) => pixels.to_rgba8(),
+            Err(error) => {
+                warn!("Could not decode {}: {error}", path.display());
+                return None;
+            }
+        };
+        // Left over from a different thumbnail size.
+        if pixels.dimensions() != (size, size) {
+            return None;
+        }
+
+        let mut image = Image::new_target_texture(size, size, TextureFormat::Rgba8UnormSrgb, None);
+        image.data = Some(pixels.into_raw());
+        Some(image)
+    }
+
+    /// Caches the thumbnail of the part with `id`, rendered with `fingerprint`, in place of the
+    /// one it had.
+    pub fn store(&mut self, id: &str, fingerprint: u64, image: &Image) {
+        let Some(png) = to_rgba(image, image.size()).and_then(|pixels| encode_png(&pixels)) else {
+            return;
+        };
+        let path = image_path(fingerprint);
+        if let Err(error) = fs::create_dir_all(CACHE_FOLDER).and_then(|()| fs::write(&path, png)) {
+            warn!("Could not cache {}: {error}", path.display());
+            return;
+        }
+
+        // Parts that looked the same share an image, which is only removed with the last one.
+        if let Some(previous) = self.0.insert(id.to_string(), fingerprint)
+            && previous != fingerprint
+            && !self.0.values().any(|&other| other == previous)
+        {
+            let _ = fs::remove_file(image_path(previous));
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let path = Path::new(CACHE_FOLDER).join(INDEX_FILE);
+        let result = ron::ser::to_string_pretty(self, default())
+            .map_err(io::Error::other)
+            .and_then(|contents| fs::write(&path, contents));
+        if let Err(error) = result {
+            warn!("Could not write {}: {error}", path.display());
+        }
+    }
+}
+
+fn image_path(fingerprint: u64) -> PathBuf {
+    Path::new(CACHE_FOLDER).join(format!("{fingerprint:016x}.png"))
+}
+
+/// Loads catalogs from their index while it's current, see the [module docs](self).
+#[derive(TypePath)]
+pub struct CatalogLoader;
+
+/// A catalog as it was parsed, with the hash of the file it was parsed from.
+#[derive(Serialize, Deserialize)]
+struct CatalogIndex {
+    hash: u64,
+    catalog: PartCatalog,
+}
+
+impl AssetLoader for CatalogLoader {
+    type Asset = PartCatalog;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<PartCatalog, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let hash = fnv1a(&bytes);
+        let path = catalog_index_path(&load_context.path().to_string());
+        if let Some(index) = read_catalog_index(&path)
+            && index.hash == hash
+        {
+            return Ok(index.catalog);
+        }
+
+        let index = CatalogIndex {
+            hash,
+            catalog: ron::de::from_bytes(&bytes)?,
+        };
+        let result = ron::ser::to_string(&index)
+            .map_err(io::Error::other)
+            .and_then(|contents| {
+                fs::create_dir_all(CATALOG_FOLDER).and_then(|()| fs::write(&path, contents))
+            });
+        if let Err(error) = result {
+            warn!("Could not write {}: {error}", path.display());
+        }
+        Ok(index.catalog)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["catalog.ron"]
+    }
+}
+
+/// The index at `path`, if there's one that can be read.
+fn read_catalog_index(path: &Path) -> Option<CatalogIndex> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            if error.kind() != io::ErrorKind::NotFound {
+                warn!("Could not read {}: {error}", path.display());
+            }
+            return None;
+        }
+    };
+    // Left over from an older version of the catalog format, and written again.
+    ron::from_str(&contents).ok()
+}
+
+fn catalog_index_path(asset_path: &str) -> PathBuf {
+    Path::new(CATALOG_FOLDER).join(format!("{:016x}.ron", fnv1a(asset_path.as_bytes())))
+}
+
+/// FNV-1a, which unlike the standard hasher stays the same from one build to the next.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
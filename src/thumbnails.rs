@@ -0,0 +1,397 @@
+//! Thumbnails of the catalog parts for the parts panel, rendered in the background.
+//!
+//! One part at a time is put in a studio of its own: an offscreen camera and neutral lights on
+//! a render layer nothing else is on, framing the part from the same angle as every other.
+//! The rendered images stay cached for the session, and a part is rendered again when its
+//! model changes, as when it's edited with hot reloading on, or when the catalog changes what
+//! the part looks like. Native builds also keep the images on disk, in `thumbnail_cache`, so a
+//! large catalog only has its new and changed parts rendered at the next launch.
+
+use std::collections::VecDeque;
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy::{
+    app::{HierarchyPropagatePlugin, Propagate, PropagateSet},
+    camera::{
+        RenderTarget,
+        visibility::{RenderLayers, VisibilitySystems},
+    },
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::TextureFormat,
+};
+
+use crate::{
+    LevelAssets, Screen,
+    capture::CAPTURE_DELAY_FRAMES,
+    model_cache::PartModels,
+    parts::{PartCatalog, PartSpec},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(HierarchyPropagatePlugin::<RenderLayers>::new(PostUpdate));
+    app.configure_sets(
+        PostUpdate,
+        PropagateSet::<RenderLayers>::default().before(VisibilitySystems::CheckVisibility),
+    );
+    app.init_resource::<Thumbnails>();
+    #[cfg(not(target_arch = "wasm32"))]
+    app.insert_resource(crate::thumbnail_cache::ThumbnailCache::open());
+    app.add_systems(OnExit(Screen::Game), drop_thumbnail_job);
+    app.add_systems(
+        Update,
+        (
+            queue_thumbnails,
+            start_thumbnail_job,
+            finish_thumbnail_job,
+            show_thumbnails,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+#[derive(Resource, Debug, Default)]
+struct Thumbnails {
+    /// The rendered image of each part, by id.
+    images: HashMap<String, Handle<Image>>,
+    /// The fingerprint of what each image shows, by id.
+    fingerprints: HashMap<String, u64>,
+    /// Scenes of the parts rendered so far, to render them again when they change while
+    /// they're loaded.
+    models: HashMap<String, AssetId<Scene>>,
+    /// Parts waiting to be rendered.
+    pending: VecDeque<String>,
+    job: Option<ThumbnailJob>,
+}
+
+impl Thumbnails {
+    fn queue(&mut self, id: &str) {
+        if !self.pending.iter().any(|pending| pending == id) {
+            self.pending.push_back(id.to_string());
+        }
+    }
+}
+
+/// The part being rendered, and the studio entities to despawn once it's done.
+#[derive(Debug)]
+struct ThumbnailJob {
+    id: String,
+    fingerprint: u64,
+    studio: Vec<Entity>,
+    subject: Entity,
+    frames_left: u32,
+    /// The part is in view, rather than still loading or failed to.
+    in_view: bool,
+}
+
+/// Shows the thumbnail of the part with this id.
+#[derive(Component, Debug, Clone)]
+struct PartThumbnail(String);
+
+/// A render layer of its own, so the studio and the scene don't see or light each other.
+const STUDIO_LAYER: usize = 7;
+/// Width and height of the rendered thumbnails, in pixels.
+const THUMBNAIL_SIZE: u32 = 128;
+/// Width and height the thumbnails are shown at, in logical pixels.
+const THUMBNAIL_DISPLAY_SIZE: f32 = 32.0;
+/// The direction the studio camera looks at parts from.
+const VIEW_DIRECTION: Vec3 = Vec3::new(1.0, 0.8, 1.2);
+/// How much room is left around the part.
+const FRAMING_MARGIN: f32 = 1.15;
+const STUDIO_BACKGROUND: Color = Color::srgb(0.22, 0.22, 0.24);
+const KEY_ILLUMINANCE: f32 = 3000.0;
+const FILL_ILLUMINANCE: f32 = 1000.0;
+/// Frames to wait for a part's model to load before giving up on it.
+const LOAD_TIMEOUT_FRAMES: u32 = 600;
+/// Changes the fingerprint of every thumbnail, for when the studio changes how they look.
+const STUDIO_VERSION: u32 = 1;
+
+/// What the thumbnail of `spec` shows, so it's only rendered again once that changes: the
+/// part's kind, size and model, and on native builds the model file's size and modification
+/// time.
+fn fingerprint(spec: &PartSpec) -> u64 {
+    let mut description = format!(
+        "{STUDIO_VERSION} {THUMBNAIL_SIZE} {:?} {:?} {:?}",
+        spec.kind, spec.size, spec.model
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(metadata) = spec
+        .model
+        .as_deref()
+        .and_then(|model| crate::asset_roots::locate(std::path::Path::new(model)))
+        .and_then(|file| file.metadata().ok())
+    {
+        description += &format!(" {} {:?}", metadata.len(), metadata.modified().ok());
+    }
+    // FNV-1a, which unlike the standard hasher stays the same from one build to the next.
+    description
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// The thumbnail of the part with `id`, for the parts panel. Empty until it's been rendered.
+pub fn thumbnail(id: &str) -> impl Bundle {
+    (
+        PartThumbnail(id.to_string()),
+        ImageNode::default(),
+        Node {
+            width: px(THUMBNAIL_DISPLAY_SIZE),
+            height: px(THUMBNAIL_DISPLAY_SIZE),
+            ..default()
+        },
+    )
+}
+
+/// Queues the parts that look different from their thumbnails when the catalog loads or
+/// changes, and parts whose model changed. Thumbnails that are cached on disk are shown from
+/// there instead.
+fn queue_thumbnails(
+    mut thumbnails: ResMut<Thumbnails>,
+    #[cfg(not(target_arch = "wasm32"))] cache: Res<crate::thumbnail_cache::ThumbnailCache>,
+    #[cfg(not(target_arch = "wasm32"))] mut images: ResMut<Assets<Image>>,
+    mut catalog_events: MessageReader<AssetEvent<PartCatalog>>,
+    mut scene_events: MessageReader<AssetEvent<Scene>>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let catalog_changed = catalog_events.read().any(
+        |event| matches!(event, AssetEvent::Modified { id } if *id == level_assets.catalog.id()),
+    );
+    if catalog_changed || thumbnails.fingerprints.is_empty() && thumbnails.pending.is_empty() {
+        for part in &catalog.parts {
+            let fingerprint = fingerprint(part);
+            if thumbnails.fingerprints.get(&part.id) == Some(&fingerprint) {
+                continue;
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(image) = cache.load(&part.id, fingerprint, THUMBNAIL_SIZE) {
+                let image = images.add(image);
+                thumbnails.images.insert(part.id.clone(), image);
+                thumbnails.fingerprints.insert(part.id.clone(), fingerprint);
+                continue;
+            }
+            thumbnails.queue(&part.id);
+        }
+    }
+
+    for event in scene_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let changed: Vec<String> = thumbnails
+            .models
+            .iter()
+            .filter(|(_, model)| *model == id)
+            .map(|(part, _)| part.clone())
+            .collect();
+        for part in changed {
+            thumbnails.queue(&part);
+        }
+    }
+}
+
+/// Sets up the studio for the next part waiting to be rendered.
+fn start_thumbnail_job(
+    mut commands: Commands,
+    mut thumbnails: ResMut<Thumbnails>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+    asset_server: Res<AssetServer>,
+    mut part_models: ResMut<PartModels>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if thumbnails.job.is_some() {
+        return;
+    }
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let Some(spec) = thumbnails
+        .pending
+        .pop_front()
+        .and_then(|id| catalog.get(&id))
+    else {
+        return;
+    };
+
+    // Rendered again into the same image, so the panel picks the new one up by itself.
+    let image = thumbnails
+        .images
+        .entry(spec.id.clone())
+        .or_insert_with(|| {
+            images.add(Image::new_target_texture(
+                THUMBNAIL_SIZE,
+                THUMBNAIL_SIZE,
+                TextureFormat::Rgba8UnormSrgb,
+                None,
+            ))
+        })
+        .clone();
+    let fingerprint = fingerprint(spec);
+    thumbnails.fingerprints.insert(spec.id.clone(), fingerprint);
+    let layer = RenderLayers::layer(STUDIO_LAYER);
+
+    let camera = commands
+        .spawn((
+            Name::new("Thumbnail Camera"),
+            DespawnOnExit(Screen::Game),
+            Camera3d::default(),
+            Camera {
+                clear_color: ClearColorConfig::Custom(STUDIO_BACKGROUND),
+                // Before the views of the scene, which may be taken in the same frame.
+                order: -2,
+                ..default()
+            },
+            RenderTarget::Image(image.into()),
+            frame_part(spec),
+            layer.clone(),
+        ))
+        .id();
+    let key_light = commands
+        .spawn((
+            Name::new("Thumbnail Key Light"),
+            DespawnOnExit(Screen::Game),
+            DirectionalLight {
+                illuminance: KEY_ILLUMINANCE,
+                shadows_enabled: false,
+                ..default()
+            },
+            Transform::from_translation(Vec3::new(1.0, 2.0, 1.5)).looking_at(Vec3::ZERO, Vec3::Y),
+            layer.clone(),
+        ))
+        .id();
+    let fill_light = commands
+        .spawn((
+            Name::new("Thumbnail Fill Light"),
+            DespawnOnExit(Screen::Game),
+            DirectionalLight {
+                illuminance: FILL_ILLUMINANCE,
+                shadows_enabled: false,
+                ..default()
+            },
+            Transform::from_translation(Vec3::new(-1.5, 0.5, 0.5)).looking_at(Vec3::ZERO, Vec3::Y),
+            layer.clone(),
+        ))
+        .id();
+
+    let mut subject = commands.spawn((
+        Name::new(format!("Thumbnail of {}", spec.name)),
+        DespawnOnExit(Screen::Game),
+        Transform::default(),
+        Visibility::default(),
+        Propagate(layer),
+    ));
+    match &spec.model {
+        Some(model) => {
+            let scene = part_models.load(&asset_server, model);
+            thumbnails.models.insert(spec.id.clone(), scene.id());
+            subject.insert(SceneRoot(scene));
+        }
+        None => {
+            subject.insert((
+                Mesh3d(meshes.add(Cuboid::from_size(Vec3::from(spec.size)))),
+                MeshMaterial3d(materials.add(spec.kind.placeholder_color())),
+            ));
+        }
+    }
+    let subject = subject.id();
+
+    thumbnails.job = Some(ThumbnailJob {
+        id: spec.id.clone(),
+        fingerprint,
+        studio: vec![camera, key_light, fill_light, subject],
+        subject,
+        frames_left: LOAD_TIMEOUT_FRAMES,
+        in_view: false,
+    });
+}
+
+/// Where the studio camera sits to fit the whole of `spec` in view.
+fn frame_part(spec: &PartSpec) -> Transform {
+    let radius = Vec3::from(spec.size).length() / 2.0;
+    let fov = PerspectiveProjection::default().fov;
+    let distance = radius * FRAMING_MARGIN / (fov / 2.0).sin();
+    Transform::from_translation(VIEW_DIRECTION.normalize() * distance)
+        .looking_at(Vec3::ZERO, Vec3::Y)
+}
+
+/// Takes the studio down once the part has been in view for a few frames.
+fn finish_thumbnail_job(
+    mut commands: Commands,
+    mut thumbnails: ResMut<Thumbnails>,
+    scene_query: Query<(&SceneRoot, Has<Children>)>,
+    asset_server: Res<AssetServer>,
+) {
+    // So the job and the images can be borrowed at the same time.
+    let thumbnails = &mut *thumbnails;
+    let Some(job) = &mut thumbnails.job else {
+        return;
+    };
+    let (loading, failed) = match scene_query.get(job.subject) {
+        Ok((scene, spawned)) => {
+            let failed = asset_server.load_state(&scene.0).is_failed();
+            (!spawned && !failed, failed)
+        }
+        Err(_) => (false, false),
+    };
+    // Counts down to the timeout while loading, and then from the capture delay.
+    if !loading && job.frames_left > CAPTURE_DELAY_FRAMES {
+        job.frames_left = CAPTURE_DELAY_FRAMES;
+        job.in_view = !failed;
+    }
+    if job.frames_left > 0 {
+        job.frames_left -= 1;
+        return;
+    }
+
+    // The image keeps what was last rendered into it once the studio is gone. Parts that
+    // didn't load aren't cached, so they're tried again at the next launch.
+    #[cfg(not(target_arch = "wasm32"))]
+    if job.in_view
+        && let Some(image) = thumbnails.images.get(&job.id)
+    {
+        let id = job.id.clone();
+        let fingerprint = job.fingerprint;
+        commands.spawn(Screenshot::image(image.clone())).observe(
+            move |captured: On<ScreenshotCaptured>,
+                  mut cache: ResMut<crate::thumbnail_cache::ThumbnailCache>| {
+                cache.store(&id, fingerprint, &captured.image);
+            },
+        );
+    }
+
+    for entity in job.studio.drain(..) {
+        commands.entity(entity).despawn();
+    }
+    thumbnails.job = None;
+}
+
+/// The studio is despawned with the scene, so the part it was rendering has to wait its turn
+/// again.
+fn drop_thumbnail_job(mut thumbnails: ResMut<Thumbnails>) {
+    if let Some(job) = thumbnails.job.take() {
+        thumbnails.pending.push_front(job.id);
+    }
+}
+
+fn show_thumbnails(
+    thumbnails: Res<Thumbnails>,
+    mut thumbnail_query: Query<(&PartThumbnail, &mut ImageNode)>,
+) {
+    for (thumbnail, mut image_node) in &mut thumbnail_query {
+        if let Some(image) = thumbnails.images.get(&thumbnail.0)
+            && image_node.image != *image
+        {
+            image_node.image = image.clone();
+        }
+    }
+}
@@ -0,0 +1,757 @@
+//! A timeline for keyframing short product animations.
+//!
+//! Press Y to open it. Each track (the camera, the exploded view, the case panels and the
+//! lighting environment) holds keys at whole frames: "Key" stores the scene as it is now at the
+//! playhead, "Delete" drops the key there. Scrub by dragging along a track, step a frame at a
+//! time with ',' and '.', or play the animation back. The camera, exploded view and panels blend
+//! between their keys, while the lighting holds each key until the next one.
+//!
+//! "Export" renders every frame offscreen through the turntable recorder, so the animation is
+//! saved as a PNG sequence or GIF in the turntable's format.
+
+use bevy::{
+    ecs::system::SystemId, input::InputSystems, input_focus::tab_navigation::TabGroup, prelude::*,
+    ui::RelativeCursorPosition,
+};
+
+use crate::{
+    OrbitCamera, Screen,
+    case_panels::CasePanel,
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    exploded_view::ExplodedView,
+    keybindings::{KeyAction, key_just_pressed},
+    lighting::LightingEnvironment,
+    turntable::{RecordAnimation, Turntable},
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, PANEL_BACKGROUND, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let set_length = app.register_system(set_length);
+    app.insert_resource(Timeline {
+        frame: 0,
+        length: DEFAULT_LENGTH * FRAME_RATE,
+        playback: None,
+        pose_pending: false,
+        exporting: false,
+        set_length,
+    });
+    app.init_resource::<TimelineKeys>();
+    app.add_systems(OnEnter(Screen::Game), spawn_timeline_panel);
+    app.add_systems(OnExit(Screen::Game), stop_playback);
+    // Before anything in `Update` reads the camera, exploded view or lighting, so every frame
+    // of an export shows the scene at that frame.
+    app.add_systems(
+        PreUpdate,
+        pose_scene
+            .after(InputSystems)
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_systems(
+        Update,
+        (
+            toggle_timeline_panel.run_if(key_just_pressed(KeyAction::Timeline)),
+            (
+                step_back.run_if(key_just_pressed(KeyAction::PreviousFrame)),
+                step_forward.run_if(key_just_pressed(KeyAction::NextFrame)),
+            )
+                .run_if(timeline_panel_open),
+            scrub,
+            play,
+            update_key_ticks
+                .run_if(resource_changed::<TimelineKeys>.or(any_match_filter::<Added<KeyTicks>>)),
+            update_playheads,
+            update_labels,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_timeline_button);
+    app.add_palette_command("Timeline: toggle the panel", toggle_timeline_panel);
+    app.add_palette_command("Timeline: play or pause", toggle_playback);
+    app.add_palette_command("Timeline: export", export_animation);
+    app.add_palette_command("Timeline: set the length", prompt_for_length);
+}
+
+/// The playhead, and what it is doing.
+#[derive(Resource)]
+struct Timeline {
+    /// The frame under the playhead.
+    frame: u32,
+    /// How many frames the animation lasts.
+    length: u32,
+    /// Where playback started, while the timeline is playing.
+    playback: Option<Playback>,
+    /// Whether the playhead moved since the scene was last posed.
+    pose_pending: bool,
+    /// Whether the turntable recorder is rendering this animation.
+    exporting: bool,
+    /// Parses the length in seconds it's given.
+    set_length: SystemId<In<String>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Playback {
+    start: u32,
+    /// Frames played since `start`, counting fractions of a frame.
+    played: f32,
+}
+
+impl Timeline {
+    /// Moves the playhead to `frame`, posing the scene there.
+    fn seek(&mut self, frame: u32) {
+        let frame = frame.min(self.last_frame());
+        if frame != self.frame {
+            self.frame = frame;
+            self.pose_pending = true;
+        }
+    }
+
+    fn last_frame(&self) -> u32 {
+        self.length.saturating_sub(1)
+    }
+}
+
+/// The keys of every track.
+#[derive(Resource, Default)]
+struct TimelineKeys {
+    camera: Track<CameraKey>,
+    exploded_view: Track<f32>,
+    panels: Track<PanelsKey>,
+    lighting: Track<LightingEnvironment>,
+}
+
+impl TimelineKeys {
+    fn frames(&self, track: TrackKind) -> Vec<u32> {
+        match track {
+            TrackKind::Camera => self.camera.frames(),
+            TrackKind::ExplodedView => self.exploded_view.frames(),
+            TrackKind::Panels => self.panels.frames(),
+            TrackKind::Lighting => self.lighting.frames(),
+        }
+    }
+
+    fn remove(&mut self, track: TrackKind, frame: u32) {
+        match track {
+            TrackKind::Camera => self.camera.remove(frame),
+            TrackKind::ExplodedView => self.exploded_view.remove(frame),
+            TrackKind::Panels => self.panels.remove(frame),
+            TrackKind::Lighting => self.lighting.remove(frame),
+        }
+    }
+
+    /// Where the camera is at `frame`, or `None` if the camera track has no keys.
+    fn camera_at(&self, frame: u32) -> Option<CameraKey> {
+        self.camera.sample(frame, |from, to, s| {
+            from.lerp(to, EaseFunction::SmoothStep.sample_clamped(s))
+        })
+    }
+}
+
+/// Keys sorted by frame, at most one per frame.
+#[derive(Debug)]
+struct Track<T> {
+    keys: Vec<(u32, T)>,
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<T: Clone> Track<T> {
+    fn set(&mut self, frame: u32, value: T) {
+        match self.keys.binary_search_by_key(&frame, |&(key, _)| key) {
+            Ok(index) => self.keys[index].1 = value,
+            Err(index) => self.keys.insert(index, (frame, value)),
+        }
+    }
+
+    fn remove(&mut self, frame: u32) {
+        self.keys.retain(|&(key, _)| key != frame);
+    }
+
+    fn frames(&self) -> Vec<u32> {
+        self.keys.iter().map(|&(key, _)| key).collect()
+    }
+
+    /// The value at `frame`, blending the keys on either side with `blend`. Before the first key
+    /// and after the last, the track holds that key.
+    fn sample(&self, frame: u32, blend: impl Fn(T, T, f32) -> T) -> Option<T> {
+        let next = self.keys.partition_point(|&(key, _)| key <= frame);
+        let previous = next.checked_sub(1).map(|index| self.keys[index].clone());
+        match (previous, self.keys.get(next).cloned()) {
+            (Some((from_frame, from)), Some((to_frame, to))) => Some(blend(
+                from,
+                to,
+                (frame - from_frame) as f32 / (to_frame - from_frame) as f32,
+            )),
+            (Some((_, value)), None) | (None, Some((_, value))) => Some(value),
+            (None, None) => None,
+        }
+    }
+}
+
+/// The parts of the orbit camera a key stores.
+#[derive(Debug, Clone, Copy)]
+struct CameraKey {
+    yaw: f32,
+    pitch: f32,
+    radius: f32,
+    target: Vec3,
+}
+
+impl CameraKey {
+    fn from_orbit(orbit: &OrbitCamera) -> Self {
+        Self {
+            yaw: orbit.yaw,
+            pitch: orbit.pitch,
+            // Skip the rest of an ongoing zoom.
+            radius: orbit.desired_radius,
+            target: orbit.target,
+        }
+    }
+
+    fn lerp(self, other: Self, s: f32) -> Self {
+        Self {
+            yaw: self.yaw.lerp(other.yaw, s),
+            pitch: self.pitch.lerp(other.pitch, s),
+            radius: self.radius.lerp(other.radius, s),
+            target: self.target.lerp(other.target, s),
+        }
+    }
+
+    fn apply(self, orbit: &mut OrbitCamera) {
+        orbit.yaw = self.yaw;
+        orbit.pitch = self.pitch;
+        orbit.radius = self.radius;
+        orbit.desired_radius = self.radius;
+        orbit.target = self.target;
+    }
+}
+
+/// How far off the case each panel is, by the name of its node.
+#[derive(Debug, Clone)]
+struct PanelsKey(Vec<(String, f32)>);
+
+impl PanelsKey {
+    fn get(&self, panel: &str) -> Option<f32> {
+        self.0
+            .iter()
+            .find(|(name, _)| name == panel)
+            .map(|&(_, progress)| progress)
+    }
+
+    /// Blends the panels both keys have, and holds the ones only `self` has.
+    fn lerp(self, other: Self, s: f32) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|(name, from)| {
+                    let to = other.get(name).unwrap_or(*from);
+                    (name.clone(), from.lerp(to, s))
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum TrackKind {
+    Camera,
+    ExplodedView,
+    Panels,
+    Lighting,
+}
+
+impl TrackKind {
+    const ALL: [Self; 4] = [
+        Self::Camera,
+        Self::ExplodedView,
+        Self::Panels,
+        Self::Lighting,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Camera => "Camera",
+            Self::ExplodedView => "Explode",
+            Self::Panels => "Panels",
+            Self::Lighting => "Lighting",
+        }
+    }
+}
+
+#[derive(Component)]
+struct TimelinePanel;
+
+/// Moves the playhead to where it is pressed.
+#[derive(Component)]
+struct ScrubBar;
+
+/// The container the key markers of a track are spawned into.
+#[derive(Component)]
+struct KeyTicks(TrackKind);
+
+/// Marks the playhead's frame on a track.
+#[derive(Component)]
+struct Playhead;
+
+/// Shows the playhead's frame and time.
+#[derive(Component)]
+struct FrameLabel;
+
+#[derive(Component, Debug, Clone, Copy, Eq, PartialEq)]
+enum TimelineButton {
+    ToStart,
+    StepBack,
+    Play,
+    StepForward,
+    Export,
+    SetKey(TrackKind),
+    DeleteKey(TrackKind),
+}
+
+/// Frames per second the timeline plays and exports at.
+const FRAME_RATE: u32 = 30;
+/// Seconds a new timeline lasts.
+const DEFAULT_LENGTH: u32 = 10;
+/// Seconds the timeline can be set to last at most.
+const MAX_LENGTH: f32 = 120.0;
+
+const TRACK_WIDTH: f32 = 480.0;
+const TRACK_HEIGHT: f32 = 14.0;
+const LABEL_WIDTH: f32 = 64.0;
+const TRACK_BACKGROUND: Color = Color::srgb(0.15, 0.15, 0.15);
+const KEY_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const PLAYHEAD_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+
+fn timeline_panel_open(panel_query: Query<&Node, With<TimelinePanel>>) -> bool {
+    panel_query.iter().any(|node| node.display != Display::None)
+}
+
+fn toggle_timeline_panel(mut panel_query: Query<&mut Node, With<TimelinePanel>>) {
+    for mut node in &mut panel_query {
+        node.display = match node.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+fn step_back(mut timeline: ResMut<Timeline>) {
+    timeline.playback = None;
+    let frame = timeline.frame.saturating_sub(1);
+    timeline.seek(frame);
+}
+
+fn step_forward(mut timeline: ResMut<Timeline>) {
+    timeline.playback = None;
+    let frame = timeline.frame + 1;
+    timeline.seek(frame);
+}
+
+fn toggle_playback(mut timeline: ResMut<Timeline>) {
+    if timeline.playback.take().is_some() {
+        return;
+    }
+    // Playing from the end starts over.
+    if timeline.frame == timeline.last_frame() {
+        timeline.seek(0);
+    }
+    timeline.playback = Some(Playback {
+        start: timeline.frame,
+        played: 0.0,
+    });
+}
+
+fn stop_playback(mut timeline: ResMut<Timeline>) {
+    timeline.playback = None;
+    timeline.exporting = false;
+}
+
+fn play(time: Res<Time>, mut timeline: ResMut<Timeline>) {
+    let Some(playback) = &mut timeline.playback else {
+        return;
+    };
+    playback.played += time.delta_secs() * FRAME_RATE as f32;
+    let frame = playback.start + playback.played as u32;
+    if frame >= timeline.last_frame() {
+        timeline.playback = None;
+    }
+    timeline.seek(frame);
+}
+
+fn scrub(
+    mut timeline: ResMut<Timeline>,
+    bar_query: Query<(&Interaction, &RelativeCursorPosition), With<ScrubBar>>,
+) {
+    for (interaction, cursor) in &bar_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        // `normalized` is relative to the bar's center.
+        let fraction = (position.x + 0.5).clamp(0.0, 1.0);
+        timeline.playback = None;
+        let frame = (fraction * timeline.last_frame() as f32).round() as u32;
+        timeline.seek(frame);
+    }
+}
+
+/// Puts the camera, exploded view, panels and lighting where the timeline has them at the
+/// playhead, or at the frame being exported.
+fn pose_scene(
+    mut timeline: ResMut<Timeline>,
+    keys: Res<TimelineKeys>,
+    turntable: Res<Turntable>,
+    mut exploded: ResMut<ExplodedView>,
+    mut lighting: ResMut<LightingEnvironment>,
+    mut camera_query: Query<&mut OrbitCamera>,
+    mut panel_query: Query<(&Name, &mut CasePanel)>,
+) {
+    let frame = if timeline.exporting {
+        let Some(frame) = turntable.frame_being_recorded() else {
+            timeline.exporting = false;
+            return;
+        };
+        frame
+    } else if timeline.pose_pending {
+        timeline.pose_pending = false;
+        // The recorder moves the camera itself while exporting.
+        if let Some(key) = keys.camera_at(timeline.frame) {
+            for mut orbit in &mut camera_query {
+                key.apply(&mut orbit);
+            }
+        }
+        timeline.frame
+    } else {
+        return;
+    };
+
+    if let Some(progress) = keys.exploded_view.sample(frame, f32::lerp) {
+        exploded.target = progress;
+        exploded.progress = progress;
+    }
+    if let Some(key) = keys.panels.sample(frame, PanelsKey::lerp) {
+        for (name, mut panel) in &mut panel_query {
+            if let Some(progress) = key.get(name) {
+                panel.target = progress;
+                panel.progress = progress;
+            }
+        }
+    }
+    if let Some(environment) = keys.lighting.sample(frame, |from, _, _| from) {
+        lighting.set_if_neq(environment);
+    }
+}
+
+fn activate_timeline_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    mut timeline: ResMut<Timeline>,
+    mut keys: ResMut<TimelineKeys>,
+    button_query: Query<&TimelineButton>,
+    exploded: Res<ExplodedView>,
+    lighting: Res<LightingEnvironment>,
+    camera_query: Query<&OrbitCamera>,
+    panel_query: Query<(&Name, &CasePanel)>,
+) {
+    let Ok(button) = button_query.get(activate.entity) else {
+        return;
+    };
+
+    let frame = timeline.frame;
+    match *button {
+        TimelineButton::ToStart => {
+            timeline.playback = None;
+            timeline.seek(0);
+        }
+        TimelineButton::StepBack => commands.run_system_cached(step_back),
+        TimelineButton::Play => commands.run_system_cached(toggle_playback),
+        TimelineButton::StepForward => commands.run_system_cached(step_forward),
+        TimelineButton::Export => commands.run_system_cached(export_animation),
+        TimelineButton::SetKey(TrackKind::Camera) => {
+            if let Ok(orbit) = camera_query.single() {
+                keys.camera.set(frame, CameraKey::from_orbit(orbit));
+            }
+        }
+        TimelineButton::SetKey(TrackKind::ExplodedView) => {
+            keys.exploded_view.set(frame, exploded.target);
+        }
+        TimelineButton::SetKey(TrackKind::Panels) => {
+            let panels = panel_query
+                .iter()
+                .map(|(name, panel)| (name.to_string(), panel.target))
+                .collect();
+            keys.panels.set(frame, PanelsKey(panels));
+        }
+        TimelineButton::SetKey(TrackKind::Lighting) => keys.lighting.set(frame, *lighting),
+        TimelineButton::DeleteKey(track) => keys.remove(track, frame),
+    }
+}
+
+/// Renders every frame of the timeline through the turntable recorder.
+fn export_animation(
+    mut commands: Commands,
+    mut timeline: ResMut<Timeline>,
+    keys: Res<TimelineKeys>,
+    turntable: Res<Turntable>,
+    camera_query: Query<&OrbitCamera>,
+) {
+    if turntable.frame_being_recorded().is_some() {
+        warn!("Wait for the current recording to finish before exporting the timeline");
+        return;
+    }
+    let Ok(orbit) = camera_query.single() else {
+        return;
+    };
+
+    let poses = (0..timeline.length)
+        .map(|frame| {
+            let mut orbit = orbit.clone();
+            // Without camera keys, the camera stays where it is.
+            let key = keys
+                .camera_at(frame)
+                .unwrap_or_else(|| CameraKey::from_orbit(&orbit));
+            key.apply(&mut orbit);
+            orbit
+        })
+        .collect();
+    timeline.playback = None;
+    timeline.exporting = true;
+    commands.trigger(RecordAnimation {
+        name: "animation",
+        poses,
+        frame_duration: 1.0 / FRAME_RATE as f32,
+    });
+}
+
+fn prompt_for_length(mut commands: Commands, timeline: Res<Timeline>) {
+    commands.trigger(OpenPrompt {
+        label: format!(
+            "Timeline length in seconds (now {})",
+            timeline.length as f32 / FRAME_RATE as f32
+        ),
+        submit: timeline.set_length,
+    });
+}
+
+fn set_length(
+    In(text): In<String>,
+    mut timeline: ResMut<Timeline>,
+    mut keys: ResMut<TimelineKeys>,
+) {
+    match text.parse::<f32>() {
+        Ok(seconds) if seconds > 0.0 && seconds <= MAX_LENGTH => {
+            timeline.length = ((seconds * FRAME_RATE as f32).round() as u32).max(1);
+            let frame = timeline.frame;
+            timeline.seek(frame);
+            // Keys past the new end are kept, in case the timeline grows again, but the ticks
+            // of the others move.
+            keys.set_changed();
+        }
+        _ => warn!("\"{text}\" is not a length from 0 to {MAX_LENGTH} seconds"),
+    }
+}
+
+fn spawn_timeline_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Timeline"),
+            DespawnOnExit(Screen::Game),
+            // Spans the window to center the panel, but shouldn't catch clicks meant for the
+            // scene.
+            Node {
+                position_type: PositionType::Absolute,
+                // Above the compatibility warnings.
+                bottom: px(110.0),
+                width: percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    TimelinePanel,
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: px(4.0),
+                        padding: UiRect::all(px(6.0)),
+                        // Opened with Y.
+                        display: Display::None,
+                        ..default()
+                    },
+                    BackgroundColor(PANEL_BACKGROUND),
+                    TabGroup::new(5),
+                ))
+                .with_children(|panel| {
+                    panel
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: px(4.0),
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            row.spawn((Text::new("Timeline"), TextColor(Color::WHITE)));
+                            row.spawn(button(TimelineButton::ToStart, "|<"));
+                            row.spawn(button(TimelineButton::StepBack, "<"));
+                            row.spawn(button(TimelineButton::Play, ""));
+                            row.spawn(button(TimelineButton::StepForward, ">"));
+                            row.spawn((
+                                FrameLabel,
+                                Text::default(),
+                                TextFont::from_font_size(FONT_SIZE),
+                                TextColor(Color::WHITE),
+                            ));
+                            row.spawn(button(TimelineButton::Export, "Export"));
+                        });
+
+                    for track in TrackKind::ALL {
+                        panel
+                            .spawn(Node {
+                                flex_direction: FlexDirection::Row,
+                                align_items: AlignItems::Center,
+                                column_gap: px(4.0),
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                row.spawn((
+                                    Text::new(track.label()),
+                                    TextFont::from_font_size(FONT_SIZE),
+                                    TextColor(Color::WHITE),
+                                    Node {
+                                        width: px(LABEL_WIDTH),
+                                        ..default()
+                                    },
+                                ));
+                                row.spawn((
+                                    Button,
+                                    ScrubBar,
+                                    RelativeCursorPosition::default(),
+                                    Node {
+                                        width: px(TRACK_WIDTH),
+                                        height: px(TRACK_HEIGHT),
+                                        ..default()
+                                    },
+                                    BackgroundColor(TRACK_BACKGROUND),
+                                    children![
+                                        (
+                                            KeyTicks(track),
+                                            Node {
+                                                position_type: PositionType::Absolute,
+                                                width: percent(100.0),
+                                                height: percent(100.0),
+                                                ..default()
+                                            },
+                                            Pickable::IGNORE,
+                                        ),
+                                        (Playhead, marker(0.0, PLAYHEAD_COLOR), Pickable::IGNORE,),
+                                    ],
+                                ));
+                                row.spawn(button(TimelineButton::SetKey(track), "Key"));
+                                row.spawn(button(TimelineButton::DeleteKey(track), "Delete"));
+                            });
+                    }
+                });
+        });
+}
+
+fn button(action: TimelineButton, label: &str) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+/// A thin vertical line a fraction of the way along a track.
+fn marker(fraction: f32, color: Color) -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            left: percent(fraction * 100.0),
+            width: px(2.0),
+            height: percent(100.0),
+            ..default()
+        },
+        BackgroundColor(color),
+    )
+}
+
+/// How far along the tracks `frame` is drawn.
+fn track_fraction(timeline: &Timeline, frame: u32) -> f32 {
+    frame as f32 / timeline.last_frame().max(1) as f32
+}
+
+fn update_key_ticks(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    keys: Res<TimelineKeys>,
+    ticks_query: Query<(Entity, &KeyTicks)>,
+) {
+    for (entity, ticks) in &ticks_query {
+        commands
+            .entity(entity)
+            .despawn_children()
+            .with_children(|ticks_node| {
+                for frame in keys.frames(ticks.0) {
+                    ticks_node.spawn((
+                        marker(track_fraction(&timeline, frame), KEY_COLOR),
+                        Pickable::IGNORE,
+                    ));
+                }
+            });
+    }
+}
+
+fn update_playheads(timeline: Res<Timeline>, mut playhead_query: Query<&mut Node, With<Playhead>>) {
+    let left = percent(track_fraction(&timeline, timeline.frame) * 100.0);
+    for mut node in &mut playhead_query {
+        if node.left != left {
+            node.left = left;
+        }
+    }
+}
+
+fn update_labels(
+    timeline: Res<Timeline>,
+    mut frame_query: Query<&mut Text, With<FrameLabel>>,
+    mut button_query: Query<(&TimelineButton, &mut Text), Without<FrameLabel>>,
+) {
+    let label = format!(
+        "Frame {}/{} ({:.2} s)",
+        timeline.frame + 1,
+        timeline.length,
+        timeline.frame as f32 / FRAME_RATE as f32
+    );
+    for mut text in &mut frame_query {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+
+    let play = if timeline.playback.is_some() {
+        "Pause"
+    } else {
+        "Play"
+    };
+    for (button, mut text) in &mut button_query {
+        if *button == TimelineButton::Play && text.0 != play {
+            text.0 = play.to_string();
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Touch gestures for the camera, for tablets and phones on the web build.
+//!
+//! Dragging one finger orbits the camera, pinching two fingers zooms and dragging two fingers
+//! pans, through the same [`Actions`] the mouse and gamepads move it with. Touches that start
+//! on a panel or button are left to the UI.
+
+use bevy::{
+    picking::{hover::HoverMap, pointer::PointerId},
+    platform::collections::HashSet,
+    prelude::*,
+};
+
+use crate::{Screen, ZOOM_STEP, actions::Actions, orbit_camera_system};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        read_touch_gestures
+            .before(orbit_camera_system)
+            .run_if(in_state(Screen::Game)),
+    );
+}
+
+fn read_touch_gestures(
+    // Touches that started over the UI, until they're lifted.
+    mut ui_touches: Local<HashSet<u64>>,
+    mut actions: ResMut<Actions>,
+    touches: Res<Touches>,
+    hover_map: Res<HoverMap>,
+    node_query: Query<(), With<Node>>,
+) {
+    for touch in touches.iter_just_pressed() {
+        let over_ui = hover_map
+            .get(&PointerId::Touch(touch.id()))
+            .is_some_and(|hits| hits.keys().any(|entity| node_query.contains(*entity)));
+        if over_ui {
+            ui_touches.insert(touch.id());
+        }
+    }
+    for touch in touches
+        .iter_just_released()
+        .chain(touches.iter_just_canceled())
+    {
+        ui_touches.remove(&touch.id());
+    }
+
+    let fingers: Vec<_> = touches
+        .iter()
+        .filter(|touch| !ui_touches.contains(&touch.id()))
+        .collect();
+    match fingers.as_slice() {
+        [finger] => actions.orbit_drag += finger.delta(),
+        [first, second] => {
+            let midpoint = first.position().midpoint(second.position());
+            let previous_midpoint = first
+                .previous_position()
+                .midpoint(second.previous_position());
+            actions.pan_drag += midpoint - previous_midpoint;
+
+            // As many scroll wheel lines as it takes to scale the view by as much as the
+            // fingers spread, so the case stays under them.
+            let spread = first.position().distance(second.position());
+            let previous_spread = first
+                .previous_position()
+                .distance(second.previous_position());
+            if spread > 0.0 && previous_spread > 0.0 {
+                actions.zoom_steps += (spread / previous_spread).ln() / ZOOM_STEP.ln();
+            }
+        }
+        // Three fingers and more are no gesture.
+        _ => {}
+    }
+}
@@ -0,0 +1,421 @@
+//! Recording a turntable: a full, constant-speed spin around the case, exported as a numbered
+//! PNG sequence or an animated GIF.
+//!
+//! Press T or use the command palette to record. The orbit camera is locked for the length of
+//! the recording, and the frames are rendered offscreen like a view capture, at the capture
+//! panel's resolution and background. On the web, every PNG of a sequence is its own download.
+//!
+//! Other animations, like the timeline's, are recorded the same way with [`RecordAnimation`].
+
+use std::{
+    f32::consts::TAU,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use bevy::{
+    ecs::system::SystemId,
+    prelude::*,
+    render::view::screenshot::{Screenshot, ScreenshotCaptured},
+    tasks::AsyncComputeTaskPool,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    OrbitCamera, Screen,
+    capture::{
+        CAPTURE_DELAY_FRAMES, CaptureSettings, ExportStyle, OffscreenCamera, WatermarkImage,
+        encode_png, save_file, spawn_offscreen_camera, timestamp, to_rgba,
+    },
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    keybindings::{KeyAction, key_just_pressed},
+    orbit_camera_system,
+    settings::Settings,
+    ui_navigation::FONT_SIZE,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    let set_frame_count = app.register_system(set_frame_count);
+    app.insert_resource(Turntable {
+        frames: DEFAULT_FRAMES,
+        format: TurntableFormat::PngSequence,
+        set_frame_count,
+        recording: None,
+    });
+    app.add_observer(record_animation);
+    app.add_systems(OnEnter(Screen::Game), spawn_status_label);
+    app.add_systems(OnExit(Screen::Game), stop_recording);
+    app.add_systems(
+        Update,
+        (
+            start_recording.run_if(key_just_pressed(KeyAction::Turntable)),
+            lock_orbit_camera.before(orbit_camera_system),
+            capture_frame.after(orbit_camera_system),
+            finish_recording,
+            update_status_label,
+        )
+            .chain()
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Turntable: record", start_recording);
+    app.add_palette_command(
+        "Turntable: switch between PNG frames and GIF",
+        toggle_format,
+    );
+    app.add_palette_command("Turntable: set the frame count", prompt_for_frame_count);
+}
+
+/// Records an animation with the orbit camera at each of `poses` in turn, in the turntable's
+/// format. Ignored while another recording is in progress.
+#[derive(Event, Clone)]
+pub struct RecordAnimation {
+    /// Names the files, like `turntable-<timestamp>.gif`.
+    pub name: &'static str,
+    /// The camera for each frame.
+    pub poses: Vec<OrbitCamera>,
+    /// Seconds each frame is shown for when the GIF plays back.
+    pub frame_duration: f32,
+}
+
+#[derive(Resource)]
+pub struct Turntable {
+    /// How many frames a full spin is split into.
+    frames: u32,
+    format: TurntableFormat,
+    /// Parses the frame count it's given.
+    set_frame_count: SystemId<In<String>>,
+    recording: Option<Recording>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum TurntableFormat {
+    PngSequence,
+    Gif,
+}
+
+struct Recording {
+    camera: Entity,
+    name: &'static str,
+    /// The orbit camera for each frame.
+    poses: Vec<OrbitCamera>,
+    frame_duration: f32,
+    format: TurntableFormat,
+    /// Names the files of this recording.
+    timestamp: u64,
+    /// Frames left before the first one is taken, while the camera warms up.
+    delay: u32,
+    /// Frames whose screenshot has been requested.
+    requested: u32,
+    /// Frames whose screenshot has arrived.
+    received: Arc<AtomicU32>,
+    /// The arrived frames, kept for encoding the GIF.
+    gif_frames: Arc<Mutex<Vec<Option<image::RgbaImage>>>>,
+}
+
+impl Recording {
+    fn frames(&self) -> u32 {
+        self.poses.len() as u32
+    }
+
+    /// The frame the camera is on: the next one to be requested, or the last one once they all
+    /// have been.
+    fn current_frame(&self) -> u32 {
+        self.requested.min(self.frames() - 1)
+    }
+}
+
+impl Turntable {
+    /// The frame of the recording in progress that is being rendered, if any.
+    pub fn frame_being_recorded(&self) -> Option<u32> {
+        self.recording.as_ref().map(Recording::current_frame)
+    }
+}
+
+#[derive(Component)]
+struct TurntableStatus;
+
+const DEFAULT_FRAMES: u32 = 72;
+const MAX_FRAMES: u32 = 720;
+/// Seconds a full spin takes when the GIF plays back.
+const GIF_SPIN_DURATION: f32 = 6.0;
+/// GIFs are scaled down to this width at most, as they get very large otherwise.
+const GIF_MAX_WIDTH: u32 = 640;
+const STATUS_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+
+fn start_recording(
+    mut commands: Commands,
+    turntable: Res<Turntable>,
+    camera_query: Query<&OrbitCamera>,
+) {
+    let Ok(orbit) = camera_query.single() else {
+        return;
+    };
+
+    let frames = turntable.frames;
+    let poses = (0..frames)
+        .map(|frame| {
+            let mut orbit = orbit.clone();
+            // Skip the rest of an ongoing zoom.
+            orbit.radius = orbit.desired_radius;
+            orbit.yaw += TAU * frame as f32 / frames as f32;
+            orbit
+        })
+        .collect();
+    commands.trigger(RecordAnimation {
+        name: "turntable",
+        poses,
+        frame_duration: GIF_SPIN_DURATION / frames as f32,
+    });
+}
+
+fn record_animation(
+    record: On<RecordAnimation>,
+    mut commands: Commands,
+    mut turntable: ResMut<Turntable>,
+    settings: Res<CaptureSettings>,
+    mut images: ResMut<Assets<Image>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_query: Query<&Projection, With<OrbitCamera>>,
+) {
+    if turntable.recording.is_some() {
+        return;
+    }
+    let (Some(first), Ok(projection)) = (record.poses.first(), camera_query.single()) else {
+        return;
+    };
+
+    let camera = spawn_offscreen_camera(
+        &mut commands,
+        &mut images,
+        &settings,
+        window.physical_size(),
+        first.transform(),
+        projection.clone(),
+    )
+    .insert(Name::new("Turntable Camera"))
+    .id();
+
+    let frames = record.poses.len();
+    turntable.recording = Some(Recording {
+        camera,
+        name: record.name,
+        poses: record.poses.clone(),
+        frame_duration: record.frame_duration,
+        format: turntable.format,
+        timestamp: timestamp(),
+        delay: CAPTURE_DELAY_FRAMES,
+        requested: 0,
+        received: Arc::new(AtomicU32::new(0)),
+        gif_frames: Arc::new(Mutex::new(vec![None; frames])),
+    });
+}
+
+fn stop_recording(mut turntable: ResMut<Turntable>) {
+    // The camera is despawned with the level.
+    turntable.recording = None;
+}
+
+/// Keeps the orbit camera on the frame being recorded, undoing any input.
+fn lock_orbit_camera(turntable: Res<Turntable>, mut camera_query: Query<&mut OrbitCamera>) {
+    let Some(recording) = &turntable.recording else {
+        return;
+    };
+    let orbit = &recording.poses[recording.current_frame() as usize];
+    for mut camera in &mut camera_query {
+        *camera = orbit.clone();
+    }
+}
+
+fn capture_frame(
+    mut commands: Commands,
+    mut turntable: ResMut<Turntable>,
+    mut camera_query: Query<(&OffscreenCamera, &mut Transform)>,
+    settings: Res<Settings>,
+    watermark: Res<WatermarkImage>,
+    images: Res<Assets<Image>>,
+) {
+    let Some(recording) = &mut turntable.recording else {
+        return;
+    };
+    if recording.requested == recording.frames() {
+        return;
+    }
+    let Ok((offscreen, mut transform)) = camera_query.get_mut(recording.camera) else {
+        return;
+    };
+
+    let frame = recording.requested;
+    *transform = recording.poses[frame as usize].transform();
+    if recording.delay > 0 {
+        recording.delay -= 1;
+        return;
+    }
+
+    let size = offscreen.size;
+    let format = recording.format;
+    let path = format!(
+        "{}-{}/frame-{:04}.png",
+        recording.name,
+        recording.timestamp,
+        frame + 1
+    );
+    let received = recording.received.clone();
+    let gif_frames = recording.gif_frames.clone();
+    let style = ExportStyle::new(offscreen, &settings, &watermark, &images);
+    commands
+        .spawn(Screenshot::image(offscreen.image.clone()))
+        .observe(move |captured: On<ScreenshotCaptured>| {
+            if let Some(mut image) = to_rgba(&captured.image, size) {
+                style.apply(&mut image);
+                match format {
+                    TurntableFormat::PngSequence => {
+                        if let Some(png) = encode_png(&image) {
+                            save_file(&path, png);
+                        }
+                    }
+                    TurntableFormat::Gif => {
+                        if let Ok(mut frames) = gif_frames.lock() {
+                            frames[frame as usize] = Some(image);
+                        }
+                    }
+                }
+            }
+            received.fetch_add(1, Ordering::Relaxed);
+        });
+    recording.requested += 1;
+}
+
+/// Ends the recording once every frame has arrived, encoding the GIF in the background.
+fn finish_recording(mut commands: Commands, mut turntable: ResMut<Turntable>) {
+    let Some(recording) = &turntable.recording else {
+        return;
+    };
+    if recording.received.load(Ordering::Relaxed) < recording.frames() {
+        return;
+    }
+
+    commands.entity(recording.camera).despawn();
+    if recording.format == TurntableFormat::Gif
+        && let Ok(mut frames) = recording.gif_frames.lock()
+    {
+        let frames: Vec<_> = frames.drain(..).flatten().collect();
+        let path = format!("{}-{}.gif", recording.name, recording.timestamp);
+        let frame_duration = recording.frame_duration;
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                if let Some(gif) = encode_gif(frames, frame_duration) {
+                    save_file(&path, gif);
+                }
+            })
+            .detach();
+    } else {
+        info!(
+            "Recorded the {} ({} frames)",
+            recording.name,
+            recording.frames()
+        );
+    }
+    turntable.recording = None;
+}
+
+fn encode_gif(frames: Vec<image::RgbaImage>, frame_duration: f32) -> Option<Vec<u8>> {
+    use image::{
+        Delay, Frame,
+        codecs::gif::{GifEncoder, Repeat},
+        imageops::FilterType,
+    };
+
+    let delay = Delay::from_numer_denom_ms((frame_duration * 1000.0) as u32, 1);
+    let frames = frames.into_iter().map(|image| {
+        let image = if image.width() > GIF_MAX_WIDTH {
+            let height = image.height() * GIF_MAX_WIDTH / image.width();
+            image::imageops::resize(&image, GIF_MAX_WIDTH, height, FilterType::Triangle)
+        } else {
+            image
+        };
+        Frame::from_parts(image, 0, 0, delay)
+    });
+
+    let mut gif = Vec::new();
+    let result = {
+        let mut encoder = GifEncoder::new(&mut gif);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .and_then(|()| encoder.encode_frames(frames))
+    };
+    match result {
+        Ok(()) => Some(gif),
+        Err(error) => {
+            error!("Could not encode the GIF: {error}");
+            None
+        }
+    }
+}
+
+fn toggle_format(mut turntable: ResMut<Turntable>) {
+    turntable.format = match turntable.format {
+        TurntableFormat::PngSequence => TurntableFormat::Gif,
+        TurntableFormat::Gif => TurntableFormat::PngSequence,
+    };
+    info!("Turntables are recorded as {:?}", turntable.format);
+}
+
+fn prompt_for_frame_count(mut commands: Commands, turntable: Res<Turntable>) {
+    commands.trigger(OpenPrompt {
+        label: format!("Frames per spin (now {})", turntable.frames),
+        submit: turntable.set_frame_count,
+    });
+}
+
+fn set_frame_count(In(text): In<String>, mut turntable: ResMut<Turntable>) {
+    match text.parse::<u32>() {
+        Ok(frames) if (1..=MAX_FRAMES).contains(&frames) => turntable.frames = frames,
+        _ => warn!("\"{text}\" is not a frame count from 1 to {MAX_FRAMES}"),
+    }
+}
+
+fn spawn_status_label(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Turntable Status"),
+        DespawnOnExit(Screen::Game),
+        // Spans the window to center the label, but shouldn't catch clicks meant for the scene.
+        Node {
+            position_type: PositionType::Absolute,
+            // Below the macro recording label.
+            top: px(60.0),
+            width: percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            TurntableStatus,
+            Text::default(),
+            TextFont::from_font_size(FONT_SIZE),
+            TextColor(STATUS_COLOR),
+            Pickable::IGNORE,
+        )],
+    ));
+}
+
+fn update_status_label(
+    turntable: Res<Turntable>,
+    mut label_query: Query<&mut Text, With<TurntableStatus>>,
+) {
+    let label = match &turntable.recording {
+        Some(recording) => format!(
+            "Recording the {} ({}/{} frames)",
+            recording.name,
+            recording.received.load(Ordering::Relaxed),
+            recording.frames()
+        ),
+        None => String::new(),
+    };
+    for mut text in &mut label_query {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
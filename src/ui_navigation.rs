@@ -0,0 +1,168 @@
+//! Keyboard navigation for the UI.
+//!
+//! Tab/Shift+Tab or the arrow keys move the focus between focusable elements, Enter or Space
+//! activates the focused element and Escape hands the keyboard back to the scene. A gamepad's
+//! D-pad, A and B buttons do the same, through the [`Actions`] they map to.
+//!
+//! Every panel shares one look: panels over the scene have a [`PANEL_BACKGROUND`] and those
+//! over other panels or menus an [`OVERLAY_BACKGROUND`], a [`ButtonStyle`] lights up as it's
+//! hovered and pressed, and panel text is [`FONT_SIZE`].
+
+use bevy::{
+    color::palettes::css::YELLOW,
+    input_focus::{
+        InputDispatchPlugin, InputFocus, InputFocusVisible,
+        tab_navigation::{TabIndex, TabNavigation, TabNavigationPlugin},
+    },
+    prelude::*,
+};
+
+use crate::actions::Actions;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((InputDispatchPlugin, TabNavigationPlugin));
+    app.add_observer(activate_on_click);
+    app.add_systems(
+        Update,
+        (
+            navigate_focus,
+            activate_focused,
+            release_focus,
+            highlight_focus
+                .run_if(resource_changed::<InputFocus>.or(resource_changed::<InputFocusVisible>)),
+        )
+            .chain(),
+    );
+    app.add_systems(Update, update_button_colors);
+}
+
+/// The size of the text in panels and on their buttons.
+pub const FONT_SIZE: f32 = 14.0;
+/// Behind a panel, which leaves the scene showing through.
+pub const PANEL_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+/// Behind the menus, tables and dialogs shown over the scene and the panels, which need to
+/// stand out from both.
+pub const OVERLAY_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.8);
+
+const BUTTON_BACKGROUND: Color = Color::srgb(0.2, 0.2, 0.2);
+const BUTTON_HOVERED: Color = Color::srgb(0.3, 0.3, 0.3);
+const BUTTON_PRESSED: Color = Color::srgb(0.4, 0.4, 0.4);
+
+/// A button whose background lights up while it is hovered or pressed.
+#[derive(Component, Default)]
+#[require(Button, BackgroundColor = BackgroundColor(BUTTON_BACKGROUND))]
+pub struct ButtonStyle;
+
+/// Triggered on a focusable element when it is clicked, or activated from the keyboard.
+#[derive(EntityEvent, Debug, Clone)]
+pub struct Activate {
+    pub entity: Entity,
+}
+
+/// Makes a UI element reachable with the keyboard. Its parent chain needs a
+/// [`TabGroup`](bevy::input_focus::tab_navigation::TabGroup).
+pub fn focusable() -> impl Bundle {
+    (
+        TabIndex(0),
+        // Hidden until focused, so focusing doesn't move the element between archetypes.
+        Outline::new(px(2.0), px(1.0), Color::NONE),
+    )
+}
+
+/// A focusable element with a value, like a slider, that Left and Right step rather than moving
+/// the focus off it (see [`Actions::adjust`](crate::actions::Actions::adjust)).
+#[derive(Component, Default)]
+pub struct Adjustable;
+
+/// A run condition for scene controls that would clash with UI navigation keys.
+pub fn ui_not_focused(focus: Res<InputFocus>, focusable_query: Query<(), With<TabIndex>>) -> bool {
+    focus
+        .get()
+        .is_none_or(|entity| !focusable_query.contains(entity))
+}
+
+fn activate_on_click(
+    click: On<Pointer<Click>>,
+    mut commands: Commands,
+    focusable_query: Query<(), With<TabIndex>>,
+) {
+    if click.button == PointerButton::Primary && focusable_query.contains(click.entity) {
+        commands.trigger(Activate {
+            entity: click.entity,
+        });
+    }
+}
+
+fn navigate_focus(
+    actions: Res<Actions>,
+    nav: TabNavigation,
+    mut focus: ResMut<InputFocus>,
+    mut visible: ResMut<InputFocusVisible>,
+) {
+    let Some(action) = actions.navigate else {
+        return;
+    };
+
+    if let Ok(next) = nav.navigate(&focus, action) {
+        focus.set(next);
+        visible.0 = true;
+    }
+}
+
+fn activate_focused(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    focus: Res<InputFocus>,
+    focusable_query: Query<(), With<TabIndex>>,
+) {
+    if !actions.activate {
+        return;
+    }
+
+    if let Some(entity) = focus.get()
+        && focusable_query.contains(entity)
+    {
+        commands.trigger(Activate { entity });
+    }
+}
+
+pub fn release_focus(
+    actions: Res<Actions>,
+    mut focus: ResMut<InputFocus>,
+    mut visible: ResMut<InputFocusVisible>,
+) {
+    if actions.back {
+        focus.clear();
+        visible.0 = false;
+    }
+}
+
+fn highlight_focus(
+    focus: Res<InputFocus>,
+    visible: Res<InputFocusVisible>,
+    mut outline_query: Query<(Entity, &mut Outline), With<TabIndex>>,
+) {
+    for (entity, mut outline) in &mut outline_query {
+        let color = if visible.0 && focus.get() == Some(entity) {
+            YELLOW.into()
+        } else {
+            Color::NONE
+        };
+        outline.color = color;
+    }
+}
+
+fn update_button_colors(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ButtonStyle>),
+    >,
+) {
+    for (interaction, mut background) in &mut button_query {
+        background.0 = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED,
+            Interaction::Hovered => BUTTON_HOVERED,
+            Interaction::None => BUTTON_BACKGROUND,
+        };
+    }
+}
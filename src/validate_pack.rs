@@ -0,0 +1,638 @@
+//! A `validate-pack` mode for pack authors to check an asset pack before publishing it.
+//!
+//! A pack is laid out like `assets`: parts in `parts/catalog.ron`, cases in `models/cases.ron`
+//! and the models they name. `pc_case_visualizer validate-pack path/to/pack` loads all of it
+//! without opening a window, checks the manifests, anchors, materials and the scale of every
+//! model, and prints a report in RON. The app exits with an error when the pack has problems
+//! that would break it, rather than only look off.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    asset::{AssetMetaCheck, RecursiveDependencyLoadState},
+    camera::primitives::MeshAabb,
+    gltf::{Gltf, GltfMesh, GltfNode},
+    log::{Level, LogPlugin},
+    math::Affine3A,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::{RenderPlugin, settings::WgpuSettings},
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    cases::{CaseManifest, NODE_ANCHOR_PREFIX, node_anchor_kind},
+    model_import::suggest_fix,
+    parts::{PartCatalog, PartKind},
+};
+
+/// The argument that starts the app in validation mode, followed by the pack's directory.
+const VALIDATE_FLAG: &str = "validate-pack";
+const CATALOG_PATH: &str = "parts/catalog.ron";
+const CASES_PATH: &str = "models/cases.ron";
+/// How far, in millimeters, an anchor may sit outside its case's dimensions, for mount points
+/// on the outside of a panel.
+const ANCHOR_MARGIN: f32 = 10.0;
+
+/// The pack to validate, if the app was started with [`VALIDATE_FLAG`]. Without a directory
+/// after it, the app's own `assets` are validated.
+pub fn requested() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    if args.next()? != VALIDATE_FLAG {
+        return None;
+    }
+    Some(args.next().unwrap_or_else(|| "assets".to_string()).into())
+}
+
+/// Validates the pack at `root` and prints the report.
+pub fn run(root: &Path) -> AppExit {
+    let root = match root.canonicalize() {
+        Ok(root) => root,
+        Err(error) => {
+            eprintln!("Can't open the pack at {}: {error}", root.display());
+            return AppExit::error();
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(AssetPlugin {
+                file_path: root.to_string_lossy().into_owned(),
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            })
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            })
+            // Assets load without a GPU, so the pack can be checked on a build server.
+            .set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    backends: None,
+                    ..default()
+                }
+                .into(),
+                ..default()
+            })
+            // Keeps the log off stdout's report unless something's wrong.
+            .set(LogPlugin {
+                level: Level::WARN,
+                ..default()
+            })
+            .disable::<WinitPlugin>(),
+    );
+    // Winit would otherwise run the app's loop.
+    app.add_plugins(ScheduleRunnerPlugin::default());
+    let mut issues = Issues::default();
+    let files = check_files(&root, &mut issues);
+    app.insert_resource(Pack {
+        root,
+        files,
+        issues,
+        models: Vec::new(),
+    });
+    app.add_systems(Startup, load_models);
+    app.add_systems(Update, finish_validation);
+    app.run()
+}
+
+#[derive(Resource)]
+struct Pack {
+    root: PathBuf,
+    files: PackFiles,
+    /// What [`check_files`] found, to report with the rest.
+    issues: Issues,
+    /// The models the manifests name, by path.
+    models: Vec<(String, Handle<Gltf>)>,
+}
+
+/// The manifests of a pack that could be read, and the models they name that are there.
+struct PackFiles {
+    catalog: Option<PartCatalog>,
+    cases: Option<CaseManifest>,
+    models: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct Report {
+    pack: String,
+    /// Whether there are no errors. Warnings don't fail a pack.
+    passed: bool,
+    issues: Vec<Issue>,
+}
+
+#[derive(Serialize, Debug)]
+struct Issue {
+    severity: Severity,
+    /// The file, relative to the pack, the issue is in.
+    file: String,
+    /// What in the file it's about: a part's id, a case's name or a node of a model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subject: Option<String>,
+    message: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Eq, PartialEq)]
+enum Severity {
+    /// The pack won't work as intended.
+    Error,
+    /// Probably a mistake, though the pack works.
+    Warning,
+}
+
+/// Collects the issues of a pack as it's checked.
+#[derive(Default)]
+struct Issues(Vec<Issue>);
+
+impl Issues {
+    fn push(
+        &mut self,
+        severity: Severity,
+        file: &str,
+        subject: Option<&str>,
+        message: impl Into<String>,
+    ) {
+        self.0.push(Issue {
+            severity,
+            file: file.to_string(),
+            subject: subject.map(str::to_string),
+            message: message.into(),
+        });
+    }
+
+    fn error(&mut self, file: &str, subject: Option<&str>, message: impl Into<String>) {
+        self.push(Severity::Error, file, subject, message);
+    }
+
+    fn warn(&mut self, file: &str, subject: Option<&str>, message: impl Into<String>) {
+        self.push(Severity::Warning, file, subject, message);
+    }
+}
+
+/// What's learned about a model from its glTF, for the checks that compare it with the
+/// manifests.
+struct ModelSummary {
+    /// The bounds of its meshes, in its own space.
+    size: Vec3,
+    /// Every node's name and position.
+    nodes: Vec<(String, Vec3)>,
+}
+
+/// The checks that need only the pack's files: that it has a manifest, that each one parses,
+/// and that the models they name are there. The models are checked further once loaded.
+fn check_files(root: &Path, issues: &mut Issues) -> PackFiles {
+    // A pack may bring only parts or only cases.
+    let catalog = read_manifest::<PartCatalog>(root, CATALOG_PATH, issues);
+    let cases = read_manifest::<CaseManifest>(root, CASES_PATH, issues);
+    if !root.join(CATALOG_PATH).is_file() && !root.join(CASES_PATH).is_file() {
+        issues.error(
+            "",
+            None,
+            format!("The pack has neither a {CATALOG_PATH} nor a {CASES_PATH}"),
+        );
+    }
+
+    let named: Vec<(&str, &str, &String)> = cases
+        .iter()
+        .flat_map(|manifest| &manifest.cases)
+        .map(|case| (CASES_PATH, case.name.as_str(), &case.model))
+        .chain(catalog.iter().flat_map(|catalog| {
+            catalog.parts.iter().filter_map(|part| {
+                let model = part.model.as_ref()?;
+                Some((CATALOG_PATH, part.id.as_str(), model))
+            })
+        }))
+        .collect();
+    let mut checked = HashSet::new();
+    let mut models = Vec::new();
+    for (file, subject, model) in named {
+        if !checked.insert(model.clone()) {
+            continue;
+        }
+        if root.join(model).is_file() {
+            models.push(model.clone());
+        } else {
+            issues.error(
+                file,
+                Some(subject),
+                format!("The model {model} doesn't exist"),
+            );
+        }
+    }
+    PackFiles {
+        catalog,
+        cases,
+        models,
+    }
+}
+
+/// Parses the manifest at `path` in the pack, if it has one.
+fn read_manifest<T: DeserializeOwned>(root: &Path, path: &str, issues: &mut Issues) -> Option<T> {
+    let contents = match fs::read_to_string(root.join(path)) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return None,
+        Err(error) => {
+            issues.error(path, None, format!("Can't read the file: {error}"));
+            return None;
+        }
+    };
+    ron::from_str(&contents)
+        .map_err(|error| issues.error(path, None, format!("Can't parse the file: {error}")))
+        .ok()
+}
+
+fn load_models(mut pack: ResMut<Pack>, asset_server: Res<AssetServer>) {
+    pack.models = pack
+        .files
+        .models
+        .iter()
+        .map(|path| (path.clone(), asset_server.load(path.clone())))
+        .collect();
+}
+
+fn finish_validation(
+    mut exit: MessageWriter<AppExit>,
+    mut pack: ResMut<Pack>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    gltf_nodes: Res<Assets<GltfNode>>,
+    gltf_meshes: Res<Assets<GltfMesh>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    if pack.models.iter().any(|(_, handle)| {
+        !matches!(
+            asset_server.get_recursive_dependency_load_state(handle),
+            Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed(_))
+        )
+    }) {
+        return;
+    }
+
+    let mut issues = std::mem::take(&mut pack.issues);
+    let mut summaries = HashMap::new();
+    for (path, handle) in &pack.models {
+        if let Some(RecursiveDependencyLoadState::Failed(error)) =
+            asset_server.get_recursive_dependency_load_state(handle)
+        {
+            issues.error(path, None, error.to_string());
+            continue;
+        }
+        let Some(gltf) = gltfs.get(handle) else {
+            continue;
+        };
+        check_materials(&mut issues, path, gltf, &gltf_meshes);
+        let summary = summarize_model(gltf, &gltf_nodes, &gltf_meshes, &meshes);
+        if summary.size == Vec3::ZERO {
+            issues.error(path, None, "The model has no meshes");
+        }
+        summaries.insert(path.clone(), summary);
+    }
+
+    let catalog = pack.files.catalog.as_ref();
+    if let Some(catalog) = catalog {
+        check_catalog(&mut issues, catalog, &summaries);
+    }
+    if let Some(manifest) = &pack.files.cases {
+        check_cases(&mut issues, manifest, catalog, &summaries);
+    }
+
+    let issues = issues.0;
+    let passed = issues.iter().all(|issue| issue.severity != Severity::Error);
+    let report = Report {
+        pack: pack.root.display().to_string(),
+        passed,
+        issues,
+    };
+    match ron::ser::to_string_pretty(&report, default()) {
+        Ok(report) => println!("{report}"),
+        Err(error) => {
+            error!("Couldn't write the report: {error}");
+            exit.write(AppExit::error());
+            return;
+        }
+    }
+    exit.write(if passed {
+        AppExit::Success
+    } else {
+        AppExit::error()
+    });
+}
+
+/// Primitives without a material render in plain white, which is rarely what the modeler saw.
+fn check_materials(issues: &mut Issues, path: &str, gltf: &Gltf, gltf_meshes: &Assets<GltfMesh>) {
+    for mesh in gltf.meshes.iter().filter_map(|mesh| gltf_meshes.get(mesh)) {
+        let unmaterialed = mesh
+            .primitives
+            .iter()
+            .filter(|primitive| primitive.material.is_none())
+            .count();
+        if unmaterialed > 0 {
+            issues.warn(
+                path,
+                Some(&mesh.name),
+                format!("{unmaterialed} of its primitives have no material"),
+            );
+        }
+    }
+}
+
+fn summarize_model(
+    gltf: &Gltf,
+    gltf_nodes: &Assets<GltfNode>,
+    gltf_meshes: &Assets<GltfMesh>,
+    meshes: &Assets<Mesh>,
+) -> ModelSummary {
+    let children: HashSet<AssetId<GltfNode>> = gltf
+        .nodes
+        .iter()
+        .filter_map(|node| gltf_nodes.get(node))
+        .flat_map(|node| node.children.iter().map(Handle::id))
+        .collect();
+    let mut stack: Vec<(Handle<GltfNode>, Affine3A)> = gltf
+        .nodes
+        .iter()
+        .filter(|node| !children.contains(&node.id()))
+        .map(|node| (node.clone(), Affine3A::IDENTITY))
+        .collect();
+
+    let (mut min, mut max) = (Vec3::MAX, Vec3::MIN);
+    let mut nodes = Vec::new();
+    while let Some((handle, parent)) = stack.pop() {
+        let Some(node) = gltf_nodes.get(&handle) else {
+            continue;
+        };
+        let transform = parent * node.transform.compute_affine();
+        nodes.push((node.name.clone(), transform.translation.into()));
+        stack.extend(node.children.iter().map(|child| (child.clone(), transform)));
+
+        let primitives = node
+            .mesh
+            .as_ref()
+            .and_then(|mesh| gltf_meshes.get(mesh))
+            .into_iter()
+            .flat_map(|mesh| &mesh.primitives);
+        for aabb in primitives.filter_map(|primitive| meshes.get(&primitive.mesh)?.compute_aabb()) {
+            let (center, half) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+            for corner in [-1.0, 1.0].into_iter().flat_map(|x| {
+                [-1.0, 1.0]
+                    .into_iter()
+                    .flat_map(move |y| [-1.0, 1.0].map(|z| Vec3::new(x, y, z)))
+            }) {
+                let corner = transform.transform_point3(center + half * corner);
+                min = min.min(corner);
+                max = max.max(corner);
+            }
+        }
+    }
+
+    ModelSummary {
+        size: if min.cmple(max).all() {
+            max - min
+        } else {
+            Vec3::ZERO
+        },
+        nodes,
+    }
+}
+
+fn check_scale(issues: &mut Issues, model: &str, summary: &ModelSummary, expected: [f32; 3]) {
+    if let Some(fix) = suggest_fix(summary.size, Vec3::from(expected)) {
+        let size = summary.size;
+        issues.warn(
+            model,
+            None,
+            format!(
+                "{}: it measures {:.1} x {:.1} x {:.1}",
+                fix.describe(model),
+                size.x,
+                size.y,
+                size.z
+            ),
+        );
+    }
+}
+
+fn check_catalog(
+    issues: &mut Issues,
+    catalog: &PartCatalog,
+    summaries: &HashMap<String, ModelSummary>,
+) {
+    let mut ids = HashSet::new();
+    for part in &catalog.parts {
+        let subject = Some(part.id.as_str());
+        if !ids.insert(&part.id) {
+            issues.error(CATALOG_PATH, subject, "Another part has the same id");
+        }
+        if part.size.iter().any(|&side| side <= 0.0) {
+            issues.error(CATALOG_PATH, subject, "Its size has to be positive");
+        }
+        if let Some(model) = &part.model
+            && let Some(summary) = summaries.get(model)
+        {
+            check_scale(issues, model, summary, part.size);
+        }
+    }
+}
+
+fn check_cases(
+    issues: &mut Issues,
+    manifest: &CaseManifest,
+    catalog: Option<&PartCatalog>,
+    summaries: &HashMap<String, ModelSummary>,
+) {
+    let mut models = HashSet::new();
+    for case in &manifest.cases {
+        let subject = Some(case.name.as_str());
+        if !models.insert(&case.model) {
+            issues.error(CASES_PATH, subject, "Another case has the same model");
+        }
+        if case.dimensions.iter().any(|&side| side <= 0.0) {
+            issues.error(CASES_PATH, subject, "Its dimensions have to be positive");
+        }
+        for clearance in &case.clearances {
+            let (min, max) = (Vec3::from(clearance.min), Vec3::from(clearance.max));
+            if !min.cmplt(max).all() {
+                issues.error(
+                    CASES_PATH,
+                    subject,
+                    format!(
+                        "The clearance \"{}\" has a min that isn't below its max",
+                        clearance.name
+                    ),
+                );
+            }
+            if clearance.kinds.is_empty() {
+                issues.warn(
+                    CASES_PATH,
+                    subject,
+                    format!("The clearance \"{}\" applies to no parts", clearance.name),
+                );
+            }
+        }
+
+        // The case floor's center is the origin.
+        let half = Vec3::from(case.dimensions) / 2.0;
+        let bounds_min = Vec3::new(-half.x, 0.0, -half.z) - ANCHOR_MARGIN;
+        let bounds_max = Vec3::new(half.x, case.dimensions[1], half.z) + ANCHOR_MARGIN;
+        let outside = |position: Vec3| {
+            !(position.cmpge(bounds_min).all() && position.cmple(bounds_max).all())
+        };
+
+        let mut anchors: Vec<(String, PartKind)> = Vec::new();
+        for anchor in &case.anchors {
+            if anchors.iter().any(|(name, _)| *name == anchor.name) {
+                issues.error(
+                    CASES_PATH,
+                    subject,
+                    format!("More than one anchor is named \"{}\"", anchor.name),
+                );
+            }
+            if outside(Vec3::from(anchor.position)) {
+                issues.warn(
+                    CASES_PATH,
+                    subject,
+                    format!("The anchor \"{}\" is outside the case", anchor.name),
+                );
+            }
+            anchors.push((anchor.name.clone(), anchor.kind));
+        }
+
+        if let Some(summary) = summaries.get(&case.model) {
+            check_scale(issues, &case.model, summary, case.dimensions);
+            for (node, position) in &summary.nodes {
+                let Some(anchor_name) = node.strip_prefix(NODE_ANCHOR_PREFIX) else {
+                    continue;
+                };
+                let Some(kind) = node_anchor_kind(anchor_name) else {
+                    issues.warn(
+                        &case.model,
+                        Some(node),
+                        format!(
+                            "The anchor node doesn't start with a kind of part after \
+                             \"{NODE_ANCHOR_PREFIX}\""
+                        ),
+                    );
+                    continue;
+                };
+                if outside(*position) {
+                    issues.warn(
+                        &case.model,
+                        Some(node),
+                        "The anchor node is outside the case",
+                    );
+                }
+                anchors.push((anchor_name.to_string(), kind));
+            }
+        }
+
+        // Parts without an anchor still show, at their catalog position.
+        let kinds: Vec<PartKind> = catalog
+            .into_iter()
+            .flat_map(|catalog| catalog.parts.iter().map(|part| part.kind))
+            .collect();
+        for kind in PartKind::ALL
+            .into_iter()
+            .filter(|kind| kinds.contains(kind))
+        {
+            if !anchors.iter().any(|(_, anchor_kind)| *anchor_kind == kind) {
+                issues.warn(
+                    CASES_PATH,
+                    subject,
+                    format!(
+                        "No anchor takes {} parts, so they'll sit at their catalog positions",
+                        kind.name()
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pack in a temporary directory of its own, with `files` written into it.
+    fn pack(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("validate_pack_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn check(root: &Path) -> (PackFiles, Vec<(String, Option<String>, String)>) {
+        let mut issues = Issues::default();
+        let files = check_files(root, &mut issues);
+        let _ = fs::remove_dir_all(root);
+        let messages = issues
+            .0
+            .into_iter()
+            .inspect(|issue| assert_eq!(issue.severity, Severity::Error))
+            .map(|issue| (issue.file, issue.subject, issue.message))
+            .collect();
+        (files, messages)
+    }
+
+    const CATALOG: &str = r#"(parts: [(id: "gpu", name: "GPU", kind: Gpu,
+        size: (40.0, 120.0, 300.0), position: (0.0, 0.0, 0.0), model: Some("models/gpu.glb"))])"#;
+
+    #[test]
+    fn missing_catalog() {
+        let (files, messages) = check(&pack("missing_catalog", &[]));
+        assert!(files.catalog.is_none() && files.cases.is_none());
+        assert_eq!(
+            messages,
+            [(
+                String::new(),
+                None,
+                format!("The pack has neither a {CATALOG_PATH} nor a {CASES_PATH}")
+            )]
+        );
+    }
+
+    #[test]
+    fn bad_ron() {
+        let (files, messages) = check(&pack("bad_ron", &[(CATALOG_PATH, "(parts: [")]));
+        assert!(files.catalog.is_none());
+        assert_eq!(messages.len(), 1);
+        let (file, subject, message) = &messages[0];
+        assert_eq!((file.as_str(), subject), (CATALOG_PATH, &None));
+        assert!(message.starts_with("Can't parse the file"), "{message}");
+    }
+
+    #[test]
+    fn missing_model() {
+        let (files, messages) = check(&pack("missing_model", &[(CATALOG_PATH, CATALOG)]));
+        assert!(files.catalog.is_some());
+        assert!(files.models.is_empty());
+        assert_eq!(
+            messages,
+            [(
+                CATALOG_PATH.to_string(),
+                Some("gpu".to_string()),
+                "The model models/gpu.glb doesn't exist".to_string()
+            )]
+        );
+
+        let (files, messages) = check(&pack(
+            "found_model",
+            &[(CATALOG_PATH, CATALOG), ("models/gpu.glb", "")],
+        ));
+        assert_eq!(files.models, ["models/gpu.glb"]);
+        assert!(messages.is_empty());
+    }
+}
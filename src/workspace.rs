@@ -0,0 +1,317 @@
+//! A workspace of builds side by side, such as three quotes for the same client.
+//!
+//! Each option of the workspace is a build saved in the `workspace` folder, see [`storage`],
+//! and has a tab in the build panel. Switching tabs saves the open option and opens the other,
+//! so each can be changed in turn; the parts panel's filters stay as they are across them. A
+//! new option, from the last tab, saves the build in the level under the name it asks for.
+//! Export writes the parts and the total cost of every option into one CSV file, priced in the
+//! currency set in the settings, with each part's SKU and vendor page to order it from, and the
+//! logo set on it, see [`logos`](crate::logos).
+
+use bevy::{ecs::system::SystemId, prelude::*};
+
+use crate::{
+    LevelAssets, OrbitCamera, Screen,
+    capture::{save_file, timestamp},
+    cases::CaseManifest,
+    command_palette::{AddPaletteCommand, OpenPrompt},
+    parts::PartCatalog,
+    persistence::{CurrentBuild, SavedBuild, apply_build, load_build, save_build},
+    settings::Settings,
+    storage,
+    ui_navigation::{Activate, ButtonStyle, FONT_SIZE, focusable},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Workspace>();
+    let add_option = app.register_system(add_option);
+    app.insert_resource(NewOptionPrompt(add_option));
+    app.add_systems(OnEnter(Screen::Game), list_options);
+    app.add_systems(OnExit(Screen::Game), close_option);
+    app.add_systems(
+        Update,
+        update_workspace_tabs
+            .run_if(resource_changed::<Workspace>.or(any_match_filter::<Added<WorkspaceTabs>>))
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_observer(activate_workspace_button);
+    app.add_palette_command(
+        "Workspace: save the build as a new option",
+        prompt_for_option,
+    );
+    app.add_palette_command("Workspace: export every option", export_workspace);
+    app.add_palette_command("Workspace: delete the open option", delete_open_option);
+}
+
+/// The options of the workspace, by name, and the one open in the level.
+#[derive(Resource, Debug, Default)]
+struct Workspace {
+    options: Vec<String>,
+    open: Option<String>,
+}
+
+/// Saves the build as a new option named by the text it's given.
+#[derive(Resource, Debug)]
+struct NewOptionPrompt(SystemId<In<String>>);
+
+/// Where the tabs are listed, in the build panel.
+#[derive(Component)]
+pub struct WorkspaceTabs;
+
+#[derive(Component, Debug, Clone, Eq, PartialEq)]
+enum WorkspaceButton {
+    Open(String),
+    New,
+    Delete,
+    Export,
+}
+
+/// The folder the options are saved in.
+const WORKSPACE_FOLDER: &str = "workspace";
+const OPEN_TEXT: Color = Color::srgb(1.0, 0.85, 0.3);
+
+fn option_key(name: &str) -> String {
+    format!("{WORKSPACE_FOLDER}/{name}")
+}
+
+/// The name an option entered as `text` is saved under: trimmed, with characters that files
+/// can't be named with replaced. `None` if nothing is left.
+pub fn option_name(text: &str) -> Option<String> {
+    let name: String = text
+        .trim()
+        .chars()
+        .map(|character| match character {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '.' => '_',
+            character => character,
+        })
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// The parts of every option with their prices, SKUs and vendor pages, then each option's
+/// total, as CSV.
+pub fn workspace_csv(
+    options: &[(String, SavedBuild)],
+    catalog: &PartCatalog,
+    settings: &Settings,
+) -> String {
+    let row = |fields: [&str; 7]| {
+        let quoted = fields.map(|field| format!("\"{}\"", field.replace('"', "\"\"")));
+        format!("{}\n", quoted.join(","))
+    };
+    let mut csv = row(["Option", "Part", "Status", "Price", "SKU", "URL", "Logo"]);
+    for (name, build) in options {
+        let mut total = 0.0;
+        for saved in &build.parts {
+            let id = &saved.id;
+            let spec = catalog.get(id);
+            let part = spec.map_or(id.as_str(), |spec| spec.name.as_str());
+            let price = spec.map_or(0.0, |spec| spec.price);
+            let sku = spec
+                .and_then(|spec| spec.sku.as_deref())
+                .unwrap_or_default();
+            let url = spec
+                .and_then(|spec| spec.url.as_deref())
+                .unwrap_or_default();
+            let logo = saved.logo.as_deref().unwrap_or_default();
+            let status = if build.planned.contains(id) {
+                "Planned"
+            } else {
+                "Owned"
+            };
+            total += price;
+            let price = settings.format_price(price);
+            csv += &row([name, part, status, &price, sku, url, logo]);
+        }
+        csv += &row([name, "Total", "", &settings.format_price(total), "", "", ""]);
+    }
+    csv
+}
+
+fn list_options(mut workspace: ResMut<Workspace>) {
+    match storage::list(WORKSPACE_FOLDER) {
+        Ok(options) => workspace.options = options,
+        Err(error) => error!("Could not list the workspace: {error}"),
+    }
+}
+
+fn close_option(mut workspace: ResMut<Workspace>) {
+    workspace.open = None;
+}
+
+fn update_workspace_tabs(
+    mut commands: Commands,
+    workspace: Res<Workspace>,
+    tabs_query: Query<Entity, With<WorkspaceTabs>>,
+) {
+    let Ok(tabs) = tabs_query.single() else {
+        return;
+    };
+    commands
+        .entity(tabs)
+        .despawn_children()
+        .with_children(|tabs| {
+            for name in &workspace.options {
+                let open = workspace.open.as_ref() == Some(name);
+                tabs.spawn(button(name.clone(), WorkspaceButton::Open(name.clone())))
+                    .insert(TextColor(if open { OPEN_TEXT } else { Color::WHITE }));
+            }
+            tabs.spawn(button("+", WorkspaceButton::New));
+            if workspace.open.is_some() {
+                tabs.spawn(button("Delete option", WorkspaceButton::Delete));
+            }
+            if !workspace.options.is_empty() {
+                tabs.spawn(button("Export options", WorkspaceButton::Export));
+            }
+        });
+}
+
+fn button(label: impl Into<String>, action: WorkspaceButton) -> impl Bundle {
+    (
+        Button,
+        action,
+        focusable(),
+        Text::new(label),
+        TextFont::from_font_size(FONT_SIZE),
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(px(6.0), px(2.0)),
+            ..default()
+        },
+        ButtonStyle,
+    )
+}
+
+fn activate_workspace_button(
+    activate: On<Activate>,
+    mut commands: Commands,
+    button_query: Query<&WorkspaceButton>,
+) {
+    match button_query.get(activate.entity) {
+        Ok(WorkspaceButton::Open(name)) => {
+            commands.run_system_cached(save_open_option);
+            commands.run_system_cached_with(open_option, name.clone());
+        }
+        Ok(WorkspaceButton::New) => commands.run_system_cached(prompt_for_option),
+        Ok(WorkspaceButton::Delete) => commands.run_system_cached(delete_open_option),
+        Ok(WorkspaceButton::Export) => {
+            commands.run_system_cached(save_open_option);
+            commands.run_system_cached(export_workspace);
+        }
+        Err(_) => {}
+    }
+}
+
+fn prompt_for_option(mut commands: Commands, prompt: Res<NewOptionPrompt>) {
+    commands.trigger(OpenPrompt {
+        label: "Name of the new option".to_string(),
+        submit: prompt.0,
+    });
+}
+
+fn add_option(In(text): In<String>, current: CurrentBuild, mut workspace: ResMut<Workspace>) {
+    let Some(name) = option_name(&text) else {
+        return;
+    };
+    if workspace.options.contains(&name) {
+        info!("The workspace already has an option named \"{name}\"");
+        return;
+    }
+    let Some(build) = current.get() else {
+        return;
+    };
+    save_build(&option_key(&name), &build);
+    workspace.options.push(name.clone());
+    workspace.options.sort();
+    workspace.open = Some(name);
+}
+
+fn save_open_option(current: CurrentBuild, workspace: Res<Workspace>) {
+    if let Some(open) = &workspace.open
+        && let Some(build) = current.get()
+    {
+        save_build(&option_key(open), &build);
+    }
+}
+
+fn open_option(
+    In(name): In<String>,
+    mut commands: Commands,
+    mut workspace: ResMut<Workspace>,
+    mut camera_query: Query<&mut OrbitCamera>,
+    level_assets: Res<LevelAssets>,
+    manifests: Res<Assets<CaseManifest>>,
+) {
+    if let Some(build) = load_build(&option_key(&name)) {
+        let manifest = manifests.get(&level_assets.cases);
+        apply_build(&mut commands, &build, manifest, &mut camera_query);
+        workspace.open = Some(name);
+    }
+}
+
+fn delete_open_option(mut workspace: ResMut<Workspace>) {
+    let Some(open) = workspace.open.take() else {
+        info!("Open an option of the workspace to delete it first");
+        return;
+    };
+    match storage::remove(&option_key(&open)) {
+        Ok(()) => workspace.options.retain(|name| *name != open),
+        Err(error) => error!("Could not delete the option \"{open}\": {error}"),
+    }
+}
+
+fn export_workspace(
+    workspace: Res<Workspace>,
+    settings: Res<Settings>,
+    level_assets: Res<LevelAssets>,
+    catalogs: Res<Assets<PartCatalog>>,
+) {
+    let Some(catalog) = catalogs.get(&level_assets.catalog) else {
+        return;
+    };
+    let options: Vec<_> = workspace
+        .options
+        .iter()
+        .filter_map(|name| Some((name.clone(), load_build(&option_key(name))?)))
+        .collect();
+    let csv = workspace_csv(&options, catalog, &settings);
+    save_file(&format!("workspace-{}.csv", timestamp()), csv.into_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_names() {
+        assert_eq!(option_name("  Budget "), Some("Budget".to_string()));
+        assert_eq!(option_name("A/B 2.0"), Some("A_B 2_0".to_string()));
+        assert_eq!(option_name("   "), None);
+    }
+
+    #[test]
+    fn csv_totals() {
+        let catalog: PartCatalog = ron::de::from_str(
+            "(parts: [(id: \"gpu\", name: \"Card \\\"X\\\"\", kind: Gpu, size: (1.0, 1.0, 1.0), \
+             position: (0.0, 0.0, 0.0), price: 100.0, sku: Some(\"GPU-1\"), \
+             url: Some(\"https://example.com/gpu\"))])",
+        )
+        .unwrap();
+        let build: SavedBuild = ron::de::from_str(
+            "(version: 2, case_model: \"case\", parts: [\"gpu\", \
+             (id: \"unknown\", logo: Some(\"logos/studio.png\"))], \
+             planned: [\"gpu\"], camera: (radius: 1.0, yaw: 0.0, pitch: 0.0, \
+             target: (0.0, 0.0, 0.0)))",
+        )
+        .unwrap();
+        let csv = workspace_csv(&[("A".to_string(), build)], &catalog, &Settings::default());
+        assert_eq!(
+            csv,
+            "\"Option\",\"Part\",\"Status\",\"Price\",\"SKU\",\"URL\",\"Logo\"\n\
+             \"A\",\"Card \"\"X\"\"\",\"Planned\",\"$100.00\",\"GPU-1\",\
+             \"https://example.com/gpu\",\"\"\n\
+             \"A\",\"unknown\",\"Owned\",\"$0.00\",\"\",\"\",\"logos/studio.png\"\n\
+             \"A\",\"Total\",\"\",\"$100.00\",\"\",\"\",\"\"\n"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! An x-ray view that turns the case's shell see-through, so the layout inside shows without
+//! taking the panels off.
+//!
+//! Press Z to toggle it. Every mesh of the case model wears a translucent copy of its material
+//! while the view is on and gets its own back when it's off. Installed parts and RGB lighting
+//! stay as they are. Whether the view is on is saved with the build.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    Level, Screen,
+    command_palette::AddPaletteCommand,
+    keybindings::{KeyAction, key_just_pressed},
+    part_selection::{HighlightSelectedPart, SelectedPart},
+    parts::InstalledPart,
+    rgb::RgbZone,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<XRay>();
+    app.add_systems(
+        Update,
+        (
+            // The selection highlight swaps materials too, and would restore the wrong one.
+            toggle_xray
+                .run_if(key_just_pressed(KeyAction::XRay))
+                .before(HighlightSelectedPart),
+            (
+                apply_ghost_materials.run_if(xray_active),
+                restore_materials.run_if(not(xray_active)),
+            )
+                .after(HighlightSelectedPart),
+        )
+            .run_if(in_state(Screen::Game)),
+    );
+    app.add_palette_command("Toggle x-ray view", toggle_xray);
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct XRay {
+    pub active: bool,
+    /// The translucent copy of each original material, shared like the originals are.
+    ghosts: HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>,
+}
+
+/// Holds on to a mesh's own material while it wears a ghost.
+#[derive(Component)]
+struct XRayed {
+    original: Handle<StandardMaterial>,
+}
+
+/// How much of a material's opacity its ghost keeps.
+const GHOST_ALPHA: f32 = 0.15;
+
+fn xray_active(xray: Res<XRay>) -> bool {
+    xray.active
+}
+
+fn toggle_xray(mut xray: ResMut<XRay>, mut selected: ResMut<SelectedPart>) {
+    xray.active = !xray.active;
+    selected.set_if_neq(SelectedPart(None));
+}
+
+/// Turns the view on or off, as a loaded build had it.
+pub fn set_xray(In(active): In<bool>, mut xray: ResMut<XRay>, mut selected: ResMut<SelectedPart>) {
+    if xray.active != active {
+        xray.active = active;
+        selected.set_if_neq(SelectedPart(None));
+    }
+}
+
+/// Runs every frame, since a case's meshes may finish spawning well after it was switched to.
+fn apply_ghost_materials(
+    mut commands: Commands,
+    mut xray: ResMut<XRay>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level_query: Query<Entity, With<Level>>,
+    children_query: Query<&Children>,
+    // Parts and LEDs keep their own materials, so they stand out through the shell.
+    skipped_query: Query<(), Or<(With<InstalledPart>, With<RgbZone>)>>,
+    mut mesh_query: Query<&mut MeshMaterial3d<StandardMaterial>, Without<XRayed>>,
+) {
+    let mut pending: Vec<Entity> = level_query.iter().collect();
+    while let Some(entity) = pending.pop() {
+        if skipped_query.contains(entity) {
+            continue;
+        }
+        pending.extend(children_query.get(entity).into_iter().flatten());
+
+        let Ok(mut material) = mesh_query.get_mut(entity) else {
+            continue;
+        };
+        let ghost = match xray.ghosts.get(&material.0.id()) {
+            Some(ghost) => ghost.clone(),
+            None => {
+                let Some(mut ghost) = materials.get(&material.0).cloned() else {
+                    continue;
+                };
+                ghost
+                    .base_color
+                    .set_alpha(ghost.base_color.alpha() * GHOST_ALPHA);
+                ghost.alpha_mode = AlphaMode::Blend;
+                let ghost = materials.add(ghost);
+                xray.ghosts.insert(material.0.id(), ghost.clone());
+                ghost
+            }
+        };
+        commands.entity(entity).insert(XRayed {
+            original: material.0.clone(),
+        });
+        material.0 = ghost;
+    }
+}
+
+fn restore_materials(
+    mut commands: Commands,
+    mut xray: ResMut<XRay>,
+    mut mesh_query: Query<(Entity, &XRayed, &mut MeshMaterial3d<StandardMaterial>)>,
+) {
+    for (entity, xrayed, mut material) in &mut mesh_query {
+        material.0 = xrayed.original.clone();
+        commands.entity(entity).remove::<XRayed>();
+    }
+    // Let the ghosts unload.
+    if !xray.ghosts.is_empty() {
+        xray.ghosts.clear();
+    }
+}
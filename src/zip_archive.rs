@@ -0,0 +1,326 @@
+//! Reads files out of `.zip` archives, so asset packs can be handed around as a single file
+//! and dropped into `packs/` without unpacking them.
+//!
+//! The whole archive is read into memory when it's opened, and a file is inflated each time
+//! it's read. Files have to be stored or deflated, which is what zip tools write by default.
+//! ZIP64 archives, for packs over 4 GB or with more than 65535 files, are turned down with an
+//! error rather than misread.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bevy::{
+    asset::io::{AssetReader, AssetReaderError, PathStream, Reader, VecReader},
+    tasks::futures_lite::stream,
+};
+use thiserror::Error;
+
+/// The signature of the record at the end of an archive that says where its directory is.
+const END_SIGNATURE: u32 = 0x0605_4b50;
+/// The signature of a file's entry in the archive's directory.
+const ENTRY_SIGNATURE: u32 = 0x0201_4b50;
+/// The signature of the header in front of each file's data.
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+/// The size of the end record without its comment, which may be up to `u16::MAX` long.
+const END_SIZE: usize = 22;
+/// What a size, offset or count is set to when the real one is in a ZIP64 record.
+const ZIP64_MARKER: u32 = u32::MAX;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+#[derive(Debug, Error)]
+pub enum ZipError {
+    #[error("could not read the archive: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a zip archive")]
+    NotZip,
+    #[error("the archive is cut short")]
+    Truncated,
+    #[error("the archive is in the ZIP64 format, which isn't supported")]
+    Zip64,
+    #[error("{0} is compressed in a way that isn't supported")]
+    Compression(String),
+    #[error("{0} could not be inflated")]
+    Inflate(String),
+}
+
+/// A zip archive's files, by their path in the archive.
+#[derive(Debug)]
+pub struct ZipArchive {
+    bytes: Vec<u8>,
+    files: HashMap<PathBuf, File>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct File {
+    /// Where the file's local header starts.
+    header: usize,
+    compressed_size: usize,
+    size: usize,
+    method: u16,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Result<u16, ZipError> {
+    let field = bytes.get(at..at + 2).ok_or(ZipError::Truncated)?;
+    Ok(u16::from_le_bytes([field[0], field[1]]))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Result<u32, ZipError> {
+    let field = bytes.get(at..at + 4).ok_or(ZipError::Truncated)?;
+    Ok(u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+}
+
+impl ZipArchive {
+    pub fn open(path: &Path) -> Result<Self, ZipError> {
+        Self::parse(std::fs::read(path)?)
+    }
+
+    /// Lists the files in the archive's directory.
+    pub fn parse(bytes: Vec<u8>) -> Result<Self, ZipError> {
+        // The end record is followed only by its comment, so it's the last signature found.
+        let end = (0..=bytes.len().saturating_sub(END_SIZE))
+            .rev()
+            .take(u16::MAX as usize + 1)
+            .find(|&at| u32_at(&bytes, at).is_ok_and(|signature| signature == END_SIGNATURE))
+            .ok_or(ZipError::NotZip)?;
+        let count = u16_at(&bytes, end + 10)?;
+        let directory = u32_at(&bytes, end + 16)?;
+        if count == u16::MAX || directory == ZIP64_MARKER {
+            return Err(ZipError::Zip64);
+        }
+        let mut at = directory as usize;
+
+        let mut files = HashMap::new();
+        for _ in 0..count {
+            if u32_at(&bytes, at)? != ENTRY_SIGNATURE {
+                return Err(ZipError::NotZip);
+            }
+            let name_length = u16_at(&bytes, at + 28)? as usize;
+            let extra_length = u16_at(&bytes, at + 30)? as usize;
+            let comment_length = u16_at(&bytes, at + 32)? as usize;
+            let name = bytes
+                .get(at + 46..at + 46 + name_length)
+                .ok_or(ZipError::Truncated)?;
+            let name = String::from_utf8_lossy(name).into_owned();
+            let header = u32_at(&bytes, at + 42)?;
+            let compressed_size = u32_at(&bytes, at + 20)?;
+            let size = u32_at(&bytes, at + 24)?;
+            if [header, compressed_size, size].contains(&ZIP64_MARKER) {
+                return Err(ZipError::Zip64);
+            }
+            // Folders are listed as entries of their own, ending in a slash.
+            if !name.ends_with('/') {
+                files.insert(
+                    PathBuf::from(name),
+                    File {
+                        header: header as usize,
+                        compressed_size: compressed_size as usize,
+                        size: size as usize,
+                        method: u16_at(&bytes, at + 10)?,
+                    },
+                );
+            }
+            at += 46 + name_length + extra_length + comment_length;
+        }
+        Ok(Self { bytes, files })
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Whether any file in the archive is under `path`.
+    pub fn is_directory(&self, path: &Path) -> bool {
+        self.files
+            .keys()
+            .any(|file| file.starts_with(path) && file != path)
+    }
+
+    /// The files and folders directly in the folder at `path`.
+    pub fn list(&self, path: &Path) -> Vec<PathBuf> {
+        let mut children: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter_map(|file| {
+                let rest = file.strip_prefix(path).ok()?;
+                Some(path.join(rest.components().next()?))
+            })
+            .collect();
+        children.sort();
+        children.dedup();
+        children
+    }
+
+    /// The contents of the file at `path`, if the archive has one.
+    pub fn read(&self, path: &Path) -> Option<Result<Vec<u8>, ZipError>> {
+        let file = *self.files.get(path)?;
+        Some(self.inflate(path, file))
+    }
+
+    fn inflate(&self, path: &Path, file: File) -> Result<Vec<u8>, ZipError> {
+        if u32_at(&self.bytes, file.header)? != LOCAL_SIGNATURE {
+            return Err(ZipError::NotZip);
+        }
+        // The local header's name and extra field can differ in length from the directory's.
+        let start = file.header
+            + 30
+            + u16_at(&self.bytes, file.header + 26)? as usize
+            + u16_at(&self.bytes, file.header + 28)? as usize;
+        let data = self
+            .bytes
+            .get(start..start + file.compressed_size)
+            .ok_or(ZipError::Truncated)?;
+        match file.method {
+            STORED => Ok(data.to_vec()),
+            DEFLATED => miniz_oxide::inflate::decompress_to_vec_with_limit(data, file.size)
+                .map_err(|_| ZipError::Inflate(path.display().to_string())),
+            _ => Err(ZipError::Compression(path.display().to_string())),
+        }
+    }
+}
+
+/// Reads assets out of a [`ZipArchive`], as if it was a folder.
+pub struct ZipReader(pub Arc<ZipArchive>);
+
+impl ZipReader {
+    fn open(&self, path: &Path) -> Result<VecReader, AssetReaderError> {
+        match self.0.read(path) {
+            Some(Ok(bytes)) => Ok(VecReader::new(bytes)),
+            Some(Err(error)) => Err(AssetReaderError::Io(Arc::new(std::io::Error::other(error)))),
+            None => Err(AssetReaderError::NotFound(path.to_path_buf())),
+        }
+    }
+}
+
+impl AssetReader for ZipReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.open(path)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let mut meta = path.as_os_str().to_owned();
+        meta.push(".meta");
+        self.open(Path::new(&meta))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        if !self.0.is_directory(path) {
+            return Err(AssetReaderError::NotFound(path.to_path_buf()));
+        }
+        Ok(Box::new(stream::iter(self.0.list(path))))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        if self.0.contains(path) {
+            Ok(false)
+        } else if self.0.is_directory(path) {
+            Ok(true)
+        } else {
+            Err(AssetReaderError::NotFound(path.to_path_buf()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An archive of `files`, each with its name, compression method and contents.
+    fn archive(files: &[(&str, u16, &[u8])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut directory = Vec::new();
+        for &(name, method, contents) in files {
+            let data = match method {
+                DEFLATED => miniz_oxide::deflate::compress_to_vec(contents, 6),
+                _ => contents.to_vec(),
+            };
+            let header = bytes.len() as u32;
+            bytes.extend(LOCAL_SIGNATURE.to_le_bytes());
+            bytes.extend([0; 4]);
+            bytes.extend(method.to_le_bytes());
+            bytes.extend([0; 8]);
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend((contents.len() as u32).to_le_bytes());
+            bytes.extend((name.len() as u16).to_le_bytes());
+            bytes.extend([0; 2]);
+            bytes.extend(name.as_bytes());
+            bytes.extend(&data);
+
+            directory.extend(ENTRY_SIGNATURE.to_le_bytes());
+            directory.extend([0; 6]);
+            directory.extend(method.to_le_bytes());
+            directory.extend([0; 8]);
+            directory.extend((data.len() as u32).to_le_bytes());
+            directory.extend((contents.len() as u32).to_le_bytes());
+            directory.extend((name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]);
+            directory.extend(header.to_le_bytes());
+            directory.extend(name.as_bytes());
+        }
+        let directory_start = bytes.len() as u32;
+        bytes.extend(&directory);
+        bytes.extend(END_SIGNATURE.to_le_bytes());
+        bytes.extend([0; 6]);
+        bytes.extend((files.len() as u16).to_le_bytes());
+        bytes.extend((directory.len() as u32).to_le_bytes());
+        bytes.extend(directory_start.to_le_bytes());
+        bytes.extend([0; 2]);
+        bytes
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_files() {
+        let catalog = b"(parts: [])".repeat(20);
+        let archive = ZipArchive::parse(archive(&[
+            ("parts/catalog.ron", DEFLATED, &catalog),
+            ("models/case.glb", STORED, b"glTF"),
+        ]))
+        .unwrap();
+        let read = |path: &str| archive.read(Path::new(path)).map(Result::unwrap);
+        assert_eq!(read("parts/catalog.ron"), Some(catalog.clone()));
+        assert_eq!(read("models/case.glb"), Some(b"glTF".to_vec()));
+        assert_eq!(read("models/missing.glb"), None);
+    }
+
+    #[test]
+    fn lists_folders() {
+        let archive = ZipArchive::parse(archive(&[
+            ("models/case.glb", STORED, b""),
+            ("models/fans/fan.glb", STORED, b""),
+            ("parts/catalog.ron", STORED, b""),
+        ]))
+        .unwrap();
+        assert!(archive.is_directory(Path::new("models")));
+        assert!(!archive.is_directory(Path::new("models/case.glb")));
+        assert_eq!(
+            archive.list(Path::new("models")),
+            vec![
+                PathBuf::from("models/case.glb"),
+                PathBuf::from("models/fans")
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            ZipArchive::parse(b"not an archive at all".to_vec()),
+            Err(ZipError::NotZip)
+        ));
+    }
+
+    #[test]
+    fn rejects_zip64() {
+        let mut bytes = archive(&[("models/case.glb", STORED, b"glTF")]);
+        // The file's size in its directory entry, which comes after its header and data.
+        let entry = 30 + "models/case.glb".len() + 4;
+        bytes[entry + 24..entry + 28].copy_from_slice(&ZIP64_MARKER.to_le_bytes());
+        assert!(matches!(ZipArchive::parse(bytes), Err(ZipError::Zip64)));
+    }
+}